@@ -1,9 +1,11 @@
 use std::str;
 
-use clap::{App, ArgMatches, SubCommand};
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use serde_json::Value;
 
 use casper_node::rpcs::{
-    chain::{GetBlock, GetBlockParams},
+    chain::{GetBlock, GetBlockExecution, GetBlockExecutionParams, GetBlockParams},
     RpcWithOptionalParams,
 };
 
@@ -13,6 +15,63 @@ use crate::{command::ClientCommand, common, RpcClient};
 enum DisplayOrder {
     NodeAddress,
     BlockHash,
+    Height,
+    WithResults,
+}
+
+/// Handles providing the arg for and retrieval of the block height.
+mod height {
+    use super::*;
+
+    const ARG_NAME: &str = "height";
+    const ARG_VALUE_NAME: &str = "INTEGER";
+    const ARG_HELP: &str =
+        "Height of the block.  If neither this nor the block hash is given, the latest finalized \
+        block as known at the given node will be used";
+
+    pub(super) fn arg(order: usize) -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .conflicts_with(common::block_hash::ARG_NAME)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(order)
+    }
+
+    pub(super) fn get(matches: &ArgMatches) -> Option<u64> {
+        matches.value_of(ARG_NAME).map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|error| panic!("should parse {} as height: {}", value, error))
+        })
+    }
+}
+
+/// Handles providing the arg for and retrieval of the with-results flag.
+mod with_results {
+    use super::*;
+
+    const ARG_NAME: &str = "with-results";
+    const ARG_HELP: &str =
+        "If this flag is passed, the summary of executing the block's deploys is retrieved too \
+        and included in the output";
+
+    pub(super) fn arg(order: usize) -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .help(ARG_HELP)
+            .display_order(order)
+    }
+
+    pub(super) fn get(matches: &ArgMatches) -> bool {
+        matches.is_present(ARG_NAME)
+    }
+}
+
+impl RpcClient for GetBlockExecution {
+    const RPC_METHOD: &'static str = Self::METHOD;
 }
 
 impl RpcClient for GetBlock {
@@ -31,20 +90,43 @@ impl<'a, 'b> ClientCommand<'a, 'b> for GetBlock {
                 DisplayOrder::NodeAddress as usize,
             ))
             .arg(common::block_hash::arg(DisplayOrder::BlockHash as usize))
+            .arg(height::arg(DisplayOrder::Height as usize))
+            .arg(with_results::arg(DisplayOrder::WithResults as usize))
     }
 
     fn run(matches: &ArgMatches<'_>) {
         let node_address = common::node_address::get(matches);
         let maybe_block_hash = common::block_hash::get(matches);
+        let maybe_height = height::get(matches);
+        let with_results = with_results::get(matches);
 
-        let response_value = match maybe_block_hash {
-            Some(block_hash) => {
-                let params = GetBlockParams { block_hash };
+        let mut response_value = match (maybe_block_hash, maybe_height) {
+            (None, None) => Self::request(&node_address),
+            (block_hash, height) => {
+                let params = GetBlockParams { block_hash, height };
                 Self::request_with_map_params(&node_address, params)
             }
-            None => Self::request(&node_address),
         }
         .unwrap_or_else(|error| panic!("response error: {}", error));
+
+        if with_results {
+            let execution_summary = match response_value["block"]["hash"].as_str() {
+                Some(block_hash) => {
+                    let params = GetBlockExecutionParams {
+                        block_hash: block_hash.to_string(),
+                    };
+                    let execution_response =
+                        GetBlockExecution::request_with_map_params(&node_address, params)
+                            .unwrap_or_else(|error| panic!("response error: {}", error));
+                    execution_response["execution_summary"].clone()
+                }
+                None => Value::Null,
+            };
+            if let Some(response_map) = response_value.as_object_mut() {
+                let _ = response_map.insert("execution_summary".to_string(), execution_summary);
+            }
+        }
+
         println!("{}", response_value);
     }
 }
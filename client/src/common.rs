@@ -129,7 +129,7 @@ pub mod global_state_hash {
 pub mod block_hash {
     use super::*;
 
-    const ARG_NAME: &str = "block-hash";
+    pub const ARG_NAME: &str = "block-hash";
     const ARG_SHORT: &str = "b";
     const ARG_VALUE_NAME: &str = super::ARG_HEX_STRING;
     const ARG_HELP: &str =
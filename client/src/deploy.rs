@@ -1,6 +1,8 @@
 mod creation_common;
 mod get;
 mod list;
+mod list_account;
+mod list_pending;
 mod make;
 mod put;
 mod send;
@@ -17,7 +17,11 @@ use serde_json::Value as JsonValue;
 use casper_execution_engine::core::engine_state::executable_deploy_item::ExecutableDeployItem;
 use casper_node::{
     crypto::hash::Digest,
-    rpcs::account::PutDeployParams,
+    rpcs::{
+        account::PutDeployParams,
+        speculative_exec::{SpeculativeExec, SpeculativeExecParams},
+        RpcWithParams,
+    },
     types::{Deploy, TimeDiff, Timestamp},
 };
 use casper_types::{
@@ -26,12 +30,13 @@ use casper_types::{
     AccessRights, CLType, CLTyped, CLValue, Key, NamedArg, RuntimeArgs, URef, U128, U256, U512,
 };
 
-use crate::common;
+use crate::{common, Result, RpcClient};
 
 /// This struct defines the order in which the args are shown for this subcommand's help message.
 pub(super) enum DisplayOrder {
     ShowArgExamples,
     NodeAddress,
+    DryRun,
     SecretKey,
     Input,
     Output,
@@ -726,10 +731,12 @@ pub(super) fn apply_common_creation_options<'a, 'b>(
         .arg(show_arg_examples::arg());
 
     if include_node_address {
-        subcommand = subcommand.arg(
-            common::node_address::arg(DisplayOrder::NodeAddress as usize)
-                .required_unless(show_arg_examples::ARG_NAME),
-        );
+        subcommand = subcommand
+            .arg(
+                common::node_address::arg(DisplayOrder::NodeAddress as usize)
+                    .required_unless(show_arg_examples::ARG_NAME),
+            )
+            .arg(dry_run::arg());
     }
 
     subcommand = subcommand
@@ -820,6 +827,45 @@ pub(super) fn deploy_into_params(deploy: Deploy) -> PutDeployParams {
     PutDeployParams { deploy }
 }
 
+/// Handles providing the arg for and retrieval of the dry-run flag.
+pub(super) mod dry_run {
+    use super::*;
+
+    const ARG_NAME: &str = "dry-run";
+    const ARG_HELP: &str =
+        "If this flag is passed, the deploy is executed by the node against its latest global \
+        state without committing the effects, and the execution result is printed. The deploy is \
+        not sent to the network";
+
+    pub(in crate::deploy) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::DryRun as usize)
+    }
+
+    pub(in crate::deploy) fn get(matches: &ArgMatches) -> bool {
+        matches.is_present(ARG_NAME)
+    }
+}
+
+impl RpcClient for SpeculativeExec {
+    const RPC_METHOD: &'static str = Self::METHOD;
+}
+
+/// Asks the node to execute the deploy held in `params` without committing its effects.
+pub(super) fn speculatively_execute(
+    node_address: &str,
+    params: PutDeployParams,
+) -> Result<JsonValue> {
+    let params = SpeculativeExecParams {
+        deploy: params.deploy,
+        global_state_hash: None,
+    };
+    SpeculativeExec::request_with_map_params(node_address, params)
+}
+
 pub(super) fn construct_deploy(
     matches: &ArgMatches<'_>,
     session: ExecutableDeployItem,
@@ -0,0 +1,134 @@
+use std::str;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use casper_node::rpcs::{
+    account::{ListDeploys as ListAccountDeploys, ListDeploysParams},
+    RpcWithParams,
+};
+
+use crate::{command::ClientCommand, common, RpcClient};
+
+/// This struct defines the order in which the args are shown for this subcommand's help message.
+enum DisplayOrder {
+    NodeAddress,
+    PublicKey,
+    Offset,
+    Limit,
+}
+
+/// Handles providing the arg for and retrieval of the account's public key.
+mod public_key {
+    use super::*;
+
+    const ARG_NAME: &str = "public-key";
+    const ARG_SHORT: &str = "p";
+    const ARG_VALUE_NAME: &str = common::ARG_HEX_STRING;
+    const ARG_HELP: &str = "Hex-encoded public key of the account, as output by the keygen command";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .short(ARG_SHORT)
+            .required(true)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::PublicKey as usize)
+    }
+
+    pub(super) fn get(matches: &ArgMatches) -> String {
+        matches
+            .value_of(ARG_NAME)
+            .unwrap_or_else(|| panic!("should have {} arg", ARG_NAME))
+            .to_string()
+    }
+}
+
+/// Handles providing the arg for and retrieval of the number of most recent deploys to skip.
+mod offset {
+    use super::*;
+
+    const ARG_NAME: &str = "offset";
+    const ARG_VALUE_NAME: &str = "INTEGER";
+    const ARG_HELP: &str = "Number of most recent deploys to skip [default: 0]";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::Offset as usize)
+    }
+
+    pub(super) fn get(matches: &ArgMatches) -> Option<usize> {
+        matches.value_of(ARG_NAME).map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|error| panic!("should parse {} as offset: {}", value, error))
+        })
+    }
+}
+
+/// Handles providing the arg for and retrieval of the maximum number of deploys to list.
+mod limit {
+    use super::*;
+
+    const ARG_NAME: &str = "limit";
+    const ARG_VALUE_NAME: &str = "INTEGER";
+    const ARG_HELP: &str =
+        "Maximum number of deploys to list.  If not given, the node's default is used";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::Limit as usize)
+    }
+
+    pub(super) fn get(matches: &ArgMatches) -> Option<usize> {
+        matches.value_of(ARG_NAME).map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|error| panic!("should parse {} as limit: {}", value, error))
+        })
+    }
+}
+
+impl RpcClient for ListAccountDeploys {
+    const RPC_METHOD: &'static str = Self::METHOD;
+}
+
+impl<'a, 'b> ClientCommand<'a, 'b> for ListAccountDeploys {
+    const NAME: &'static str = "list-account-deploys";
+    const ABOUT: &'static str =
+        "Retrieves the deploys sent from the given account along with their execution results, \
+        most recent first";
+
+    fn build(display_order: usize) -> App<'a, 'b> {
+        SubCommand::with_name(Self::NAME)
+            .about(Self::ABOUT)
+            .display_order(display_order)
+            .arg(common::node_address::arg(
+                DisplayOrder::NodeAddress as usize,
+            ))
+            .arg(public_key::arg())
+            .arg(offset::arg())
+            .arg(limit::arg())
+    }
+
+    fn run(matches: &ArgMatches<'_>) {
+        let node_address = common::node_address::get(matches);
+        let params = ListDeploysParams {
+            public_key: public_key::get(matches),
+            offset: offset::get(matches),
+            limit: limit::get(matches),
+        };
+
+        let response_value = Self::request_with_map_params(&node_address, params)
+            .unwrap_or_else(|error| panic!("response error: {}", error));
+        println!("{}", response_value);
+    }
+}
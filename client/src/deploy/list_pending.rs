@@ -0,0 +1,107 @@
+use std::str;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use casper_node::{
+    rpcs::{
+        info::{GetPendingDeploys as ListPendingDeploys, GetPendingDeploysParams},
+        RpcWithOptionalParams,
+    },
+    types::Timestamp,
+};
+
+use crate::{command::ClientCommand, common, RpcClient};
+
+/// This struct defines the order in which the args are shown for this subcommand's help message.
+enum DisplayOrder {
+    NodeAddress,
+    PublicKey,
+    ExpiresBefore,
+}
+
+/// Handles providing the arg for and retrieval of the account's public key.
+mod public_key {
+    use super::*;
+
+    const ARG_NAME: &str = "public-key";
+    const ARG_SHORT: &str = "p";
+    const ARG_VALUE_NAME: &str = common::ARG_HEX_STRING;
+    const ARG_HELP: &str =
+        "Hex-encoded public key of the account, as output by the keygen command.  If given, only \
+        deploys sent from this account are listed";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .short(ARG_SHORT)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::PublicKey as usize)
+    }
+
+    pub(super) fn get(matches: &ArgMatches) -> Option<String> {
+        matches.value_of(ARG_NAME).map(ToString::to_string)
+    }
+}
+
+/// Handles providing the arg for and retrieval of the expiry filter.
+mod expires_before {
+    use super::*;
+
+    const ARG_NAME: &str = "expires-before";
+    const ARG_VALUE_NAME: &str = "MILLISECONDS";
+    const ARG_HELP: &str =
+        "Milliseconds since the Unix epoch.  If given, only deploys expiring before this time are \
+        listed";
+
+    pub(super) fn arg() -> Arg<'static, 'static> {
+        Arg::with_name(ARG_NAME)
+            .long(ARG_NAME)
+            .required(false)
+            .value_name(ARG_VALUE_NAME)
+            .help(ARG_HELP)
+            .display_order(DisplayOrder::ExpiresBefore as usize)
+    }
+
+    pub(super) fn get(matches: &ArgMatches) -> Option<Timestamp> {
+        matches.value_of(ARG_NAME).map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|error| panic!("should parse {} as timestamp: {}", value, error))
+        })
+    }
+}
+
+impl RpcClient for ListPendingDeploys {
+    const RPC_METHOD: &'static str = Self::METHOD;
+}
+
+impl<'a, 'b> ClientCommand<'a, 'b> for ListPendingDeploys {
+    const NAME: &'static str = "list-pending-deploys";
+    const ABOUT: &'static str =
+        "Retrieves the deploys which the node holds and which are neither finalized nor expired";
+
+    fn build(display_order: usize) -> App<'a, 'b> {
+        SubCommand::with_name(Self::NAME)
+            .about(Self::ABOUT)
+            .display_order(display_order)
+            .arg(common::node_address::arg(
+                DisplayOrder::NodeAddress as usize,
+            ))
+            .arg(public_key::arg())
+            .arg(expires_before::arg())
+    }
+
+    fn run(matches: &ArgMatches<'_>) {
+        let node_address = common::node_address::get(matches);
+        let params = GetPendingDeploysParams {
+            public_key: public_key::get(matches),
+            expires_before: expires_before::get(matches),
+        };
+
+        let response_value = Self::request_with_map_params(&node_address, params)
+            .unwrap_or_else(|error| panic!("response error: {}", error));
+        println!("{}", response_value);
+    }
+}
@@ -192,8 +192,12 @@ impl<'a, 'b> ClientCommand<'a, 'b> for Transfer {
 
         let params = creation_common::construct_deploy(matches, session);
 
-        let response_value = Self::request_with_map_params(&node_address, params)
-            .unwrap_or_else(|error| panic!("response error: {}", error));
+        let response_value = if creation_common::dry_run::get(matches) {
+            creation_common::speculatively_execute(&node_address, params)
+        } else {
+            Self::request_with_map_params(&node_address, params)
+        }
+        .unwrap_or_else(|error| panic!("response error: {}", error));
         println!("{}", response_value);
     }
 }
@@ -13,9 +13,9 @@ mod rpc;
 use clap::{crate_description, crate_version, App};
 
 use casper_node::rpcs::{
-    account::PutDeploy,
+    account::{ListDeploys as ListAccountDeploys, PutDeploy},
     chain::{GetBlock, GetGlobalStateHash},
-    info::GetDeploy,
+    info::{GetDeploy, GetPendingDeploys as ListPendingDeploys},
     state::{GetBalance, GetItem as QueryState},
 };
 
@@ -40,6 +40,8 @@ enum DisplayOrder {
     GetDeploy,
     GetBlock,
     ListDeploys,
+    ListAccountDeploys,
+    ListPendingDeploys,
     GetBalance,
     GetGlobalStateHash,
     QueryState,
@@ -59,6 +61,12 @@ fn cli<'a, 'b>() -> App<'a, 'b> {
         .subcommand(GetDeploy::build(DisplayOrder::GetDeploy as usize))
         .subcommand(GetBlock::build(DisplayOrder::GetBlock as usize))
         .subcommand(ListDeploys::build(DisplayOrder::ListDeploys as usize))
+        .subcommand(ListAccountDeploys::build(
+            DisplayOrder::ListAccountDeploys as usize,
+        ))
+        .subcommand(ListPendingDeploys::build(
+            DisplayOrder::ListPendingDeploys as usize,
+        ))
         .subcommand(GetBalance::build(DisplayOrder::GetBalance as usize))
         .subcommand(GetGlobalStateHash::build(
             DisplayOrder::GetGlobalStateHash as usize,
@@ -82,6 +90,8 @@ async fn main() {
         (GetDeploy::NAME, Some(matches)) => GetDeploy::run(matches),
         (GetBlock::NAME, Some(matches)) => GetBlock::run(matches),
         (ListDeploys::NAME, Some(matches)) => ListDeploys::run(matches),
+        (ListAccountDeploys::NAME, Some(matches)) => ListAccountDeploys::run(matches),
+        (ListPendingDeploys::NAME, Some(matches)) => ListPendingDeploys::run(matches),
         (GetBalance::NAME, Some(matches)) => GetBalance::run(matches),
         (GetGlobalStateHash::NAME, Some(matches)) => GetGlobalStateHash::run(matches),
         (QueryState::NAME, Some(matches)) => QueryState::run(matches),
@@ -1,9 +1,10 @@
 use std::str;
 
 use casper_node::rpcs::{
-    account::PutDeployParams,
-    chain::{GetBlockParams, GetGlobalStateHashParams},
-    info::GetDeployParams,
+    account::{ListDeploysParams, PutDeployParams},
+    chain::{GetBlockExecutionParams, GetBlockParams, GetGlobalStateHashParams},
+    info::{GetDeployParams, GetPendingDeploysParams},
+    speculative_exec::SpeculativeExecParams,
     state::{GetBalanceParams, GetItemParams},
     RPC_API_PATH,
 };
@@ -85,8 +86,12 @@ pub(crate) trait IntoJsonMap: Serialize {
 }
 
 impl IntoJsonMap for PutDeployParams {}
+impl IntoJsonMap for ListDeploysParams {}
 impl IntoJsonMap for GetBlockParams {}
+impl IntoJsonMap for GetBlockExecutionParams {}
 impl IntoJsonMap for GetGlobalStateHashParams {}
 impl IntoJsonMap for GetDeployParams {}
+impl IntoJsonMap for GetPendingDeploysParams {}
 impl IntoJsonMap for GetBalanceParams {}
 impl IntoJsonMap for GetItemParams {}
+impl IntoJsonMap for SpeculativeExecParams {}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+use casper_types::{bytesrepr, Key};
+
+use crate::{
+    core::{engine_state::execution_result::ExecutionResults, tracking_copy::AddResult},
+    shared::{newtypes::Blake2bHash, TypeMismatch},
+    storage::global_state::CommitResult,
+};
+
+/// The result of executing all deploys of a block and committing their effects.
+#[derive(Debug)]
+pub enum BlockExecutionResult {
+    RootNotFound,
+    KeyNotFound(Key),
+    TypeMismatch(TypeMismatch),
+    Serialization(bytesrepr::Error),
+    Success {
+        post_state_hash: Blake2bHash,
+        /// The results of executing the deploys, in the order of the block.
+        execution_results: ExecutionResults,
+    },
+}
+
+impl fmt::Display for BlockExecutionResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            BlockExecutionResult::RootNotFound => write!(f, "Root not found"),
+            BlockExecutionResult::KeyNotFound(key) => write!(f, "Key not found: {}", key),
+            BlockExecutionResult::TypeMismatch(type_mismatch) => {
+                write!(f, "Type mismatch: {:?}", type_mismatch)
+            }
+            BlockExecutionResult::Serialization(error) => {
+                write!(f, "Serialization error: {:?}", error)
+            }
+            BlockExecutionResult::Success {
+                post_state_hash,
+                execution_results,
+            } => write!(
+                f,
+                "Success: {} ({} deploys)",
+                post_state_hash,
+                execution_results.len()
+            ),
+        }
+    }
+}
+
+impl BlockExecutionResult {
+    pub fn from_commit_result(
+        commit_result: CommitResult,
+        execution_results: ExecutionResults,
+    ) -> Self {
+        match commit_result {
+            CommitResult::RootNotFound => BlockExecutionResult::RootNotFound,
+            CommitResult::KeyNotFound(key) => BlockExecutionResult::KeyNotFound(key),
+            CommitResult::TypeMismatch(type_mismatch) => {
+                BlockExecutionResult::TypeMismatch(type_mismatch)
+            }
+            CommitResult::Serialization(error) => BlockExecutionResult::Serialization(error),
+            CommitResult::Success { state_root, .. } => BlockExecutionResult::Success {
+                post_state_hash: state_root,
+                execution_results,
+            },
+        }
+    }
+
+    /// Returns the failure corresponding to the given result of applying a deploy's effects to the
+    /// block's tracking copy, or `None` if they were applied successfully.
+    pub(crate) fn from_add_result(add_result: AddResult) -> Option<Self> {
+        match add_result {
+            AddResult::Success => None,
+            AddResult::KeyNotFound(key) => Some(BlockExecutionResult::KeyNotFound(key)),
+            AddResult::TypeMismatch(type_mismatch) => {
+                Some(BlockExecutionResult::TypeMismatch(type_mismatch))
+            }
+            AddResult::Serialization(error) => Some(BlockExecutionResult::Serialization(error)),
+        }
+    }
+}
@@ -3,6 +3,8 @@
 pub struct EngineConfig {
     // feature flags go here
     use_system_contracts: bool,
+    keep_intermediate_state_roots: bool,
+    parallel_execution: bool,
 }
 
 impl EngineConfig {
@@ -19,4 +21,28 @@ impl EngineConfig {
         self.use_system_contracts = use_system_contracts;
         self
     }
+
+    /// Whether `execute_block` commits the effects of each deploy separately, keeping the
+    /// intermediate state root after each deploy, rather than committing the block's effects once.
+    pub fn keep_intermediate_state_roots(self) -> bool {
+        self.keep_intermediate_state_roots
+    }
+
+    pub fn with_keep_intermediate_state_roots(
+        mut self,
+        keep_intermediate_state_roots: bool,
+    ) -> EngineConfig {
+        self.keep_intermediate_state_roots = keep_intermediate_state_roots;
+        self
+    }
+    /// Whether `execute_block` executes the deploys of a block optimistically in parallel against
+    /// the block's pre-state, re-executing the deploys which conflict with earlier ones serially.
+    pub fn parallel_execution(self) -> bool {
+        self.parallel_execution
+    }
+
+    pub fn with_parallel_execution(mut self, parallel_execution: bool) -> EngineConfig {
+        self.parallel_execution = parallel_execution;
+        self
+    }
 }
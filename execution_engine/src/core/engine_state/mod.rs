@@ -1,4 +1,5 @@
 pub mod balance;
+pub mod block_execution;
 pub mod deploy_item;
 pub mod engine_config;
 mod error;
@@ -8,8 +9,10 @@ pub mod execution_effect;
 pub mod execution_result;
 pub mod genesis;
 pub mod op;
+mod optimistic_execution;
 pub mod query;
 pub mod run_genesis_request;
+pub mod step;
 pub mod system_contract_cache;
 mod transfer;
 pub mod upgrade;
@@ -22,12 +25,18 @@ use std::{
 
 use num_traits::Zero;
 use parity_wasm::elements::Module;
+use rayon::prelude::*;
 use tracing::{debug, warn};
 
 use casper_types::{
     account::AccountHash,
+    auction::{
+        EraValidators, ARG_VALIDATOR_PUBLIC_KEYS, ERA_VALIDATORS_KEY, METHOD_RUN_AUCTION,
+        METHOD_SLASH,
+    },
     bytesrepr::{self, ToBytes},
     contracts::{NamedKeys, ENTRY_POINT_NAME_INSTALL, UPGRADE_ENTRY_POINT_NAME},
+    mint::{ARG_SOURCE, ARG_TARGET, METHOD_MINT, METHOD_TRANSFER},
     runtime_args,
     system_contract_errors::mint,
     AccessRights, BlockTime, Contract, ContractHash, ContractPackage, ContractPackageHash,
@@ -37,6 +46,7 @@ use casper_types::{
 
 pub use self::{
     balance::{BalanceRequest, BalanceResult},
+    block_execution::BlockExecutionResult,
     deploy_item::DeployItem,
     engine_config::EngineConfig,
     error::{Error, RootNotFound},
@@ -45,6 +55,7 @@ pub use self::{
     execution_result::{ExecutionResult, ForcedTransferResult},
     genesis::{ExecConfig, GenesisResult, POS_PAYMENT_PURSE, POS_REWARDS_PURSE},
     query::{QueryRequest, QueryResult},
+    step::{RewardItem, StepRequest, StepResult},
     system_contract_cache::SystemContractCache,
     transfer::{TransferRuntimeArgsBuilder, TransferTargetMode},
     upgrade::{UpgradeConfig, UpgradeResult},
@@ -54,7 +65,7 @@ use crate::{
         execution::{
             self, AddressGenerator, AddressGeneratorBuilder, DirectSystemContractCall, Executor,
         },
-        tracking_copy::{TrackingCopy, TrackingCopyExt},
+        tracking_copy::{TrackingCopy, TrackingCopyExt, TrackingCopyQueryResult},
     },
     shared::{
         account::Account,
@@ -66,14 +77,16 @@ use crate::{
         transform::Transform,
         wasm_costs::WasmCosts,
         wasm_prep::{self, Preprocessor},
+        TypeMismatch,
     },
     storage::{
-        global_state::{CommitResult, StateProvider},
+        global_state::{lmdb::LmdbGlobalState, CommitResult, StateProvider, StateReader},
         protocol_data::ProtocolData,
     },
 };
 use execution_result::ExecutionResults;
 use genesis::GenesisAccount;
+use optimistic_execution::{ChangedKeys, OptimisticExecution, RecordingReader};
 
 // TODO?: MAX_PAYMENT && CONV_RATE values are currently arbitrary w/ real values
 // TBD gas * CONV_RATE = motes
@@ -655,7 +668,7 @@ where
                     ret
                 };
 
-                let blocktime = BlockTime::default();
+                let blocktime = BlockTime::new(upgrade_config.block_time());
 
                 let deploy_hash = {
                     // seeds address generator w/ protocol version
@@ -728,6 +741,151 @@ where
         Ok(UpgradeResult::from_commit_result(commit_result, effects))
     }
 
+    /// Runs the system operations at the end of a block as the system account, and commits their
+    /// effects: slashes the given validators and mints the rewards into the recipients' main
+    /// purses, then runs the auction if requested.
+    pub fn commit_step(
+        &self,
+        correlation_id: CorrelationId,
+        step_request: StepRequest,
+    ) -> Result<StepResult, Error> {
+        let pre_state_hash = step_request.pre_state_hash();
+        let tracking_copy = match self.tracking_copy(pre_state_hash)? {
+            Some(tracking_copy) => Rc::new(RefCell::new(tracking_copy)),
+            None => return Ok(StepResult::RootNotFound),
+        };
+
+        let protocol_version = step_request.protocol_version();
+        let protocol_data = match self.state.get_protocol_data(protocol_version) {
+            Ok(Some(protocol_data)) => protocol_data,
+            Ok(None) => return Err(Error::InvalidProtocolVersion(protocol_version)),
+            Err(error) => return Err(Error::Exec(error.into())),
+        };
+
+        let system_account = tracking_copy
+            .borrow_mut()
+            .get_account(correlation_id, SYSTEM_ACCOUNT_ADDR)?;
+
+        // Rewards for accounts which don't exist are skipped, as there is no purse to pay them to.
+        let mut reward_purses = Vec::new();
+        for reward_item in step_request.reward_items() {
+            match tracking_copy
+                .borrow_mut()
+                .get_account(correlation_id, reward_item.account_hash)
+            {
+                Ok(account) => reward_purses.push((account.main_purse(), reward_item.amount)),
+                Err(execution::Error::KeyNotFound(_)) => {
+                    warn!(account_hash = ?reward_item.account_hash, "no account to reward")
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+        let extra_keys: Vec<Key> = reward_purses
+            .iter()
+            .map(|(purse, _)| Key::URef(*purse))
+            .collect();
+
+        // The system contracts are called from a runtime instantiated with the mint module.
+        let module = {
+            let contract = tracking_copy
+                .borrow_mut()
+                .get_contract(correlation_id, protocol_data.mint())?;
+            let contract_wasm = tracking_copy
+                .borrow_mut()
+                .get_contract_wasm(correlation_id, contract.contract_wasm_hash())?;
+            wasm_prep::deserialize(contract_wasm.bytes())?
+        };
+
+        let mut named_keys = system_account.named_keys().clone();
+        let authorization_keys = {
+            let mut ret = BTreeSet::new();
+            ret.insert(SYSTEM_ACCOUNT_ADDR);
+            ret
+        };
+        // system operations have no gas limit; approximating with MAX
+        let gas_limit = Gas::new(std::u64::MAX.into());
+        let phase = Phase::System;
+        let hash_address_generator = {
+            let generator = AddressGenerator::new(pre_state_hash.as_ref(), phase);
+            Rc::new(RefCell::new(generator))
+        };
+        let uref_address_generator = {
+            let generator = AddressGenerator::new(pre_state_hash.as_ref(), phase);
+            Rc::new(RefCell::new(generator))
+        };
+        let system_contract_cache = SystemContractCache::clone(&self.system_contract_cache);
+
+        let executor = Executor::new(self.config);
+        let (_instance, mut runtime) = executor.create_runtime(
+            module,
+            EntryPointType::Session,
+            RuntimeArgs::new(),
+            &mut named_keys,
+            &extra_keys,
+            Key::Account(SYSTEM_ACCOUNT_ADDR),
+            &system_account,
+            authorization_keys,
+            BlockTime::new(step_request.block_time()),
+            pre_state_hash.value(),
+            gas_limit,
+            hash_address_generator,
+            uref_address_generator,
+            protocol_version,
+            correlation_id,
+            Rc::clone(&tracking_copy),
+            phase,
+            protocol_data,
+            system_contract_cache,
+        )?;
+
+        if !step_request.slash_items().is_empty() {
+            let args = runtime_args! {
+                ARG_VALIDATOR_PUBLIC_KEYS => step_request.slash_items().to_vec(),
+            };
+            runtime.call_contract(protocol_data.auction(), METHOD_SLASH, args)?;
+        }
+
+        for (target, amount) in reward_purses {
+            let args = runtime_args! {
+                ARG_AMOUNT => amount,
+            };
+            let source: URef = runtime
+                .call_contract(protocol_data.mint(), METHOD_MINT, args)?
+                .into_t::<Result<URef, mint::Error>>()
+                .map_err(execution::Error::from)??;
+            let args = runtime_args! {
+                ARG_SOURCE => source,
+                ARG_TARGET => target,
+                ARG_AMOUNT => amount,
+            };
+            runtime
+                .call_contract(protocol_data.mint(), METHOD_TRANSFER, args)?
+                .into_t::<Result<(), mint::Error>>()
+                .map_err(execution::Error::from)??;
+        }
+
+        if step_request.run_auction() {
+            runtime.call_contract(
+                protocol_data.auction(),
+                METHOD_RUN_AUCTION,
+                RuntimeArgs::new(),
+            )?;
+        }
+
+        let effects = tracking_copy.borrow().effect();
+
+        let commit_result = self
+            .state
+            .commit(
+                correlation_id,
+                pre_state_hash,
+                effects.transforms.to_owned(),
+            )
+            .map_err(Into::into)?;
+
+        Ok(StepResult::from_commit_result(commit_result, effects))
+    }
+
     pub fn tracking_copy(
         &self,
         hash: Blake2bHash,
@@ -756,6 +914,50 @@ where
             .into())
     }
 
+    /// Reads the validator weights of the current and upcoming eras from the auction contract's
+    /// state. Returns `None` if the given global state doesn't exist.
+    pub fn get_era_validators(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Blake2bHash,
+        protocol_version: ProtocolVersion,
+    ) -> Result<Option<EraValidators>, Error> {
+        let tracking_copy = match self.tracking_copy(state_hash)? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(None),
+        };
+
+        let protocol_data = match self.state.get_protocol_data(protocol_version) {
+            Ok(Some(protocol_data)) => protocol_data,
+            Ok(None) => return Err(Error::InvalidProtocolVersion(protocol_version)),
+            Err(error) => return Err(Error::Exec(error.into())),
+        };
+
+        let query_result = tracking_copy
+            .query(
+                correlation_id,
+                protocol_data.auction().into(),
+                &[ERA_VALIDATORS_KEY.to_string()],
+            )
+            .map_err(|error| Error::Exec(error.into()))?;
+
+        match query_result {
+            TrackingCopyQueryResult::Success(StoredValue::CLValue(cl_value)) => cl_value
+                .into_t()
+                .map(Some)
+                .map_err(|error| Error::Exec(execution::Error::CLValue(error))),
+            TrackingCopyQueryResult::Success(other) => {
+                Err(Error::Exec(execution::Error::TypeMismatch(
+                    TypeMismatch::new("CLValue".to_string(), other.type_name()),
+                )))
+            }
+            TrackingCopyQueryResult::ValueNotFound(message)
+            | TrackingCopyQueryResult::CircularReference(message) => {
+                Err(Error::Exec(execution::Error::NamedKeyNotFound(message)))
+            }
+        }
+    }
+
     pub fn run_execute(
         &self,
         correlation_id: CorrelationId,
@@ -807,15 +1009,241 @@ where
         Ok(results)
     }
 
-    pub fn get_module(
+    /// Executes the deploys of a block in order, each one against the state left by the previous
+    /// ones, and commits their effects.
+    ///
+    /// The effects of all deploys are accumulated in a single tracking copy and committed to
+    /// global state once, unless the engine is configured to keep the intermediate state roots, in
+    /// which case the effects of each deploy are committed separately.
+    ///
+    /// If the engine is configured for parallel execution, the deploys are first executed
+    /// concurrently against the block's pre-state, and only the ones which might depend on changes
+    /// made by earlier deploys are executed again, in block order.  Additions to a key which the
+    /// deploy itself only adds to, such as the rewards purse, don't count as such changes.  The
+    /// results are the same as those of serial execution.
+    pub fn execute_block(
+        &self,
+        correlation_id: CorrelationId,
+        mut exec_request: ExecuteRequest,
+    ) -> Result<BlockExecutionResult, Error>
+    where
+        S: Sync,
+    {
+        let protocol_version = exec_request.protocol_version;
+        let protocol_data = match self.get_protocol_data(protocol_version)? {
+            Some(protocol_data) => protocol_data,
+            None => return Err(Error::InvalidProtocolVersion(protocol_version)),
+        };
+        let executor = Executor::new(self.config);
+        let preprocessor = Preprocessor::new(*protocol_data.wasm_costs());
+        let blocktime = BlockTime::new(exec_request.block_time);
+        let keep_intermediate_state_roots = self.config.keep_intermediate_state_roots();
+        let parallel_execution = self.config.parallel_execution();
+
+        let mut state_hash = exec_request.parent_state_hash;
+        let mut tracking_copy = match self.tracking_copy(state_hash)? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(BlockExecutionResult::RootNotFound),
+        };
+
+        let deploys = exec_request.take_deploys();
+        let optimistic_executions = if parallel_execution {
+            self.execute_optimistically(
+                correlation_id,
+                &deploys,
+                state_hash,
+                protocol_version,
+                protocol_data,
+                blocktime,
+            )?
+        } else {
+            deploys.iter().map(|_| None).collect()
+        };
+        let mut execution_results = ExecutionResults::with_capacity(deploys.len());
+        let mut changed_keys = ChangedKeys::default();
+
+        for (deploy_item, maybe_optimistic_execution) in
+            deploys.into_iter().zip(optimistic_executions)
+        {
+            let execution_result = match deploy_item {
+                Err(execution_result) => execution_result,
+                Ok(deploy_item) => match maybe_optimistic_execution
+                    .and_then(|execution| execution.into_result(&changed_keys))
+                {
+                    Some(execution_result) => execution_result,
+                    None => {
+                        let deploy_tracking_copy = Rc::new(RefCell::new(tracking_copy.fork()));
+                        self.execute_with_tracking_copy(
+                            correlation_id,
+                            &executor,
+                            &preprocessor,
+                            protocol_version,
+                            protocol_data,
+                            blocktime,
+                            deploy_item,
+                            deploy_tracking_copy,
+                        )
+                    }
+                },
+            };
+            if parallel_execution {
+                changed_keys.record(execution_result.effect());
+            }
+            let transforms = execution_result.effect().transforms.clone();
+            execution_results.push_back(execution_result);
+
+            if keep_intermediate_state_roots {
+                match self
+                    .state
+                    .commit(correlation_id, state_hash, transforms)
+                    .map_err(Into::into)?
+                {
+                    CommitResult::Success { state_root } => state_hash = state_root,
+                    commit_result => {
+                        return Ok(BlockExecutionResult::from_commit_result(
+                            commit_result,
+                            execution_results,
+                        ))
+                    }
+                }
+                tracking_copy = match self.tracking_copy(state_hash)? {
+                    Some(tracking_copy) => tracking_copy,
+                    None => return Ok(BlockExecutionResult::RootNotFound),
+                };
+            } else {
+                let add_result = tracking_copy
+                    .apply_transforms(correlation_id, transforms)
+                    .map_err(Into::into)?;
+                if let Some(failure) = BlockExecutionResult::from_add_result(add_result) {
+                    return Ok(failure);
+                }
+            }
+        }
+
+        if keep_intermediate_state_roots {
+            return Ok(BlockExecutionResult::Success {
+                post_state_hash: state_hash,
+                execution_results,
+            });
+        }
+
+        let commit_result = self
+            .state
+            .commit(
+                correlation_id,
+                state_hash,
+                tracking_copy.effect().transforms,
+            )
+            .map_err(Into::into)?;
+        Ok(BlockExecutionResult::from_commit_result(
+            commit_result,
+            execution_results,
+        ))
+    }
+
+    /// Executes each of the given deploys concurrently against the given pre-state, recording the
+    /// keys it accesses.  Returns `None` for the entries which are not deploys to be executed.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_optimistically(
+        &self,
+        correlation_id: CorrelationId,
+        deploys: &[Result<DeployItem, ExecutionResult>],
+        pre_state_hash: Blake2bHash,
+        protocol_version: ProtocolVersion,
+        protocol_data: ProtocolData,
+        blocktime: BlockTime,
+    ) -> Result<Vec<Option<OptimisticExecution>>, Error>
+    where
+        S: Sync,
+    {
+        let deploy_items: Vec<Option<&DeployItem>> =
+            deploys.iter().map(|deploy| deploy.as_ref().ok()).collect();
+
+        deploy_items
+            .into_par_iter()
+            .map(|maybe_deploy_item| {
+                let deploy_item = match maybe_deploy_item {
+                    Some(deploy_item) => deploy_item.clone(),
+                    None => return Ok(None),
+                };
+                let reader = match self.state.checkout(pre_state_hash).map_err(Into::into)? {
+                    Some(reader) => RecordingReader::new(reader),
+                    None => return Ok(None),
+                };
+                let tracking_copy = Rc::new(RefCell::new(TrackingCopy::new(reader)));
+                let executor = Executor::new(self.config);
+                let preprocessor = Preprocessor::new(*protocol_data.wasm_costs());
+
+                let execution_result = self.execute_with_tracking_copy(
+                    correlation_id,
+                    &executor,
+                    &preprocessor,
+                    protocol_version,
+                    protocol_data,
+                    blocktime,
+                    deploy_item,
+                    Rc::clone(&tracking_copy),
+                );
+                let read_keys = tracking_copy.borrow().reader().take_read_keys();
+                Ok(Some(OptimisticExecution::new(execution_result, read_keys)))
+            })
+            .collect()
+    }
+
+    /// Executes a deploy, which may be a wasmless transfer, against the given tracking copy.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_with_tracking_copy<R>(
         &self,
-        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        preprocessor: &Preprocessor,
+        protocol_version: ProtocolVersion,
+        protocol_data: ProtocolData,
+        blocktime: BlockTime,
+        deploy_item: DeployItem,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+    ) -> ExecutionResult
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
+        match deploy_item.session {
+            ExecutableDeployItem::Transfer { .. } => self.transfer_with_tracking_copy(
+                correlation_id,
+                executor,
+                preprocessor,
+                protocol_version,
+                protocol_data,
+                blocktime,
+                deploy_item,
+                tracking_copy,
+            ),
+            _ => self.deploy_with_tracking_copy(
+                correlation_id,
+                executor,
+                preprocessor,
+                protocol_version,
+                protocol_data,
+                blocktime,
+                deploy_item,
+                tracking_copy,
+            ),
+        }
+    }
+
+    pub fn get_module<R>(
+        &self,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
         deploy_item: &ExecutableDeployItem,
         account: &Account,
         correlation_id: CorrelationId,
         preprocessor: &Preprocessor,
         protocol_version: &ProtocolVersion,
-    ) -> Result<GetModuleResult, Error> {
+    ) -> Result<GetModuleResult, Error>
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
         let (contract_package, contract, base_key) = match deploy_item {
             ExecutableDeployItem::ModuleBytes { module_bytes, .. } => {
                 let module = preprocessor.preprocess(&module_bytes)?;
@@ -925,13 +1353,17 @@ where
         }
     }
 
-    fn get_module_from_contract_hash(
+    fn get_module_from_contract_hash<R>(
         &self,
-        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
         contract_hash: ContractHash,
         correlation_id: CorrelationId,
         protocol_version: &ProtocolVersion,
-    ) -> Result<Module, Error> {
+    ) -> Result<Module, Error>
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
         let contract = tracking_copy
             .borrow_mut()
             .get_contract(correlation_id, contract_hash)?;
@@ -955,13 +1387,17 @@ where
         Ok(module)
     }
 
-    fn get_authorized_account(
+    fn get_authorized_account<R>(
         &self,
         correlation_id: CorrelationId,
         account_hash: AccountHash,
         authorization_keys: &BTreeSet<AccountHash>,
-        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
-    ) -> Result<Account, Error> {
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+    ) -> Result<Account, Error>
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
         let account: Account = match tracking_copy
             .borrow_mut()
             .get_account(correlation_id, account_hash)
@@ -1030,14 +1466,41 @@ where
             Ok(Some(tracking_copy)) => Rc::new(RefCell::new(tracking_copy)),
         };
 
+        Ok(self.transfer_with_tracking_copy(
+            correlation_id,
+            executor,
+            preprocessor,
+            protocol_version,
+            protocol_data,
+            blocktime,
+            deploy_item,
+            tracking_copy,
+        ))
+    }
+
+    /// Executes a wasmless transfer against the given tracking copy.
+    #[allow(clippy::too_many_arguments)]
+    fn transfer_with_tracking_copy<R>(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        preprocessor: &Preprocessor,
+        protocol_version: ProtocolVersion,
+        protocol_data: ProtocolData,
+        blocktime: BlockTime,
+        deploy_item: DeployItem,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+    ) -> ExecutionResult
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
         let base_key = Key::Account(deploy_item.address);
 
         let account_public_key = match base_key.into_account() {
             Some(account_addr) => account_addr,
             None => {
-                return Ok(ExecutionResult::precondition_failure(
-                    error::Error::Authorization,
-                ));
+                return ExecutionResult::precondition_failure(error::Error::Authorization);
             }
         };
 
@@ -1050,7 +1513,7 @@ where
             Rc::clone(&tracking_copy),
         ) {
             Ok(account) => account,
-            Err(e) => return Ok(ExecutionResult::precondition_failure(e)),
+            Err(e) => return ExecutionResult::precondition_failure(e),
         };
 
         let mint_contract = match tracking_copy
@@ -1059,7 +1522,7 @@ where
         {
             Ok(contract) => contract,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error.into()));
+                return ExecutionResult::precondition_failure(error.into());
             }
         };
 
@@ -1074,7 +1537,7 @@ where
             ) {
                 Ok(module) => module,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return ExecutionResult::precondition_failure(error.into());
                 }
             }
         };
@@ -1086,7 +1549,7 @@ where
 
         let input_runtime_args = match deploy_item.session.into_runtime_args() {
             Ok(runtime_args) => runtime_args,
-            Err(error) => return Ok(ExecutionResult::precondition_failure(error.into())),
+            Err(error) => return ExecutionResult::precondition_failure(error.into()),
         };
 
         let mut runtime_args_builder = TransferRuntimeArgsBuilder::new(input_runtime_args);
@@ -1125,17 +1588,17 @@ where
                                 .write(Key::Account(public_key), StoredValue::Account(new_account))
                         }
                         None => {
-                            return Ok(execution_result);
+                            return execution_result;
                         }
                     }
                 }
             },
             Err(error) => {
-                return Ok(ExecutionResult::Failure {
+                return ExecutionResult::Failure {
                     error,
                     effect: Default::default(),
                     cost: Gas::default(),
-                });
+                };
             }
         }
 
@@ -1143,11 +1606,11 @@ where
             match runtime_args_builder.build(&account, correlation_id, Rc::clone(&tracking_copy)) {
                 Ok(runtime_args) => runtime_args,
                 Err(error) => {
-                    return Ok(ExecutionResult::Failure {
+                    return ExecutionResult::Failure {
                         error,
                         effect: Default::default(),
                         cost: Gas::default(),
-                    });
+                    };
                 }
             };
 
@@ -1172,7 +1635,7 @@ where
                 SystemContractCache::clone(&self.system_contract_cache),
             );
 
-        Ok(execution_result)
+        execution_result
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1212,6 +1675,36 @@ where
             Ok(Some(tracking_copy)) => Rc::new(RefCell::new(tracking_copy)),
         };
 
+        Ok(self.deploy_with_tracking_copy(
+            correlation_id,
+            executor,
+            preprocessor,
+            protocol_version,
+            protocol_data,
+            blocktime,
+            deploy_item,
+            tracking_copy,
+        ))
+    }
+
+    /// Executes the payment, session and finalization code of a deploy against the given tracking
+    /// copy.
+    #[allow(clippy::too_many_arguments)]
+    fn deploy_with_tracking_copy<R>(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        preprocessor: &Preprocessor,
+        protocol_version: ProtocolVersion,
+        protocol_data: ProtocolData,
+        blocktime: BlockTime,
+        deploy_item: DeployItem,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+    ) -> ExecutionResult
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
         let base_key = Key::Account(deploy_item.address);
 
         // Get addr bytes from `address` (which is actually a Key)
@@ -1219,9 +1712,7 @@ where
         let account_public_key = match base_key.into_account() {
             Some(account_addr) => account_addr,
             None => {
-                return Ok(ExecutionResult::precondition_failure(
-                    error::Error::Authorization,
-                ));
+                return ExecutionResult::precondition_failure(error::Error::Authorization);
             }
         };
 
@@ -1236,7 +1727,7 @@ where
             Rc::clone(&tracking_copy),
         ) {
             Ok(account) => account,
-            Err(e) => return Ok(ExecutionResult::precondition_failure(e)),
+            Err(e) => return ExecutionResult::precondition_failure(e),
         };
 
         let session = deploy_item.session;
@@ -1256,7 +1747,7 @@ where
         ) {
             Ok(module) => module,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error));
+                return ExecutionResult::precondition_failure(error);
             }
         };
 
@@ -1270,7 +1761,7 @@ where
         {
             Ok(contract) => contract,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error.into()));
+                return ExecutionResult::precondition_failure(error.into());
             }
         };
 
@@ -1284,7 +1775,7 @@ where
             ) {
                 Ok(contract) => contract,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return ExecutionResult::precondition_failure(error.into());
                 }
             };
 
@@ -1304,7 +1795,7 @@ where
         {
             Ok(contract) => contract,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error.into()));
+                return ExecutionResult::precondition_failure(error.into());
             }
         };
 
@@ -1316,7 +1807,7 @@ where
         ) {
             Ok(module) => module,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error.into()));
+                return ExecutionResult::precondition_failure(error.into());
             }
         };
 
@@ -1336,7 +1827,7 @@ where
             {
                 Ok(key) => key,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return ExecutionResult::precondition_failure(error.into());
                 }
             }
         };
@@ -1348,7 +1839,7 @@ where
             .get_purse_balance(correlation_id, account_main_purse_balance_key)
         {
             Ok(balance) => balance,
-            Err(error) => return Ok(ExecutionResult::precondition_failure(error.into())),
+            Err(error) => return ExecutionResult::precondition_failure(error.into()),
         };
 
         let max_payment_cost: Motes = Motes::new(U512::from(MAX_PAYMENT));
@@ -1356,9 +1847,7 @@ where
         // Enforce minimum main purse balance validation
         // validation_spec_5: account main purse minimum balance
         if account_main_purse_balance < max_payment_cost {
-            return Ok(ExecutionResult::precondition_failure(
-                Error::InsufficientPayment,
-            ));
+            return ExecutionResult::precondition_failure(Error::InsufficientPayment);
         }
 
         // Finalization is executed by system account (currently genesis account)
@@ -1394,11 +1883,11 @@ where
                     match self.state.get_protocol_data(protocol_version) {
                         Ok(Some(protocol_data)) => protocol_data.standard_payment(),
                         Ok(None) => {
-                            return Ok(ExecutionResult::precondition_failure(
+                            return ExecutionResult::precondition_failure(
                                 Error::InvalidProtocolVersion(protocol_version),
-                            ));
+                            );
                         }
-                        Err(_) => return Ok(ExecutionResult::precondition_failure(Error::Deploy)),
+                        Err(_) => return ExecutionResult::precondition_failure(Error::Deploy),
                     };
 
                 // if "use-system-contracts" is false, "do_nothing" wasm is returned
@@ -1427,7 +1916,7 @@ where
             let payment_module = match maybe_payment_module {
                 Ok(module) => module,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error));
+                    return ExecutionResult::precondition_failure(error);
                 }
             };
 
@@ -1471,7 +1960,7 @@ where
                 Err(e) => {
                     let exec_err: execution::Error = e.into();
                     warn!("Unable to deserialize arguments: {:?}", exec_err);
-                    return Ok(ExecutionResult::precondition_failure(exec_err.into()));
+                    return ExecutionResult::precondition_failure(exec_err.into());
                 }
             };
 
@@ -1531,7 +2020,7 @@ where
                 ) {
                     Ok((_instance, runtime)) => runtime,
                     Err(error) => {
-                        return Ok(ExecutionResult::precondition_failure(Error::Exec(error)));
+                        return ExecutionResult::precondition_failure(Error::Exec(error));
                     }
                 };
 
@@ -1562,7 +2051,7 @@ where
             let payment_purse_key: Key =
                 match proof_of_stake_contract.named_keys().get(POS_PAYMENT_PURSE) {
                     Some(key) => *key,
-                    None => return Ok(ExecutionResult::precondition_failure(Error::Deploy)),
+                    None => return ExecutionResult::precondition_failure(Error::Deploy),
                 };
 
             let purse_balance_key = match tracking_copy
@@ -1571,7 +2060,7 @@ where
             {
                 Ok(key) => key,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return ExecutionResult::precondition_failure(error.into());
                 }
             };
 
@@ -1581,7 +2070,7 @@ where
             {
                 Ok(balance) => balance,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return ExecutionResult::precondition_failure(error.into());
                 }
             }
         };
@@ -1596,7 +2085,7 @@ where
                     match proof_of_stake_contract.named_keys().get(POS_REWARDS_PURSE) {
                         Some(key) => *key,
                         None => {
-                            return Ok(ExecutionResult::precondition_failure(Error::Deploy));
+                            return ExecutionResult::precondition_failure(Error::Deploy);
                         }
                     };
 
@@ -1606,7 +2095,7 @@ where
                 {
                     Ok(key) => key,
                     Err(error) => {
-                        return Ok(ExecutionResult::precondition_failure(error.into()));
+                        return ExecutionResult::precondition_failure(error.into());
                     }
                 }
             };
@@ -1615,13 +2104,13 @@ where
                 ForcedTransferResult::InsufficientPayment => Error::InsufficientPayment,
                 ForcedTransferResult::PaymentFailure => payment_result.take_error().unwrap(),
             };
-            return Ok(ExecutionResult::new_payment_code_error(
+            return ExecutionResult::new_payment_code_error(
                 error,
                 max_payment_cost,
                 account_main_purse_balance,
                 account_main_purse_balance_key,
                 rewards_purse_balance_key,
-            ));
+            );
         }
 
         // Transfer the contents of the rewards purse to block proposer
@@ -1670,7 +2159,7 @@ where
             Err(e) => {
                 let exec_err: execution::Error = e.into();
                 warn!("Unable to deserialize session arguments: {:?}", exec_err);
-                return Ok(ExecutionResult::precondition_failure(exec_err.into()));
+                return ExecutionResult::precondition_failure(exec_err.into());
             }
         };
         let session_result = {
@@ -1742,7 +2231,7 @@ where
                 .get_contract(correlation_id, proof_of_stake_hash)
             {
                 Ok(info) => info,
-                Err(error) => return Ok(ExecutionResult::precondition_failure(error.into())),
+                Err(error) => return ExecutionResult::precondition_failure(error.into()),
             };
 
             let mut proof_of_stake_keys = proof_of_stake_contract.named_keys().to_owned();
@@ -1773,15 +2262,13 @@ where
 
         execution_result_builder.set_finalize_execution_result(finalize_result);
 
-        // We panic here to indicate that the builder was not used properly.
-        let ret = execution_result_builder
-            .build(tracking_copy.borrow().reader(), correlation_id)
-            .expect("ExecutionResultBuilder not initialized properly");
-
         // NOTE: payment_code_spec_5_a is enforced in execution_result_builder.build()
         // payment_code_spec_6: return properly combined set of transforms and
         // appropriate error
-        Ok(ret)
+        // We panic here to indicate that the builder was not used properly.
+        execution_result_builder
+            .build(tracking_copy.borrow().reader(), correlation_id)
+            .expect("ExecutionResultBuilder not initialized properly")
     }
 
     pub fn apply_effect(
@@ -1799,3 +2286,58 @@ where
         }
     }
 }
+
+impl EngineState<LmdbGlobalState> {
+    /// Removes the parts of global state which aren't reachable from any of the given state
+    /// hashes.
+    ///
+    /// Returns the number of tries removed.
+    pub fn prune_global_state(
+        &self,
+        correlation_id: CorrelationId,
+        retained_state_hashes: &[Blake2bHash],
+    ) -> Result<usize, Error> {
+        let removed_count = self
+            .state
+            .prune_unreachable_tries(correlation_id, retained_state_hashes)?;
+        Ok(removed_count)
+    }
+
+    /// Reads the serialized trie stored under the given hash, e.g. to send it to a peer
+    /// synchronizing global state.
+    pub fn get_trie(
+        &self,
+        correlation_id: CorrelationId,
+        trie_hash: &Blake2bHash,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let maybe_trie_bytes = self.state.get_trie_bytes(correlation_id, trie_hash)?;
+        Ok(maybe_trie_bytes)
+    }
+
+    /// Stores the given serialized trie, e.g. one received from a peer while synchronizing global
+    /// state.  It only becomes part of the trie store once all its descendants do.
+    ///
+    /// Returns the hashes of the tries it points to which are not stored yet.
+    pub fn put_trie(
+        &self,
+        correlation_id: CorrelationId,
+        trie_bytes: &[u8],
+    ) -> Result<Vec<Blake2bHash>, Error> {
+        let missing_children = self.state.put_trie_bytes(correlation_id, trie_bytes)?;
+        Ok(missing_children)
+    }
+
+    /// Returns the hashes of the tries reachable from the given global state hash which are not
+    /// stored yet, i.e. which remain to be synchronized.  Once none do, the synchronized global
+    /// state becomes available.
+    pub fn missing_trie_descendants(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: &Blake2bHash,
+    ) -> Result<Vec<Blake2bHash>, Error> {
+        let missing = self
+            .state
+            .missing_trie_descendants(correlation_id, state_hash)?;
+        Ok(missing)
+    }
+}
@@ -0,0 +1,493 @@
+//! Support for executing the deploys of a block optimistically in parallel.
+//!
+//! Each deploy is first executed against the block's pre-state, with the keys it reads from global
+//! state being recorded.  The results are then taken in block order: a deploy whose accessed keys
+//! don't overlap with the keys changed by the deploys before it would have behaved identically if
+//! executed serially, so its result is kept.  Otherwise it is re-executed on top of the effects of
+//! the deploys before it.  Keys which are only ever added to, such as the balance of the rewards
+//! purse every deploy pays into, don't count as overlapping.
+
+use std::{cell::RefCell, collections::BTreeSet};
+
+use casper_types::Key;
+
+use crate::{
+    core::engine_state::{execution_effect::ExecutionEffect, op::Op, ExecutionResult},
+    shared::{newtypes::CorrelationId, stored_value::StoredValue, transform::Transform},
+    storage::global_state::StateReader,
+};
+
+/// A reader which records every key read through it.
+pub(super) struct RecordingReader<R> {
+    reader: R,
+    read_keys: RefCell<BTreeSet<Key>>,
+}
+
+impl<R> RecordingReader<R> {
+    pub(super) fn new(reader: R) -> Self {
+        RecordingReader {
+            reader,
+            read_keys: RefCell::new(BTreeSet::new()),
+        }
+    }
+
+    /// Returns the keys read so far, whether a value was found for them or not.
+    pub(super) fn take_read_keys(&self) -> BTreeSet<Key> {
+        self.read_keys.replace(BTreeSet::new())
+    }
+}
+
+impl<R: StateReader<Key, StoredValue>> StateReader<Key, StoredValue> for RecordingReader<R> {
+    type Error = R::Error;
+
+    fn read(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<StoredValue>, Self::Error> {
+        self.read_keys.borrow_mut().insert(key.normalize());
+        self.reader.read(correlation_id, key)
+    }
+}
+
+/// The result of executing a deploy against the block's pre-state.
+pub(super) struct OptimisticExecution {
+    execution_result: ExecutionResult,
+    /// The keys read from the pre-state, along with the keys in the effect's ops and transforms,
+    /// other than the ones in `added_keys`.
+    dependencies: BTreeSet<Key>,
+    /// The keys the deploy only added to.
+    added_keys: BTreeSet<Key>,
+}
+
+impl OptimisticExecution {
+    pub(super) fn new(execution_result: ExecutionResult, mut read_keys: BTreeSet<Key>) -> Self {
+        let effect = execution_result.effect();
+        let added_keys: BTreeSet<Key> = effect
+            .ops
+            .iter()
+            .filter(|(_, op)| **op == Op::Add)
+            .map(|(key, _)| *key)
+            .collect();
+        read_keys.extend(effect.ops.keys().chain(effect.transforms.keys()).copied());
+        let dependencies = read_keys.difference(&added_keys).copied().collect();
+        OptimisticExecution {
+            execution_result,
+            dependencies,
+            added_keys,
+        }
+    }
+
+    /// Returns the execution result, unless it might depend on the changes made by the deploys
+    /// before it, in which case the deploy has to be re-executed.
+    ///
+    /// Additions commute, so keys which both this deploy and the ones before it only added to don't
+    /// conflict.  The tracking copy reads such a key only to check that it exists and holds a value
+    /// of an addable type, neither of which another addition changes.  Without this, every deploy
+    /// would conflict with the ones before it on the rewards purse they all pay into.
+    pub(super) fn into_result(self, changed_keys: &ChangedKeys) -> Option<ExecutionResult> {
+        if self.dependencies.is_disjoint(&changed_keys.written)
+            && self.dependencies.is_disjoint(&changed_keys.added)
+            && self.added_keys.is_disjoint(&changed_keys.written)
+        {
+            Some(self.execution_result)
+        } else {
+            None
+        }
+    }
+}
+
+/// The keys changed by the deploys executed so far.
+#[derive(Default)]
+pub(super) struct ChangedKeys {
+    /// The keys changed other than by additions alone.
+    written: BTreeSet<Key>,
+    /// The keys which have only been added to.
+    added: BTreeSet<Key>,
+}
+
+impl ChangedKeys {
+    /// Records the keys changed by the given effect.
+    pub(super) fn record(&mut self, effect: &ExecutionEffect) {
+        for (key, transform) in effect.transforms.iter() {
+            if *transform == Transform::Identity {
+                continue;
+            }
+            if effect.ops.get(key) == Some(&Op::Add) && !self.written.contains(key) {
+                self.added.insert(*key);
+            } else {
+                self.added.remove(key);
+                self.written.insert(*key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        fmt::Debug,
+    };
+
+    use casper_types::{account::AccountHash, CLValue, Key, U512};
+
+    use super::{ChangedKeys, OptimisticExecution, RecordingReader};
+    use crate::{
+        core::{
+            engine_state::{
+                execution_effect::ExecutionEffect, execution_result::ExecutionResultBuilder,
+                op::Op, Error, ExecutionResult,
+            },
+            execution,
+            tracking_copy::{AddResult, TrackingCopy, TrackingCopyExt},
+        },
+        shared::{
+            gas::Gas, newtypes::CorrelationId, stored_value::StoredValue, transform::Transform,
+        },
+        storage::global_state::StateReader,
+    };
+
+    const ACCOUNT_COUNT: u8 = 16;
+    const INITIAL_BALANCE: u64 = 1_000;
+    const PAYMENT_PURSE: Key = Key::Hash([100; 32]);
+    const REWARDS_PURSE: Key = Key::Hash([101; 32]);
+
+    struct MapReader(BTreeMap<Key, StoredValue>);
+
+    impl StateReader<Key, StoredValue> for MapReader {
+        type Error = execution::Error;
+
+        fn read(
+            &self,
+            _correlation_id: CorrelationId,
+            key: &Key,
+        ) -> Result<Option<StoredValue>, Self::Error> {
+            Ok(self.0.get(key).cloned())
+        }
+    }
+
+    fn stored_value(value: u64) -> StoredValue {
+        StoredValue::CLValue(CLValue::from_t(value).unwrap())
+    }
+
+    fn balance(value: U512) -> StoredValue {
+        StoredValue::CLValue(CLValue::from_t(value).unwrap())
+    }
+
+    fn purse(account: u8) -> Key {
+        Key::Hash([account; 32])
+    }
+
+    /// The purses of the accounts and the rewards purse start with the same balance, and the
+    /// payment purse empty.
+    fn pre_state() -> BTreeMap<Key, StoredValue> {
+        let mut pre_state: BTreeMap<Key, StoredValue> = (0..ACCOUNT_COUNT)
+            .map(purse)
+            .chain(vec![REWARDS_PURSE])
+            .map(|key| (key, balance(U512::from(INITIAL_BALANCE))))
+            .collect();
+        pre_state.insert(PAYMENT_PURSE, balance(U512::zero()));
+        pre_state
+    }
+
+    /// A deploy transferring `amount` between the purses of two accounts, modelled on the payment,
+    /// session and finalization phases the engine runs for each deploy.
+    struct Transfer {
+        source: u8,
+        target: u8,
+        amount: u64,
+        payment: u64,
+        cost: u64,
+    }
+
+    fn read_balance<R>(tracking_copy: &mut TrackingCopy<R>, key: Key) -> U512
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Debug,
+    {
+        match tracking_copy.read(CorrelationId::new(), &key).unwrap() {
+            Some(StoredValue::CLValue(cl_value)) => cl_value.into_t().unwrap(),
+            _ => panic!("should have a balance"),
+        }
+    }
+
+    /// Moves `amount` from the `source` to the `target` purse the way the mint does, returning
+    /// `false` if the source purse holds too little.
+    fn transfer<R>(
+        tracking_copy: &mut TrackingCopy<R>,
+        source: Key,
+        target: Key,
+        amount: U512,
+    ) -> bool
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Debug,
+    {
+        let source_balance = read_balance(tracking_copy, source);
+        if source_balance < amount {
+            return false;
+        }
+        tracking_copy.write(source, balance(source_balance - amount));
+        let add_result = tracking_copy
+            .add(CorrelationId::new(), target, balance(amount))
+            .unwrap();
+        assert!(matches!(add_result, AddResult::Success));
+        true
+    }
+
+    /// Pays the cost into the rewards purse and refunds the rest of the payment.
+    fn finalize<R>(tracking_copy: &mut TrackingCopy<R>, account: Key, cost: U512) -> ExecutionResult
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Debug,
+    {
+        let refund = read_balance(tracking_copy, PAYMENT_PURSE) - cost;
+        assert!(transfer(tracking_copy, PAYMENT_PURSE, REWARDS_PURSE, cost));
+        assert!(transfer(tracking_copy, PAYMENT_PURSE, account, refund));
+        ExecutionResult::Success {
+            effect: tracking_copy.effect(),
+            cost: Gas::default(),
+        }
+    }
+
+    fn execute<R>(deploy: &Transfer, tracking_copy: &TrackingCopy<R>) -> ExecutionResult
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Debug,
+    {
+        let account = purse(deploy.source);
+        let mut builder = ExecutionResultBuilder::new();
+
+        let mut payment_tracking_copy = tracking_copy.fork();
+        if !transfer(
+            &mut payment_tracking_copy,
+            account,
+            PAYMENT_PURSE,
+            U512::from(deploy.payment),
+        ) {
+            return ExecutionResult::precondition_failure(Error::InsufficientPayment);
+        }
+        builder.set_payment_execution_result(ExecutionResult::Success {
+            effect: payment_tracking_copy.effect(),
+            cost: Gas::new(U512::from(deploy.cost)),
+        });
+
+        let mut session_tracking_copy = payment_tracking_copy.fork();
+        let cost = U512::from(deploy.cost);
+        let finalize_result = if transfer(
+            &mut session_tracking_copy,
+            account,
+            purse(deploy.target),
+            U512::from(deploy.amount),
+        ) {
+            builder.set_session_execution_result(ExecutionResult::Success {
+                effect: session_tracking_copy.effect(),
+                cost: Gas::default(),
+            });
+            finalize(&mut session_tracking_copy.fork(), account, cost)
+        } else {
+            builder.set_session_execution_result(ExecutionResult::Failure {
+                error: Error::Mint("insufficient funds".to_string()),
+                effect: ExecutionEffect::default(),
+                cost: Gas::default(),
+            });
+            finalize(&mut payment_tracking_copy.fork(), account, cost)
+        };
+        builder.set_finalize_execution_result(finalize_result);
+
+        builder.build(&tracking_copy, CorrelationId::new()).unwrap()
+    }
+
+    /// Executes the deploys in block order the way `execute_block` does, re-executing the ones
+    /// whose optimistic execution can't be kept.  Returns the results, the post-state and the
+    /// number of deploys re-executed.
+    fn execute_block(
+        deploys: &[Transfer],
+        optimistic_executions: Vec<Option<OptimisticExecution>>,
+    ) -> (Vec<ExecutionResult>, Vec<Option<StoredValue>>, usize) {
+        let correlation_id = CorrelationId::new();
+        let pre_state = pre_state();
+        let keys: Vec<Key> = pre_state.keys().copied().collect();
+        let mut tracking_copy = TrackingCopy::new(MapReader(pre_state));
+        let mut changed_keys = ChangedKeys::default();
+        let mut execution_results = vec![];
+        let mut reexecuted_count = 0;
+
+        for (deploy, maybe_optimistic_execution) in deploys.iter().zip(optimistic_executions) {
+            let execution_result = match maybe_optimistic_execution
+                .and_then(|execution| execution.into_result(&changed_keys))
+            {
+                Some(execution_result) => execution_result,
+                None => {
+                    reexecuted_count += 1;
+                    execute(deploy, &tracking_copy)
+                }
+            };
+            changed_keys.record(execution_result.effect());
+            let add_result = tracking_copy
+                .apply_transforms(correlation_id, execution_result.effect().transforms.clone())
+                .unwrap();
+            assert!(matches!(add_result, AddResult::Success));
+            execution_results.push(execution_result);
+        }
+
+        let post_state = keys
+            .iter()
+            .map(|key| tracking_copy.get(correlation_id, key).unwrap())
+            .collect();
+        (execution_results, post_state, reexecuted_count)
+    }
+
+    fn execute_optimistically(deploys: &[Transfer]) -> Vec<Option<OptimisticExecution>> {
+        deploys
+            .iter()
+            .map(|deploy| {
+                let tracking_copy = TrackingCopy::new(RecordingReader::new(MapReader(pre_state())));
+                let execution_result = execute(deploy, &tracking_copy);
+                let read_keys = tracking_copy.reader().take_read_keys();
+                Some(OptimisticExecution::new(execution_result, read_keys))
+            })
+            .collect()
+    }
+
+    /// Executes the deploys both serially and optimistically, checks the outcomes are identical and
+    /// returns the number of deploys re-executed.
+    fn assert_same_as_serial_execution(deploys: &[Transfer]) -> usize {
+        let serial_executions = deploys.iter().map(|_| None).collect();
+        let (serial_results, serial_post_state, serial_count) =
+            execute_block(deploys, serial_executions);
+        assert_eq!(serial_count, deploys.len());
+
+        let (results, post_state, reexecuted_count) =
+            execute_block(deploys, execute_optimistically(deploys));
+        assert_eq!(post_state, serial_post_state);
+        assert_eq!(results.len(), serial_results.len());
+        for (result, serial_result) in results.iter().zip(&serial_results) {
+            assert_eq!(result.is_success(), serial_result.is_success());
+            assert_eq!(result.cost(), serial_result.cost());
+            assert_eq!(result.effect(), serial_result.effect());
+        }
+        reexecuted_count
+    }
+
+    #[test]
+    fn should_record_keys_read_without_ops() {
+        let correlation_id = CorrelationId::new();
+        let existing_key = Key::Hash([1; 32]);
+        let missing_key = Key::Hash([2; 32]);
+        let mut values = BTreeMap::new();
+        values.insert(existing_key, stored_value(1));
+        let missing_account = AccountHash::new([3; 32]);
+        let mut tracking_copy = TrackingCopy::new(RecordingReader::new(MapReader(values)));
+
+        // `get` records no op, but the key must still count as accessed.
+        assert!(tracking_copy
+            .get(correlation_id, &existing_key)
+            .unwrap()
+            .is_some());
+        assert!(tracking_copy
+            .read(correlation_id, &missing_key)
+            .unwrap()
+            .is_none());
+        // Cached values aren't read from the reader again.
+        assert!(tracking_copy
+            .get(correlation_id, &existing_key)
+            .unwrap()
+            .is_some());
+        assert!(tracking_copy
+            .get_account(correlation_id, missing_account)
+            .is_err());
+
+        let read_keys = tracking_copy.reader().take_read_keys();
+        let expected: BTreeSet<Key> =
+            vec![existing_key, missing_key, Key::Account(missing_account)]
+                .into_iter()
+                .collect();
+        assert_eq!(read_keys, expected);
+        assert!(tracking_copy.reader().take_read_keys().is_empty());
+    }
+
+    #[test]
+    fn should_keep_result_only_without_conflicts() {
+        let read_key = Key::Hash([1; 32]);
+        let written_key = Key::Hash([2; 32]);
+        let added_key = Key::Hash([3; 32]);
+        let unrelated_key = Key::Hash([4; 32]);
+
+        let mut effect = ExecutionEffect::default();
+        effect.ops.insert(written_key, Op::Write);
+        effect
+            .transforms
+            .insert(written_key, Transform::Write(stored_value(2)));
+        effect.ops.insert(added_key, Op::Add);
+        effect.transforms.insert(added_key, Transform::AddUInt64(1));
+
+        let execution = || {
+            OptimisticExecution::new(
+                ExecutionResult::Success {
+                    effect: effect.clone(),
+                    cost: Gas::default(),
+                },
+                vec![read_key, added_key].into_iter().collect(),
+            )
+        };
+        let changed_keys = |written: &[Key], added: &[Key]| ChangedKeys {
+            written: written.iter().copied().collect(),
+            added: added.iter().copied().collect(),
+        };
+
+        let mut recorded = ChangedKeys::default();
+        recorded.record(&effect);
+        assert_eq!(recorded.written, vec![written_key].into_iter().collect());
+        assert_eq!(recorded.added, vec![added_key].into_iter().collect());
+
+        for no_conflict in &[
+            changed_keys(&[unrelated_key], &[]),
+            changed_keys(&[], &[unrelated_key, added_key]),
+        ] {
+            assert!(execution().into_result(no_conflict).is_some());
+        }
+        for conflict in &[
+            changed_keys(&[read_key], &[]),
+            changed_keys(&[], &[read_key]),
+            changed_keys(&[written_key], &[]),
+            changed_keys(&[], &[written_key]),
+            changed_keys(&[added_key], &[]),
+        ] {
+            assert!(execution().into_result(conflict).is_none());
+        }
+    }
+
+    #[test]
+    fn should_not_reexecute_deploys_only_adding_to_shared_purses() {
+        // Every deploy pays into the rewards purse, but the accounts are otherwise independent.
+        let deploys: Vec<Transfer> = (0..ACCOUNT_COUNT / 2)
+            .map(|account| Transfer {
+                source: account,
+                target: account + ACCOUNT_COUNT / 2,
+                amount: 100,
+                payment: 50,
+                cost: 10 + u64::from(account),
+            })
+            .collect();
+        assert_eq!(assert_same_as_serial_execution(&deploys), 0);
+    }
+
+    #[test]
+    fn should_match_serial_execution() {
+        // Deploys spending from and paying into the same accounts, including ones whose session
+        // fails or which can't pay, in a fixed pseudo-random order.
+        let deploys: Vec<Transfer> = (0..64u64)
+            .map(|index| Transfer {
+                source: ((index * 7) % u64::from(ACCOUNT_COUNT)) as u8,
+                target: ((index * 11 + 3) % u64::from(ACCOUNT_COUNT)) as u8,
+                amount: (index * 37) % 400,
+                payment: 20 + (index * 13) % 300,
+                cost: (index * 5) % 20,
+            })
+            .collect();
+        let reexecuted_count = assert_same_as_serial_execution(&deploys);
+        assert!(reexecuted_count < deploys.len() - 1);
+    }
+}
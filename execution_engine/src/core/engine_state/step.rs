@@ -0,0 +1,125 @@
+use std::fmt;
+
+use casper_types::{account::AccountHash, bytesrepr, Key, ProtocolVersion, PublicKey, U512};
+
+use crate::{
+    core::engine_state::execution_effect::ExecutionEffect,
+    shared::{newtypes::Blake2bHash, TypeMismatch},
+    storage::global_state::CommitResult,
+};
+
+/// A block reward to be minted into the main purse of the account with the given hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RewardItem {
+    pub account_hash: AccountHash,
+    pub amount: U512,
+}
+
+impl RewardItem {
+    pub fn new(account_hash: AccountHash, amount: U512) -> Self {
+        RewardItem {
+            account_hash,
+            amount,
+        }
+    }
+}
+
+/// The system operations to run as the system account at the end of a block: slashing
+/// equivocators, paying out rewards and, at the end of an era, running the auction.
+#[derive(Clone, Debug)]
+pub struct StepRequest {
+    pre_state_hash: Blake2bHash,
+    block_time: u64,
+    protocol_version: ProtocolVersion,
+    slash_items: Vec<PublicKey>,
+    reward_items: Vec<RewardItem>,
+    run_auction: bool,
+}
+
+impl StepRequest {
+    pub fn new(
+        pre_state_hash: Blake2bHash,
+        block_time: u64,
+        protocol_version: ProtocolVersion,
+        slash_items: Vec<PublicKey>,
+        reward_items: Vec<RewardItem>,
+        run_auction: bool,
+    ) -> Self {
+        StepRequest {
+            pre_state_hash,
+            block_time,
+            protocol_version,
+            slash_items,
+            reward_items,
+            run_auction,
+        }
+    }
+
+    pub fn pre_state_hash(&self) -> Blake2bHash {
+        self.pre_state_hash
+    }
+
+    pub fn block_time(&self) -> u64 {
+        self.block_time
+    }
+
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
+
+    pub fn slash_items(&self) -> &[PublicKey] {
+        &self.slash_items
+    }
+
+    pub fn reward_items(&self) -> &[RewardItem] {
+        &self.reward_items
+    }
+
+    pub fn run_auction(&self) -> bool {
+        self.run_auction
+    }
+}
+
+#[derive(Debug)]
+pub enum StepResult {
+    RootNotFound,
+    KeyNotFound(Key),
+    TypeMismatch(TypeMismatch),
+    Serialization(bytesrepr::Error),
+    Success {
+        post_state_hash: Blake2bHash,
+        effect: ExecutionEffect,
+    },
+}
+
+impl fmt::Display for StepResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            StepResult::RootNotFound => write!(f, "Root not found"),
+            StepResult::KeyNotFound(key) => write!(f, "Key not found: {}", key),
+            StepResult::TypeMismatch(type_mismatch) => {
+                write!(f, "Type mismatch: {:?}", type_mismatch)
+            }
+            StepResult::Serialization(error) => write!(f, "Serialization error: {:?}", error),
+            StepResult::Success {
+                post_state_hash,
+                effect,
+            } => write!(f, "Success: {} {:?}", post_state_hash, effect),
+        }
+    }
+}
+
+impl StepResult {
+    pub fn from_commit_result(commit_result: CommitResult, effect: ExecutionEffect) -> Self {
+        match commit_result {
+            CommitResult::RootNotFound => StepResult::RootNotFound,
+            CommitResult::KeyNotFound(key) => StepResult::KeyNotFound(key),
+            CommitResult::TypeMismatch(type_mismatch) => StepResult::TypeMismatch(type_mismatch),
+            CommitResult::Serialization(error) => StepResult::Serialization(error),
+            CommitResult::Success { state_root, .. } => StepResult::Success {
+                post_state_hash: state_root,
+                effect,
+            },
+        }
+    }
+}
@@ -57,6 +57,7 @@ impl UpgradeResult {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UpgradeConfig {
     pre_state_hash: Blake2bHash,
+    block_time: u64,
     current_protocol_version: ProtocolVersion,
     new_protocol_version: ProtocolVersion,
     upgrade_installer_args: Option<Vec<u8>>,
@@ -68,6 +69,7 @@ pub struct UpgradeConfig {
 impl UpgradeConfig {
     pub fn new(
         pre_state_hash: Blake2bHash,
+        block_time: u64,
         current_protocol_version: ProtocolVersion,
         new_protocol_version: ProtocolVersion,
         upgrade_installer_args: Option<Vec<u8>>,
@@ -77,6 +79,7 @@ impl UpgradeConfig {
     ) -> Self {
         UpgradeConfig {
             pre_state_hash,
+            block_time,
             current_protocol_version,
             new_protocol_version,
             upgrade_installer_args,
@@ -90,6 +93,10 @@ impl UpgradeConfig {
         self.pre_state_hash
     }
 
+    pub fn block_time(&self) -> u64 {
+        self.block_time
+    }
+
     pub fn current_protocol_version(&self) -> ProtocolVersion {
         self.current_protocol_version
     }
@@ -326,6 +326,47 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
         }
     }
 
+    /// Applies the given transforms, e.g. the effect of a deploy executed against a fork of this
+    /// `TrackingCopy`, on top of the current state, in the same way committing them to global
+    /// state would. The transforms are recorded as they are, so that the effect of this
+    /// `TrackingCopy` accumulates the effects of all transforms applied to it.
+    ///
+    /// Ok(AddResult::KeyNotFound) represents a missing key to which a transform other than a
+    /// write was to be applied.  Err(error) is reserved for unexpected errors when accessing
+    /// global state.
+    pub fn apply_transforms(
+        &mut self,
+        correlation_id: CorrelationId,
+        transforms: AdditiveMap<Key, Transform>,
+    ) -> Result<AddResult, R::Error> {
+        for (key, transform) in transforms {
+            let normalized_key = key.normalize();
+            let (op, new_value) = match transform {
+                Transform::Identity => continue,
+                Transform::Write(ref value) => (Op::Write, value.clone()),
+                _ => {
+                    let current_value = match self.get(correlation_id, &normalized_key)? {
+                        None => return Ok(AddResult::KeyNotFound(normalized_key)),
+                        Some(current_value) => current_value,
+                    };
+                    match transform.clone().apply(current_value) {
+                        Ok(new_value) => (Op::Add, new_value),
+                        Err(transform::Error::TypeMismatch(type_mismatch)) => {
+                            return Ok(AddResult::TypeMismatch(type_mismatch))
+                        }
+                        Err(transform::Error::Serialization(error)) => {
+                            return Ok(AddResult::Serialization(error))
+                        }
+                    }
+                }
+            };
+            self.cache.insert_write(normalized_key, new_value);
+            self.ops.insert_add(normalized_key, op);
+            self.fns.insert_add(normalized_key, transform);
+        }
+        Ok(AddResult::Success)
+    }
+
     pub fn effect(&self) -> ExecutionEffect {
         ExecutionEffect::new(self.ops.clone(), self.fns.clone())
     }
@@ -287,6 +287,46 @@ fn tracking_copy_aw() {
     assert_eq!(tc.ops.get(&k), Some(&Op::Write));
 }
 
+#[test]
+fn tracking_copy_apply_transforms() {
+    let correlation_id = CorrelationId::new();
+    let three = StoredValue::CLValue(CLValue::from_t(3_i32).unwrap());
+    let db = CountingDb::new_init(three);
+    let mut tc = TrackingCopy::new(db);
+    let k1 = Key::Hash([0u8; 32]);
+    let k2 = Key::Hash([1u8; 32]);
+
+    let two = StoredValue::CLValue(CLValue::from_t(2_i32).unwrap());
+    let seven = StoredValue::CLValue(CLValue::from_t(7_i32).unwrap());
+
+    // applying the effect of a fork should update the values and record the transforms
+    let transforms = {
+        let mut fork = tc.fork();
+        let _ = fork.add(correlation_id, k1, two.clone());
+        fork.write(k2, seven.clone());
+        fork.effect().transforms
+    };
+    let result = tc.apply_transforms(correlation_id, transforms);
+    assert_matches!(result, Ok(AddResult::Success));
+    assert_eq!(tc.fns.get(&k1), Some(&Transform::AddInt32(2)));
+    assert_eq!(tc.ops.get(&k1), Some(&Op::Add));
+    assert_eq!(tc.fns.get(&k2), Some(&Transform::Write(seven.clone())));
+    assert_eq!(tc.ops.get(&k2), Some(&Op::Write));
+    let five = StoredValue::CLValue(CLValue::from_t(5_i32).unwrap());
+    assert_eq!(tc.get(correlation_id, &k1), Ok(Some(five)));
+
+    // applying the effect of a later fork should accumulate
+    let transforms = {
+        let mut fork = tc.fork();
+        let _ = fork.add(correlation_id, k1, two);
+        fork.effect().transforms
+    };
+    let result = tc.apply_transforms(correlation_id, transforms);
+    assert_matches!(result, Ok(AddResult::Success));
+    assert_eq!(tc.fns.get(&k1), Some(&Transform::AddInt32(4)));
+    assert_eq!(tc.get(correlation_id, &k1), Ok(Some(seven)));
+}
+
 proptest! {
     #[test]
     fn query_empty_path(k in key_arb(), missing_key in key_arb(), v in stored_value_arb()) {
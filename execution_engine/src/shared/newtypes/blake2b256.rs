@@ -5,11 +5,12 @@ use blake2::{
     digest::{Input, VariableOutput},
     VarBlake2b,
 };
+use serde::{Deserialize, Serialize};
 
 use casper_types::bytesrepr::{self, FromBytes, ToBytes};
 
 /// Represents a 32-byte BLAKE2b hash digest
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
 pub struct Blake2bHash([u8; Blake2bHash::LENGTH]);
 
 impl Blake2bHash {
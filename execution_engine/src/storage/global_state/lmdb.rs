@@ -1,4 +1,11 @@
-use std::{ops::Deref, sync::Arc};
+use std::{
+    collections::HashSet,
+    mem,
+    ops::Deref,
+    sync::{Arc, Mutex},
+};
+
+use lmdb::{Cursor, Database, DatabaseFlags};
 
 use crate::shared::{
     additive_map::AdditiveMap,
@@ -6,7 +13,10 @@ use crate::shared::{
     stored_value::StoredValue,
     transform::Transform,
 };
-use casper_types::{Key, ProtocolVersion};
+use casper_types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    Key, ProtocolVersion,
+};
 
 use crate::storage::{
     error,
@@ -14,7 +24,7 @@ use crate::storage::{
     protocol_data::ProtocolData,
     protocol_data_store::lmdb::LmdbProtocolDataStore,
     store::Store,
-    transaction_source::{lmdb::LmdbEnvironment, Transaction, TransactionSource},
+    transaction_source::{lmdb::LmdbEnvironment, Readable, Transaction, TransactionSource},
     trie::{operations::create_hashed_empty_trie, Trie},
     trie_store::{
         lmdb::LmdbTrieStore,
@@ -22,11 +32,41 @@ use crate::storage::{
     },
 };
 
+/// The maximum number of tries removed in a single write transaction when pruning.
+const PRUNE_BATCH_SIZE: usize = 1_000;
+
+/// The name of the store holding the tries of a global state being synchronized.
+const SYNC_STORE_NAME: &str = "SYNC";
+
 pub struct LmdbGlobalState {
     pub environment: Arc<LmdbEnvironment>,
     pub trie_store: Arc<LmdbTrieStore>,
     pub protocol_data_store: Arc<LmdbProtocolDataStore>,
     pub empty_root_hash: Blake2bHash,
+    /// The tries stored while synchronizing a global state whose descendants weren't all in the
+    /// trie store yet.  They are moved there once the whole state is, so that a trie in the trie
+    /// store always has all its descendants there too.
+    sync_store: LmdbTrieStore,
+    /// The roots in use recently, if tracked, which are retained when pruning.
+    used_roots: Option<Mutex<UsedRoots>>,
+}
+
+/// The roots of the global states checked out or committed to since the previous pruning started.
+///
+/// A pruning retains these along with everything reachable from them, as they may still be in
+/// use, e.g. as the pre-state of a block being executed or as an intermediate post-state of one of
+/// its deploys.
+#[derive(Default)]
+struct UsedRoots {
+    /// The roots used before the current or most recent pruning started.
+    previous: HashSet<Blake2bHash>,
+    /// The roots used since the current or most recent pruning started.
+    current: HashSet<Blake2bHash>,
+    /// The post-state of the latest commit, which is always retained, as the next commit is likely
+    /// to be based on it, however long it takes.
+    latest: Option<Blake2bHash>,
+    /// Whether a pruning is in progress, in which case stored tries are recorded as used too.
+    pruning: bool,
 }
 
 /// Represents a "view" of global state at a particular root hash.
@@ -50,10 +90,13 @@ impl LmdbGlobalState {
             txn.commit()?;
             root_hash
         };
+        let sync_store =
+            LmdbTrieStore::new(&environment, Some(SYNC_STORE_NAME), DatabaseFlags::empty())?;
         Ok(LmdbGlobalState::new(
             environment,
             trie_store,
             protocol_data_store,
+            sync_store,
             root_hash,
         ))
     }
@@ -64,6 +107,7 @@ impl LmdbGlobalState {
         environment: Arc<LmdbEnvironment>,
         trie_store: Arc<LmdbTrieStore>,
         protocol_data_store: Arc<LmdbProtocolDataStore>,
+        sync_store: LmdbTrieStore,
         empty_root_hash: Blake2bHash,
     ) -> Self {
         LmdbGlobalState {
@@ -71,7 +115,295 @@ impl LmdbGlobalState {
             trie_store,
             protocol_data_store,
             empty_root_hash,
+            sync_store,
+            used_roots: None,
+        }
+    }
+
+    /// Tracks the roots in use, so that pruning can run concurrently with execution.
+    pub fn with_used_roots_tracking(mut self) -> Self {
+        self.used_roots = Some(Mutex::new(UsedRoots::default()));
+        self
+    }
+
+    /// Records the given root as used, if tracking the roots in use.
+    fn record_used_root(&self, root: Blake2bHash) -> Result<(), error::Error> {
+        if let Some(used_roots) = &self.used_roots {
+            used_roots.lock()?.current.insert(root);
         }
+        Ok(())
+    }
+}
+
+impl LmdbGlobalState {
+    /// Removes all tries which are not reachable from any of the given roots, e.g. to reclaim the
+    /// space held by global states which are no longer needed.  The empty root is always retained.
+    ///
+    /// If tracking the roots in use, the roots used since the previous pruning started are retained
+    /// too, as are those used while pruning, so execution can continue meanwhile.  The tries are
+    /// removed in batches, each in its own write transaction.
+    ///
+    /// Returns the number of tries removed.
+    pub fn prune_unreachable_tries(
+        &self,
+        _correlation_id: CorrelationId,
+        retained_roots: &[Blake2bHash],
+    ) -> Result<usize, error::Error> {
+        let mut roots = retained_roots.to_vec();
+        roots.push(self.empty_root_hash);
+        if let Some(used_roots) = &self.used_roots {
+            let mut used_roots = used_roots.lock()?;
+            let current = mem::take(&mut used_roots.current);
+            let previous = mem::replace(&mut used_roots.previous, current);
+            roots.extend(previous);
+            roots.extend(used_roots.previous.iter().copied());
+            roots.extend(used_roots.latest);
+            used_roots.pruning = true;
+        }
+
+        let result = self.remove_unreachable_tries(roots);
+
+        if let Some(used_roots) = &self.used_roots {
+            used_roots.lock()?.pruning = false;
+        }
+        result
+    }
+
+    fn remove_unreachable_tries(&self, mut roots: Vec<Blake2bHash>) -> Result<usize, error::Error> {
+        let handle = Store::<Blake2bHash, Trie<Key, StoredValue>>::handle(self.trie_store.deref());
+
+        // Mark all tries reachable from the retained roots, and find all others, in one snapshot.
+        // The tries of a global state being synchronized are retained too, even if its
+        // synchronization was interrupted.
+        let mut reachable = HashSet::new();
+        let mut unreachable = vec![];
+        {
+            let txn = self.environment.create_read_txn()?;
+            {
+                let sync_handle =
+                    Store::<Blake2bHash, Trie<Key, StoredValue>>::handle(&self.sync_store);
+                let mut cursor = lmdb::Transaction::open_ro_cursor(&txn, sync_handle)?;
+                for (_key_bytes, value_bytes) in cursor.iter() {
+                    let trie: Trie<Key, StoredValue> =
+                        bytesrepr::deserialize(value_bytes.to_vec())?;
+                    roots.extend(child_hashes(&trie));
+                }
+            }
+            self.mark_reachable(&txn, roots, &mut reachable)?;
+            {
+                let mut cursor = lmdb::Transaction::open_ro_cursor(&txn, handle)?;
+                for (key_bytes, _value_bytes) in cursor.iter() {
+                    let (trie_hash, _) = Blake2bHash::from_bytes(key_bytes)?;
+                    if !reachable.contains(&trie_hash) {
+                        unreachable.push(trie_hash);
+                    }
+                }
+            }
+            txn.commit()?;
+        }
+
+        // Sweep them in batches, sparing those which became reachable from roots used meanwhile.
+        let mut removed_count = 0;
+        for batch in unreachable.chunks(PRUNE_BATCH_SIZE) {
+            let maybe_used_roots = match &self.used_roots {
+                Some(used_roots) => Some(used_roots.lock()?),
+                None => None,
+            };
+            let mut txn = self.environment.create_read_write_txn()?;
+            if let Some(used_roots) = &maybe_used_roots {
+                let roots = used_roots.current.iter().copied().collect();
+                self.mark_reachable(&txn, roots, &mut reachable)?;
+            }
+            for trie_hash in batch {
+                if !reachable.contains(trie_hash) {
+                    txn.del(handle, &trie_hash.to_bytes()?, None)?;
+                    removed_count += 1;
+                }
+            }
+            txn.commit()?;
+        }
+        Ok(removed_count)
+    }
+
+    /// Adds the tries reachable from the given roots to `reachable`, skipping the descendants of
+    /// those already in it.
+    fn mark_reachable<T>(
+        &self,
+        txn: &T,
+        roots: Vec<Blake2bHash>,
+        reachable: &mut HashSet<Blake2bHash>,
+    ) -> Result<(), error::Error>
+    where
+        T: Readable<Handle = Database, Error = lmdb::Error>,
+    {
+        let mut pending = roots;
+        while let Some(trie_hash) = pending.pop() {
+            if !reachable.insert(trie_hash) {
+                continue;
+            }
+            let maybe_trie: Option<Trie<Key, StoredValue>> =
+                self.trie_store.get(txn, &trie_hash)?;
+            if let Some(trie) = maybe_trie {
+                pending.extend(child_hashes(&trie));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the trie stored under the given hash, in its serialized form.
+    pub fn get_trie_bytes(
+        &self,
+        _correlation_id: CorrelationId,
+        trie_hash: &Blake2bHash,
+    ) -> Result<Option<Vec<u8>>, error::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let maybe_trie: Option<Trie<Key, StoredValue>> = self.trie_store.get(&txn, trie_hash)?;
+        txn.commit()?;
+        maybe_trie
+            .map(|trie| trie.to_bytes())
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// Stores the given serialized trie under its hash, e.g. one received from a peer while
+    /// synchronizing a global state.  Unless all its descendants are already in the trie store, it
+    /// is held in the sync store until the whole state is, see `missing_trie_descendants`.
+    ///
+    /// Returns the hashes of the tries it points to which are in neither store.
+    pub fn put_trie_bytes(
+        &self,
+        _correlation_id: CorrelationId,
+        trie_bytes: &[u8],
+    ) -> Result<Vec<Blake2bHash>, error::Error> {
+        let trie: Trie<Key, StoredValue> = bytesrepr::deserialize(trie_bytes.to_vec())?;
+        let trie_hash = Blake2bHash::new(trie_bytes);
+        // While pruning, record the trie and its children as used, so that those found present
+        // here are spared.
+        let _maybe_used_roots = match &self.used_roots {
+            Some(used_roots) => {
+                let mut used_roots = used_roots.lock()?;
+                if used_roots.pruning {
+                    used_roots.current.insert(trie_hash);
+                    used_roots.current.extend(child_hashes(&trie));
+                }
+                Some(used_roots)
+            }
+            None => None,
+        };
+        let mut txn = self.environment.create_read_write_txn()?;
+        if contains(&self.trie_store, &txn, &trie_hash)? {
+            txn.commit()?;
+            return Ok(vec![]);
+        }
+        let mut is_complete = true;
+        let mut missing_children = vec![];
+        for child_hash in child_hashes(&trie) {
+            if contains(&self.trie_store, &txn, &child_hash)? {
+                continue;
+            }
+            is_complete = false;
+            if !contains(&self.sync_store, &txn, &child_hash)? {
+                missing_children.push(child_hash);
+            }
+        }
+        if is_complete {
+            self.trie_store.put(&mut txn, &trie_hash, &trie)?;
+        } else {
+            self.sync_store.put(&mut txn, &trie_hash, &trie)?;
+        }
+        txn.commit()?;
+        Ok(missing_children)
+    }
+
+    /// Returns the hashes of all the tries reachable from the given root which are in neither the
+    /// trie store nor the sync store, e.g. to resume synchronizing a global state.  Returns the
+    /// root's hash if it is in neither.
+    ///
+    /// Only the tries in the sync store are traversed, as those in the trie store are complete.
+    /// Once none are missing, they are all moved to the trie store, making the state available.
+    /// Any others in the sync store, e.g. from an abandoned synchronization, are discarded.
+    pub fn missing_trie_descendants(
+        &self,
+        _correlation_id: CorrelationId,
+        root_hash: &Blake2bHash,
+    ) -> Result<Vec<Blake2bHash>, error::Error> {
+        // Hold the lock while moving the tries, so a concurrent pruning can't remove any of them
+        // before the root is recorded as used.
+        let mut maybe_used_roots = match &self.used_roots {
+            Some(used_roots) => Some(used_roots.lock()?),
+            None => None,
+        };
+        let mut txn = self.environment.create_read_write_txn()?;
+        let mut visited = HashSet::new();
+        let mut synced = vec![];
+        let mut missing = vec![];
+        let mut pending = vec![*root_hash];
+        while let Some(trie_hash) = pending.pop() {
+            if !visited.insert(trie_hash) || contains(&self.trie_store, &txn, &trie_hash)? {
+                continue;
+            }
+            let maybe_trie: Option<Trie<Key, StoredValue>> =
+                self.sync_store.get(&txn, &trie_hash)?;
+            match maybe_trie {
+                Some(trie) => {
+                    pending.extend(child_hashes(&trie));
+                    synced.push((trie_hash, trie));
+                }
+                None => missing.push(trie_hash),
+            }
+        }
+
+        let sync_handle = Store::<Blake2bHash, Trie<Key, StoredValue>>::handle(&self.sync_store);
+        let mut discarded = vec![];
+        {
+            let mut cursor = lmdb::Transaction::open_ro_cursor(&txn, sync_handle)?;
+            for (key_bytes, _value_bytes) in cursor.iter() {
+                let (trie_hash, _) = Blake2bHash::from_bytes(key_bytes)?;
+                if missing.is_empty() || !visited.contains(&trie_hash) {
+                    discarded.push(trie_hash);
+                }
+            }
+        }
+        for trie_hash in discarded {
+            txn.del(sync_handle, &trie_hash.to_bytes()?, None)?;
+        }
+        if missing.is_empty() {
+            for (trie_hash, trie) in &synced {
+                self.trie_store.put(&mut txn, trie_hash, trie)?;
+            }
+        }
+        txn.commit()?;
+
+        if let Some(used_roots) = &mut maybe_used_roots {
+            used_roots.current.insert(*root_hash);
+        }
+        Ok(missing)
+    }
+}
+
+/// Returns `true` if the given store holds a trie under the given hash.
+fn contains<T>(
+    store: &LmdbTrieStore,
+    txn: &T,
+    trie_hash: &Blake2bHash,
+) -> Result<bool, error::Error>
+where
+    T: Readable<Handle = Database, Error = lmdb::Error>,
+{
+    let maybe_trie: Option<Trie<Key, StoredValue>> = store.get(txn, trie_hash)?;
+    Ok(maybe_trie.is_some())
+}
+
+/// Returns the hashes of the tries the given trie points to.
+fn child_hashes(trie: &Trie<Key, StoredValue>) -> Vec<Blake2bHash> {
+    match trie {
+        Trie::Node { pointer_block } => pointer_block[0..]
+            .iter()
+            .flatten()
+            .map(|pointer| *pointer.hash())
+            .collect(),
+        Trie::Extension { pointer, .. } => vec![*pointer.hash()],
+        Trie::Leaf { .. } => vec![],
     }
 }
 
@@ -106,6 +438,7 @@ impl StateProvider for LmdbGlobalState {
     type Reader = LmdbGlobalStateView;
 
     fn checkout(&self, state_hash: Blake2bHash) -> Result<Option<Self::Reader>, Self::Error> {
+        self.record_used_root(state_hash)?;
         let txn = self.environment.create_read_txn()?;
         let maybe_root: Option<Trie<Key, StoredValue>> = self.trie_store.get(&txn, &state_hash)?;
         let maybe_state = maybe_root.map(|_| LmdbGlobalStateView {
@@ -123,6 +456,12 @@ impl StateProvider for LmdbGlobalState {
         prestate_hash: Blake2bHash,
         effects: AdditiveMap<Key, Transform>,
     ) -> Result<CommitResult, Self::Error> {
+        // Hold the lock while committing, so a concurrent pruning can't remove any of the stored
+        // tries before the new state root is recorded as used.
+        let mut maybe_used_roots = match &self.used_roots {
+            Some(used_roots) => Some(used_roots.lock()?),
+            None => None,
+        };
         let commit_result = commit::<LmdbEnvironment, LmdbTrieStore, _, Self::Error>(
             &self.environment,
             &self.trie_store,
@@ -130,6 +469,13 @@ impl StateProvider for LmdbGlobalState {
             prestate_hash,
             effects,
         )?;
+        if let (Some(used_roots), CommitResult::Success { state_root, .. }) =
+            (&mut maybe_used_roots, &commit_result)
+        {
+            used_roots.current.insert(prestate_hash);
+            used_roots.current.insert(*state_root);
+            used_roots.latest = Some(*state_root);
+        }
         Ok(commit_result)
     }
 
@@ -161,6 +507,8 @@ impl StateProvider for LmdbGlobalState {
 
 #[cfg(test)]
 mod tests {
+    use std::{collections::VecDeque, thread};
+
     use lmdb::DatabaseFlags;
     use tempfile::tempdir;
 
@@ -209,8 +557,7 @@ mod tests {
         ]
     }
 
-    fn create_test_state() -> (LmdbGlobalState, Blake2bHash) {
-        let correlation_id = CorrelationId::new();
+    fn create_empty_state() -> LmdbGlobalState {
         let _temp_dir = tempdir().unwrap();
         let environment = Arc::new(
             LmdbEnvironment::new(&_temp_dir.path().to_path_buf(), DEFAULT_TEST_MAX_DB_SIZE)
@@ -221,7 +568,12 @@ mod tests {
         let protocol_data_store = Arc::new(
             LmdbProtocolDataStore::new(&environment, None, DatabaseFlags::empty()).unwrap(),
         );
-        let ret = LmdbGlobalState::empty(environment, trie_store, protocol_data_store).unwrap();
+        LmdbGlobalState::empty(environment, trie_store, protocol_data_store).unwrap()
+    }
+
+    fn create_test_state() -> (LmdbGlobalState, Blake2bHash) {
+        let correlation_id = CorrelationId::new();
+        let ret = create_empty_state();
         let mut current_root = ret.empty_root_hash;
         {
             let mut txn = ret.environment.create_read_write_txn().unwrap();
@@ -298,6 +650,213 @@ mod tests {
         }
     }
 
+    #[test]
+    fn prune_removes_only_unreachable_tries() {
+        let correlation_id = CorrelationId::new();
+        let test_pairs_updated = create_test_pairs_updated();
+
+        let (state, root_hash) = create_test_state();
+
+        let effects: AdditiveMap<Key, Transform> = {
+            let mut tmp = AdditiveMap::new();
+            for TestPair { key, value } in &test_pairs_updated {
+                tmp.insert(*key, Transform::Write(value.to_owned()));
+            }
+            tmp
+        };
+
+        let updated_hash = match state.commit(correlation_id, root_hash, effects).unwrap() {
+            CommitResult::Success { state_root, .. } => state_root,
+            _ => panic!("commit failed"),
+        };
+
+        // Retaining both roots should keep both states intact.
+        state
+            .prune_unreachable_tries(correlation_id, &[root_hash, updated_hash])
+            .unwrap();
+        assert!(state.checkout(root_hash).unwrap().is_some());
+        let removed_count = state
+            .prune_unreachable_tries(correlation_id, &[root_hash, updated_hash])
+            .unwrap();
+        assert_eq!(removed_count, 0);
+
+        // Retaining only the updated root should remove the original one.
+        let removed_count = state
+            .prune_unreachable_tries(correlation_id, &[updated_hash])
+            .unwrap();
+        assert!(removed_count > 0);
+        assert!(state.checkout(root_hash).unwrap().is_none());
+        assert!(state.checkout(state.empty_root_hash).unwrap().is_some());
+
+        let updated_checkout = state.checkout(updated_hash).unwrap().unwrap();
+        for TestPair { key, value } in test_pairs_updated.iter().cloned() {
+            assert_eq!(
+                Some(value),
+                updated_checkout.read(correlation_id, &key).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn prune_retains_roots_used_since_previous_prune() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state();
+        let state = state.with_used_roots_tracking();
+
+        let effects: AdditiveMap<Key, Transform> = {
+            let mut tmp = AdditiveMap::new();
+            for TestPair { key, value } in &create_test_pairs_updated() {
+                tmp.insert(*key, Transform::Write(value.to_owned()));
+            }
+            tmp
+        };
+        let updated_hash = match state.commit(correlation_id, root_hash, effects).unwrap() {
+            CommitResult::Success { state_root, .. } => state_root,
+            _ => panic!("commit failed"),
+        };
+
+        // Both roots were used since the start of the previous prune for the next two prunes, so
+        // should be retained by them.  Checking their presence mustn't count as using them.
+        let is_stored = |trie_hash| {
+            state
+                .get_trie_bytes(correlation_id, &trie_hash)
+                .unwrap()
+                .is_some()
+        };
+        for _ in 0..2 {
+            state.prune_unreachable_tries(correlation_id, &[]).unwrap();
+            assert!(is_stored(root_hash));
+            assert!(is_stored(updated_hash));
+        }
+
+        // Unused since the start of the previous prune, the pre-state shouldn't be retained any
+        // longer, unlike the post-state of the latest commit.
+        let removed_count = state.prune_unreachable_tries(correlation_id, &[]).unwrap();
+        assert!(removed_count > 0);
+        assert!(!is_stored(root_hash));
+        assert!(is_stored(updated_hash));
+        assert!(is_stored(state.empty_root_hash));
+    }
+
+    #[test]
+    fn prune_while_committing_should_not_remove_state_in_use() {
+        const COMMIT_COUNT: i32 = 100;
+
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) = create_test_state();
+        let state = Arc::new(state.with_used_roots_tracking());
+        let key = Key::Account(AccountHash::new([4u8; 32]));
+
+        // Execute a chain of commits, each on the post-state of the previous one, while pruning
+        // everything not reachable from the initial state, like that of the block's parent.
+        let committer = {
+            let state = Arc::clone(&state);
+            thread::spawn(move || {
+                let mut current_root = root_hash;
+                for value in 0..COMMIT_COUNT {
+                    let mut effects = AdditiveMap::new();
+                    let value = StoredValue::CLValue(CLValue::from_t(value).unwrap());
+                    effects.insert(key, Transform::Write(value));
+                    current_root = match state.commit(correlation_id, current_root, effects) {
+                        Ok(CommitResult::Success { state_root, .. }) => state_root,
+                        _ => panic!("commit failed"),
+                    };
+                }
+                current_root
+            })
+        };
+        while Arc::strong_count(&state) > 1 {
+            state
+                .prune_unreachable_tries(correlation_id, &[root_hash])
+                .unwrap();
+        }
+        let final_root = committer.join().unwrap();
+
+        let checkout = state.checkout(final_root).unwrap().unwrap();
+        let expected_value = StoredValue::CLValue(CLValue::from_t(COMMIT_COUNT - 1).unwrap());
+        assert_eq!(
+            Some(expected_value),
+            checkout.read(correlation_id, &key).unwrap()
+        );
+        for TestPair { key, value } in create_test_pairs().iter().cloned() {
+            assert_eq!(Some(value), checkout.read(correlation_id, &key).unwrap());
+        }
+    }
+
+    #[test]
+    fn synced_state_should_only_be_available_once_complete() {
+        let correlation_id = CorrelationId::new();
+        let (source, root_hash) = create_test_state();
+        let state = create_empty_state().with_used_roots_tracking();
+        let is_stored = |trie_hash| {
+            state
+                .get_trie_bytes(correlation_id, &trie_hash)
+                .unwrap()
+                .is_some()
+        };
+
+        // Synchronize the state from the source in breadth-first order, pruning everything else
+        // and looking for the missing tries as if resuming after each trie.
+        let mut tries_to_fetch: VecDeque<_> = state
+            .missing_trie_descendants(correlation_id, &root_hash)
+            .unwrap()
+            .into();
+        assert_eq!(tries_to_fetch, vec![root_hash]);
+        let mut synced_count = 0;
+        while let Some(trie_hash) = tries_to_fetch.pop_front() {
+            assert!(!is_stored(root_hash));
+            let trie_bytes = source
+                .get_trie_bytes(correlation_id, &trie_hash)
+                .unwrap()
+                .unwrap();
+            let missing_children = state.put_trie_bytes(correlation_id, &trie_bytes).unwrap();
+            tries_to_fetch.extend(missing_children);
+            synced_count += 1;
+
+            for _ in 0..2 {
+                state.prune_unreachable_tries(correlation_id, &[]).unwrap();
+            }
+            let missing = state
+                .missing_trie_descendants(correlation_id, &root_hash)
+                .unwrap();
+            assert_eq!(
+                missing.into_iter().collect::<HashSet<_>>(),
+                tries_to_fetch.iter().copied().collect()
+            );
+        }
+        assert!(synced_count > 1);
+
+        let checkout = state.checkout(root_hash).unwrap().unwrap();
+        for TestPair { key, value } in create_test_pairs().iter().cloned() {
+            assert_eq!(Some(value), checkout.read(correlation_id, &key).unwrap());
+        }
+    }
+
+    #[test]
+    fn missing_trie_descendants_should_discard_abandoned_sync() {
+        let correlation_id = CorrelationId::new();
+        let (source, root_hash) = create_test_state();
+        let state = create_empty_state();
+
+        let root_bytes = source
+            .get_trie_bytes(correlation_id, &root_hash)
+            .unwrap()
+            .unwrap();
+        let missing_children = state.put_trie_bytes(correlation_id, &root_bytes).unwrap();
+        assert!(!missing_children.is_empty());
+
+        // Synchronizing another state discards the tries of the first one.
+        let other_root_hash = Blake2bHash::new(&[1u8; 32]);
+        let missing = state
+            .missing_trie_descendants(correlation_id, &other_root_hash)
+            .unwrap();
+        assert_eq!(missing, vec![other_root_hash]);
+        let missing = state
+            .missing_trie_descendants(correlation_id, &root_hash)
+            .unwrap();
+        assert_eq!(missing, vec![root_hash]);
+    }
+
     #[test]
     fn commit_updates_state_and_original_state_stays_intact() {
         let correlation_id = CorrelationId::new();
@@ -14,6 +14,8 @@ impl TryFrom<UpgradeRequest> for UpgradeConfig {
             .try_into()
             .map_err(|_| MappingError::InvalidStateHash("pre_state_hash".to_string()))?;
 
+        let block_time = pb_upgrade_request.get_block_time();
+
         let current_protocol_version = pb_upgrade_request.take_protocol_version().into();
 
         let upgrade_point = pb_upgrade_request.mut_upgrade_point();
@@ -43,6 +45,7 @@ impl TryFrom<UpgradeRequest> for UpgradeConfig {
 
         Ok(UpgradeConfig::new(
             pre_state_hash,
+            block_time,
             current_protocol_version,
             new_protocol_version,
             upgrade_installer_args,
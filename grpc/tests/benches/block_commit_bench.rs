@@ -0,0 +1,141 @@
+use std::{path::Path, time::Duration};
+
+use criterion::{
+    criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, Criterion, Throughput,
+};
+use tempfile::TempDir;
+
+use casper_engine_test_support::internal::{
+    DeployItemBuilder, ExecuteRequestBuilder, LmdbWasmTestBuilder, DEFAULT_ACCOUNT_ADDR,
+    DEFAULT_RUN_GENESIS_REQUEST,
+};
+use casper_execution_engine::{
+    core::engine_state::{BlockExecutionResult, EngineConfig},
+    shared::newtypes::CorrelationId,
+};
+use casper_types::{account::AccountHash, runtime_args, RuntimeArgs, U512};
+
+const CONTRACT_CREATE_ACCOUNTS: &str = "create_accounts.wasm";
+
+/// Numbers of transfers per block to benchmark.
+const BLOCK_SIZES: [u64; 3] = [10, 100, 500];
+const TARGET_ADDR: AccountHash = AccountHash::new([127; 32]);
+const ARG_AMOUNT: &str = "amount";
+const ARG_ACCOUNTS: &str = "accounts";
+const ARG_SEED_AMOUNT: &str = "seed_amount";
+const ARG_TARGET: &str = "target";
+
+/// Converts an integer into an array of type [u8; 32] by converting integer
+/// into its big endian representation and embedding it at the end of the
+/// range.
+fn make_deploy_hash(i: u64) -> [u8; 32] {
+    let mut result = [128; 32];
+    result[32 - 8..].copy_from_slice(&i.to_be_bytes());
+    result
+}
+
+fn bootstrap(data_dir: &Path, keep_intermediate_state_roots: bool) -> LmdbWasmTestBuilder {
+    let exec_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_CREATE_ACCOUNTS,
+        runtime_args! { ARG_ACCOUNTS => vec![TARGET_ADDR], ARG_SEED_AMOUNT => U512::one() },
+    )
+    .build();
+
+    let engine_config = EngineConfig::new()
+        .with_use_system_contracts(cfg!(feature = "use-system-contracts"))
+        .with_keep_intermediate_state_roots(keep_intermediate_state_roots);
+
+    let mut builder = LmdbWasmTestBuilder::new_with_config(data_dir, engine_config);
+
+    builder
+        .run_genesis(&DEFAULT_RUN_GENESIS_REQUEST)
+        .exec(exec_request)
+        .expect_success()
+        .commit();
+
+    builder
+}
+
+/// Executes a block of `block_size` wasmless transfers to an existing account on top of the given
+/// state, and returns the block's post state hash.
+fn execute_block_of_transfers(
+    builder: &LmdbWasmTestBuilder,
+    pre_state_hash: &[u8],
+    block_size: u64,
+) -> Vec<u8> {
+    let mut exec_builder = ExecuteRequestBuilder::new().with_pre_state_hash(pre_state_hash);
+
+    for i in 0..block_size {
+        let deploy = DeployItemBuilder::new()
+            .with_address(*DEFAULT_ACCOUNT_ADDR)
+            .with_empty_payment_bytes(runtime_args! {})
+            .with_transfer_args(runtime_args! {
+                ARG_TARGET => TARGET_ADDR,
+                ARG_AMOUNT => U512::one(),
+            })
+            .with_authorization_keys(&[*DEFAULT_ACCOUNT_ADDR])
+            .with_deploy_hash(make_deploy_hash(i))
+            .build();
+        exec_builder = exec_builder.push_deploy(deploy);
+    }
+
+    let result = builder
+        .get_engine_state()
+        .execute_block(CorrelationId::new(), exec_builder.build())
+        .expect("should execute block");
+    match result {
+        BlockExecutionResult::Success {
+            post_state_hash, ..
+        } => post_state_hash.to_vec(),
+        result => panic!("should commit block: {}", result),
+    }
+}
+
+/// Executes blocks of transfers, either committing the effects of each transfer separately or
+/// committing the effects of the whole block once.
+fn transfers_per_block(
+    group: &mut BenchmarkGroup<WallTime>,
+    block_size: u64,
+    keep_intermediate_state_roots: bool,
+) {
+    let data_dir = TempDir::new().expect("should create temp dir");
+    let builder = bootstrap(data_dir.path(), keep_intermediate_state_roots);
+    let mut state_hash = builder.get_post_state_hash();
+
+    let commit_mode = if keep_intermediate_state_roots {
+        "commit_per_deploy"
+    } else {
+        "commit_per_block"
+    };
+
+    group.throughput(Throughput::Elements(block_size));
+    group.bench_function(format!("{}/{}", commit_mode, block_size), |b| {
+        b.iter(|| {
+            // Each block builds on the state left by the previous one
+            state_hash = execute_block_of_transfers(&builder, &state_hash, block_size)
+        })
+    });
+}
+
+pub fn block_commit_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("block_commit");
+
+    // Minimum number of samples and measurement times to decrease the total time of this benchmark.
+    // This may or may not decrease the quality of the numbers.
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(10));
+
+    for block_size in BLOCK_SIZES.iter() {
+        // Transfers with a commit and a new state root per deploy
+        transfers_per_block(&mut group, *block_size, true);
+
+        // Transfers accumulated in a single tracking copy and committed once per block
+        transfers_per_block(&mut group, *block_size, false);
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, block_commit_bench);
+criterion_main!(benches);
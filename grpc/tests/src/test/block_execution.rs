@@ -0,0 +1,269 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use casper_engine_test_support::{
+    internal::{
+        DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder,
+        DEFAULT_RUN_GENESIS_REQUEST,
+    },
+    DEFAULT_ACCOUNT_ADDR,
+};
+use casper_execution_engine::{
+    core::engine_state::{
+        deploy_item::DeployItem, execute_request::ExecuteRequest, BlockExecutionResult,
+        EngineConfig, ExecutionResult,
+    },
+    shared::newtypes::{Blake2bHash, CorrelationId},
+    storage::global_state::in_memory::InMemoryGlobalState,
+};
+use casper_types::{account::AccountHash, runtime_args, RuntimeArgs, U512};
+
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1u8; 32]);
+const ACCOUNT_2_ADDR: AccountHash = AccountHash::new([2u8; 32]);
+const ARG_TARGET: &str = "target";
+const ARG_AMOUNT: &str = "amount";
+
+/// Seeds of the randomized workloads executed by the differential test.
+const SEEDS: [u64; 4] = [1, 7, 42, 1337];
+const ACCOUNT_COUNT: u8 = 8;
+const FUNDING_AMOUNT: u64 = 100_000_000_000;
+const BLOCK_COUNT: usize = 4;
+const BLOCK_SIZE: usize = 24;
+
+fn make_deploy_hash(i: u64) -> [u8; 32] {
+    let mut result = [128; 32];
+    result[32 - 8..].copy_from_slice(&i.to_be_bytes());
+    result
+}
+
+fn transfer(source: AccountHash, target: AccountHash, amount: U512, i: u64) -> DeployItem {
+    DeployItemBuilder::new()
+        .with_address(source)
+        .with_empty_payment_bytes(runtime_args! {})
+        .with_transfer_args(runtime_args! { ARG_TARGET => target, ARG_AMOUNT => amount })
+        .with_authorization_keys(&[source])
+        .with_deploy_hash(make_deploy_hash(i))
+        .build()
+}
+
+/// A block of transfers where later deploys depend on the effects of earlier ones, including a
+/// failing one.
+fn block_of_transfers(pre_state_hash: &[u8]) -> ExecuteRequest {
+    ExecuteRequestBuilder::new()
+        .with_pre_state_hash(pre_state_hash)
+        .push_deploy(transfer(
+            *DEFAULT_ACCOUNT_ADDR,
+            ACCOUNT_1_ADDR,
+            U512::from(1_000_000),
+            0,
+        ))
+        .push_deploy(transfer(
+            ACCOUNT_1_ADDR,
+            ACCOUNT_2_ADDR,
+            U512::from(400_000),
+            1,
+        ))
+        .push_deploy(transfer(
+            ACCOUNT_2_ADDR,
+            ACCOUNT_1_ADDR,
+            U512::from(100_000),
+            2,
+        ))
+        .push_deploy(transfer(
+            ACCOUNT_1_ADDR,
+            ACCOUNT_2_ADDR,
+            U512::from(u64::max_value()),
+            3,
+        ))
+        .push_deploy(transfer(
+            *DEFAULT_ACCOUNT_ADDR,
+            ACCOUNT_2_ADDR,
+            U512::from(1_000_000),
+            4,
+        ))
+        .build()
+}
+
+fn new_builder(
+    keep_intermediate_state_roots: bool,
+    parallel_execution: bool,
+) -> InMemoryWasmTestBuilder {
+    let engine_config = EngineConfig::new()
+        .with_use_system_contracts(cfg!(feature = "use-system-contracts"))
+        .with_keep_intermediate_state_roots(keep_intermediate_state_roots)
+        .with_parallel_execution(parallel_execution);
+    let global_state = InMemoryGlobalState::empty().expect("should create global state");
+    let mut builder = InMemoryWasmTestBuilder::new(global_state, engine_config, Vec::new());
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+    builder
+}
+
+fn execute_request(
+    builder: &InMemoryWasmTestBuilder,
+    exec_request: ExecuteRequest,
+) -> (Blake2bHash, Vec<ExecutionResult>) {
+    let result = builder
+        .get_engine_state()
+        .execute_block(CorrelationId::new(), exec_request)
+        .expect("should execute block");
+    match result {
+        BlockExecutionResult::Success {
+            post_state_hash,
+            execution_results,
+        } => (post_state_hash, execution_results.into_iter().collect()),
+        result => panic!("should commit block: {}", result),
+    }
+}
+
+fn execute_block(keep_intermediate_state_roots: bool) -> (Blake2bHash, Vec<ExecutionResult>) {
+    let builder = new_builder(keep_intermediate_state_roots, false);
+    let pre_state_hash = builder.get_post_state_hash();
+    execute_request(&builder, block_of_transfers(&pre_state_hash))
+}
+
+/// Generates a block funding the accounts of the workload, followed by blocks of random transfers
+/// between them and to fresh accounts.  Some of the transfers exceed the balance of their source.
+fn random_workload(seed: u64) -> Vec<Vec<DeployItem>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let accounts: Vec<AccountHash> = (1..=ACCOUNT_COUNT)
+        .map(|i| AccountHash::new([i; 32]))
+        .collect();
+    let mut deploy_count = 0;
+    let mut next_deploy_index = || {
+        deploy_count += 1;
+        deploy_count
+    };
+
+    let funding_block = accounts
+        .iter()
+        .map(|account| {
+            transfer(
+                *DEFAULT_ACCOUNT_ADDR,
+                *account,
+                U512::from(FUNDING_AMOUNT),
+                next_deploy_index(),
+            )
+        })
+        .collect();
+
+    let mut blocks = vec![funding_block];
+    for _ in 0..BLOCK_COUNT {
+        let block = (0..BLOCK_SIZE)
+            .map(|_| {
+                let source = accounts[rng.gen_range(0, accounts.len())];
+                let target = if rng.gen_bool(0.2) {
+                    AccountHash::new(rng.gen())
+                } else {
+                    accounts[rng.gen_range(0, accounts.len())]
+                };
+                let amount = if rng.gen_bool(0.1) {
+                    U512::from(u64::max_value())
+                } else {
+                    U512::from(rng.gen_range(1, FUNDING_AMOUNT / 4))
+                };
+                transfer(source, target, amount, next_deploy_index())
+            })
+            .collect();
+        blocks.push(block);
+    }
+    blocks
+}
+
+/// Executes the given blocks one after the other, returning the post state hash and the execution
+/// results of each block.
+fn execute_blocks(
+    builder: &InMemoryWasmTestBuilder,
+    blocks: &[Vec<DeployItem>],
+) -> Vec<(Blake2bHash, Vec<ExecutionResult>)> {
+    let mut pre_state_hash = builder.get_post_state_hash();
+    let mut block_results = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        let exec_request = block
+            .iter()
+            .cloned()
+            .fold(
+                ExecuteRequestBuilder::new().with_pre_state_hash(&pre_state_hash),
+                ExecuteRequestBuilder::push_deploy,
+            )
+            .build();
+        let (post_state_hash, execution_results) = execute_request(builder, exec_request);
+        pre_state_hash = post_state_hash.to_vec();
+        block_results.push((post_state_hash, execution_results));
+    }
+    block_results
+}
+
+#[ignore]
+#[test]
+fn should_commit_block_once_with_same_post_state_as_commit_per_deploy() {
+    let (post_state_hash, execution_results) = execute_block(false);
+    let (expected_post_state_hash, expected_execution_results) = execute_block(true);
+
+    assert_eq!(post_state_hash, expected_post_state_hash);
+    assert_eq!(execution_results.len(), expected_execution_results.len());
+    for (result, expected_result) in execution_results
+        .iter()
+        .zip(expected_execution_results.iter())
+    {
+        assert_eq!(result.is_success(), expected_result.is_success());
+        assert_eq!(result.cost(), expected_result.cost());
+    }
+
+    // Only the transfer exceeding the balance of account 1 fails.
+    let failures: Vec<_> = execution_results
+        .iter()
+        .map(ExecutionResult::is_failure)
+        .collect();
+    assert_eq!(failures, vec![false, false, false, true, false]);
+}
+
+#[ignore]
+#[test]
+fn should_execute_random_transfers_in_parallel_with_same_results_as_serial_execution() {
+    for keep_intermediate_state_roots in [false, true].iter() {
+        let serial_builder = new_builder(*keep_intermediate_state_roots, false);
+        let parallel_builder = new_builder(*keep_intermediate_state_roots, true);
+
+        for seed in SEEDS.iter() {
+            let blocks = random_workload(*seed);
+            let expected_block_results = execute_blocks(&serial_builder, &blocks);
+            let block_results = execute_blocks(&parallel_builder, &blocks);
+
+            for (block_index, (block_result, expected_block_result)) in block_results
+                .iter()
+                .zip(expected_block_results.iter())
+                .enumerate()
+            {
+                let (post_state_hash, execution_results) = block_result;
+                let (expected_post_state_hash, expected_execution_results) = expected_block_result;
+                assert_eq!(
+                    post_state_hash, expected_post_state_hash,
+                    "post state hash of block {} differs for seed {}",
+                    block_index, seed
+                );
+                assert_eq!(execution_results.len(), expected_execution_results.len());
+                for (deploy_index, (result, expected_result)) in execution_results
+                    .iter()
+                    .zip(expected_execution_results.iter())
+                    .enumerate()
+                {
+                    assert_eq!(
+                        result.is_success(),
+                        expected_result.is_success(),
+                        "outcome of deploy {} of block {} differs for seed {}",
+                        deploy_index,
+                        block_index,
+                        seed
+                    );
+                    assert_eq!(
+                        result.cost(),
+                        expected_result.cost(),
+                        "cost of deploy {} of block {} differs for seed {}",
+                        deploy_index,
+                        block_index,
+                        seed
+                    );
+                }
+            }
+        }
+    }
+}
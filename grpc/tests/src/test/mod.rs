@@ -1,3 +1,4 @@
+mod block_execution;
 mod check_transfer_success;
 mod contract_api;
 mod contract_context;
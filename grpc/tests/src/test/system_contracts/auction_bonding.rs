@@ -9,7 +9,7 @@ use casper_execution_engine::{core::engine_state::genesis::GenesisAccount, share
 use casper_types::{
     account::AccountHash,
     auction::{
-        BidPurses, UnbondingPurses, ARG_VALIDATOR_PUBLIC_KEYS, BID_PURSES_KEY,
+        BidPurses, Bids, UnbondingPurses, ARG_VALIDATOR_PUBLIC_KEYS, BIDS_KEY, BID_PURSES_KEY,
         DEFAULT_UNBONDING_DELAY, INITIAL_ERA_ID, METHOD_RUN_AUCTION, METHOD_SLASH,
         UNBONDING_PURSES_KEY,
     },
@@ -201,6 +201,9 @@ fn should_run_successful_bond_and_unbond_and_slashing() {
     let bid_purses: BidPurses = get_value(&mut builder, auction, BID_PURSES_KEY);
 
     assert!(bid_purses.is_empty());
+
+    let bids: Bids = get_value(&mut builder, auction, BIDS_KEY);
+    assert!(!bids.contains_key(&*DEFAULT_ACCOUNT_PUBLIC_KEY));
 }
 
 #[ignore]
@@ -4,7 +4,11 @@
 
 pub mod arglang;
 
-use std::{env, fs, path::PathBuf, str::FromStr};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use anyhow::{self, bail, Context};
 use rand::SeedableRng;
@@ -16,7 +20,7 @@ use tracing::{info, trace};
 
 use crate::config;
 use casper_node::{
-    logging,
+    archive, logging,
     reactor::{initializer, joiner, validator, Runner},
     utils::WithDir,
 };
@@ -34,6 +38,62 @@ pub enum Cli {
         /// Path to configuration file.
         config: PathBuf,
 
+        #[structopt(
+            short = "C",
+            long,
+            env = "NODE_CONFIG",
+            use_delimiter(true),
+            value_delimiter(";")
+        )]
+        /// Overrides and extensions for configuration file entries in the form
+        /// <SECTION>.<KEY>=<VALUE>.  For example, '-C=node.chainspec_config_path=chainspec.toml'
+        config_ext: Vec<ConfigExt>,
+    },
+    /// Export blocks to an archive file.
+    ///
+    /// Writes the blocks in the given range of heights, along with their deploys and execution
+    /// results, from the node's storage to a versioned archive file.  The node must not be
+    /// running.
+    ExportBlocks {
+        /// Path to configuration file.
+        config: PathBuf,
+
+        /// Height of the first block to export.
+        #[structopt(long)]
+        from: u64,
+
+        /// Height of the last block to export.
+        #[structopt(long)]
+        to: u64,
+
+        /// Path of the archive file to write.
+        #[structopt(long)]
+        out: PathBuf,
+
+        #[structopt(
+            short = "C",
+            long,
+            env = "NODE_CONFIG",
+            use_delimiter(true),
+            value_delimiter(";")
+        )]
+        /// Overrides and extensions for configuration file entries in the form
+        /// <SECTION>.<KEY>=<VALUE>.  For example, '-C=node.chainspec_config_path=chainspec.toml'
+        config_ext: Vec<ConfigExt>,
+    },
+    /// Import blocks from an archive file.
+    ///
+    /// Re-executes the blocks held in an archive file written by `export-blocks`, verifying each
+    /// block's global state hash, and stores them in the node's storage.  The node must not be
+    /// running.
+    ImportBlocks {
+        /// Path to configuration file.
+        config: PathBuf,
+
+        /// Path of the archive file to read.
+        #[structopt(long = "in")]
+        input: PathBuf,
+
         #[structopt(
             short = "C",
             long,
@@ -113,29 +173,7 @@ impl Cli {
     pub async fn run(self) -> anyhow::Result<()> {
         match self {
             Cli::Validator { config, config_ext } => {
-                // Determine the parent directory of the configuration file, if any.
-                // Otherwise, we default to `/`.
-                let root = config
-                    .parent()
-                    .map(|path| path.to_owned())
-                    .unwrap_or_else(|| "/".into());
-
-                // The app supports running without a config file, using default values.
-                let config_raw: String = fs::read_to_string(&config)
-                    .context("could not read configuration file")
-                    .with_context(|| config.display().to_string())?;
-
-                // Get the TOML table version of the config indicated from CLI args, or from a new
-                // defaulted config instance if one is not provided.
-                let mut config_table: Value = toml::from_str(&config_raw)?;
-
-                // If any command line overrides to the config values are passed, apply them.
-                for item in config_ext {
-                    item.update_toml_table(&mut config_table)?;
-                }
-
-                // Create validator config, including any overridden values.
-                let validator_config: validator::Config = config_table.try_into()?;
+                let (root, validator_config) = load_config(&config, config_ext)?;
                 logging::init_with_config(&validator_config.logging)?;
                 info!(version = %env!("CARGO_PKG_VERSION"), "node starting up");
                 trace!("{}", config::to_string(&validator_config)?);
@@ -181,8 +219,68 @@ impl Cli {
                         .await?;
                 validator_runner.run(&mut rng).await;
             }
+            Cli::ExportBlocks {
+                config,
+                from,
+                to,
+                out,
+                config_ext,
+            } => {
+                let (_root, validator_config) = load_config(&config, config_ext)?;
+                logging::init_with_config(&validator_config.logging)?;
+                let exported_count =
+                    archive::export_blocks(&validator_config.storage, from, to, &out)?;
+                println!("exported {} blocks to {}", exported_count, out.display());
+            }
+            Cli::ImportBlocks {
+                config,
+                input,
+                config_ext,
+            } => {
+                let (root, validator_config) = load_config(&config, config_ext)?;
+                logging::init_with_config(&validator_config.logging)?;
+                let imported_count =
+                    archive::import_blocks(WithDir::new(root, validator_config), &input)?;
+                println!(
+                    "imported {} blocks from {}",
+                    imported_count,
+                    input.display()
+                );
+            }
         }
 
         Ok(())
     }
 }
+
+/// Loads the configuration file, applying any command line overrides.
+///
+/// Returns the parent directory of the configuration file along with the resulting config.
+fn load_config(
+    config: &Path,
+    config_ext: Vec<ConfigExt>,
+) -> anyhow::Result<(PathBuf, validator::Config)> {
+    // Determine the parent directory of the configuration file, if any.
+    // Otherwise, we default to `/`.
+    let root = config
+        .parent()
+        .map(|path| path.to_owned())
+        .unwrap_or_else(|| "/".into());
+
+    // The app supports running without a config file, using default values.
+    let config_raw: String = fs::read_to_string(config)
+        .context("could not read configuration file")
+        .with_context(|| config.display().to_string())?;
+
+    // Get the TOML table version of the config indicated from CLI args, or from a new
+    // defaulted config instance if one is not provided.
+    let mut config_table: Value = toml::from_str(&config_raw)?;
+
+    // If any command line overrides to the config values are passed, apply them.
+    for item in config_ext {
+        item.update_toml_table(&mut config_table)?;
+    }
+
+    // Create validator config, including any overridden values.
+    Ok((root, config_table.try_into()?))
+}
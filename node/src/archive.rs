@@ -0,0 +1,460 @@
+//! Offline export and import of the linear chain.
+//!
+//! An archive starts with a header made up of the magic bytes [`MAGIC`] followed by the format
+//! version as a little-endian `u32`.  The header is followed by one record per block, ordered by
+//! height.  Each record is a little-endian `u32` length followed by that many bytes of a
+//! MessagePack-encoded [`ArchivedBlock`], holding the block along with its deploys and their
+//! execution results.
+//!
+//! Importing an archive re-executes each block on top of the global state of the block's parent in
+//! the same way as the block executor: its deploys, then its system operations, then the upgrade
+//! activated in the next era if it is a switch block, all under the protocol version of the block's
+//! era.  The resulting global state hash is verified before storing the block.
+
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use prometheus::Registry;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+use casper_execution_engine::{
+    core::engine_state::{
+        self, genesis::GenesisResult, step::StepResult, upgrade::UpgradeResult,
+        BlockExecutionResult,
+    },
+    shared::newtypes::CorrelationId,
+};
+
+use crate::{
+    components::{
+        block_executor,
+        contract_runtime::{ConfigError as ContractRuntimeConfigError, ContractRuntime},
+        storage::{Storage, StorageType},
+    },
+    crypto::hash::Digest,
+    reactor::validator::Config,
+    types::{
+        json_compatibility::ExecutionResult, Block, BlockError, BlockExecutionSummary, Deploy,
+        DeployHash, FinalizedBlock,
+    },
+    utils::{Loadable, WithDir},
+    Chainspec, StorageConfig, StorageError,
+};
+
+/// The magic bytes at the start of every archive.
+pub const MAGIC: &[u8; 8] = b"CSPRCHN\0";
+/// The current version of the archive format.
+pub const FORMAT_VERSION: u32 = 1;
+/// The maximum length of a record, which bounds the memory allocated to read one.
+pub const MAX_RECORD_LENGTH: u32 = 268_435_456; // 256 MiB
+
+/// Error exporting or importing an archive.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Error reading or writing the archive file.
+    #[error("archive I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// Error accessing the block or deploy store.
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+    /// Error encoding a record.
+    #[error("failed to encode archived block: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+    /// Error decoding a record.
+    #[error("failed to decode archived block: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+    /// The archive doesn't start with the expected magic bytes.
+    #[error("not a chain archive")]
+    NotAnArchive,
+    /// The archive was written in an unsupported version of the format.
+    #[error("unsupported archive format version {0}")]
+    UnsupportedVersion(u32),
+    /// A record is too large to be written to the archive.
+    #[error("archived block at height {0} is too large")]
+    RecordTooLarge(u64),
+    /// A record read from the archive claims to be longer than the maximum record length.
+    #[error(
+        "archive record length {0} exceeds the maximum of {}",
+        MAX_RECORD_LENGTH
+    )]
+    InvalidRecordLength(u32),
+    /// A deploy of an exported block is missing from the deploy store.
+    #[error("deploy {deploy_hash} of block at height {height} is missing")]
+    MissingDeploy {
+        /// The height of the block.
+        height: u64,
+        /// The hash of the missing deploy.
+        deploy_hash: DeployHash,
+    },
+    /// Failed to load the chainspec.
+    #[error("failed to load chainspec: {0}")]
+    Chainspec(String),
+    /// Failed to initialize the contract runtime.
+    #[error(transparent)]
+    ContractRuntime(#[from] ContractRuntimeConfigError),
+    /// Failed to commit genesis.
+    #[error("failed to commit genesis: {0}")]
+    Genesis(String),
+    /// An imported block is invalid.
+    #[error("block at height {height} is invalid: {error}")]
+    InvalidBlock {
+        /// The height of the block.
+        height: u64,
+        /// The underlying error.
+        error: BlockError,
+    },
+    /// The parent of the first imported block isn't held locally.
+    #[error("parent of block at height {0} is not held locally")]
+    MissingParent(u64),
+    /// An imported block doesn't follow on from its predecessor.
+    #[error("block at height {0} is not a child of its predecessor")]
+    NotAChild(u64),
+    /// The global state to execute a block on top of is missing.
+    #[error("global state {global_state_hash} for block at height {height} not found")]
+    RootNotFound {
+        /// The height of the block.
+        height: u64,
+        /// The missing global state hash.
+        global_state_hash: Digest,
+    },
+    /// Error executing or committing a deploy.
+    #[error("failed to execute block at height {height}: {error}")]
+    Execution {
+        /// The height of the block.
+        height: u64,
+        /// The underlying error.
+        error: engine_state::Error,
+    },
+    /// Committing the effects of the block's deploys failed.
+    #[error("failed to commit effects of block at height {height}: {result}")]
+    Commit {
+        /// The height of the block.
+        height: u64,
+        /// The failed block execution result.
+        result: BlockExecutionResult,
+    },
+    /// Running the block's system operations failed.
+    #[error("failed to commit step of block at height {height}: {result}")]
+    Step {
+        /// The height of the block.
+        height: u64,
+        /// The failed step result.
+        result: StepResult,
+    },
+    /// Running the upgrade activated after the block failed.
+    #[error("failed to commit upgrade after block at height {height}: {result}")]
+    Upgrade {
+        /// The height of the block.
+        height: u64,
+        /// The failed upgrade result.
+        result: UpgradeResult,
+    },
+    /// Re-executing a block resulted in a different global state hash.
+    #[error(
+        "global state hash mismatch for block at height {height}: expected {expected}, got {actual}"
+    )]
+    GlobalStateHashMismatch {
+        /// The height of the block.
+        height: u64,
+        /// The global state hash held in the block.
+        expected: Digest,
+        /// The global state hash resulting from re-executing the block.
+        actual: Digest,
+    },
+}
+
+/// A block along with its deploys and their execution results, as held in an archive.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedBlock {
+    block: Block,
+    deploys: Vec<Deploy>,
+    execution_results: HashMap<DeployHash, ExecutionResult>,
+}
+
+/// Writes the blocks with heights in the inclusive range `from..=to` along with their deploys and
+/// execution results to a new archive at `out`.
+///
+/// Returns the number of blocks exported.
+pub fn export_blocks(
+    storage_config: &StorageConfig,
+    from: u64,
+    to: u64,
+    out: &Path,
+) -> Result<usize, Error> {
+    let storage = Storage::new(storage_config)?;
+    let block_store = storage.block_store();
+    let deploy_store = storage.deploy_store();
+
+    let mut writer = BufWriter::new(File::create(out)?);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+
+    let blocks = block_store.get_by_height_range(from, to)?;
+    let exported_count = blocks.len();
+    for block in blocks {
+        let height = block.height();
+        let mut deploys = Vec::with_capacity(block.deploy_hashes().len());
+        let mut execution_results = HashMap::new();
+        for deploy_hash in block.deploy_hashes() {
+            let (deploy, mut metadata) = deploy_store
+                .get_deploy_and_metadata(*deploy_hash)?
+                .ok_or(Error::MissingDeploy {
+                    height,
+                    deploy_hash: *deploy_hash,
+                })?;
+            if let Some(execution_result) = metadata.execution_results.remove(block.hash()) {
+                let _ = execution_results.insert(*deploy_hash, execution_result);
+            }
+            deploys.push(deploy);
+        }
+
+        let record = rmp_serde::to_vec(&ArchivedBlock {
+            block,
+            deploys,
+            execution_results,
+        })?;
+        let length = u32::try_from(record.len())
+            .ok()
+            .filter(|length| *length <= MAX_RECORD_LENGTH)
+            .ok_or(Error::RecordTooLarge(height))?;
+        writer.write_all(&length.to_le_bytes())?;
+        writer.write_all(&record)?;
+        debug!(%height, "exported block");
+    }
+    writer.flush()?;
+
+    info!(%exported_count, %from, %to, "exported blocks");
+    Ok(exported_count)
+}
+
+/// Reads the archive at `input`, re-executes each of its blocks and stores them along with their
+/// deploys and execution results.
+///
+/// The first block must either be the genesis child or the child of a block already held locally.
+/// Import stops at the first block whose re-executed global state hash differs from the one held
+/// in the block.  Returns the number of blocks imported.
+pub fn import_blocks(config: WithDir<Config>, input: &Path) -> Result<usize, Error> {
+    let (root, config) = config.into_parts();
+    let storage = Storage::new(&config.storage)?;
+    let block_store = storage.block_store();
+    let deploy_store = storage.deploy_store();
+    let contract_runtime =
+        ContractRuntime::new(&config.storage, config.contract_runtime, &Registry::new())?;
+    let chainspec: Chainspec = config
+        .node
+        .chainspec_config_path
+        .clone()
+        .load(&root)
+        .map_err(|error| Error::Chainspec(error.to_string()))?;
+
+    let mut reader = BufReader::new(File::open(input)?);
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(Error::NotAnArchive);
+    }
+    let mut version = [0u8; 4];
+    reader.read_exact(&mut version)?;
+    let version = u32::from_le_bytes(version);
+    if version != FORMAT_VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+
+    let mut parent: Option<Block> = None;
+    let mut imported_count = 0;
+    while let Some(archived_block) = read_record(&mut reader)? {
+        let ArchivedBlock {
+            block,
+            deploys,
+            execution_results: archived_results,
+        } = archived_block;
+        let height = block.height();
+        block
+            .verify()
+            .map_err(|error| Error::InvalidBlock { height, error })?;
+
+        // Find the global state hash to execute the block on top of.
+        let pre_state_hash = if block.is_genesis_child() {
+            match contract_runtime.commit_genesis(Box::new(chainspec.clone())) {
+                Ok(GenesisResult::Success {
+                    post_state_hash, ..
+                }) => post_state_hash.into(),
+                Ok(genesis_result) => return Err(Error::Genesis(genesis_result.to_string())),
+                Err(error) => return Err(Error::Genesis(error.to_string())),
+            }
+        } else {
+            let parent = match parent.take() {
+                Some(parent) => parent,
+                None => block_store
+                    .get_by_height(height - 1)?
+                    .ok_or(Error::MissingParent(height))?,
+            };
+            if parent.hash() != block.parent_hash() || parent.height() + 1 != height {
+                return Err(Error::NotAChild(height));
+            }
+            *parent.global_state_hash()
+        };
+
+        let (post_state_hash, execution_results) = execute_block(
+            &contract_runtime,
+            &chainspec,
+            &block,
+            &deploys,
+            pre_state_hash,
+        )?;
+        if post_state_hash != *block.global_state_hash() {
+            return Err(Error::GlobalStateHashMismatch {
+                height,
+                expected: *block.global_state_hash(),
+                actual: post_state_hash,
+            });
+        }
+        if archived_results.len() != execution_results.len() {
+            debug!(
+                %height,
+                archived = archived_results.len(),
+                executed = execution_results.len(),
+                "archive holds a different number of execution results than re-executed"
+            );
+        }
+
+        let block_hash = *block.hash();
+        for deploy in deploys {
+            let _ = deploy_store.put_deploy(deploy)?;
+        }
+        for (deploy_hash, execution_result) in &execution_results {
+            let _ = deploy_store.put_execution_result(
+                *deploy_hash,
+                block_hash,
+                execution_result.clone(),
+            )?;
+        }
+        let execution_summary = BlockExecutionSummary::new(&block, &execution_results);
+        let _ = block_store.put_block(block.clone())?;
+        let _ = block_store.put_execution_summary(block_hash, execution_summary)?;
+
+        debug!(%height, %post_state_hash, "imported block");
+        parent = Some(block);
+        imported_count += 1;
+    }
+
+    info!(%imported_count, "imported blocks");
+    Ok(imported_count)
+}
+
+/// Reads the next record from the archive, or returns `None` at the end of the archive.
+fn read_record<R: Read>(reader: &mut R) -> Result<Option<ArchivedBlock>, Error> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => (),
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error.into()),
+    }
+    let length = u32::from_le_bytes(length);
+    if length > MAX_RECORD_LENGTH {
+        return Err(Error::InvalidRecordLength(length));
+    }
+    let mut record = vec![0u8; length as usize];
+    reader.read_exact(&mut record)?;
+    Ok(Some(rmp_serde::from_read_ref(&record)?))
+}
+
+/// Executes the given block on top of `pre_state_hash` and commits its effects in the same way as
+/// the block executor, returning the resulting global state hash along with the execution results
+/// of its deploys.
+fn execute_block(
+    contract_runtime: &ContractRuntime,
+    chainspec: &Chainspec,
+    block: &Block,
+    deploys: &[Deploy],
+    pre_state_hash: Digest,
+) -> Result<(Digest, HashMap<DeployHash, ExecutionResult>), Error> {
+    let height = block.height();
+    let engine_state = contract_runtime.engine_state();
+    let correlation_id = CorrelationId::new();
+    let finalized_block = FinalizedBlock::from(block.clone());
+    let deploys_by_hash: HashMap<_, _> = deploys
+        .iter()
+        .map(|deploy| (*deploy.id(), deploy))
+        .collect();
+    let mut block_deploys = Vec::with_capacity(block.deploy_hashes().len());
+    for deploy_hash in block.deploy_hashes() {
+        let deploy = *deploys_by_hash
+            .get(deploy_hash)
+            .ok_or(Error::MissingDeploy {
+                height,
+                deploy_hash: *deploy_hash,
+            })?;
+        block_deploys.push(deploy.clone());
+    }
+
+    let mut state_hash = pre_state_hash;
+    let mut execution_results = HashMap::new();
+    if !block_deploys.is_empty() {
+        let (deploy_hashes, execute_request) =
+            block_executor::execute_request(chainspec, &finalized_block, state_hash, block_deploys);
+        match engine_state
+            .execute_block(correlation_id, execute_request)
+            .map_err(|error| Error::Execution { height, error })?
+        {
+            BlockExecutionResult::Success {
+                post_state_hash,
+                execution_results: ee_execution_results,
+            } => {
+                execution_results = deploy_hashes
+                    .into_iter()
+                    .zip(&ee_execution_results)
+                    .map(|(deploy_hash, ee_execution_result)| {
+                        (deploy_hash, ExecutionResult::from(ee_execution_result))
+                    })
+                    .collect();
+                state_hash = post_state_hash.into();
+            }
+            BlockExecutionResult::RootNotFound => {
+                return Err(Error::RootNotFound {
+                    height,
+                    global_state_hash: state_hash,
+                })
+            }
+            result => return Err(Error::Commit { height, result }),
+        }
+    }
+
+    if block_executor::requires_step(&finalized_block) {
+        let step_request = block_executor::step_request(chainspec, &finalized_block, state_hash);
+        match engine_state.commit_step(correlation_id, step_request) {
+            Ok(StepResult::Success {
+                post_state_hash, ..
+            }) => state_hash = post_state_hash.into(),
+            Ok(result) => return Err(Error::Step { height, result }),
+            // As in the block executor, failed system operations leave the state unchanged.
+            Err(error) if block_executor::is_system_contract_failure(&error) => {
+                warn!(height, %error, "system operations failed - their effects are discarded")
+            }
+            Err(error) => return Err(Error::Execution { height, error }),
+        }
+    }
+
+    if let Some(upgrade_config) =
+        block_executor::upgrade_config(chainspec, &finalized_block, state_hash)
+    {
+        match engine_state
+            .commit_upgrade(correlation_id, upgrade_config)
+            .map_err(|error| Error::Execution { height, error })?
+        {
+            UpgradeResult::Success {
+                post_state_hash, ..
+            } => state_hash = post_state_hash.into(),
+            result => return Err(Error::Upgrade { height, result }),
+        }
+    }
+
+    Ok((state_hash, execution_results))
+}
@@ -9,18 +9,23 @@
 //!
 //! For the list of supported RPCs, see
 //! https://github.com/CasperLabs/ceps/blob/master/text/0009-client-api.md#rpcs
+//!
+//! Alongside the JSON-RPC API, the server provides a stream of server-sent events at the path
+//! `/events`, notifying clients of new blocks, executed deploys and detected faults.
 
 mod config;
 mod event;
 pub mod rpcs;
+pub mod sse_server;
 
-use std::{convert::Infallible, fmt::Debug, net::SocketAddr};
+use std::{convert::Infallible, fmt::Debug, net::SocketAddr, sync::Arc};
 
-use futures::{future, join};
-use hyper::Server;
+use futures::{future, join, FutureExt};
+use hyper::{service::Service, Body, Request, Server};
 use lazy_static::lazy_static;
 use rand::{CryptoRng, Rng};
 use semver::Version;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use tracing::{debug, info, warn};
 use warp::Filter;
 
@@ -31,22 +36,28 @@ use casper_types::{Key, URef};
 
 use super::Component;
 use crate::{
-    components::storage::Storage,
+    components::{
+        block_executor,
+        chainspec_loader::Chainspec,
+        consensus::EraId,
+        storage::{Storage, Value},
+    },
     crypto::hash::Digest,
     effect::{
         announcements::ApiServerAnnouncement,
         requests::{
-            ApiRequest, ContractRuntimeRequest, LinearChainRequest, MetricsRequest,
-            NetworkInfoRequest, StorageRequest,
+            ApiRequest, ContractRuntimeRequest, DeployBufferRequest, LinearChainRequest,
+            MetricsRequest, NetworkInfoRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, Effects, Responder,
     },
     small_network::NodeId,
-    types::StatusFeed,
+    types::{Block, StatusFeed, Timestamp},
 };
 pub use config::Config;
 pub(crate) use event::Event;
 use rpcs::{RpcWithOptionalParamsExt, RpcWithParamsExt, RpcWithoutParamsExt};
+use sse_server::{EventStream, SseData, SSE_API_PATH};
 
 // TODO - confirm if we want to use the protocol version for this.
 lazy_static! {
@@ -61,6 +72,7 @@ trait ReactorEventT:
     + From<StorageRequest<Storage>>
     + From<LinearChainRequest<NodeId>>
     + From<ContractRuntimeRequest>
+    + From<DeployBufferRequest>
     + Send
 {
 }
@@ -71,53 +83,88 @@ impl<REv> ReactorEventT for REv where
         + From<StorageRequest<Storage>>
         + From<LinearChainRequest<NodeId>>
         + From<ContractRuntimeRequest>
+        + From<DeployBufferRequest>
         + Send
         + 'static
 {
 }
 
 #[derive(Debug)]
-pub(crate) struct ApiServer {}
+pub(crate) struct ApiServer {
+    /// Channel sender to pass event-stream data to the event-stream server.
+    sse_data_sender: UnboundedSender<SseData>,
+    /// The chainspec, used to find the protocol version to speculatively execute deploys under.
+    chainspec: Chainspec,
+}
 
 impl ApiServer {
-    pub(crate) fn new<REv>(config: Config, effect_builder: EffectBuilder<REv>) -> Self
+    pub(crate) fn new<REv>(
+        config: Config,
+        chainspec: Chainspec,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Self
     where
         REv: From<Event>
             + From<ApiRequest<NodeId>>
             + From<StorageRequest<Storage>>
             + From<LinearChainRequest<NodeId>>
             + From<ContractRuntimeRequest>
+            + From<DeployBufferRequest>
             + Send,
     {
-        tokio::spawn(run_server(config, effect_builder));
-        ApiServer {}
+        let (sse_data_sender, sse_data_receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run_server(config, effect_builder, sse_data_receiver));
+        ApiServer {
+            sse_data_sender,
+            chainspec,
+        }
     }
 }
 
 /// Run the HTTP server.
-async fn run_server<REv: ReactorEventT>(config: Config, effect_builder: EffectBuilder<REv>) {
+async fn run_server<REv: ReactorEventT>(
+    config: Config,
+    effect_builder: EffectBuilder<REv>,
+    sse_data_receiver: UnboundedReceiver<SseData>,
+) {
     let put_deploy = rpcs::account::PutDeploy::create_filter(effect_builder);
+    let list_deploys = rpcs::account::ListDeploys::create_filter(effect_builder);
     let get_block = rpcs::chain::GetBlock::create_filter(effect_builder);
+    let get_block_range = rpcs::chain::GetBlockRange::create_filter(effect_builder);
+    let get_block_execution = rpcs::chain::GetBlockExecution::create_filter(effect_builder);
+    let get_era_evidence = rpcs::chain::GetEraEvidence::create_filter(effect_builder);
     let get_global_state_hash = rpcs::chain::GetGlobalStateHash::create_filter(effect_builder);
     let get_item = rpcs::state::GetItem::create_filter(effect_builder);
     let get_balance = rpcs::state::GetBalance::create_filter(effect_builder);
+    let speculative_exec = rpcs::speculative_exec::SpeculativeExec::create_filter(effect_builder);
     let get_deploy = rpcs::info::GetDeploy::create_filter(effect_builder);
+    let get_pending_deploys = rpcs::info::GetPendingDeploys::create_filter(effect_builder);
     let get_peers = rpcs::info::GetPeers::create_filter(effect_builder);
     let get_status = rpcs::info::GetStatus::create_filter(effect_builder);
     let get_metrics = rpcs::info::GetMetrics::create_filter(effect_builder);
 
-    let service = warp_json_rpc::service(
+    let rpc_service = warp_json_rpc::service(
         put_deploy
+            .or(list_deploys)
             .or(get_block)
+            .or(get_block_range)
+            .or(get_block_execution)
+            .or(get_era_evidence)
             .or(get_global_state_hash)
             .or(get_item)
             .or(get_balance)
+            .or(speculative_exec)
             .or(get_deploy)
+            .or(get_pending_deploys)
             .or(get_peers)
             .or(get_status)
             .or(get_metrics),
     );
 
+    let event_stream = EventStream::new(config.event_stream_buffer_length as usize);
+    tokio::spawn(Arc::clone(&event_stream).run(sse_data_receiver));
+    let sse_service = warp::service(sse_server::create_filter(event_stream));
+
     let mut server_addr = SocketAddr::from((config.bind_interface, config.bind_port));
 
     // Try to bind to the user's chosen port, or if that fails, try once to bind to any port then
@@ -126,7 +173,17 @@ async fn run_server<REv: ReactorEventT>(config: Config, effect_builder: EffectBu
         match Server::try_bind(&server_addr) {
             Ok(builder) => {
                 let make_svc = hyper::service::make_service_fn(move |_| {
-                    future::ok::<_, Infallible>(service.clone())
+                    let mut rpc_service = rpc_service.clone();
+                    let mut sse_service = sse_service.clone();
+                    // Route requests for the event stream past the JSON-RPC handling.
+                    let service = hyper::service::service_fn(move |request: Request<Body>| {
+                        if request.uri().path().trim_start_matches('/') == SSE_API_PATH {
+                            sse_service.call(request).boxed()
+                        } else {
+                            rpc_service.call(request).boxed()
+                        }
+                    });
+                    future::ok::<_, Infallible>(service)
                 });
                 let server = builder.serve(make_svc);
                 info!(address = %server.local_addr(), "started HTTP server");
@@ -181,6 +238,31 @@ impl ApiServer {
                 main_responder: responder,
             })
     }
+
+    /// Passes the given data to the event-stream server, to be sent to all subscribed clients.
+    fn broadcast(&mut self, sse_data: SseData) -> Effects<Event> {
+        if self.sse_data_sender.send(sse_data).is_err() {
+            debug!("event stream server has stopped; dropping event");
+        }
+        Effects::new()
+    }
+}
+
+/// Attaches the finality signatures stored for the given block to it.
+async fn with_finality_signatures<REv>(
+    effect_builder: EffectBuilder<REv>,
+    mut block: Block,
+) -> Block
+where
+    REv: From<StorageRequest<Storage>> + Send,
+{
+    let finality_signatures = effect_builder
+        .get_finality_signatures_from_storage::<Storage>(*block.hash())
+        .await;
+    for (public_key, signature) in finality_signatures {
+        block.append_proof(public_key, signature);
+    }
+    block
 }
 
 impl<REv, R> Component<REv, R> for ApiServer
@@ -189,6 +271,7 @@ where
         + From<NetworkInfoRequest<NodeId>>
         + From<LinearChainRequest<NodeId>>
         + From<ContractRuntimeRequest>
+        + From<DeployBufferRequest>
         + From<MetricsRequest>
         + From<StorageRequest<Storage>>
         + From<Event>
@@ -230,6 +313,50 @@ where
                     result: Box::new(result),
                     main_responder: responder,
                 }),
+            Event::ApiRequest(ApiRequest::GetBlockAtHeight { height, responder }) => async move {
+                let maybe_block = match effect_builder
+                    .get_block_at_height_from_storage(height)
+                    .await
+                {
+                    Some(block) => Some(with_finality_signatures(effect_builder, block).await),
+                    None => None,
+                };
+                responder.respond(maybe_block).await
+            }
+            .ignore(),
+            Event::ApiRequest(ApiRequest::GetBlockExecutionSummary {
+                block_hash,
+                responder,
+            }) => async move {
+                let maybe_execution_summary = effect_builder
+                    .get_execution_summary_from_storage(block_hash)
+                    .await;
+                responder.respond(maybe_execution_summary).await
+            }
+            .ignore(),
+            Event::ApiRequest(ApiRequest::GetEraEvidence { era_id, responder }) => async move {
+                let era_evidence = effect_builder.get_era_evidence_from_storage(era_id).await;
+                responder.respond(era_evidence).await
+            }
+            .ignore(),
+            Event::ApiRequest(ApiRequest::GetLowestRetainedHeight { responder }) => async move {
+                let lowest_retained_height = effect_builder
+                    .get_lowest_retained_height_from_storage()
+                    .await;
+                responder.respond(lowest_retained_height).await
+            }
+            .ignore(),
+            Event::ApiRequest(ApiRequest::GetBlocksInHeightRange {
+                from_height,
+                to_height,
+                responder,
+            }) => async move {
+                let blocks = effect_builder
+                    .get_blocks_in_height_range_from_storage(from_height, to_height)
+                    .await;
+                responder.respond(blocks).await
+            }
+            .ignore(),
             Event::ApiRequest(ApiRequest::QueryGlobalState {
                 global_state_hash,
                 base_key,
@@ -241,6 +368,18 @@ where
                 purse_uref,
                 responder,
             }) => self.handle_get_balance(effect_builder, global_state_hash, purse_uref, responder),
+            Event::ApiRequest(ApiRequest::SpeculativeExecute {
+                global_state_hash,
+                deploy,
+                responder,
+            }) => effect_builder
+                .get_highest_block_from_storage()
+                .event(move |maybe_block| Event::GetHighestBlockResult {
+                    global_state_hash,
+                    deploy,
+                    maybe_block: Box::new(maybe_block),
+                    main_responder: responder,
+                }),
             Event::ApiRequest(ApiRequest::GetDeploy { hash, responder }) => effect_builder
                 .get_deploy_and_metadata_from_storage(hash)
                 .event(move |result| Event::GetDeployResult {
@@ -248,12 +387,45 @@ where
                     result: Box::new(result),
                     main_responder: responder,
                 }),
+            Event::ApiRequest(ApiRequest::GetDeployStatus { hash, responder }) => async move {
+                let status = effect_builder.get_deploy_status(hash).await;
+                responder.respond(status).await
+            }
+            .ignore(),
+            Event::ApiRequest(ApiRequest::ListPendingDeploys {
+                account,
+                expires_before,
+                responder,
+            }) => async move {
+                let pending_deploys = effect_builder
+                    .list_pending_deploys(account, expires_before)
+                    .await;
+                responder.respond(pending_deploys).await
+            }
+            .ignore(),
+            Event::ApiRequest(ApiRequest::GetDeploysForAccount {
+                account,
+                offset,
+                limit,
+                responder,
+            }) => async move {
+                let account_deploys = effect_builder
+                    .get_deploys_for_account_from_storage(account, offset, limit)
+                    .await;
+                responder.respond(account_deploys).await
+            }
+            .ignore(),
             Event::ApiRequest(ApiRequest::GetPeers { responder }) => effect_builder
                 .network_peers()
                 .event(move |peers| Event::GetPeersResult {
                     peers,
                     main_responder: responder,
                 }),
+            Event::ApiRequest(ApiRequest::GetPeerReputations { responder }) => async move {
+                let reputations = effect_builder.network_peer_reputations().await;
+                responder.respond(reputations).await
+            }
+            .ignore(),
             Event::ApiRequest(ApiRequest::GetStatus { responder }) => async move {
                 let (last_finalized_block, peers) = join!(
                     effect_builder.get_last_finalized_block(),
@@ -274,7 +446,38 @@ where
                 maybe_hash: _,
                 result,
                 main_responder,
-            } => main_responder.respond(*result).ignore(),
+            } => match *result {
+                Some(block) => async move {
+                    let block = with_finality_signatures(effect_builder, block).await;
+                    main_responder.respond(Some(block)).await
+                }
+                .ignore(),
+                None => main_responder.respond(None).ignore(),
+            },
+            Event::GetHighestBlockResult {
+                global_state_hash,
+                deploy,
+                maybe_block,
+                main_responder,
+            } => {
+                // Execute under the protocol version of the era the next block will be created in.
+                let current_era =
+                    (*maybe_block).map_or(EraId(0), |block| block.header().next_block_era_id());
+                let protocol_version =
+                    block_executor::protocol_version(&self.chainspec, current_era);
+                async move {
+                    let result = effect_builder
+                        .speculatively_execute(
+                            global_state_hash,
+                            Timestamp::now(),
+                            protocol_version,
+                            deploy,
+                        )
+                        .await;
+                    main_responder.respond(result).await
+                }
+                .ignore()
+            }
             Event::QueryGlobalStateResult {
                 result,
                 main_responder,
@@ -296,6 +499,28 @@ where
                 text,
                 main_responder,
             } => main_responder.respond(text).ignore(),
+            Event::BlockAdded(block) => self.broadcast(SseData::BlockAdded {
+                block_hash: hex::encode(block.hash().as_ref()),
+                block: block.to_json(),
+            }),
+            Event::DeployProcessed {
+                deploy_hash,
+                block_hash,
+                execution_result,
+            } => self.broadcast(SseData::DeployProcessed {
+                deploy_hash: hex::encode(deploy_hash.as_ref()),
+                block_hash: hex::encode(block_hash.as_ref()),
+                execution_result: *execution_result,
+            }),
+            Event::Fault {
+                era_id,
+                public_key,
+                timestamp,
+            } => self.broadcast(SseData::Fault {
+                era_id,
+                public_key: public_key.to_hex(),
+                timestamp,
+            }),
         }
     }
 }
@@ -2,6 +2,9 @@ use std::net::{IpAddr, Ipv4Addr};
 
 use serde::{Deserialize, Serialize};
 
+/// Default number of past events held by the event stream.
+const DEFAULT_EVENT_STREAM_BUFFER_LENGTH: u32 = 100;
+
 /// API server configuration.
 #[derive(Debug, Deserialize, Serialize)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
@@ -12,6 +15,10 @@ pub struct Config {
 
     /// Port to bind to. Use 0 for a random port.
     pub bind_port: u16,
+
+    /// The number of past events held by the event stream, available to clients resuming a
+    /// dropped connection.
+    pub event_stream_buffer_length: u32,
 }
 
 impl Config {
@@ -20,6 +27,7 @@ impl Config {
         Config {
             bind_interface: Ipv4Addr::LOCALHOST.into(),
             bind_port: 0,
+            event_stream_buffer_length: DEFAULT_EVENT_STREAM_BUFFER_LENGTH,
         }
     }
 }
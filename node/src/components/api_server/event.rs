@@ -6,12 +6,15 @@ use std::{
 
 use derive_more::From;
 
-use casper_execution_engine::core::engine_state::{self, BalanceResult, QueryResult};
+use casper_execution_engine::core::engine_state::{
+    self, execution_result::ExecutionResult as EngineExecutionResult, BalanceResult, QueryResult,
+};
 
 use crate::{
-    components::{small_network::NodeId, storage::DeployMetadata},
+    components::{consensus::EraId, small_network::NodeId, storage::DeployMetadata},
+    crypto::{asymmetric_key::PublicKey, hash::Digest},
     effect::{requests::ApiRequest, Responder},
-    types::{Block, BlockHash, Deploy, DeployHash},
+    types::{json_compatibility::ExecutionResult, Block, BlockHash, Deploy, DeployHash, Timestamp},
 };
 
 #[derive(Debug, From)]
@@ -23,6 +26,12 @@ pub enum Event {
         result: Box<Option<Block>>,
         main_responder: Responder<Option<Block>>,
     },
+    GetHighestBlockResult {
+        global_state_hash: Digest,
+        deploy: Box<Deploy>,
+        maybe_block: Box<Option<Block>>,
+        main_responder: Responder<Result<EngineExecutionResult, engine_state::RootNotFound>>,
+    },
     QueryGlobalStateResult {
         result: Result<QueryResult, engine_state::Error>,
         main_responder: Responder<Result<QueryResult, engine_state::Error>>,
@@ -44,6 +53,20 @@ pub enum Event {
         result: Result<BalanceResult, engine_state::Error>,
         main_responder: Responder<Result<BalanceResult, engine_state::Error>>,
     },
+    /// A block has been added to the linear chain.
+    BlockAdded(Box<Block>),
+    /// A deploy has been executed as part of a block.
+    DeployProcessed {
+        deploy_hash: DeployHash,
+        block_hash: BlockHash,
+        execution_result: Box<ExecutionResult>,
+    },
+    /// A validator has been found to have equivocated.
+    Fault {
+        era_id: EraId,
+        public_key: Box<PublicKey>,
+        timestamp: Timestamp,
+    },
 }
 
 impl Display for Event {
@@ -60,6 +83,16 @@ impl Display for Event {
                 result,
                 ..
             } => write!(formatter, "get latest block result: {:?}", result),
+            Event::GetHighestBlockResult {
+                deploy,
+                maybe_block,
+                ..
+            } => write!(
+                formatter,
+                "get highest block result for speculatively executing {}: {:?}",
+                deploy.id(),
+                maybe_block
+            ),
             Event::QueryGlobalStateResult { result, .. } => {
                 write!(formatter, "query result: {:?}", result)
             }
@@ -74,6 +107,13 @@ impl Display for Event {
                 Some(txt) => write!(formatter, "get metrics ({} bytes)", txt.len()),
                 None => write!(formatter, "get metrics (failed)"),
             },
+            Event::BlockAdded(block) => write!(formatter, "block added {}", block.hash()),
+            Event::DeployProcessed { deploy_hash, .. } => {
+                write!(formatter, "deploy processed {}", deploy_hash)
+            }
+            Event::Fault {
+                era_id, public_key, ..
+            } => write!(formatter, "fault in era {} by {}", era_id, public_key),
         }
     }
 }
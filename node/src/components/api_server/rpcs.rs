@@ -5,6 +5,7 @@
 pub mod account;
 pub mod chain;
 pub mod info;
+pub mod speculative_exec;
 pub mod state;
 
 use std::str;
@@ -43,6 +44,12 @@ enum ErrorCode {
     ParseGetBalanceURef = 32009,
     GetBalanceFailed = 32010,
     GetBalanceFailedToExecute = 32011,
+    InvalidBlockParams = 32012,
+    InvalidBlockRange = 32013,
+    ParsePublicKey = 32014,
+    NoGlobalState = 32015,
+    SpeculativeExecutionFailed = 32016,
+    BlockPruned = 32017,
 }
 
 #[derive(Debug)]
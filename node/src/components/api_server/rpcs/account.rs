@@ -11,12 +11,20 @@ use serde_json::{Map, Value};
 use tracing::info;
 use warp_json_rpc::Builder;
 
-use super::{ApiRequest, Error, ErrorCode, ReactorEventT, RpcWithParams, RpcWithParamsExt};
+use super::{
+    info::JsonExecutionResult, ApiRequest, Error, ErrorCode, ReactorEventT, RpcWithParams,
+    RpcWithParamsExt,
+};
 use crate::{
-    components::api_server::CLIENT_API_VERSION, effect::EffectBuilder, reactor::QueueKind,
-    types::Deploy,
+    components::api_server::CLIENT_API_VERSION, crypto::asymmetric_key::PublicKey,
+    effect::EffectBuilder, reactor::QueueKind, types::Deploy,
 };
 
+/// The number of deploys returned by "account_list_deploys" if no limit is specified.
+pub const DEFAULT_LIST_DEPLOYS_LIMIT: usize = 20;
+/// The maximum number of deploys returned by a single "account_list_deploys" RPC.
+pub const MAX_LIST_DEPLOYS_LIMIT: usize = 100;
+
 /// Params for "account_put_deploy" RPC request.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PutDeployParams {
@@ -85,3 +93,111 @@ impl RpcWithParamsExt for PutDeploy {
         .boxed()
     }
 }
+
+/// Params for "account_list_deploys" RPC request.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListDeploysParams {
+    /// Hex-encoded public key of the account.
+    pub public_key: String,
+    /// The number of most recent deploys to skip.  Defaults to 0.
+    pub offset: Option<usize>,
+    /// The maximum number of deploys to return.  Defaults to `DEFAULT_LIST_DEPLOYS_LIMIT` and is
+    /// capped at `MAX_LIST_DEPLOYS_LIMIT`.
+    pub limit: Option<usize>,
+}
+
+/// A deploy sent from the account, along with its execution status.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AccountDeploy {
+    /// Hex-encoded deploy hash.
+    pub deploy_hash: String,
+    /// The results of executing the deploy in the blocks which include it.  Empty if the deploy
+    /// has not been executed yet.
+    pub execution_results: Vec<JsonExecutionResult>,
+}
+
+/// Result for "account_list_deploys" RPC response.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListDeploysResult {
+    /// The RPC API version.
+    pub api_version: Version,
+    /// The total number of deploys from the account known to the node.
+    pub total_count: usize,
+    /// The requested page of deploys, ordered from the most recent.
+    pub deploys: Vec<AccountDeploy>,
+}
+
+/// "account_list_deploys" RPC
+pub struct ListDeploys {}
+
+impl RpcWithParams for ListDeploys {
+    const METHOD: &'static str = "account_list_deploys";
+    type RequestParams = ListDeploysParams;
+    type ResponseResult = ListDeploysResult;
+}
+
+impl RpcWithParamsExt for ListDeploys {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            // Try to parse the account's public key from the params.
+            let account =
+                match PublicKey::from_hex(&params.public_key).map_err(|error| error.to_string()) {
+                    Ok(public_key) => public_key,
+                    Err(error_msg) => {
+                        info!("failed to list deploys: {}", error_msg);
+                        return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                            ErrorCode::ParsePublicKey as i64,
+                            error_msg,
+                        ))?);
+                    }
+                };
+            let offset = params.offset.unwrap_or_default();
+            let limit = params
+                .limit
+                .unwrap_or(DEFAULT_LIST_DEPLOYS_LIMIT)
+                .min(MAX_LIST_DEPLOYS_LIMIT);
+
+            // Get the account's deploys and their metadata from storage.
+            let account_deploys = effect_builder
+                .make_request(
+                    |responder| ApiRequest::GetDeploysForAccount {
+                        account,
+                        offset,
+                        limit,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            // Return the result.
+            let deploys = account_deploys
+                .deploys
+                .into_iter()
+                .map(|(deploy_hash, metadata)| AccountDeploy {
+                    deploy_hash: hex::encode(deploy_hash.inner()),
+                    execution_results: metadata
+                        .execution_results
+                        .into_iter()
+                        .map(|(block_hash, result)| JsonExecutionResult {
+                            block_hash: hex::encode(block_hash.as_ref()),
+                            result,
+                        })
+                        .collect(),
+                })
+                .collect();
+
+            let result = Self::ResponseResult {
+                api_version: CLIENT_API_VERSION.clone(),
+                total_count: account_deploys.total_count,
+                deploys,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
@@ -13,20 +13,29 @@ use warp_json_rpc::Builder;
 
 use super::{
     ApiRequest, Error, ErrorCode, ReactorEventT, RpcWithOptionalParams, RpcWithOptionalParamsExt,
+    RpcWithParams, RpcWithParamsExt,
 };
 use crate::{
-    components::api_server::CLIENT_API_VERSION,
+    components::{api_server::CLIENT_API_VERSION, consensus::EraId},
     crypto::hash::Digest,
     effect::EffectBuilder,
     reactor::QueueKind,
-    types::{Block, BlockHash},
+    types::{Block, BlockHash, Evidence},
 };
 
+/// The maximum number of blocks which can be requested via a single "chain_get_block_range" RPC.
+pub const MAX_BLOCK_RANGE_LENGTH: u64 = 100;
+
 /// Params for "chain_get_block" RPC request.
-#[derive(Serialize, Deserialize, Debug)]
+///
+/// At most one of `block_hash` and `height` may be given.  If neither is given, the latest block
+/// is returned.
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct GetBlockParams {
     /// Hex-encoded block hash.
-    pub block_hash: String,
+    pub block_hash: Option<String>,
+    /// Block height.
+    pub height: Option<u64>,
 }
 
 /// Result for "chain_get_block" RPC response.
@@ -55,8 +64,20 @@ impl RpcWithOptionalParamsExt for GetBlock {
     ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
         async move {
             // Get the block.
-            let maybe_block_hash = maybe_params.map(|params| params.block_hash);
-            let maybe_block = match get_block(maybe_block_hash, effect_builder).await {
+            let params = maybe_params.unwrap_or_default();
+            let maybe_block = match (params.block_hash, params.height) {
+                (Some(_), Some(_)) => {
+                    info!("invalid block params: both hash and height given");
+                    let error = warp_json_rpc::Error::custom(
+                        ErrorCode::InvalidBlockParams as i64,
+                        "only one of block hash and height may be given",
+                    );
+                    return Ok(response_builder.error(error)?);
+                }
+                (maybe_block_hash, None) => get_block(maybe_block_hash, effect_builder).await,
+                (None, Some(height)) => get_block_at_height(height, effect_builder).await,
+            };
+            let maybe_block = match maybe_block {
                 Ok(maybe_block) => maybe_block,
                 Err(error) => return Ok(response_builder.error(error)?),
             };
@@ -72,6 +93,93 @@ impl RpcWithOptionalParamsExt for GetBlock {
     }
 }
 
+/// Params for "chain_get_block_range" RPC request.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetBlockRangeParams {
+    /// Height of the first block to be retrieved.
+    pub from_height: u64,
+    /// Height of the last block to be retrieved.  Must not be less than `from_height`, and at most
+    /// `MAX_BLOCK_RANGE_LENGTH` blocks may be requested.
+    pub to_height: u64,
+}
+
+/// Result for "chain_get_block_range" RPC response.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetBlockRangeResult {
+    /// The RPC API version.
+    pub api_version: Version,
+    /// JSON-encoded blocks, ordered by height.  Blocks not held by the node are omitted.
+    pub blocks: Vec<Value>,
+}
+
+/// "chain_get_block_range" RPC.
+pub struct GetBlockRange {}
+
+impl RpcWithParams for GetBlockRange {
+    const METHOD: &'static str = "chain_get_block_range";
+    type RequestParams = GetBlockRangeParams;
+    type ResponseResult = GetBlockRangeResult;
+}
+
+impl RpcWithParamsExt for GetBlockRange {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let GetBlockRangeParams {
+                from_height,
+                to_height,
+            } = params;
+
+            // Check the range is valid.
+            if to_height < from_height || to_height - from_height >= MAX_BLOCK_RANGE_LENGTH {
+                let error_msg = format!(
+                    "invalid block range {}..={}: at most {} blocks may be requested",
+                    from_height, to_height, MAX_BLOCK_RANGE_LENGTH
+                );
+                info!("{}", error_msg);
+                let error =
+                    warp_json_rpc::Error::custom(ErrorCode::InvalidBlockRange as i64, error_msg);
+                return Ok(response_builder.error(error)?);
+            }
+
+            // Check the range hasn't been pruned.
+            let lowest_retained_height = get_lowest_retained_height(effect_builder).await;
+            if from_height < lowest_retained_height {
+                let error_msg = format!(
+                    "invalid block range {}..={}: blocks below height {} have been pruned",
+                    from_height, to_height, lowest_retained_height
+                );
+                info!("{}", error_msg);
+                let error = warp_json_rpc::Error::custom(ErrorCode::BlockPruned as i64, error_msg);
+                return Ok(response_builder.error(error)?);
+            }
+
+            // Get the blocks from storage.
+            let blocks = effect_builder
+                .make_request(
+                    |responder| ApiRequest::GetBlocksInHeightRange {
+                        from_height,
+                        to_height,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            // Return the result.
+            let result = Self::ResponseResult {
+                api_version: CLIENT_API_VERSION.clone(),
+                blocks: blocks.iter().map(Block::to_json).collect(),
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
 /// Params for "chain_get_global_state_hash" RPC request.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetGlobalStateHashParams {
@@ -122,6 +230,131 @@ impl RpcWithOptionalParamsExt for GetGlobalStateHash {
     }
 }
 
+/// Params for "chain_get_block_execution" RPC request.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetBlockExecutionParams {
+    /// Hex-encoded block hash.
+    pub block_hash: String,
+}
+
+/// Result for "chain_get_block_execution" RPC response.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetBlockExecutionResult {
+    /// The RPC API version.
+    pub api_version: Version,
+    /// Hex-encoded block hash.
+    pub block_hash: Option<String>,
+    /// JSON-encoded summary of executing the block's deploys.
+    pub execution_summary: Option<Value>,
+}
+
+/// "chain_get_block_execution" RPC.
+pub struct GetBlockExecution {}
+
+impl RpcWithOptionalParams for GetBlockExecution {
+    const METHOD: &'static str = "chain_get_block_execution";
+    type OptionalRequestParams = GetBlockExecutionParams;
+    type ResponseResult = GetBlockExecutionResult;
+}
+
+impl RpcWithOptionalParamsExt for GetBlockExecution {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        maybe_params: Option<Self::OptionalRequestParams>,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            // Get the block.
+            let maybe_block_hash = maybe_params.map(|params| params.block_hash);
+            let maybe_block = match get_block(maybe_block_hash, effect_builder).await {
+                Ok(maybe_block) => maybe_block,
+                Err(error) => return Ok(response_builder.error(error)?),
+            };
+
+            // Get the execution summary from storage.
+            let maybe_execution_summary = match &maybe_block {
+                Some(block) => {
+                    let block_hash = *block.hash();
+                    effect_builder
+                        .make_request(
+                            |responder| ApiRequest::GetBlockExecutionSummary {
+                                block_hash,
+                                responder,
+                            },
+                            QueueKind::Api,
+                        )
+                        .await
+                }
+                None => None,
+            };
+
+            // Return the result.
+            let result = Self::ResponseResult {
+                api_version: CLIENT_API_VERSION.clone(),
+                block_hash: maybe_block.map(|block| hex::encode(block.hash().inner())),
+                execution_summary: maybe_execution_summary
+                    .map(|execution_summary| execution_summary.to_json()),
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Params for "chain_get_era_evidence" RPC request.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetEraEvidenceParams {
+    /// The era ID.
+    pub era_id: u64,
+}
+
+/// Result for "chain_get_era_evidence" RPC response.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetEraEvidenceResult {
+    /// The RPC API version.
+    pub api_version: Version,
+    /// JSON-encoded evidence of the equivocations in the era.
+    pub evidence: Vec<Value>,
+}
+
+/// "chain_get_era_evidence" RPC.
+pub struct GetEraEvidence {}
+
+impl RpcWithParams for GetEraEvidence {
+    const METHOD: &'static str = "chain_get_era_evidence";
+    type RequestParams = GetEraEvidenceParams;
+    type ResponseResult = GetEraEvidenceResult;
+}
+
+impl RpcWithParamsExt for GetEraEvidence {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            // Get the evidence from storage.
+            let era_evidence = effect_builder
+                .make_request(
+                    |responder| ApiRequest::GetEraEvidence {
+                        era_id: EraId(params.era_id),
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            // Return the result.
+            let result = Self::ResponseResult {
+                api_version: CLIENT_API_VERSION.clone(),
+                evidence: era_evidence.iter().map(Evidence::to_json).collect(),
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
 async fn get_block<REv: ReactorEventT>(
     maybe_hex_block_hash: Option<String>,
     effect_builder: EffectBuilder<REv>,
@@ -174,3 +407,45 @@ async fn get_block<REv: ReactorEventT>(
             .await)
     }
 }
+
+async fn get_block_at_height<REv: ReactorEventT>(
+    height: u64,
+    effect_builder: EffectBuilder<REv>,
+) -> Result<Option<Block>, warp_json_rpc::Error> {
+    let maybe_block = effect_builder
+        .make_request(
+            |responder| ApiRequest::GetBlockAtHeight { height, responder },
+            QueueKind::Api,
+        )
+        .await;
+
+    if maybe_block.is_none() {
+        let lowest_retained_height = get_lowest_retained_height(effect_builder).await;
+        if height < lowest_retained_height {
+            info!("block at height {} has been pruned", height);
+            return Err(warp_json_rpc::Error::custom(
+                ErrorCode::BlockPruned as i64,
+                format!(
+                    "block pruned: blocks below height {} are no longer held",
+                    lowest_retained_height
+                ),
+            ));
+        }
+        info!("failed to get block at height {} from storage", height);
+        return Err(warp_json_rpc::Error::custom(
+            ErrorCode::NoSuchBlock as i64,
+            "block not known",
+        ));
+    }
+
+    Ok(maybe_block)
+}
+
+async fn get_lowest_retained_height<REv: ReactorEventT>(effect_builder: EffectBuilder<REv>) -> u64 {
+    effect_builder
+        .make_request(
+            |responder| ApiRequest::GetLowestRetainedHeight { responder },
+            QueueKind::Api,
+        )
+        .await
+}
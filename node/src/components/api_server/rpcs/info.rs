@@ -16,15 +16,18 @@ use tracing::info;
 use warp_json_rpc::Builder;
 
 use super::{
-    ApiRequest, Error, ErrorCode, ReactorEventT, RpcWithParams, RpcWithParamsExt, RpcWithoutParams,
-    RpcWithoutParamsExt,
+    ApiRequest, Error, ErrorCode, ReactorEventT, RpcWithOptionalParams, RpcWithOptionalParamsExt,
+    RpcWithParams, RpcWithParamsExt, RpcWithoutParams, RpcWithoutParamsExt,
 };
 use crate::{
-    components::{api_server::CLIENT_API_VERSION, small_network::NodeId},
-    crypto::hash::Digest,
+    components::{
+        api_server::CLIENT_API_VERSION,
+        small_network::{NodeId, PeerReputation},
+    },
+    crypto::{asymmetric_key::PublicKey, hash::Digest},
     effect::EffectBuilder,
     reactor::QueueKind,
-    types::{json_compatibility::ExecutionResult, DeployHash},
+    types::{json_compatibility::ExecutionResult, DeployHash, DeployStatus, Timestamp},
 };
 
 /// Params for "info_get_deploy" RPC request.
@@ -52,6 +55,8 @@ pub struct GetDeployResult {
     pub deploy: Value,
     /// The map of block hash to execution result.
     pub execution_results: Vec<JsonExecutionResult>,
+    /// The stage of the deploy's lifecycle.
+    pub status: DeployStatus,
 }
 
 /// "info_get_deploy" RPC.
@@ -105,6 +110,27 @@ impl RpcWithParamsExt for GetDeploy {
                 }
             };
 
+            // A deploy with execution results is part of a finalized block, even if the deploy
+            // buffer has already forgotten about it.
+            let status = if !metadata.execution_results.is_empty() {
+                DeployStatus::Finalized
+            } else {
+                let status = effect_builder
+                    .make_request(
+                        |responder| ApiRequest::GetDeployStatus {
+                            hash: deploy_hash,
+                            responder,
+                        },
+                        QueueKind::Api,
+                    )
+                    .await;
+                if status == DeployStatus::Unknown && deploy.header().expires() < Timestamp::now() {
+                    DeployStatus::Expired
+                } else {
+                    status
+                }
+            };
+
             // Return the result.
             let deploy_as_json = deploy.to_json();
             let execution_results = metadata
@@ -120,6 +146,108 @@ impl RpcWithParamsExt for GetDeploy {
                 api_version: CLIENT_API_VERSION.clone(),
                 deploy: deploy_as_json,
                 execution_results,
+                status,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
+
+/// Params for "info_get_pending_deploys" RPC request.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GetPendingDeploysParams {
+    /// If given, only deploys sent from the account with this hex-encoded public key are listed.
+    pub public_key: Option<String>,
+    /// If given, only deploys expiring before this time are listed.
+    pub expires_before: Option<Timestamp>,
+}
+
+/// A deploy which is neither finalized nor expired.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PendingDeploy {
+    /// Hex-encoded deploy hash.
+    pub deploy_hash: String,
+    /// Hex-encoded public key of the account which sent the deploy.
+    pub account: String,
+    /// The time at which the deploy was created.
+    pub timestamp: Timestamp,
+    /// The time at which the deploy expires.
+    pub expires: Timestamp,
+    /// Whether the deploy is still pending or has been proposed in a block.
+    pub status: DeployStatus,
+}
+
+/// Result for "info_get_pending_deploys" RPC response.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetPendingDeploysResult {
+    /// The RPC API version.
+    pub api_version: Version,
+    /// The matching deploys, ordered by their timestamps.
+    pub deploys: Vec<PendingDeploy>,
+}
+
+/// "info_get_pending_deploys" RPC.
+pub struct GetPendingDeploys {}
+
+impl RpcWithOptionalParams for GetPendingDeploys {
+    const METHOD: &'static str = "info_get_pending_deploys";
+    type OptionalRequestParams = GetPendingDeploysParams;
+    type ResponseResult = GetPendingDeploysResult;
+}
+
+impl RpcWithOptionalParamsExt for GetPendingDeploys {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        maybe_params: Option<Self::OptionalRequestParams>,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            let params = maybe_params.unwrap_or_default();
+
+            // Try to parse the account's public key from the params.
+            let account = match params
+                .public_key
+                .map(|public_key| PublicKey::from_hex(&public_key))
+                .transpose()
+                .map_err(|error| error.to_string())
+            {
+                Ok(maybe_public_key) => maybe_public_key,
+                Err(error_msg) => {
+                    info!("failed to get pending deploys: {}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::ParsePublicKey as i64,
+                        error_msg,
+                    ))?);
+                }
+            };
+
+            // Get the pending deploys from the deploy buffer.
+            let pending_deploys = effect_builder
+                .make_request(
+                    |responder| ApiRequest::ListPendingDeploys {
+                        account,
+                        expires_before: params.expires_before,
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            // Return the result.
+            let deploys = pending_deploys
+                .into_iter()
+                .map(|pending_deploy| PendingDeploy {
+                    deploy_hash: hex::encode(pending_deploy.hash.inner()),
+                    account: pending_deploy.header.account().to_hex(),
+                    timestamp: pending_deploy.header.timestamp(),
+                    expires: pending_deploy.header.expires(),
+                    status: pending_deploy.status,
+                })
+                .collect();
+            let result = Self::ResponseResult {
+                api_version: CLIENT_API_VERSION.clone(),
+                deploys,
             };
             Ok(response_builder.success(result)?)
         }
@@ -134,6 +262,8 @@ pub struct GetPeersResult {
     pub api_version: Version,
     /// The node ID and network address of each connected peer.
     pub peers: BTreeMap<String, SocketAddr>,
+    /// The node ID and reputation of each peer that misbehaved or is currently banned.
+    pub reputations: BTreeMap<String, PeerReputation>,
 }
 
 /// "info_get_peers" RPC.
@@ -157,10 +287,22 @@ impl RpcWithoutParamsExt for GetPeers {
                 )
                 .await;
 
+            let reputations = effect_builder
+                .make_request(
+                    |responder| ApiRequest::GetPeerReputations { responder },
+                    QueueKind::Api,
+                )
+                .await;
+
             let peers = peers_hashmap_to_btreemap(peers);
+            let reputations = reputations
+                .into_iter()
+                .map(|(node_id, reputation)| (format!("{}", node_id), reputation))
+                .collect();
             let result = Self::ResponseResult {
                 api_version: CLIENT_API_VERSION.clone(),
                 peers,
+                reputations,
             };
             Ok(response_builder.success(result)?)
         }
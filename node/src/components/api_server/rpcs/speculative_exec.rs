@@ -0,0 +1,153 @@
+//! RPC for executing a deploy without committing its effects to global state.
+
+use std::str;
+
+use futures::{future::BoxFuture, FutureExt};
+use http::Response;
+use hyper::Body;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::info;
+use warp_json_rpc::Builder;
+
+use super::{ApiRequest, Error, ErrorCode, ReactorEventT, RpcWithParams, RpcWithParamsExt};
+use crate::{
+    components::api_server::CLIENT_API_VERSION,
+    crypto::hash::Digest,
+    effect::EffectBuilder,
+    reactor::QueueKind,
+    types::{json_compatibility::ExecutionResult, Deploy},
+};
+
+/// Params for "speculative_exec" RPC request.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpeculativeExecParams {
+    /// JSON-encoded deploy.
+    pub deploy: Map<String, Value>,
+    /// Hex-encoded global state hash to execute the deploy against.  If not given, the global
+    /// state hash of the latest block is used.
+    pub global_state_hash: Option<String>,
+}
+
+/// Result for "speculative_exec" RPC response.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpeculativeExecResult {
+    /// The RPC API version.
+    pub api_version: Version,
+    /// The deploy hash.
+    pub deploy_hash: String,
+    /// Hex-encoded global state hash the deploy was executed against.
+    pub global_state_hash: String,
+    /// The result of executing the deploy.  Its effects have not been committed.
+    pub execution_result: ExecutionResult,
+}
+
+/// "speculative_exec" RPC.
+pub struct SpeculativeExec {}
+
+impl RpcWithParams for SpeculativeExec {
+    const METHOD: &'static str = "speculative_exec";
+    type RequestParams = SpeculativeExecParams;
+    type ResponseResult = SpeculativeExecResult;
+}
+
+impl RpcWithParamsExt for SpeculativeExec {
+    fn handle_request<REv: ReactorEventT>(
+        effect_builder: EffectBuilder<REv>,
+        response_builder: Builder,
+        params: Self::RequestParams,
+    ) -> BoxFuture<'static, Result<Response<Body>, Error>> {
+        async move {
+            // Try to parse a deploy from the params.
+            let json_deploy = Value::Object(params.deploy);
+            let deploy = match Deploy::from_json(json_deploy).map_err(|error| error.to_string()) {
+                Ok(deploy) => deploy,
+                Err(error_msg) => {
+                    info!("failed to execute deploy: {}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::ParseDeploy as i64,
+                        error_msg,
+                    ))?);
+                }
+            };
+
+            // Try to parse the global state hash from the params, or else use the latest block's.
+            let global_state_hash = match params.global_state_hash {
+                Some(hex_global_state_hash) => match Digest::from_hex(&hex_global_state_hash)
+                    .map_err(|error| format!("failed to parse global state hash: {}", error))
+                {
+                    Ok(hash) => hash,
+                    Err(error_msg) => {
+                        info!("{}", error_msg);
+                        return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                            ErrorCode::ParseBlockHash as i64,
+                            error_msg,
+                        ))?);
+                    }
+                },
+                None => {
+                    let maybe_block = effect_builder
+                        .make_request(
+                            |responder| ApiRequest::GetBlock {
+                                maybe_hash: None,
+                                responder,
+                            },
+                            QueueKind::Api,
+                        )
+                        .await;
+                    match maybe_block {
+                        Some(block) => *block.global_state_hash(),
+                        None => {
+                            let error_msg = "no blocks to take the global state hash from";
+                            info!("{}", error_msg);
+                            return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                                ErrorCode::NoGlobalState as i64,
+                                error_msg,
+                            ))?);
+                        }
+                    }
+                }
+            };
+
+            let deploy_hash = *deploy.id();
+
+            // Execute the deploy.
+            let execution_result = effect_builder
+                .make_request(
+                    |responder| ApiRequest::SpeculativeExecute {
+                        global_state_hash,
+                        deploy: Box::new(deploy),
+                        responder,
+                    },
+                    QueueKind::Api,
+                )
+                .await;
+
+            let execution_result = match execution_result {
+                Ok(execution_result) => ExecutionResult::from(&execution_result),
+                Err(error) => {
+                    let error_msg = format!(
+                        "speculative execution failed: global state {} not found",
+                        hex::encode(error.to_vec())
+                    );
+                    info!("{}", error_msg);
+                    return Ok(response_builder.error(warp_json_rpc::Error::custom(
+                        ErrorCode::SpeculativeExecutionFailed as i64,
+                        error_msg,
+                    ))?);
+                }
+            };
+
+            // Return the result.
+            let result = Self::ResponseResult {
+                api_version: CLIENT_API_VERSION.clone(),
+                deploy_hash: hex::encode(deploy_hash.inner()),
+                global_state_hash: hex::encode(global_state_hash),
+                execution_result,
+            };
+            Ok(response_builder.success(result)?)
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,402 @@
+//! Server-sent events stream.
+//!
+//! Provides a long-lived HTTP endpoint next to the JSON-RPC one, over which the node pushes
+//! notifications about blocks added to the linear chain, executed deploys and detected faults.
+//!
+//! Every event is assigned an ID made of a prefix identifying the current run of the node and a
+//! sequence number, e.g. `1600000000000-42`.  The most recent events are buffered, so a client
+//! which loses its connection can resume from where it left off by reconnecting with the standard
+//! `Last-Event-ID` header set to the ID of the last event it received.  If that ID was assigned
+//! before the node was last restarted, or the events following it are no longer buffered, a `reset`
+//! event carrying the current run's prefix is sent to indicate that events were missed, followed
+//! by all buffered events.
+
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+    sync::{Arc, RwLock},
+};
+
+use futures::{future, stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, info};
+use warp::{sse::ServerSentEvent as _, Filter, Rejection, Reply};
+
+use crate::{
+    components::consensus::EraId,
+    types::{json_compatibility::ExecutionResult, Timestamp},
+};
+
+/// The URL path.
+pub const SSE_API_PATH: &str = "events";
+
+/// The name of the event telling a resuming client that some events it missed can't be sent.
+const RESET_EVENT: &str = "reset";
+
+/// The ID of a server-sent event.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Id {
+    /// Identifies the run of the node which assigned the ID: the time at which it started.
+    boot_id: u64,
+    /// The position of the event among all events of that run.
+    sequence: u64,
+}
+
+impl Id {
+    /// Parses an ID as formatted by its `Display` implementation.
+    fn parse(id: &str) -> Option<Self> {
+        let mut parts = id.splitn(2, '-');
+        let boot_id = parts.next()?.parse().ok()?;
+        let sequence = parts.next()?.parse().ok()?;
+        Some(Id { boot_id, sequence })
+    }
+}
+
+impl Display for Id {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}-{}", self.boot_id, self.sequence)
+    }
+}
+
+/// The data which is sent to clients subscribed to the event stream.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum SseData {
+    /// A block has been added to the linear chain.
+    BlockAdded {
+        /// Hex-encoded block hash.
+        block_hash: String,
+        /// JSON-encoded block.
+        block: Value,
+    },
+    /// A deploy has been executed as part of a block.
+    DeployProcessed {
+        /// Hex-encoded deploy hash.
+        deploy_hash: String,
+        /// Hex-encoded hash of the block in which the deploy was executed.
+        block_hash: String,
+        /// Execution result.
+        execution_result: ExecutionResult,
+    },
+    /// A validator has been found to have equivocated.
+    Fault {
+        /// The era in which the fault was detected.
+        era_id: EraId,
+        /// Hex-encoded public key of the faulty validator.
+        public_key: String,
+        /// The timestamp of the block in which the fault was finalized.
+        timestamp: Timestamp,
+    },
+}
+
+/// A single event, as sent to clients.
+#[derive(Clone, Debug)]
+struct ServerSentEvent {
+    id: Id,
+    data: SseData,
+}
+
+/// An item sent to a client.
+#[derive(Clone, Debug)]
+enum StreamItem {
+    /// Marks that some of the events since the client's last ID can't be sent.
+    Reset,
+    Event(ServerSentEvent),
+}
+
+/// The state shared between the task feeding the event stream and the connected clients.
+#[derive(Debug)]
+pub(super) struct EventStream {
+    /// The prefix of the IDs of all events sent by this run of the node.
+    boot_id: u64,
+    /// Channel notifying all connected clients of new events.
+    broadcaster: broadcast::Sender<ServerSentEvent>,
+    /// The most recent events, kept for clients resuming a dropped connection.
+    buffer: RwLock<VecDeque<ServerSentEvent>>,
+    /// The maximum number of events held in `buffer`.
+    buffer_length: usize,
+}
+
+impl EventStream {
+    /// Creates a new event stream, retaining up to `buffer_length` past events.
+    pub(super) fn new(buffer_length: usize) -> Arc<Self> {
+        let (broadcaster, _) = broadcast::channel(buffer_length.max(1));
+        Arc::new(EventStream {
+            boot_id: Timestamp::now().millis(),
+            broadcaster,
+            buffer: RwLock::new(VecDeque::with_capacity(buffer_length)),
+            buffer_length,
+        })
+    }
+
+    /// Assigns IDs to all data received via `sse_data_receiver` and sends them to the connected
+    /// clients.  Runs until the sending half of the channel is dropped.
+    pub(super) async fn run(
+        self: Arc<Self>,
+        mut sse_data_receiver: mpsc::UnboundedReceiver<SseData>,
+    ) {
+        let mut next_sequence = 0;
+        while let Some(data) = sse_data_receiver.recv().await {
+            let id = Id {
+                boot_id: self.boot_id,
+                sequence: next_sequence,
+            };
+            let event = ServerSentEvent { id, data };
+            next_sequence += 1;
+
+            {
+                let mut buffer = self.buffer.write().expect("should lock");
+                if buffer.len() >= self.buffer_length {
+                    let _ = buffer.pop_front();
+                }
+                if self.buffer_length > 0 {
+                    buffer.push_back(event.clone());
+                }
+            }
+
+            // An error only indicates that there are no connected clients.
+            let _ = self.broadcaster.send(event);
+        }
+        info!("event stream stopped");
+    }
+
+    /// Returns the stream of events for a newly connected client.
+    ///
+    /// If `maybe_last_id` is `Some`, buffered events with a later ID are sent first.  A last ID
+    /// which wasn't assigned by this run of the node causes all buffered events to be sent.  In
+    /// that case, or if events following the last ID have already been dropped from the buffer, a
+    /// reset marker is sent before them.
+    fn subscribe(&self, maybe_last_id: Option<&str>) -> impl Stream<Item = StreamItem> {
+        // Subscribe before reading the buffer so that no event can be missed in between.
+        let receiver = self.broadcaster.subscribe();
+        let maybe_last_sequence = maybe_last_id
+            .and_then(Id::parse)
+            .filter(|id| id.boot_id == self.boot_id)
+            .map(|id| id.sequence);
+        let (reset, buffered): (bool, Vec<ServerSentEvent>) = match maybe_last_id {
+            Some(_) => {
+                let buffer = self.buffer.read().expect("should lock");
+                let reset = match (maybe_last_sequence, buffer.front()) {
+                    (None, _) => true,
+                    (Some(sequence), Some(oldest)) => {
+                        sequence.saturating_add(1) < oldest.id.sequence
+                    }
+                    // Without a buffer, any events since the last ID are lost.
+                    (Some(_), None) => self.buffer_length == 0,
+                };
+                let buffered = buffer
+                    .iter()
+                    .filter(|event| {
+                        maybe_last_sequence.map_or(true, |sequence| event.id.sequence > sequence)
+                    })
+                    .cloned()
+                    .collect();
+                (reset, buffered)
+            }
+            None => (false, Vec::new()),
+        };
+        // Live events which have already been sent from the buffer are skipped.
+        let maybe_skip_up_to = buffered
+            .last()
+            .map(|event| event.id.sequence)
+            .or(maybe_last_sequence);
+
+        let live = stream::unfold(receiver, |mut receiver| async move {
+            match receiver.recv().await {
+                Ok(event) => Some((event, receiver)),
+                Err(broadcast::RecvError::Lagged(skipped)) => {
+                    // Close the connection; the client can resume via the buffered events.
+                    debug!(%skipped, "event stream client lagging, disconnecting");
+                    None
+                }
+                Err(broadcast::RecvError::Closed) => None,
+            }
+        })
+        .filter(move |event| {
+            future::ready(
+                maybe_skip_up_to.map_or(true, |skip_up_to| event.id.sequence > skip_up_to),
+            )
+        });
+
+        let maybe_reset = if reset { Some(StreamItem::Reset) } else { None };
+        stream::iter(maybe_reset).chain(stream::iter(buffered).chain(live).map(StreamItem::Event))
+    }
+}
+
+/// Formats an item as a server-sent event.  The reset marker carries the current run's ID prefix.
+fn format_item(
+    boot_id: u64,
+    item: StreamItem,
+) -> Result<impl warp::sse::ServerSentEvent, Infallible> {
+    match item {
+        StreamItem::Reset => Ok((warp::sse::event(RESET_EVENT), warp::sse::data(boot_id)).into_a()),
+        StreamItem::Event(event) => {
+            let id = warp::sse::id(event.id.to_string());
+            Ok((id, warp::sse::json(event.data)).into_b())
+        }
+    }
+}
+
+/// Creates the warp filter serving the event stream.
+pub(super) fn create_filter(
+    event_stream: Arc<EventStream>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::get()
+        .and(warp::path(SSE_API_PATH))
+        .and(warp::path::end())
+        .and(warp::sse::last_event_id::<String>())
+        .map(move |maybe_last_id: Option<String>| {
+            let boot_id = event_stream.boot_id;
+            let events = event_stream
+                .subscribe(maybe_last_id.as_deref())
+                .map(move |item| format_item(boot_id, item));
+            warp::sse::reply(warp::sse::keep_alive().stream(events))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fault(era: u64) -> SseData {
+        SseData::Fault {
+            era_id: EraId(era),
+            public_key: String::new(),
+            timestamp: Timestamp::zero(),
+        }
+    }
+
+    /// Feeds `count` events into the given stream, returning once they have all been processed.
+    async fn send_events(event_stream: &Arc<EventStream>, count: u64) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        for era in 0..count {
+            sender.send(fault(era)).unwrap();
+        }
+        drop(sender);
+        Arc::clone(event_stream).run(receiver).await;
+    }
+
+    /// Returns the sequence number of a sent event, or `None` for the reset marker.
+    fn sequence(item: StreamItem) -> Option<u64> {
+        match item {
+            StreamItem::Reset => None,
+            StreamItem::Event(event) => Some(event.id.sequence),
+        }
+    }
+
+    async fn received_sequences(
+        event_stream: &EventStream,
+        maybe_last_id: Option<&str>,
+        count: usize,
+    ) -> Vec<Option<u64>> {
+        event_stream
+            .subscribe(maybe_last_id)
+            .take(count)
+            .map(sequence)
+            .collect()
+            .await
+    }
+
+    #[test]
+    fn should_format_and_parse_ids() {
+        let id = Id {
+            boot_id: 1_600_000_000_000,
+            sequence: 42,
+        };
+        assert_eq!(id.to_string(), "1600000000000-42");
+        assert_eq!(Id::parse(&id.to_string()), Some(id));
+        assert_eq!(Id::parse("42"), None);
+        assert_eq!(Id::parse("a-42"), None);
+    }
+
+    #[tokio::test]
+    async fn should_assign_sequential_ids_with_boot_id_prefix() {
+        let event_stream = EventStream::new(10);
+        send_events(&event_stream, 3).await;
+
+        let ids: Vec<Id> = event_stream
+            .buffer
+            .read()
+            .unwrap()
+            .iter()
+            .map(|event| event.id)
+            .collect();
+        let expected: Vec<Id> = (0..3)
+            .map(|sequence| Id {
+                boot_id: event_stream.boot_id,
+                sequence,
+            })
+            .collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[tokio::test]
+    async fn should_replay_buffered_events_after_last_id() {
+        let event_stream = EventStream::new(3);
+        send_events(&event_stream, 5).await;
+
+        // Only the last three events are buffered.
+        let last_id = Id {
+            boot_id: event_stream.boot_id,
+            sequence: 2,
+        };
+        let sequences = received_sequences(&event_stream, Some(&last_id.to_string()), 2).await;
+        assert_eq!(sequences, vec![Some(3), Some(4)]);
+    }
+
+    #[tokio::test]
+    async fn should_reset_if_events_after_last_id_were_dropped() {
+        let event_stream = EventStream::new(3);
+        send_events(&event_stream, 5).await;
+
+        // Event 1 has been dropped from the buffer, which holds events 2 to 4.
+        let last_id = Id {
+            boot_id: event_stream.boot_id,
+            sequence: 0,
+        };
+        let sequences = received_sequences(&event_stream, Some(&last_id.to_string()), 4).await;
+        assert_eq!(sequences, vec![None, Some(2), Some(3), Some(4)]);
+
+        // Resuming right before the oldest buffered event doesn't miss any.
+        let last_id = Id {
+            boot_id: event_stream.boot_id,
+            sequence: 1,
+        };
+        let sequences = received_sequences(&event_stream, Some(&last_id.to_string()), 3).await;
+        assert_eq!(sequences, vec![Some(2), Some(3), Some(4)]);
+    }
+
+    #[tokio::test]
+    async fn should_replay_all_buffered_events_after_restart() {
+        let event_stream = EventStream::new(3);
+        send_events(&event_stream, 5).await;
+
+        // An ID from a previous run of the node, with a sequence number beyond the buffered ones.
+        let stale_id = Id {
+            boot_id: event_stream.boot_id - 1,
+            sequence: 10,
+        };
+        let sequences = received_sequences(&event_stream, Some(&stale_id.to_string()), 4).await;
+        assert_eq!(sequences, vec![None, Some(2), Some(3), Some(4)]);
+
+        let sequences = received_sequences(&event_stream, Some("invalid"), 4).await;
+        assert_eq!(sequences, vec![None, Some(2), Some(3), Some(4)]);
+    }
+
+    #[tokio::test]
+    async fn should_only_send_live_events_without_last_id() {
+        let event_stream = EventStream::new(3);
+        let subscription = event_stream.subscribe(None).take(2);
+        send_events(&event_stream, 2).await;
+
+        let sequences: Vec<_> = subscription.map(sequence).collect().await;
+        assert_eq!(sequences, vec![Some(0), Some(1)]);
+
+        // Without a last ID, buffered events aren't replayed.
+        let subscription = event_stream.subscribe(None);
+        futures::pin_mut!(subscription);
+        assert!(futures::poll!(subscription.next()).is_pending());
+    }
+}
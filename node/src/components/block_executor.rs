@@ -6,33 +6,41 @@ use std::{
 };
 
 use derive_more::From;
-use itertools::Itertools;
 use rand::{CryptoRng, Rng};
 use smallvec::SmallVec;
 use tracing::{debug, error, trace};
 
-use casper_execution_engine::{
-    core::engine_state::{
+use casper_execution_engine::core::{
+    engine_state::{
         self,
         deploy_item::DeployItem,
         execute_request::ExecuteRequest,
         execution_result::{ExecutionResult as EngineExecutionResult, ExecutionResults},
-        RootNotFound,
+        step::{RewardItem, StepRequest, StepResult},
+        upgrade::{UpgradeConfig, UpgradeResult},
+        BlockExecutionResult,
     },
-    storage::global_state::CommitResult,
+    execution,
 };
-use casper_types::ProtocolVersion;
+use casper_types::{ApiError, ProtocolVersion, U512};
 
 use crate::{
-    components::{storage::Storage, Component},
+    components::{
+        chainspec_loader::{to_protocol_version, Chainspec},
+        consensus::{EraId, BLOCK_REWARD},
+        storage::Storage,
+        Component,
+    },
     crypto::hash::Digest,
     effect::{
         announcements::BlockExecutorAnnouncement,
         requests::{BlockExecutorRequest, ContractRuntimeRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects,
     },
+    fatal,
     types::{
         json_compatibility::ExecutionResult, Block, BlockHash, Deploy, DeployHash, FinalizedBlock,
+        SystemTransaction,
     },
 };
 
@@ -69,21 +77,28 @@ pub enum Event {
         /// Contents of deploys. All deploys are expected to be present in the storage component.
         deploys: VecDeque<Deploy>,
     },
-    /// The result of executing a single deploy.
-    DeployExecutionResult {
+    /// The result of executing all deploys of the block and committing their effects.
+    ExecuteBlockResult {
+        /// State of this request.
+        state: Box<State>,
+        /// The IDs of the executed deploys, in the order they were executed.
+        deploy_hashes: Vec<DeployHash>,
+        /// Result of executing the deploys.
+        result: Result<BlockExecutionResult, engine_state::Error>,
+    },
+    /// The result of running and committing the block's system operations.
+    CommitStepResult {
         /// State of this request.
         state: Box<State>,
-        /// The ID of the deploy currently being executed.
-        deploy_hash: DeployHash,
-        /// Result of deploy execution.
-        result: Result<ExecutionResults, RootNotFound>,
+        /// Result of the step.
+        result: Result<StepResult, engine_state::Error>,
     },
-    /// The result of committing a single set of transforms after executing a single deploy.
-    CommitExecutionEffects {
+    /// The result of running and committing the upgrade activated in the next era.
+    CommitUpgradeResult {
         /// State of this request.
         state: Box<State>,
-        /// Commit result for execution request.
-        commit_result: Result<CommitResult, engine_state::Error>,
+        /// Result of the upgrade.
+        result: Result<UpgradeResult, engine_state::Error>,
     },
 }
 
@@ -100,51 +115,77 @@ impl Display for Event {
                 finalized_block.height(),
                 deploys.len()
             ),
-            Event::DeployExecutionResult {
+            Event::ExecuteBlockResult {
                 state,
-                deploy_hash,
-                result: Ok(_),
+                deploy_hashes,
+                result:
+                    Ok(BlockExecutionResult::Success {
+                        post_state_hash, ..
+                    }),
             } => write!(
                 f,
-                "execution result for {} of finalized block with height {} with \
-                pre-state hash {}: success",
-                deploy_hash,
+                "execution of {} deploys of finalized block with height {} with pre-state hash {}: \
+                success with post-state hash {}",
+                deploy_hashes.len(),
                 state.finalized_block.height(),
-                state.pre_state_hash
+                state.pre_state_hash,
+                post_state_hash,
             ),
-            Event::DeployExecutionResult {
+            Event::ExecuteBlockResult {
                 state,
-                deploy_hash,
-                result: Err(_),
+                deploy_hashes,
+                result,
             } => write!(
                 f,
-                "execution result for {} of finalized block with height {} with \
-                pre-state hash {}: root not found",
-                deploy_hash,
+                "execution of {} deploys of finalized block with height {} with pre-state hash {}: \
+                failed {:?}",
+                deploy_hashes.len(),
                 state.finalized_block.height(),
-                state.pre_state_hash
+                state.pre_state_hash,
+                result,
             ),
-            Event::CommitExecutionEffects {
+            Event::CommitStepResult {
                 state,
-                commit_result: Ok(CommitResult::Success { state_root, .. }),
+                result:
+                    Ok(StepResult::Success {
+                        post_state_hash, ..
+                    }),
             } => write!(
                 f,
-                "commit execution effects of finalized block with height {} with \
-                pre-state hash {}: success with post-state hash {}",
+                "commit step of finalized block with height {} with pre-state hash {}: success \
+                with post-state hash {}",
                 state.finalized_block.height(),
                 state.pre_state_hash,
-                state_root,
+                post_state_hash,
             ),
-            Event::CommitExecutionEffects {
+            Event::CommitStepResult { state, result } => write!(
+                f,
+                "commit step of finalized block with height {} with pre-state hash {}: failed {:?}",
+                state.finalized_block.height(),
+                state.pre_state_hash,
+                result,
+            ),
+            Event::CommitUpgradeResult {
                 state,
-                commit_result,
+                result:
+                    Ok(UpgradeResult::Success {
+                        post_state_hash, ..
+                    }),
             } => write!(
                 f,
-                "commit execution effects of finalized block with height {} with \
-                pre-state hash {}: failed {:?}",
+                "commit upgrade after finalized block with height {} with pre-state hash {}: \
+                success with post-state hash {}",
+                state.finalized_block.height(),
+                state.pre_state_hash,
+                post_state_hash,
+            ),
+            Event::CommitUpgradeResult { state, result } => write!(
+                f,
+                "commit upgrade after finalized block with height {} with pre-state hash {}: \
+                failed {:?}",
                 state.finalized_block.height(),
                 state.pre_state_hash,
-                commit_result,
+                result,
             ),
         }
     }
@@ -159,8 +200,13 @@ pub struct State {
     /// A collection of result of executing the deploys.
     execution_results: HashMap<DeployHash, ExecutionResult>,
     /// Current pre-state hash of global storage.  Is initialized with the parent block's
-    /// post-state hash, and is updated after each commit.
+    /// post-state hash, and is updated after the deploys, the system operations and the upgrade
+    /// have been committed.
     pre_state_hash: Digest,
+    /// Whether the block's system operations have already been run and committed.
+    step_committed: bool,
+    /// Whether the upgrade activated in the next era has already been run and committed.
+    upgrade_committed: bool,
 }
 
 #[derive(Debug)]
@@ -172,9 +218,11 @@ struct ExecutedBlockSummary {
 type BlockHeight = u64;
 
 /// The Block executor component.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub(crate) struct BlockExecutor {
     genesis_post_state_hash: Digest,
+    /// The chainspec, determining the protocol version and upgrades of each era.
+    chainspec: Chainspec,
     /// A mapping from proto block to executed block's ID and post-state hash, to allow
     /// identification of a parent block's details once a finalized block has been executed.
     ///
@@ -187,9 +235,10 @@ pub(crate) struct BlockExecutor {
 }
 
 impl BlockExecutor {
-    pub(crate) fn new(genesis_post_state_hash: Digest) -> Self {
+    pub(crate) fn new(genesis_post_state_hash: Digest, chainspec: Chainspec) -> Self {
         BlockExecutor {
             genesis_post_state_hash,
+            chainspec,
             parent_map: HashMap::new(),
             exec_queue: HashMap::new(),
         }
@@ -235,50 +284,51 @@ impl BlockExecutor {
             })
     }
 
-    /// Executes the first deploy in `state.remaining_deploys`, or creates the executed block if
-    /// there are no remaining deploys left.
-    fn execute_next_deploy_or_create_block<REv: ReactorEventT>(
+    /// Executes all deploys in `state.remaining_deploys` and commits their effects at once. If
+    /// there are no remaining deploys left, runs the block's system operations and the upgrade
+    /// activated in the next era if required, and then creates the executed block.
+    fn execute_deploys_or_create_block<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
         mut state: Box<State>,
     ) -> Effects<Event> {
-        let next_deploy = match state.remaining_deploys.pop_front() {
-            Some(deploy) => deploy,
-            None => {
-                // The state hash of the last execute-commit cycle is used as the block's post state
-                // hash.
-                let next_height = state.finalized_block.height() + 1;
-                let block = self.create_block(state.finalized_block, state.pre_state_hash);
-
-                let mut effects = effect_builder
-                    .announce_linear_chain_block(block, state.execution_results)
-                    .ignore();
-                // If the child is already finalized, start execution.
-                if let Some((finalized_block, deploys)) = self.exec_queue.remove(&next_height) {
-                    effects.extend(self.handle_get_deploys_result(
-                        effect_builder,
-                        finalized_block,
-                        deploys,
-                    ));
-                }
-                return effects;
+        if state.remaining_deploys.is_empty() {
+            if !state.step_committed && requires_step(&state.finalized_block) {
+                return self.commit_step(effect_builder, state);
             }
-        };
-        let deploy_hash = *next_deploy.id();
-        let deploy_item = DeployItem::from(next_deploy);
-
-        let execute_request = ExecuteRequest::new(
-            state.pre_state_hash.into(),
-            state.finalized_block.timestamp().millis(),
-            vec![Ok(deploy_item)],
-            ProtocolVersion::V1_0_0,
+            if let Some(upgrade_config) = self.upgrade_config(&state) {
+                return self.commit_upgrade(effect_builder, state, upgrade_config);
+            }
+            // The state hash of the last commit is used as the block's post state hash.
+            let next_height = state.finalized_block.height() + 1;
+            let block = self.create_block(state.finalized_block, state.pre_state_hash);
+
+            let mut effects = effect_builder
+                .announce_linear_chain_block(block, state.execution_results)
+                .ignore();
+            // If the child is already finalized, start execution.
+            if let Some((finalized_block, deploys)) = self.exec_queue.remove(&next_height) {
+                effects.extend(self.handle_get_deploys_result(
+                    effect_builder,
+                    finalized_block,
+                    deploys,
+                ));
+            }
+            return effects;
+        }
+
+        let (deploy_hashes, execute_request) = execute_request(
+            &self.chainspec,
+            &state.finalized_block,
+            state.pre_state_hash,
+            state.remaining_deploys.drain(..),
         );
 
         effect_builder
-            .request_execute(execute_request)
-            .event(move |result| Event::DeployExecutionResult {
+            .execute_block(execute_request)
+            .event(move |result| Event::ExecuteBlockResult {
                 state,
-                deploy_hash,
+                deploy_hashes,
                 result,
             })
     }
@@ -295,8 +345,10 @@ impl BlockExecutor {
                 remaining_deploys: deploys,
                 execution_results: HashMap::new(),
                 pre_state_hash,
+                step_committed: false,
+                upgrade_committed: false,
             });
-            self.execute_next_deploy_or_create_block(effect_builder, state)
+            self.execute_deploys_or_create_block(effect_builder, state)
         } else {
             let height = finalized_block.height();
             println!("No pre-state hash for height {}", height);
@@ -307,43 +359,76 @@ impl BlockExecutor {
         }
     }
 
-    /// Commits the execution effects.
-    fn commit_execution_effects<REv: ReactorEventT>(
+    /// Records the results of executing the block's deploys.
+    fn record_execution_results(
+        state: &mut State,
+        deploy_hashes: Vec<DeployHash>,
+        execution_results: ExecutionResults,
+    ) {
+        for (deploy_hash, ee_execution_result) in deploy_hashes.into_iter().zip(execution_results) {
+            match &ee_execution_result {
+                EngineExecutionResult::Success { effect, cost } => {
+                    debug!(%deploy_hash, ?effect, %cost, "execution succeeded");
+                }
+                EngineExecutionResult::Failure {
+                    error,
+                    effect,
+                    cost,
+                } => {
+                    error!(%deploy_hash, ?error, ?effect, %cost, "execution failure");
+                }
+            }
+            let execution_result = ExecutionResult::from(&ee_execution_result);
+            let _ = state
+                .execution_results
+                .insert(deploy_hash, execution_result);
+        }
+    }
+
+    /// Runs the system operations of the block: slashes the equivocators, pays out the rewards
+    /// and, in a switch block, runs the auction. Their effects are committed.
+    fn commit_step<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
         mut state: Box<State>,
-        deploy_hash: DeployHash,
-        execution_results: ExecutionResults,
     ) -> Effects<Event> {
-        let ee_execution_result = execution_results
-            .into_iter()
-            .exactly_one()
-            .expect("should only be one exec result");
-        let execution_result = ExecutionResult::from(&ee_execution_result);
-        let _ = state
-            .execution_results
-            .insert(deploy_hash, execution_result);
-
-        let execution_effect = match ee_execution_result {
-            EngineExecutionResult::Success { effect, cost } => {
-                debug!(?effect, %cost, "execution succeeded");
-                effect
-            }
-            EngineExecutionResult::Failure {
-                error,
-                effect,
-                cost,
-            } => {
-                error!(?error, ?effect, %cost, "execution failure");
-                effect
-            }
-        };
+        let step_request = step_request(
+            &self.chainspec,
+            &state.finalized_block,
+            state.pre_state_hash,
+        );
+        state.step_committed = true;
+
         effect_builder
-            .request_commit(state.pre_state_hash, execution_effect.transforms)
-            .event(|commit_result| Event::CommitExecutionEffects {
-                state,
-                commit_result,
-            })
+            .commit_step(step_request)
+            .event(|result| Event::CommitStepResult { state, result })
+    }
+
+    /// Returns the config of the upgrade to be run after the block, if it is a switch block and an
+    /// upgrade is activated in the next era, and it hasn't been committed yet.
+    fn upgrade_config(&self, state: &State) -> Option<UpgradeConfig> {
+        if state.upgrade_committed {
+            return None;
+        }
+        upgrade_config(
+            &self.chainspec,
+            &state.finalized_block,
+            state.pre_state_hash,
+        )
+    }
+
+    /// Runs the upgrade on top of the switch block's state, and commits its effects. All deploys
+    /// of the next era are executed using the upgrade's protocol version.
+    fn commit_upgrade<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        mut state: Box<State>,
+        upgrade_config: UpgradeConfig,
+    ) -> Effects<Event> {
+        state.upgrade_committed = true;
+        effect_builder
+            .commit_upgrade(upgrade_config)
+            .event(|result| Event::CommitUpgradeResult { state, result })
     }
 
     fn create_block(&mut self, finalized_block: FinalizedBlock, post_state_hash: Digest) -> Block {
@@ -381,6 +466,118 @@ impl BlockExecutor {
     }
 }
 
+/// Returns whether the block has system operations to be run after its deploys.
+pub(crate) fn requires_step(finalized_block: &FinalizedBlock) -> bool {
+    finalized_block.switch_block() || !finalized_block.system_transactions().is_empty()
+}
+
+/// Returns whether the step failed with an error returned by the mint, auction or proof-of-stake
+/// contract. Such a failure is the same on every node, so the block is created without the effects
+/// of its system operations. Any other failure, e.g. from deserialization, wasm execution or a
+/// missing key, might be specific to this node and is fatal.
+pub(crate) fn is_system_contract_failure(error: &engine_state::Error) -> bool {
+    match error {
+        engine_state::Error::Exec(execution::Error::Revert(api_error)) => matches!(
+            api_error,
+            ApiError::Mint(_) | ApiError::AuctionError(_) | ApiError::ProofOfStake(_)
+        ),
+        engine_state::Error::Exec(execution::Error::SystemContract(_))
+        | engine_state::Error::Mint(_) => true,
+        _ => false,
+    }
+}
+
+/// Returns the protocol version in effect during the given era.
+pub(crate) fn protocol_version(chainspec: &Chainspec, era_id: EraId) -> ProtocolVersion {
+    to_protocol_version(&chainspec.protocol_version(era_id))
+}
+
+/// Creates the request to execute the given deploys of the block on top of `pre_state_hash`,
+/// returning it along with the deploys' hashes in order of execution.
+pub(crate) fn execute_request<I>(
+    chainspec: &Chainspec,
+    finalized_block: &FinalizedBlock,
+    pre_state_hash: Digest,
+    deploys: I,
+) -> (Vec<DeployHash>, ExecuteRequest)
+where
+    I: IntoIterator<Item = Deploy>,
+{
+    let (deploy_hashes, deploy_items): (Vec<_>, Vec<_>) = deploys
+        .into_iter()
+        .map(|deploy| (*deploy.id(), Ok(DeployItem::from(deploy))))
+        .unzip();
+    let execute_request = ExecuteRequest::new(
+        pre_state_hash.into(),
+        finalized_block.timestamp().millis(),
+        deploy_items,
+        protocol_version(chainspec, finalized_block.era_id()),
+    );
+    (deploy_hashes, execute_request)
+}
+
+/// Creates the request to run the system operations of the block on top of `pre_state_hash`.
+pub(crate) fn step_request(
+    chainspec: &Chainspec,
+    finalized_block: &FinalizedBlock,
+    pre_state_hash: Digest,
+) -> StepRequest {
+    let mut slash_items = Vec::new();
+    let mut reward_items = Vec::new();
+    for system_transaction in finalized_block.system_transactions() {
+        match system_transaction {
+            SystemTransaction::Slash(public_key) => {
+                if finalized_block.has_evidence_against(public_key) {
+                    slash_items.push((*public_key).into());
+                } else {
+                    error!(%public_key, "not slashing validator without evidence against it");
+                }
+            }
+            // The rewards are in units of `BLOCK_REWARD` per block, and are scaled to the block
+            // reward in motes.
+            SystemTransaction::Rewards(rewards) => {
+                let block_reward = chainspec.genesis.highway_config.block_reward.value();
+                reward_items.extend(rewards.iter().map(|(public_key, amount)| {
+                    let motes = U512::from(*amount) * block_reward / BLOCK_REWARD;
+                    RewardItem::new(public_key.to_account_hash(), motes)
+                }))
+            }
+        }
+    }
+    StepRequest::new(
+        pre_state_hash.into(),
+        finalized_block.timestamp().millis(),
+        protocol_version(chainspec, finalized_block.era_id()),
+        slash_items,
+        reward_items,
+        finalized_block.switch_block(),
+    )
+}
+
+/// Returns the config of the upgrade to be run on top of `pre_state_hash` after the block, if it
+/// is a switch block and an upgrade is activated in the next era.
+pub(crate) fn upgrade_config(
+    chainspec: &Chainspec,
+    finalized_block: &FinalizedBlock,
+    pre_state_hash: Digest,
+) -> Option<UpgradeConfig> {
+    if !finalized_block.switch_block() {
+        return None;
+    }
+    let era_id = finalized_block.era_id();
+    let upgrade_point = chainspec.upgrade_activated_in(era_id.successor())?;
+    Some(UpgradeConfig::new(
+        pre_state_hash.into(),
+        finalized_block.timestamp().millis(),
+        protocol_version(chainspec, era_id),
+        to_protocol_version(&upgrade_point.protocol_version),
+        upgrade_point.upgrade_installer_args.clone(),
+        upgrade_point.upgrade_installer_bytes.clone(),
+        upgrade_point.new_costs,
+        Some(upgrade_point.activation_point.rank),
+    ))
+}
+
 impl<REv: ReactorEventT, R: Rng + CryptoRng + ?Sized> Component<REv, R> for BlockExecutor {
     type Event = Event;
 
@@ -413,41 +610,121 @@ impl<REv: ReactorEventT, R: Rng + CryptoRng + ?Sized> Component<REv, R> for Bloc
                 self.handle_get_deploys_result(effect_builder, finalized_block, deploys)
             }
 
-            Event::DeployExecutionResult {
-                state,
-                deploy_hash,
-                result,
-            } => {
-                trace!(?state, %deploy_hash, ?result, "deploy execution result");
-                // As for now a given state is expected to exist.
-                let execution_results = result.unwrap();
-                self.commit_execution_effects(effect_builder, state, deploy_hash, execution_results)
-            }
-
-            Event::CommitExecutionEffects {
+            Event::ExecuteBlockResult {
                 mut state,
-                commit_result,
+                deploy_hashes,
+                result,
             } => {
-                trace!(?state, ?commit_result, "commit result");
-                match commit_result {
-                    Ok(CommitResult::Success {
-                        state_root: post_state_hash,
+                trace!(?state, ?result, "execute block result");
+                match result {
+                    Ok(BlockExecutionResult::Success {
+                        post_state_hash,
+                        execution_results,
                     }) => {
-                        debug!(?post_state_hash, "commit succeeded");
+                        debug!(?post_state_hash, "block execution succeeded");
+                        Self::record_execution_results(
+                            &mut state,
+                            deploy_hashes,
+                            execution_results,
+                        );
                         state.pre_state_hash = post_state_hash.into();
-                        self.execute_next_deploy_or_create_block(effect_builder, state)
+                        self.execute_deploys_or_create_block(effect_builder, state)
                     }
                     _ => {
-                        // When commit fails we panic as we'll not be able to execute the next
+                        // When execution or commit fails we'll not be able to execute the next
                         // block.
                         error!(
-                            ?commit_result,
-                            "commit failed - internal contract runtime error"
+                            ?result,
+                            "block execution failed - internal contract runtime error"
                         );
-                        panic!("unable to commit");
+                        fatal!(effect_builder, "unable to execute block")
                     }
                 }
             }
+
+            Event::CommitStepResult { mut state, result } => {
+                trace!(?state, ?result, "step result");
+                match result {
+                    Ok(StepResult::Success {
+                        post_state_hash, ..
+                    }) => {
+                        debug!(?post_state_hash, "step succeeded");
+                        state.pre_state_hash = post_state_hash.into();
+                        self.execute_deploys_or_create_block(effect_builder, state)
+                    }
+                    Err(error) if is_system_contract_failure(&error) => {
+                        error!(
+                            %error,
+                            height = state.finalized_block.height(),
+                            "system operations failed - their effects are discarded"
+                        );
+                        self.execute_deploys_or_create_block(effect_builder, state)
+                    }
+                    _ => {
+                        // Any other failure means we can't compute the block's post state hash, so
+                        // we'll not be able to execute the next block.
+                        error!(?result, "step failed - internal contract runtime error");
+                        fatal!(effect_builder, "unable to commit step")
+                    }
+                }
+            }
+
+            Event::CommitUpgradeResult { mut state, result } => {
+                trace!(?state, ?result, "upgrade result");
+                match result {
+                    Ok(UpgradeResult::Success {
+                        post_state_hash, ..
+                    }) => {
+                        debug!(?post_state_hash, "upgrade succeeded");
+                        state.pre_state_hash = post_state_hash.into();
+                        self.execute_deploys_or_create_block(effect_builder, state)
+                    }
+                    _ => {
+                        // When the upgrade fails the next era can't be run under the new protocol
+                        // version.
+                        error!(?result, "upgrade failed - internal contract runtime error");
+                        fatal!(effect_builder, "unable to commit upgrade")
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_execution_engine::core::{engine_state, execution};
+    use casper_types::{bytesrepr, ApiError, Key};
+
+    use super::is_system_contract_failure;
+
+    #[test]
+    fn should_discard_system_contract_failures() {
+        let system_contract_reverts = vec![
+            ApiError::Mint(0),
+            ApiError::AuctionError(1),
+            ApiError::ProofOfStake(2),
+        ];
+        for api_error in system_contract_reverts {
+            let error = engine_state::Error::Exec(execution::Error::Revert(api_error));
+            assert!(is_system_contract_failure(&error), "{:?}", error);
+        }
+        let mint_error = engine_state::Error::Mint("insufficient funds".to_string());
+        assert!(is_system_contract_failure(&mint_error));
+    }
+
+    #[test]
+    fn non_system_exec_errors_should_be_fatal() {
+        let exec_errors = vec![
+            execution::Error::BytesRepr(bytesrepr::Error::EarlyEndOfStream),
+            execution::Error::Interpreter("unreachable".to_string()),
+            execution::Error::KeyNotFound(Key::Hash([1; 32])),
+            execution::Error::Revert(ApiError::MissingKey),
+            execution::Error::Revert(ApiError::User(3)),
+        ];
+        for exec_error in exec_errors {
+            let error = engine_state::Error::Exec(exec_error);
+            assert!(!is_system_contract_failure(&error), "{:?}", error);
         }
     }
 }
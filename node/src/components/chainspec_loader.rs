@@ -29,7 +29,11 @@ use crate::{
     },
 };
 pub use chainspec::Chainspec;
-pub(crate) use chainspec::{DeployConfig, HighwayConfig};
+pub(crate) use chainspec::{
+    to_protocol_version, ConsensusProtocolName, DeployConfig, HighwayConfig,
+};
+#[cfg(test)]
+pub(crate) use chainspec::{ActivationPoint, UpgradePoint};
 pub use error::Error;
 
 /// `ChainspecHandler` events.
@@ -15,12 +15,13 @@ use casper_execution_engine::{
     core::engine_state::genesis::{ExecConfig, GenesisAccount},
     shared::{motes::Motes, wasm_costs::WasmCosts},
 };
-use casper_types::U512;
+use casper_types::{ProtocolVersion, U512};
 
 use super::{config, error::GenesisLoadError, Error};
 #[cfg(test)]
 use crate::testing::TestRng;
 use crate::{
+    components::consensus::EraId,
     crypto::asymmetric_key::PublicKey,
     types::{TimeDiff, Timestamp},
     utils::Loadable,
@@ -71,6 +72,23 @@ impl DeployConfig {
     }
 }
 
+/// The consensus protocol run by the validators of an era.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ConsensusProtocolName {
+    /// The Highway protocol.
+    Highway,
+    /// A leader-based protocol with fixed-length rounds, in which the validators take turns
+    /// proposing blocks.
+    RoundRobin,
+}
+
+impl Default for ConsensusProtocolName {
+    fn default() -> Self {
+        ConsensusProtocolName::Highway
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 // Disallow unknown fields to ensure config files and command-line overrides contain valid keys.
 #[serde(deny_unknown_fields)]
@@ -88,6 +106,15 @@ pub(crate) struct HighwayConfig {
     pub(crate) voting_period_duration: TimeDiff,
     pub(crate) finality_threshold_percent: u8,
     pub(crate) minimum_round_exponent: u8,
+    /// The maximum round exponent: Validators adapt their round length to the observed network
+    /// conditions, but never beyond `1 << maximum_round_exponent` milliseconds.
+    pub(crate) maximum_round_exponent: u8,
+    /// The consensus protocol run in every era, unless an upgrade point switches to a different
+    /// one.
+    pub(crate) consensus_protocol: ConsensusProtocolName,
+    /// The amount of motes paid out to the validators for each block, split according to their
+    /// rewards.
+    pub(crate) block_reward: Motes,
 }
 
 impl Default for HighwayConfig {
@@ -101,6 +128,9 @@ impl Default for HighwayConfig {
             voting_period_duration: TimeDiff::from(172_800_000), // 2 days
             finality_threshold_percent: 10,
             minimum_round_exponent: 14, // 2**14 ms = ~16 seconds
+            maximum_round_exponent: 19, // 2**19 ms = ~8.7 minutes
+            consensus_protocol: ConsensusProtocolName::Highway,
+            block_reward: Motes::new(U512::from(1_000_000_000u64)),
         }
     }
 }
@@ -117,7 +147,16 @@ impl HighwayConfig {
             entropy_duration: TimeDiff::from(rng.gen_range(600_000, 10_800_000)),
             voting_period_duration: TimeDiff::from(rng.gen_range(600_000, 172_800_000)),
             finality_threshold_percent: rng.gen_range(0, 101),
-            minimum_round_exponent: rng.gen_range(0, 20),
+            minimum_round_exponent: rng.gen_range(0, 10),
+            maximum_round_exponent: rng.gen_range(10, 20),
+            consensus_protocol: if rng.gen() {
+                ConsensusProtocolName::Highway
+            } else {
+                ConsensusProtocolName::RoundRobin
+            },
+            block_reward: Motes::new(U512::from(
+                rng.gen_range::<_, u64, u64>(1_000_000, 1_000_000_000_000),
+            )),
         }
     }
 }
@@ -264,8 +303,10 @@ impl GenesisConfig {
     }
 }
 
+/// The point at which an upgrade takes effect: the start of the given era.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub(crate) struct ActivationPoint {
+    /// The ID of the first era run under the upgrade.
     pub(crate) rank: u64,
 }
 
@@ -277,6 +318,8 @@ pub(crate) struct UpgradePoint {
     pub(crate) upgrade_installer_args: Option<Vec<u8>>,
     pub(crate) new_costs: Option<WasmCosts>,
     pub(crate) new_deploy_config: Option<DeployConfig>,
+    /// The consensus protocol run in the eras starting at or after the activation point.
+    pub(crate) new_consensus_protocol: Option<ConsensusProtocolName>,
 }
 
 #[cfg(test)]
@@ -307,6 +350,11 @@ impl UpgradePoint {
         } else {
             None
         };
+        let new_consensus_protocol = if rng.gen() {
+            Some(ConsensusProtocolName::RoundRobin)
+        } else {
+            None
+        };
 
         UpgradePoint {
             activation_point,
@@ -315,6 +363,7 @@ impl UpgradePoint {
             upgrade_installer_args,
             new_costs,
             new_deploy_config,
+            new_consensus_protocol,
         }
     }
 }
@@ -328,6 +377,48 @@ pub struct Chainspec {
     pub(crate) upgrades: Vec<UpgradePoint>,
 }
 
+impl Chainspec {
+    /// Returns the upgrade points activated at or before the start of the given era, in order of
+    /// activation.
+    pub(crate) fn activated_upgrades(&self, era_id: EraId) -> impl Iterator<Item = &UpgradePoint> {
+        self.upgrades
+            .iter()
+            .take_while(move |up| up.activation_point.rank <= era_id.0)
+    }
+
+    /// Returns the upgrade point activated at the start of the given era, if any.
+    pub(crate) fn upgrade_activated_in(&self, era_id: EraId) -> Option<&UpgradePoint> {
+        self.upgrades
+            .iter()
+            .find(|up| up.activation_point.rank == era_id.0)
+    }
+
+    /// Returns the protocol version in effect during the given era.
+    pub(crate) fn protocol_version(&self, era_id: EraId) -> Version {
+        self.activated_upgrades(era_id)
+            .last()
+            .map_or(&self.genesis.protocol_version, |up| &up.protocol_version)
+            .clone()
+    }
+
+    /// Returns the deploy config in effect during the given era.
+    pub(crate) fn deploy_config(&self, era_id: EraId) -> DeployConfig {
+        self.activated_upgrades(era_id)
+            .filter_map(|up| up.new_deploy_config)
+            .last()
+            .unwrap_or(self.genesis.deploy_config)
+    }
+}
+
+/// Converts a chainspec protocol version into the execution engine's representation.
+pub(crate) fn to_protocol_version(version: &Version) -> ProtocolVersion {
+    ProtocolVersion::from_parts(
+        version.major as u32,
+        version.minor as u32,
+        version.patch as u32,
+    )
+}
+
 impl Loadable for Chainspec {
     type Error = Error;
     fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Self::Error> {
@@ -412,6 +503,15 @@ mod tests {
         );
         assert_eq!(spec.genesis.highway_config.finality_threshold_percent, 8);
         assert_eq!(spec.genesis.highway_config.minimum_round_exponent, 13);
+        assert_eq!(spec.genesis.highway_config.maximum_round_exponent, 17);
+        assert_eq!(
+            spec.genesis.highway_config.consensus_protocol,
+            ConsensusProtocolName::Highway
+        );
+        assert_eq!(
+            spec.genesis.highway_config.block_reward,
+            Motes::new(U512::from(7))
+        );
 
         assert_eq!(
             spec.genesis.deploy_config.max_payment_cost,
@@ -464,6 +564,7 @@ mod tests {
         assert_eq!(upgrade0.new_deploy_config.unwrap().max_dependencies, 36);
         assert_eq!(upgrade0.new_deploy_config.unwrap().max_block_size, 37);
         assert_eq!(upgrade0.new_deploy_config.unwrap().block_gas_limit, 38);
+        assert!(upgrade0.new_consensus_protocol.is_none());
 
         let upgrade1 = &spec.upgrades[1];
         assert_eq!(upgrade1.activation_point, ActivationPoint { rank: 39 });
@@ -472,6 +573,10 @@ mod tests {
         assert!(upgrade1.upgrade_installer_args.is_none());
         assert!(upgrade1.new_costs.is_none());
         assert!(upgrade1.new_deploy_config.is_none());
+        assert_eq!(
+            upgrade1.new_consensus_protocol,
+            Some(ConsensusProtocolName::RoundRobin)
+        );
     }
 
     #[test]
@@ -480,6 +585,32 @@ mod tests {
         check_spec(spec);
     }
 
+    #[test]
+    fn should_apply_upgrades_from_activation_era() {
+        let spec = Chainspec::from_resources("test/valid/chainspec.toml");
+
+        assert_eq!(spec.protocol_version(EraId(22)), Version::from((0, 1, 0)));
+        assert_eq!(spec.protocol_version(EraId(23)), Version::from((0, 2, 0)));
+        assert_eq!(spec.protocol_version(EraId(39)), Version::from((0, 3, 0)));
+        assert_eq!(spec.protocol_version(EraId(100)), Version::from((0, 3, 0)));
+
+        assert_eq!(spec.deploy_config(EraId(22)).max_dependencies, 11);
+        assert_eq!(spec.deploy_config(EraId(23)).max_dependencies, 36);
+        // The second upgrade leaves the deploy config unchanged.
+        assert_eq!(spec.deploy_config(EraId(39)).max_dependencies, 36);
+
+        assert!(spec.upgrade_activated_in(EraId(22)).is_none());
+        assert_eq!(
+            spec.upgrade_activated_in(EraId(23)),
+            Some(&spec.upgrades[0])
+        );
+        assert!(spec.upgrade_activated_in(EraId(24)).is_none());
+        assert_eq!(
+            spec.upgrade_activated_in(EraId(39)),
+            Some(&spec.upgrades[1])
+        );
+    }
+
     #[test]
     fn rmp_serde_roundtrip() {
         let mut rng = TestRng::new();
@@ -113,6 +113,10 @@ struct HighwayConfig {
     voting_period_duration_millis: u64,
     finality_threshold_percent: u8,
     minimum_round_exponent: u8,
+    maximum_round_exponent: u8,
+    #[serde(default)]
+    consensus_protocol: chainspec::ConsensusProtocolName,
+    block_reward: String,
 }
 
 impl Default for HighwayConfig {
@@ -132,6 +136,9 @@ impl From<chainspec::HighwayConfig> for HighwayConfig {
             voting_period_duration_millis: cfg.voting_period_duration.millis(),
             finality_threshold_percent: cfg.finality_threshold_percent,
             minimum_round_exponent: cfg.minimum_round_exponent,
+            maximum_round_exponent: cfg.maximum_round_exponent,
+            consensus_protocol: cfg.consensus_protocol,
+            block_reward: cfg.block_reward.to_string(),
         }
     }
 }
@@ -144,6 +151,7 @@ struct UpgradePoint {
     activation_point: chainspec::ActivationPoint,
     new_costs: Option<WasmCosts>,
     new_deploy_config: Option<DeployConfig>,
+    new_consensus_protocol: Option<chainspec::ConsensusProtocolName>,
 }
 
 impl From<&chainspec::UpgradePoint> for UpgradePoint {
@@ -154,6 +162,7 @@ impl From<&chainspec::UpgradePoint> for UpgradePoint {
             activation_point: upgrade_point.activation_point,
             new_costs: upgrade_point.new_costs,
             new_deploy_config: upgrade_point.new_deploy_config.map(DeployConfig::from),
+            new_consensus_protocol: upgrade_point.new_consensus_protocol,
         }
     }
 }
@@ -182,6 +191,7 @@ impl UpgradePoint {
             upgrade_installer_args,
             new_costs: self.new_costs,
             new_deploy_config,
+            new_consensus_protocol: self.new_consensus_protocol,
         })
     }
 }
@@ -228,6 +238,9 @@ impl From<&chainspec::Chainspec> for ChainspecConfig {
                 .millis(),
             finality_threshold_percent: chainspec.genesis.highway_config.finality_threshold_percent,
             minimum_round_exponent: chainspec.genesis.highway_config.minimum_round_exponent,
+            maximum_round_exponent: chainspec.genesis.highway_config.maximum_round_exponent,
+            consensus_protocol: chainspec.genesis.highway_config.consensus_protocol,
+            block_reward: chainspec.genesis.highway_config.block_reward.to_string(),
         };
 
         let deploys = chainspec.genesis.deploy_config.into();
@@ -301,6 +314,9 @@ pub(super) fn parse_toml<P: AsRef<Path>>(chainspec_path: P) -> Result<chainspec:
         voting_period_duration: TimeDiff::from(chainspec.highway.voting_period_duration_millis),
         finality_threshold_percent: chainspec.highway.finality_threshold_percent,
         minimum_round_exponent: chainspec.highway.minimum_round_exponent,
+        maximum_round_exponent: chainspec.highway.maximum_round_exponent,
+        consensus_protocol: chainspec.highway.consensus_protocol,
+        block_reward: Motes::new(U512::from_dec_str(&chainspec.highway.block_reward)?),
     };
 
     let genesis = chainspec::GenesisConfig {
@@ -10,23 +10,26 @@ mod traits;
 
 use std::fmt::{self, Debug, Display, Formatter};
 
+use casper_execution_engine::core::engine_state;
+use casper_types::auction::ValidatorWeights;
+
 use crate::{
     components::{storage::Storage, Component},
     effect::{
         announcements::ConsensusAnnouncement,
         requests::{
-            self, BlockExecutorRequest, BlockValidationRequest, DeployBufferRequest,
-            NetworkRequest, StorageRequest,
+            self, BlockExecutorRequest, BlockValidationRequest, ContractRuntimeRequest,
+            DeployBufferRequest, NetworkRequest, StorageRequest,
         },
         EffectBuilder, EffectExt, Effects,
     },
     protocol::Message,
-    types::{ProtoBlock, Timestamp},
+    types::{BlockHeader, Evidence, ProtoBlock, Timestamp},
 };
 pub use config::Config;
 pub(crate) use consensus_protocol::BlockContext;
 use derive_more::From;
-pub(crate) use era_supervisor::{EraId, EraSupervisor};
+pub(crate) use era_supervisor::{EraId, EraSupervisor, BLOCK_REWARD};
 use hex_fmt::HexFmt;
 use rand::{CryptoRng, Rng};
 use serde::{Deserialize, Serialize};
@@ -70,6 +73,13 @@ pub enum Event<I> {
         sender: I,
         proto_block: ProtoBlock,
     },
+    /// Evidence against a validator has been received from a peer and needs to be verified.
+    EvidenceReceived { sender: I, evidence: Box<Evidence> },
+    /// The validators of the era following the switch block have been read from global state.
+    CreateNewEra {
+        block_header: Box<BlockHeader>,
+        validators: Result<Option<ValidatorWeights>, engine_state::Error>,
+    },
 }
 
 impl Display for ConsensusMessage {
@@ -134,6 +144,14 @@ impl<I: Debug> Display for Event<I> {
                 "A proto-block received from {:?} turned out to be invalid for era {:?}: {:?}",
                 sender, era_id, proto_block
             ),
+            Event::EvidenceReceived { sender, evidence } => {
+                write!(f, "{} received from {:?}", evidence, sender)
+            }
+            Event::CreateNewEra { block_header, .. } => write!(
+                f,
+                "Create new era following switch block {}",
+                block_header.hash()
+            ),
         }
     }
 }
@@ -149,6 +167,7 @@ pub trait ReactorEventT<I>:
     + From<BlockExecutorRequest>
     + From<BlockValidationRequest<ProtoBlock, I>>
     + From<StorageRequest<Storage>>
+    + From<ContractRuntimeRequest>
 {
 }
 
@@ -161,6 +180,7 @@ impl<REv, I> ReactorEventT<I> for REv where
         + From<BlockExecutorRequest>
         + From<BlockValidationRequest<ProtoBlock, I>>
         + From<StorageRequest<Storage>>
+        + From<ContractRuntimeRequest>
 {
 }
 
@@ -195,6 +215,10 @@ where
                 block_header,
                 responder,
             )) => handling_es.handle_linear_chain_block(*block_header, responder),
+            Event::ConsensusRequest(requests::ConsensusRequest::EraValidators(
+                era_id,
+                responder,
+            )) => handling_es.handle_era_validators(era_id, responder),
             Event::AcceptProtoBlock {
                 era_id,
                 proto_block,
@@ -204,6 +228,13 @@ where
                 sender,
                 proto_block,
             } => handling_es.handle_invalid_proto_block(era_id, sender, proto_block),
+            Event::EvidenceReceived { sender, evidence } => {
+                handling_es.handle_evidence_received(sender, *evidence)
+            }
+            Event::CreateNewEra {
+                block_header,
+                validators,
+            } => handling_es.handle_create_new_era(*block_header, validators),
         }
     }
 }
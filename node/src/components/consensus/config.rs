@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{crypto::asymmetric_key::SecretKey, utils::External};
@@ -9,4 +11,7 @@ use crate::{crypto::asymmetric_key::SecretKey, utils::External};
 pub struct Config {
     /// Path to secret key file.
     pub secret_key_path: External<SecretKey>,
+    /// Path to the folder where the write-ahead logs of the Highway protocol state are stored, if
+    /// any.
+    pub highway_wal_dir: Option<PathBuf>,
 }
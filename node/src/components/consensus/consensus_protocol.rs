@@ -24,8 +24,6 @@ impl BlockContext {
     }
 
     /// The block's relative height within the current era.
-    // TODO - remove once used
-    #[allow(dead_code)]
     pub(crate) fn height(&self) -> u64 {
         self.height
     }
@@ -40,6 +38,9 @@ pub(crate) struct FinalizedBlock<C: ConsensusValueT, VID> {
     pub(crate) value: C,
     /// The set of newly detected equivocators.
     pub(crate) new_equivocators: Vec<VID>,
+    /// The set of all equivocators known to this block, including the ones detected in earlier
+    /// blocks.
+    pub(crate) equivocators: Vec<VID>,
     /// Rewards for finalization of earlier blocks.
     ///
     /// This is a measure of the value of each validator's contribution to consensus, in
@@ -75,6 +76,12 @@ pub(crate) enum ConsensusProtocolResult<I, C: ConsensusValueT, VID> {
     /// that it has the expected structure, or that deploys that are mentioned by hash actually
     /// exist, and then call `ConsensusProtocol::resolve_validity`.
     ValidateConsensusValue(I, C),
+    /// Evidence against the given validator has been added to the protocol state.  It can be
+    /// retrieved using `ConsensusProtocol::evidence`.
+    NewEvidence(VID),
+    /// An unrecoverable error occurred, e.g. the protocol state couldn't be persisted.  The
+    /// instance has stopped creating vertices, and the node should shut down.
+    Fatal(String),
 }
 
 /// An API for a single instance of the consensus.
@@ -113,4 +120,11 @@ pub(crate) trait ConsensusProtocol<I, C: ConsensusValueT, VID, R: Rng + CryptoRn
 
     /// Turns this instance into a passive observer, that does not create any new vertices.
     fn deactivate_validator(&mut self);
+
+    /// Returns the serialized evidence against the given validator, if any.
+    fn evidence(&self, vid: &VID) -> Option<Vec<u8>>;
+
+    /// Returns an error if the serialized evidence doesn't prove that the given validator is
+    /// faulty in this instance.
+    fn validate_evidence(&self, vid: &VID, evidence: &[u8]) -> Result<(), Error>;
 }
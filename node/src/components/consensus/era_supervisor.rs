@@ -6,54 +6,73 @@
 //! Most importantly, it doesn't care about what messages it's forwarding.
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
     fmt::{self, Debug, Formatter},
+    fs,
+    path::PathBuf,
     rc::Rc,
+    time::Duration,
 };
 
-use anyhow::Error;
+use anyhow::{Context, Error};
 use blake2::{
     digest::{Input, VariableOutput},
     VarBlake2b,
 };
-use casper_types::U512;
+use casper_types::{
+    auction::{ValidatorWeights, AUCTION_DELAY},
+    U512,
+};
 use fmt::Display;
 use num_traits::AsPrimitive;
 use rand::{CryptoRng, Rng};
 use serde::{Deserialize, Serialize};
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
 
-use casper_execution_engine::shared::motes::Motes;
+use casper_execution_engine::{core::engine_state, shared::motes::Motes};
 
 use crate::{
     components::{
-        chainspec_loader::{Chainspec, HighwayConfig},
+        chainspec_loader::{to_protocol_version, Chainspec, ConsensusProtocolName, HighwayConfig},
         consensus::{
             consensus_protocol::{
                 BlockContext, ConsensusProtocol, ConsensusProtocolResult,
                 FinalizedBlock as CpFinalizedBlock,
             },
             highway_core::{highway::Params, validators::Validators},
-            protocols::highway::{HighwayContext, HighwayProtocol, HighwaySecret},
+            protocols::{
+                highway::{HighwayContext, HighwayProtocol, HighwaySecret},
+                round_robin::RoundRobinProtocol,
+            },
             traits::NodeIdT,
             Config, ConsensusMessage, Event, ReactorEventT,
         },
+        small_network::Misbehavior,
     },
     crypto::{
-        asymmetric_key::{self, PublicKey, SecretKey, Signature},
+        asymmetric_key::{self, PublicKey, SecretKey},
         hash,
     },
     effect::{EffectBuilder, EffectExt, Effects, Responder},
-    types::{BlockHeader, FinalizedBlock, ProtoBlock, SystemTransaction, Timestamp},
+    fatal,
+    protocol::Message,
+    types::{
+        BlockHeader, Evidence, FinalitySignature, FinalizedBlock, ProtoBlock, SystemTransaction,
+        Timestamp,
+    },
     utils::WithDir,
 };
 
+/// The rewards for a block, in units of which the chainspec's block reward in motes is paid out.
 // We use one trillion as a block reward unit because it's large enough to allow precise
 // fractions, and small enough for many block rewards to fit into a u64.
-const BLOCK_REWARD: u64 = 1_000_000_000_000;
+pub(crate) const BLOCK_REWARD: u64 = 1_000_000_000_000;
 /// The number of recent eras to retain. Eras older than this are dropped from memory.
 // TODO: This needs to be in sync with AUCTION_DELAY/booking_duration_millis. (Already duplicated!)
 const RETAIN_ERAS: u64 = 4;
+/// The delay before trying again to read the validators of a new era from the global state.
+const VALIDATORS_RETRY_DELAY: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct EraId(pub(crate) u64);
@@ -82,6 +101,16 @@ pub(crate) struct Era<I, R: Rng + CryptoRng + ?Sized> {
     consensus: Box<dyn ConsensusProtocol<I, ProtoBlock, PublicKey, R>>,
     /// The height of this era's first block.
     start_height: u64,
+    /// The validators of this era, along with their stakes.
+    validator_stakes: BTreeMap<PublicKey, Motes>,
+}
+
+#[cfg(test)]
+impl<I, R: Rng + CryptoRng + ?Sized> Era<I, R> {
+    /// Returns the validators of this era, along with their stakes.
+    pub(crate) fn validator_stakes(&self) -> &BTreeMap<PublicKey, Motes> {
+        &self.validator_stakes
+    }
 }
 
 pub(crate) struct EraSupervisor<I, R: Rng + CryptoRng + ?Sized> {
@@ -90,10 +119,14 @@ pub(crate) struct EraSupervisor<I, R: Rng + CryptoRng + ?Sized> {
     active_eras: HashMap<EraId, Era<I, R>>,
     pub(super) secret_signing_key: Rc<SecretKey>,
     pub(super) public_signing_key: PublicKey,
+    /// The validators of the genesis era, along with their stakes. The auction contract is
+    /// initialized with them as the validators of the first `AUCTION_DELAY` eras, too.
     validator_stakes: Vec<(PublicKey, Motes)>,
     current_era: EraId,
     chainspec: Chainspec,
     node_start_time: Timestamp,
+    /// The folder containing the write-ahead logs of the eras' protocol states, if any.
+    highway_wal_dir: Option<PathBuf>,
 }
 
 impl<I, R: Rng + CryptoRng + ?Sized> Debug for EraSupervisor<I, R> {
@@ -119,6 +152,11 @@ where
         rng: &mut R,
     ) -> Result<(Self, Effects<Event<I>>), Error> {
         let (root, config) = config.into_parts();
+        let highway_wal_dir = config.highway_wal_dir.map(|dir| root.join(dir));
+        if let Some(dir) = highway_wal_dir.as_ref() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
         let secret_signing_key = Rc::new(config.secret_key_path.load(root)?);
         let public_signing_key = PublicKey::from(secret_signing_key.as_ref());
 
@@ -126,10 +164,11 @@ where
             active_eras: Default::default(),
             secret_signing_key,
             public_signing_key,
-            current_era: EraId(0),
             validator_stakes: validator_stakes.clone(),
+            current_era: EraId(0),
             chainspec: chainspec.clone(),
             node_start_time: Timestamp::now(),
+            highway_wal_dir,
         };
 
         let results = era_supervisor.new_era(
@@ -139,7 +178,7 @@ where
             chainspec.genesis.highway_config.genesis_era_start_timestamp,
             0,
             genesis_post_state_hash,
-        );
+        )?;
         let effects = era_supervisor
             .handling_wrapper(effect_builder, rng)
             .handle_consensus_results(EraId(0), results);
@@ -165,36 +204,8 @@ where
         self.chainspec.genesis.highway_config
     }
 
-    fn instance_id(&self, post_state_hash: hash::Digest, block_height: u64) -> hash::Digest {
-        let mut result = [0; hash::Digest::LENGTH];
-        let mut hasher = VarBlake2b::new(hash::Digest::LENGTH).expect("should create hasher");
-
-        hasher.input(&self.chainspec.genesis.name);
-        hasher.input(self.chainspec.genesis.timestamp.millis().to_le_bytes());
-        hasher.input(post_state_hash);
-
-        for upgrade_point in self
-            .chainspec
-            .upgrades
-            .iter()
-            .take_while(|up| up.activation_point.rank <= block_height)
-        {
-            hasher.input(upgrade_point.activation_point.rank.to_le_bytes());
-            if let Some(bytes) = upgrade_point.upgrade_installer_bytes.as_ref() {
-                hasher.input(bytes);
-            }
-            if let Some(bytes) = upgrade_point.upgrade_installer_args.as_ref() {
-                hasher.input(bytes);
-            }
-        }
-
-        hasher.variable_result(|slice| {
-            result.copy_from_slice(slice);
-        });
-        result.into()
-    }
-
-    /// Starts a new era; panics if it already exists.
+    /// Starts a new era; panics if it already exists.  Returns an error if the era's protocol state
+    /// can't be restored.
     fn new_era(
         &mut self,
         era_id: EraId,
@@ -203,7 +214,7 @@ where
         start_time: Timestamp,
         start_height: u64,
         post_state_hash: hash::Digest,
-    ) -> Vec<ConsensusProtocolResult<I, ProtoBlock, PublicKey>> {
+    ) -> Result<Vec<ConsensusProtocolResult<I, ProtoBlock, PublicKey>>, Error> {
         if self.active_eras.contains_key(&era_id) {
             panic!("{:?} already exists", era_id);
         }
@@ -228,6 +239,7 @@ where
         let scale_stake = |(key, stake): (PublicKey, Motes)| {
             (key, AsPrimitive::<u64>::as_(stake.value() / scaling_factor))
         };
+        let era_validator_stakes = validator_stakes.iter().cloned().collect();
         let validators: Validators<PublicKey> =
             validator_stakes.into_iter().map(scale_stake).collect();
 
@@ -244,47 +256,124 @@ where
             BLOCK_REWARD / 5, // TODO: Make reduced block reward configurable?
             reward_delay,
             self.highway_config().minimum_round_exponent,
+            self.highway_config().maximum_round_exponent,
             self.highway_config().minimum_era_height,
             start_time + self.highway_config().era_duration,
         );
 
-        // Activate the era if this node was already running when the era began, it is still
-        // ongoing based on its minimum duration, and we are one of the validators.
         let our_id = self.public_signing_key;
         let era_rounds_len = params.min_round_len() * params.end_height();
         let min_end_time = start_time + self.highway_config().era_duration.max(era_rounds_len);
-        let should_activate = self.node_start_time < start_time
-            && min_end_time >= timestamp
-            && validators.iter().any(|v| *v.id() == our_id);
+        let is_validator = validators.iter().any(|v| *v.id() == our_id);
+
+        let instance_id = instance_id(&self.chainspec, post_state_hash, era_id);
+        let secret = HighwaySecret::new(Rc::clone(&self.secret_signing_key), our_id);
+        let protocol_name = consensus_protocol_name(&self.chainspec, era_id);
+        info!(?protocol_name, "running era {}", era_id.0);
+
+        let (consensus, results): (
+            Box<dyn ConsensusProtocol<I, ProtoBlock, PublicKey, R>>,
+            Vec<ConsensusProtocolResult<I, ProtoBlock, PublicKey>>,
+        ) = match protocol_name {
+            ConsensusProtocolName::Highway => {
+                let wal_path = self
+                    .highway_wal_dir
+                    .as_ref()
+                    .map(|dir| dir.join(format!("{}-{:x}.wal", era_id.0, instance_id)));
+                let mut highway = HighwayProtocol::<I, HighwayContext>::new(
+                    instance_id,
+                    validators,
+                    params,
+                    ftt,
+                    wal_path.as_deref(),
+                )
+                .with_context(|| format!("failed to restore {:?}", era_id))?;
+
+                // Activate the era if this node was already running when the era began, or if
+                // it had already voted in it before restarting, it is still ongoing based on
+                // its minimum duration, and we are one of the validators.
+                let should_activate = (self.node_start_time < start_time
+                    || highway.has_votes_by(&our_id))
+                    && min_end_time >= timestamp
+                    && is_validator;
+
+                let results = if should_activate {
+                    info!("start voting in era {}", era_id.0);
+                    highway.activate_validator(our_id, secret, timestamp.max(start_time))
+                } else {
+                    info!("not voting in era {}", era_id.0);
+                    Vec::new()
+                };
+                (Box::new(highway), results)
+            }
+            ConsensusProtocolName::RoundRobin => {
+                let mut round_robin = RoundRobinProtocol::<I, HighwayContext>::new(
+                    instance_id,
+                    validators,
+                    ftt,
+                    params.min_round_len(),
+                    start_time,
+                    params.end_height(),
+                    params.end_timestamp(),
+                );
 
-        let mut highway = HighwayProtocol::<I, HighwayContext>::new(
-            self.instance_id(post_state_hash, start_height),
-            validators,
-            params,
-            ftt,
-        );
+                // There is no write-ahead log for this protocol, so after a restart we only
+                // observe the eras that had already begun: voting again could equivocate.
+                let should_activate =
+                    self.node_start_time < start_time && min_end_time >= timestamp && is_validator;
 
-        let results = if should_activate {
-            info!("start voting in era {}", era_id.0);
-            let secret = HighwaySecret::new(Rc::clone(&self.secret_signing_key), our_id);
-            highway.activate_validator(our_id, secret, timestamp.max(start_time))
-        } else {
-            info!("not voting in era {}", era_id.0);
-            Vec::new()
+                let results = if should_activate {
+                    info!("start voting in era {}", era_id.0);
+                    round_robin.activate_validator(our_id, secret, timestamp.max(start_time))
+                } else {
+                    info!("not voting in era {}", era_id.0);
+                    Vec::new()
+                };
+                (Box::new(round_robin), results)
+            }
         };
 
         let era = Era {
-            consensus: Box::new(highway),
+            consensus,
             start_height,
+            validator_stakes: era_validator_stakes,
         };
         let _ = self.active_eras.insert(era_id, era);
 
         // Remove the era that has become obsolete now.
         if era_id.0 > RETAIN_ERAS {
-            self.active_eras.remove(&EraId(era_id.0 - RETAIN_ERAS - 1));
+            let obsolete_era_id = EraId(era_id.0 - RETAIN_ERAS - 1);
+            let _ = self.active_eras.remove(&obsolete_era_id);
         }
 
-        results
+        Ok(results)
+    }
+
+    /// Removes the write-ahead logs of the given era and all earlier ones, including those left
+    /// over from before a restart, as we won't vote in any of them again.
+    fn remove_write_ahead_logs(&self, era_id: EraId) {
+        let dir = match self.highway_wal_dir.as_ref() {
+            Some(dir) => dir,
+            None => return,
+        };
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) => {
+                warn!(%error, dir = %dir.display(), "failed to list write-ahead logs");
+                return;
+            }
+        };
+        for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+            let wal_era_id = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str()?.strip_suffix(".wal"))
+                .and_then(|file_stem| file_stem.split('-').next()?.parse::<u64>().ok());
+            if wal_era_id.map_or(false, |wal_era_id| wal_era_id <= era_id.0) {
+                if let Err(error) = fs::remove_file(&path) {
+                    warn!(%error, path = %path.display(), "failed to remove write-ahead log");
+                }
+            }
+        }
     }
 
     /// Returns the current era.
@@ -381,7 +470,7 @@ where
     pub(super) fn handle_linear_chain_block(
         &mut self,
         block_header: BlockHeader,
-        responder: Responder<Signature>,
+        responder: Responder<FinalitySignature>,
     ) -> Effects<Event<I>> {
         assert_eq!(
             block_header.era_id(),
@@ -389,32 +478,134 @@ where
             "executed block in unexpected era"
         );
         // TODO - we should only sign if we're a validator for the given era ID.
+        let block_hash = block_header.hash();
         let signature = asymmetric_key::sign(
-            block_header.hash().inner(),
+            block_hash.inner(),
             &self.era_supervisor.secret_signing_key,
             &self.era_supervisor.public_signing_key,
             self.rng,
         );
-        let mut effects = responder.respond(signature).ignore();
+        let finality_signature = FinalitySignature::new(
+            block_hash,
+            block_header.era_id(),
+            signature,
+            self.era_supervisor.public_signing_key,
+        );
+        let mut effects = responder.respond(finality_signature).ignore();
         if block_header.switch_block() {
-            // TODO: Learn the new weights from contract (validator rotation).
-            let validator_stakes = self.era_supervisor.validator_stakes.clone();
             self.era_supervisor
                 .current_era_mut()
                 .consensus
                 .deactivate_validator();
+            self.era_supervisor
+                .remove_write_ahead_logs(block_header.era_id());
             let new_era_id = block_header.era_id().successor();
-            info!(?new_era_id, "Era created");
-            let results = self.era_supervisor.new_era(
-                new_era_id,
-                Timestamp::now(), // TODO: This should be passed in.
-                validator_stakes,
-                block_header.timestamp(),
-                block_header.height() + 1,
-                *block_header.global_state_hash(),
+            if new_era_id.0 <= AUCTION_DELAY {
+                // The validators of the first eras were determined at genesis.
+                let validator_stakes = self.era_supervisor.validator_stakes.clone();
+                effects.extend(self.start_new_era(block_header, validator_stakes));
+            } else {
+                effects.extend(self.read_validators(block_header, None));
+            }
+        } else {
+            effects.extend(
+                self.effect_builder
+                    .announce_block_handled(block_header.height())
+                    .ignore(),
             );
-            effects.extend(self.handle_consensus_results(new_era_id, results));
         }
+        effects
+    }
+
+    /// Reads the validators of the era following the given switch block from the global state
+    /// after it, i.e. the auction's winners, after the given delay if any.
+    fn read_validators(
+        &self,
+        block_header: BlockHeader,
+        delay: Option<Duration>,
+    ) -> Effects<Event<I>> {
+        let effect_builder = self.effect_builder;
+        let global_state_hash = *block_header.global_state_hash();
+        let new_era_id = block_header.era_id().successor();
+        let protocol_version =
+            to_protocol_version(&self.era_supervisor.chainspec.protocol_version(new_era_id));
+        async move {
+            if let Some(delay) = delay {
+                effect_builder.set_timeout(delay).await;
+            }
+            effect_builder
+                .get_validators(global_state_hash, new_era_id, protocol_version)
+                .await
+        }
+        .event(move |validators| Event::CreateNewEra {
+            block_header: Box::new(block_header),
+            validators,
+        })
+    }
+
+    /// Starts the era following the given switch block, with the validators read from the global
+    /// state. If they can't be read, tries again after a delay.
+    pub(super) fn handle_create_new_era(
+        &mut self,
+        block_header: BlockHeader,
+        validators: Result<Option<ValidatorWeights>, engine_state::Error>,
+    ) -> Effects<Event<I>> {
+        let new_era_id = block_header.era_id().successor();
+        let validator_weights = match validators {
+            Ok(Some(validator_weights)) => validator_weights,
+            Ok(None) => {
+                error!(
+                    ?new_era_id,
+                    global_state_hash = %block_header.global_state_hash(),
+                    "no validators for era in global state; retrying"
+                );
+                return self.read_validators(block_header, Some(VALIDATORS_RETRY_DELAY));
+            }
+            Err(error) => {
+                error!(?new_era_id, %error, "failed to read validators for era; retrying");
+                return self.read_validators(block_header, Some(VALIDATORS_RETRY_DELAY));
+            }
+        };
+        let validator_stakes = validator_weights
+            .into_iter()
+            .filter_map(
+                |(public_key, stake)| match PublicKey::try_from(public_key) {
+                    Ok(public_key) => Some((public_key, Motes::new(stake))),
+                    Err(error) => {
+                        error!(%error, "invalid validator public key in global state");
+                        None
+                    }
+                },
+            )
+            .collect();
+        self.start_new_era(block_header, validator_stakes)
+    }
+
+    /// Starts the era following the given switch block with the given validators.
+    fn start_new_era(
+        &mut self,
+        block_header: BlockHeader,
+        validator_stakes: Vec<(PublicKey, Motes)>,
+    ) -> Effects<Event<I>> {
+        let new_era_id = block_header.era_id().successor();
+        info!(?new_era_id, "Era created");
+        let results = match self.era_supervisor.new_era(
+            new_era_id,
+            Timestamp::now(), // TODO: This should be passed in.
+            validator_stakes,
+            block_header.timestamp(),
+            block_header.height() + 1,
+            *block_header.global_state_hash(),
+        ) {
+            Ok(results) => results,
+            Err(error) => {
+                return fatal!(
+                    self.effect_builder,
+                    format!("failed to start era {}: {:?}", new_era_id.0, error)
+                )
+            }
+        };
+        let mut effects = self.handle_consensus_results(new_era_id, results);
         effects.extend(
             self.effect_builder
                 .announce_block_handled(block_header.height())
@@ -423,6 +614,19 @@ where
         effects
     }
 
+    pub(super) fn handle_era_validators(
+        &mut self,
+        era_id: EraId,
+        responder: Responder<Option<BTreeMap<PublicKey, Motes>>>,
+    ) -> Effects<Event<I>> {
+        let maybe_validator_stakes = self
+            .era_supervisor
+            .active_eras
+            .get(&era_id)
+            .map(|era| era.validator_stakes.clone());
+        responder.respond(maybe_validator_stakes).ignore()
+    }
+
     pub(super) fn handle_accept_proto_block(
         &mut self,
         era_id: EraId,
@@ -450,6 +654,51 @@ where
         })
     }
 
+    pub(super) fn handle_evidence_received(
+        &mut self,
+        sender: I,
+        evidence: Evidence,
+    ) -> Effects<Event<I>> {
+        let era = match self.era_supervisor.active_eras.get(&evidence.era_id()) {
+            Some(era) => era,
+            None => {
+                debug!(%evidence, ?sender, "can't verify evidence for an inactive era");
+                return Effects::new();
+            }
+        };
+        if let Err(error) = era
+            .consensus
+            .validate_evidence(evidence.perpetrator(), evidence.proof())
+        {
+            // TODO: we will probably want to disconnect from the sender here
+            info!(%evidence, ?sender, %error, "received invalid evidence");
+            return Effects::new();
+        }
+        self.store_evidence(evidence)
+    }
+
+    /// Puts the verified evidence to storage, and announces it unless it was already stored.
+    fn store_evidence(&self, evidence: Evidence) -> Effects<Event<I>> {
+        let effect_builder = self.effect_builder;
+        async move {
+            if effect_builder
+                .put_evidence_to_storage(evidence.clone())
+                .await
+            {
+                effect_builder.announce_new_evidence(evidence).await
+            }
+        }
+        .ignore()
+    }
+
+    /// Returns the evidence against the given validator from the era's protocol state.
+    fn evidence(&self, era_id: EraId, public_key: PublicKey) -> Option<Evidence> {
+        let proof = self.era_supervisor.active_eras[&era_id]
+            .consensus
+            .evidence(&public_key)?;
+        Some(Evidence::new(era_id, public_key, proof))
+    }
+
     fn handle_consensus_results<T>(&mut self, era_id: EraId, results: T) -> Effects<Event<I>>
     where
         T: IntoIterator<Item = ConsensusProtocolResult<I, ProtoBlock, PublicKey>>,
@@ -467,7 +716,6 @@ where
     ) -> Effects<Event<I>> {
         match consensus_result {
             ConsensusProtocolResult::InvalidIncomingMessage(msg, sender, error) => {
-                // TODO: we will probably want to disconnect from the sender here
                 // TODO: Print a more readable representation of the message.
                 error!(
                     ?msg,
@@ -475,7 +723,9 @@ where
                     ?error,
                     "invalid incoming message to consensus instance"
                 );
-                Default::default()
+                self.effect_builder
+                    .report_misbehavior::<_, Message>(sender, Misbehavior::InvalidMessage)
+                    .ignore()
             }
             ConsensusProtocolResult::CreatedGossipMessage(out_msg) => {
                 // TODO: we'll want to gossip instead of broadcast here
@@ -495,7 +745,7 @@ where
             }
             ConsensusProtocolResult::CreateNewBlock { block_context } => self
                 .effect_builder
-                .request_proto_block(block_context, self.rng.gen())
+                .request_proto_block(era_id, block_context, self.rng.gen())
                 .event(move |(proto_block, block_context)| Event::NewProtoBlock {
                     era_id,
                     proto_block,
@@ -504,6 +754,7 @@ where
             ConsensusProtocolResult::FinalizedBlock(CpFinalizedBlock {
                 value: proto_block,
                 new_equivocators,
+                equivocators,
                 rewards,
                 timestamp,
                 height,
@@ -515,11 +766,29 @@ where
                     .effect_builder
                     .announce_finalized_proto_block(proto_block.clone())
                     .ignore();
-                // Create instructions for slashing equivocators.
-                let mut system_transactions: Vec<_> = new_equivocators
-                    .into_iter()
-                    .map(SystemTransaction::Slash)
-                    .collect();
+                // Announce the newly detected equivocators.
+                for public_key in &new_equivocators {
+                    effects.extend(
+                        self.effect_builder
+                            .announce_fault(era_id, *public_key, timestamp)
+                            .ignore(),
+                    );
+                }
+                // Create instructions for slashing the era's equivocators in the switch block,
+                // along with the evidence against them.  No one is slashed without evidence.
+                let mut system_transactions = Vec::new();
+                let mut evidence = Vec::new();
+                if terminal {
+                    for public_key in equivocators {
+                        match self.evidence(era_id, public_key) {
+                            Some(evidence_against) => {
+                                evidence.push(evidence_against);
+                                system_transactions.push(SystemTransaction::Slash(public_key));
+                            }
+                            None => error!(%public_key, "missing evidence against equivocator"),
+                        }
+                    }
+                }
                 if !rewards.is_empty() {
                     system_transactions.push(SystemTransaction::Rewards(rewards));
                 };
@@ -527,6 +796,7 @@ where
                     proto_block,
                     timestamp,
                     system_transactions,
+                    evidence,
                     terminal,
                     era_id,
                     self.era_supervisor.active_eras[&era_id].start_height + height,
@@ -536,6 +806,18 @@ where
                 effects.extend(self.effect_builder.execute_block(fb).ignore());
                 effects
             }
+            ConsensusProtocolResult::NewEvidence(public_key) => {
+                match self.evidence(era_id, public_key) {
+                    Some(evidence) => self.store_evidence(evidence),
+                    None => {
+                        error!(%public_key, "missing evidence reported by consensus protocol");
+                        Effects::new()
+                    }
+                }
+            }
+            ConsensusProtocolResult::Fatal(msg) => {
+                fatal!(self.effect_builder, format!("era {}: {}", era_id.0, msg))
+            }
             ConsensusProtocolResult::ValidateConsensusValue(sender, proto_block) => self
                 .effect_builder
                 .validate_block(sender.clone(), proto_block)
@@ -556,3 +838,117 @@ where
         }
     }
 }
+
+/// Returns the consensus protocol for the given era: the one set by the latest upgrade point
+/// activated by then, or else the one configured at genesis.
+fn consensus_protocol_name(chainspec: &Chainspec, era_id: EraId) -> ConsensusProtocolName {
+    chainspec
+        .activated_upgrades(era_id)
+        .filter_map(|up| up.new_consensus_protocol)
+        .last()
+        .unwrap_or(chainspec.genesis.highway_config.consensus_protocol)
+}
+
+/// Returns the ID of the given era's consensus instance, which commits to the chain, the global
+/// state the era starts from and the upgrade points activated by then, identified by their eras.
+fn instance_id(
+    chainspec: &Chainspec,
+    post_state_hash: hash::Digest,
+    era_id: EraId,
+) -> hash::Digest {
+    let mut result = [0; hash::Digest::LENGTH];
+    let mut hasher = VarBlake2b::new(hash::Digest::LENGTH).expect("should create hasher");
+
+    hasher.input(&chainspec.genesis.name);
+    hasher.input(chainspec.genesis.timestamp.millis().to_le_bytes());
+    hasher.input(post_state_hash);
+
+    for upgrade_point in chainspec.activated_upgrades(era_id) {
+        hasher.input(upgrade_point.activation_point.rank.to_le_bytes());
+        if let Some(bytes) = upgrade_point.upgrade_installer_bytes.as_ref() {
+            hasher.input(bytes);
+        }
+        if let Some(bytes) = upgrade_point.upgrade_installer_args.as_ref() {
+            hasher.input(bytes);
+        }
+    }
+
+    hasher.variable_result(|slice| {
+        result.copy_from_slice(slice);
+    });
+    result.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::chainspec_loader::{ActivationPoint, UpgradePoint},
+        testing::TestRng,
+    };
+
+    /// Returns a chainspec running Highway from genesis, with a single upgrade point switching to
+    /// round-robin at the start of the given era.
+    fn chainspec_with_upgrade(rng: &mut TestRng, activation_era: u64) -> Chainspec {
+        let mut chainspec = Chainspec::random(rng);
+        chainspec.genesis.highway_config.consensus_protocol = ConsensusProtocolName::Highway;
+        let upgrade_point = UpgradePoint {
+            activation_point: ActivationPoint {
+                rank: activation_era,
+            },
+            new_consensus_protocol: Some(ConsensusProtocolName::RoundRobin),
+            ..chainspec.upgrades[0].clone()
+        };
+        chainspec.upgrades = vec![upgrade_point];
+        chainspec
+    }
+
+    #[test]
+    fn should_switch_consensus_protocol_at_activation_era() {
+        let mut rng = TestRng::new();
+        let chainspec = chainspec_with_upgrade(&mut rng, 5);
+
+        // The activation point is compared against era IDs, not the heights of their first blocks.
+        for era in 0..5 {
+            assert_eq!(
+                consensus_protocol_name(&chainspec, EraId(era)),
+                ConsensusProtocolName::Highway
+            );
+        }
+        for era in 5..8 {
+            assert_eq!(
+                consensus_protocol_name(&chainspec, EraId(era)),
+                ConsensusProtocolName::RoundRobin
+            );
+        }
+    }
+
+    #[test]
+    fn should_change_instance_id_at_activation_era() {
+        let mut rng = TestRng::new();
+        let chainspec = chainspec_with_upgrade(&mut rng, 5);
+        let post_state_hash = hash::Digest::random(&mut rng);
+
+        // Only an upgrade activated by the era's start changes the instance ID.
+        let id_before = instance_id(&chainspec, post_state_hash, EraId(0));
+        assert_eq!(
+            instance_id(&chainspec, post_state_hash, EraId(4)),
+            id_before
+        );
+        let id_after = instance_id(&chainspec, post_state_hash, EraId(5));
+        assert_ne!(id_after, id_before);
+        assert_eq!(instance_id(&chainspec, post_state_hash, EraId(7)), id_after);
+
+        // The ID commits to the era the upgrade was activated in.
+        let mut later_chainspec = chainspec.clone();
+        later_chainspec.upgrades[0].activation_point = ActivationPoint { rank: 6 };
+        assert_eq!(
+            instance_id(&later_chainspec, post_state_hash, EraId(5)),
+            id_before
+        );
+        assert_ne!(
+            instance_id(&later_chainspec, post_state_hash, EraId(7)),
+            id_after
+        );
+    }
+}
@@ -39,7 +39,7 @@ pub(crate) mod validators;
 
 mod evidence;
 #[cfg(test)]
-mod highway_testing;
+pub(crate) mod highway_testing;
 mod state;
 
 pub(crate) use state::Weight;
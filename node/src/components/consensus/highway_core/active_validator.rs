@@ -1,3 +1,5 @@
+mod round_exp_controller;
+
 use std::fmt::{self, Debug};
 
 use rand::{CryptoRng, Rng};
@@ -6,9 +8,10 @@ use tracing::{error, warn};
 use super::{
     evidence::Evidence,
     highway::{ValidVertex, Vertex, WireVote},
-    state::{self, Observation, Panorama, State, Vote},
+    state::{self, Observation, Panorama, State, Vote, Weight},
     validators::ValidatorIndex,
 };
+use round_exp_controller::RoundExpController;
 
 use crate::{
     components::consensus::{
@@ -47,6 +50,9 @@ pub(crate) enum Effect<C: Context> {
 /// If the rounds are long enough (i.e. message delivery is fast enough) and there are enough
 /// honest validators, there will be a lot of confirmations for the proposal, and enough witness
 /// votes citing all those confirmations, to create a summit and finalize the proposal.
+///
+/// The validator adapts its round length: If proposals or confirmations often arrive too late, it
+/// increases its round exponent, and after a series of fast rounds it decreases it again.
 pub(crate) struct ActiveValidator<C: Context> {
     /// Our own validator index.
     vidx: ValidatorIndex,
@@ -54,6 +60,8 @@ pub(crate) struct ActiveValidator<C: Context> {
     secret: C::ValidatorSecret,
     /// The next round exponent: Our next round will be `1 << next_round_exp` milliseconds long.
     next_round_exp: u8,
+    /// Adapts the round exponent to the observed network conditions.
+    round_exp_controller: RoundExpController,
     /// The latest timer we scheduled.
     next_timer: Timestamp,
     /// Panorama and timestamp for a block we are about to propose when we get a consensus value.
@@ -65,6 +73,7 @@ impl<C: Context> Debug for ActiveValidator<C> {
         f.debug_struct("ActiveValidator")
             .field("vidx", &self.vidx)
             .field("next_round_exp", &self.next_round_exp)
+            .field("round_exp_controller", &self.round_exp_controller)
             .field("next_timer", &self.next_timer)
             .finish()
     }
@@ -78,10 +87,17 @@ impl<C: Context> ActiveValidator<C> {
         timestamp: Timestamp,
         state: &State<C>,
     ) -> (Self, Vec<Effect<C>>) {
+        let params = state.params();
+        let round_exp_controller = RoundExpController::new(
+            params.init_round_exp(),
+            params.min_round_exp(),
+            params.max_round_exp(),
+        );
         let mut av = ActiveValidator {
             vidx,
             secret,
-            next_round_exp: state.params().init_round_exp(),
+            next_round_exp: round_exp_controller.round_exp(),
+            round_exp_controller,
             next_timer: Timestamp::zero(),
             next_proposal: None,
         };
@@ -114,11 +130,14 @@ impl<C: Context> ActiveValidator<C> {
             effects.extend(self.request_new_block(state, instance_id, timestamp, rng))
         } else if timestamp == r_id + self.witness_offset(r_len) {
             let panorama = state.panorama().cutoff(state, timestamp);
+            let success = self.round_succeeded(r_id, &panorama, state);
             if panorama.has_correct() {
                 let witness_vote =
                     self.new_vote(panorama, timestamp, None, state, instance_id, rng);
                 effects.push(Effect::NewVertex(ValidVertex(Vertex::Vote(witness_vote))))
             }
+            // Only change the exponent after the witness vote: It must still be in this round.
+            self.next_round_exp = self.round_exp_controller.record_round(success);
         }
         effects
     }
@@ -236,12 +255,56 @@ impl<C: Context> ActiveValidator<C> {
             && vote.creator != self.vidx // We didn't send it ourselves.
             && !state.has_evidence(vote.creator) // The creator is not faulty.
             && !self.is_faulty(state) // We are not faulty.
+            && self.has_room_for_confirmation(timestamp, r_exp, state) // At most two per round.
             && self.latest_vote(state)
                 .map_or(true, |vote| {
                     !vote.panorama.sees_correct(state, vhash)
                 }) // We haven't confirmed it already.
     }
 
+    /// Returns whether the round `r_id` was successful from our point of view: whether the
+    /// leader's proposal, and confirmations by validators with more than half of the total weight,
+    /// arrived before our witness vote, i.e. are in its `panorama`.
+    fn round_succeeded(&self, r_id: Timestamp, panorama: &Panorama<C>, state: &State<C>) -> bool {
+        let leader = state.leader(r_id);
+        let opt_proposal_hash = panorama[leader].correct().and_then(|latest_hash| {
+            state
+                .swimlane(latest_hash)
+                .find(|(_, vote)| vote.timestamp <= r_id)
+                .filter(|(_, vote)| vote.timestamp == r_id)
+                .map(|(hash, _)| hash)
+        });
+        let proposal_hash = match opt_proposal_hash {
+            Some(hash) => hash,
+            None => return false, // The proposal is missing or late.
+        };
+        let confirms = |hash: &C::Hash| {
+            hash == proposal_hash || state.vote(hash).panorama.sees_correct(state, proposal_hash)
+        };
+        let confirmed_weight: Weight = panorama
+            .enumerate()
+            .filter(|(_, obs)| obs.correct().map_or(false, &confirms))
+            .map(|(vidx, _)| state.weight(vidx))
+            .sum();
+        2 * u128::from(confirmed_weight) > u128::from(state.total_weight())
+    }
+
+    /// Returns whether we can send a confirmation at `timestamp` without exceeding the limit of
+    /// two votes per round. Before the witness vote we must leave room for it: If other validators
+    /// use shorter rounds than us, there can be several proposals in a single one of our rounds.
+    fn has_room_for_confirmation(&self, timestamp: Timestamp, r_exp: u8, state: &State<C>) -> bool {
+        let r_id = state::round_id(timestamp, r_exp);
+        let votes_in_round = state.panorama().get(self.vidx).correct().map_or(0, |vh| {
+            state
+                .swimlane(vh)
+                .take_while(|(_, vote)| vote.timestamp >= r_id)
+                .count()
+        });
+        let witness_time = r_id + self.witness_offset(state::round_len(r_exp));
+        let max_votes = if timestamp < witness_time { 0 } else { 1 };
+        votes_in_round <= max_votes
+    }
+
     /// Returns the panorama of the confirmation for the leader vote `vhash`.
     fn confirmation_panorama(&self, vhash: &C::Hash, state: &State<C>) -> Panorama<C> {
         let vote = state.vote(vhash);
@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+
+/// The number of most recent rounds the controller takes into account.
+const ROUND_WINDOW: usize = 10;
+/// If at least this many of the rounds in the window failed, the round exponent is increased.
+const MAX_FAILED_ROUNDS: usize = 3;
+
+/// Adapts a validator's round exponent to the observed network conditions.
+///
+/// A round is successful if the leader's proposal and enough confirmations arrived before our
+/// witness vote. After too many failed rounds the round length is doubled, and after a full
+/// window of successful rounds it is halved, always staying within the configured bounds.
+#[derive(Debug)]
+pub(super) struct RoundExpController {
+    /// The current round exponent.
+    round_exp: u8,
+    /// The minimum round exponent.
+    min_round_exp: u8,
+    /// The maximum round exponent.
+    max_round_exp: u8,
+    /// The outcomes of the most recent rounds since the last change, oldest first.
+    outcomes: VecDeque<bool>,
+}
+
+impl RoundExpController {
+    /// Creates a new controller starting with `round_exp`, clamped to the given bounds.
+    pub(super) fn new(round_exp: u8, min_round_exp: u8, max_round_exp: u8) -> Self {
+        RoundExpController {
+            round_exp: round_exp.max(min_round_exp).min(max_round_exp),
+            min_round_exp,
+            max_round_exp,
+            outcomes: VecDeque::with_capacity(ROUND_WINDOW),
+        }
+    }
+
+    /// Returns the current round exponent.
+    pub(super) fn round_exp(&self) -> u8 {
+        self.round_exp
+    }
+
+    /// Records the outcome of a round, and returns the new round exponent.
+    pub(super) fn record_round(&mut self, success: bool) -> u8 {
+        if self.outcomes.len() == ROUND_WINDOW {
+            self.outcomes.pop_front();
+        }
+        self.outcomes.push_back(success);
+        let failures = self.outcomes.iter().filter(|success| !**success).count();
+        if failures >= MAX_FAILED_ROUNDS && self.round_exp < self.max_round_exp {
+            self.round_exp += 1;
+            self.outcomes.clear();
+        } else if failures == 0
+            && self.outcomes.len() == ROUND_WINDOW
+            && self.round_exp > self.min_round_exp
+        {
+            self.round_exp -= 1;
+            self.outcomes.clear();
+        }
+        self.round_exp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_slow_down_after_failures_and_speed_up_after_fast_rounds() {
+        let mut controller = RoundExpController::new(10, 12, 14);
+        assert_eq!(12, controller.round_exp());
+
+        // A single failed round is tolerated.
+        assert_eq!(12, controller.record_round(false));
+        for _ in 0..=(ROUND_WINDOW - MAX_FAILED_ROUNDS) {
+            assert_eq!(12, controller.record_round(true));
+        }
+        // The first failure drops out of the window before enough new ones accumulate.
+        for _ in 1..MAX_FAILED_ROUNDS {
+            assert_eq!(12, controller.record_round(false));
+        }
+        assert_eq!(13, controller.record_round(false));
+
+        // We never exceed the maximum.
+        for _ in 0..(2 * MAX_FAILED_ROUNDS) {
+            controller.record_round(false);
+        }
+        assert_eq!(14, controller.round_exp());
+
+        // A full window of fast rounds halves the round length again.
+        for _ in 1..ROUND_WINDOW {
+            assert_eq!(14, controller.record_round(true));
+        }
+        assert_eq!(13, controller.record_round(true));
+        for _ in 0..(2 * ROUND_WINDOW) {
+            controller.record_round(true);
+        }
+        assert_eq!(12, controller.round_exp());
+    }
+}
@@ -1,4 +1,4 @@
-use super::validators::ValidatorIndex;
+use super::validators::{ValidatorIndex, Validators};
 use crate::components::consensus::{
     highway_core::highway::{EvidenceError, SignedWireVote},
     traits::Context,
@@ -30,8 +30,13 @@ impl<C: Context> Evidence<C> {
     /// "Validation" can mean different things for different type of evidence.
     ///
     /// - For an equivocation, it checks whether the creators, sequence numbers and instance IDs of
-    /// the two votes are the same.
-    pub(crate) fn validate(&self) -> Result<(), EvidenceError> {
+    /// the two votes are the same, whether they belong to the given protocol instance, whether the
+    /// votes are different, and whether they are both signed by their creator.
+    pub(crate) fn validate(
+        &self,
+        validators: &Validators<C::ValidatorId>,
+        instance_id: &C::InstanceId,
+    ) -> Result<(), EvidenceError> {
         match self {
             Evidence::Equivocation(vote1, vote2) => {
                 if vote1.wire_vote.creator != vote2.wire_vote.creator {
@@ -43,6 +48,21 @@ impl<C: Context> Evidence<C> {
                 if vote1.wire_vote.instance_id != vote2.wire_vote.instance_id {
                     return Err(EvidenceError::EquivocationDifferentInstances);
                 }
+                if vote1.wire_vote.instance_id != *instance_id {
+                    return Err(EvidenceError::EquivocationInstanceId);
+                }
+                if vote1.hash() == vote2.hash() {
+                    return Err(EvidenceError::EquivocationSameVote);
+                }
+                let v_id = validators
+                    .get_by_index(vote1.wire_vote.creator)
+                    .ok_or(EvidenceError::UnknownPerpetrator)?
+                    .id();
+                for vote in &[vote1, vote2] {
+                    if !C::verify_signature(&vote.hash(), v_id, &vote.signature) {
+                        return Err(EvidenceError::Signature);
+                    }
+                }
                 Ok(())
             }
         }
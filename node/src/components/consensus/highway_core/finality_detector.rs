@@ -57,6 +57,7 @@ impl<C: Context> FinalityDetector<C> {
                 opt_validator.unwrap().id().clone() // Index exists, since we have votes from them.
             };
             let new_equivocators_iter = state.get_new_equivocators(bhash).into_iter();
+            let equivocators_iter = state.get_equivocators(bhash).into_iter();
             let rewards = rewards::compute_rewards(state, bhash);
             let rewards_iter = rewards.enumerate();
             let block = state.block(bhash);
@@ -65,6 +66,7 @@ impl<C: Context> FinalityDetector<C> {
             Some(FinalizedBlock {
                 value: block.value.clone(),
                 new_equivocators: new_equivocators_iter.map(to_id).collect(),
+                equivocators: equivocators_iter.map(to_id).collect(),
                 rewards: rewards_iter.map(|(vidx, r)| (to_id(vidx), *r)).collect(),
                 timestamp: vote.timestamp,
                 height: block.height,
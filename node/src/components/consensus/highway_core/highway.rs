@@ -12,7 +12,8 @@ use crate::{
         consensus_protocol::BlockContext,
         highway_core::{
             active_validator::{ActiveValidator, Effect},
-            state::{State, VoteError},
+            evidence::Evidence,
+            state::{Observation, State, VoteError},
             validators::{Validator, Validators},
         },
         traits::Context,
@@ -38,6 +39,12 @@ pub(crate) enum EvidenceError {
     EquivocationDifferentSeqNumbers,
     #[error("The instance IDs in the equivocating votes are different.")]
     EquivocationDifferentInstances,
+    #[error("The equivocating votes belong to a different protocol instance.")]
+    EquivocationInstanceId,
+    #[error("The two votes are the same.")]
+    EquivocationSameVote,
+    #[error("The signature of an equivocating vote is invalid.")]
+    Signature,
     #[error("The perpetrator is not a validator.")]
     UnknownPerpetrator,
 }
@@ -219,6 +226,34 @@ impl<C: Context> Highway<C> {
         }
     }
 
+    /// Adds a validated vertex to the protocol state without notifying the active validator, e.g.
+    /// when restoring the state after a restart, before activating the validator.
+    pub(crate) fn restore_vertex(&mut self, ValidVertex(vertex): ValidVertex<C>) {
+        if !self.has_vertex(&vertex) {
+            match vertex {
+                Vertex::Vote(vote) => self.state.add_valid_vote(vote),
+                Vertex::Evidence(evidence) => self.state.add_evidence(evidence),
+            }
+        }
+    }
+
+    /// Returns whether the protocol state contains any vote by the given validator.
+    pub(crate) fn has_votes_by(&self, id: &C::ValidatorId) -> bool {
+        self.validators
+            .get_index(id)
+            .map_or(false, |idx| self.state.panorama()[idx] != Observation::None)
+    }
+
+    /// Returns the evidence against the given validator, if any.
+    pub(crate) fn opt_evidence(&self, id: &C::ValidatorId) -> Option<&Evidence<C>> {
+        self.state.opt_evidence(self.validators.get_index(id)?)
+    }
+
+    /// Returns an error if the evidence is invalid, or doesn't belong to this protocol instance.
+    pub(crate) fn validate_evidence(&self, evidence: &Evidence<C>) -> Result<(), EvidenceError> {
+        evidence.validate(&self.validators, &self.instance_id)
+    }
+
     /// Returns whether the vertex is already part of this protocol state.
     pub(crate) fn has_vertex(&self, vertex: &Vertex<C>) -> bool {
         match vertex {
@@ -341,13 +376,7 @@ impl<C: Context> Highway<C> {
                 }
                 Ok(self.state.pre_validate_vote(vote)?)
             }
-            Vertex::Evidence(evidence) => {
-                evidence.validate()?;
-                if !self.validators.contains(evidence.perpetrator()) {
-                    return Err(EvidenceError::UnknownPerpetrator.into());
-                }
-                Ok(())
-            }
+            Vertex::Evidence(evidence) => Ok(self.validate_evidence(evidence)?),
         }
     }
 
@@ -476,6 +476,7 @@ where
         for FinalizedBlock {
             value,
             new_equivocators,
+            equivocators: _,
             rewards,
             timestamp: _,
             height,
@@ -683,6 +684,9 @@ struct HighwayTestHarnessBuilder<DS: DeliveryStrategy> {
     /// Seed for `Highway`.
     /// Defaults to 0.
     seed: u64,
+    /// Lower and upper limits for validators' round exponents.
+    /// Defaults to `TEST_MIN_ROUND_EXP` for both, i.e. all rounds have the same length.
+    round_exp_limits: (u8, u8),
 }
 
 // Default strategy for message delivery.
@@ -723,6 +727,7 @@ impl HighwayTestHarnessBuilder<InstantDeliveryNoDropping> {
             start_time: Timestamp::zero(),
             weight_distribution: Distribution::Uniform,
             seed: 0,
+            round_exp_limits: (TEST_MIN_ROUND_EXP, TEST_MIN_ROUND_EXP),
         }
     }
 }
@@ -760,6 +765,17 @@ impl<DS: DeliveryStrategy> HighwayTestHarnessBuilder<DS> {
         self
     }
 
+    /// Sets the limits for the validators' round exponents. Validators start with different
+    /// exponents between `lower` and `upper` (inclusive), and adapt them while the test runs.
+    fn round_exp_limits(mut self, lower: u8, upper: u8) -> Self {
+        assert!(
+            lower <= upper,
+            "Lower limit must not exceed the upper limit."
+        );
+        self.round_exp_limits = (lower, upper);
+        self
+    }
+
     fn build<R: Rng + CryptoRng + ?Sized>(
         self,
         rng: &mut R,
@@ -771,6 +787,7 @@ impl<DS: DeliveryStrategy> HighwayTestHarnessBuilder<DS> {
         let instance_id = 0;
         let seed = self.seed;
         let start_time = self.start_time;
+        let (min_round_exp, max_round_exp) = self.round_exp_limits;
 
         let (lower, upper) = {
             let (l, u) = self.weight_limits;
@@ -868,15 +885,21 @@ impl<DS: DeliveryStrategy> HighwayTestHarnessBuilder<DS> {
             |(vid, secrets): (ValidatorId, &mut HashMap<ValidatorId, TestSecret>)| {
                 let v_sec = secrets.remove(&vid).expect("Secret key should exist.");
 
+                // Cycle through the allowed round exponents, so that validators start with
+                // different round lengths.
+                let exp_count = u64::from(max_round_exp - min_round_exp) + 1;
+                let init_round_exp = min_round_exp + (vid.0 % exp_count) as u8;
                 let params = Params::new(
                     seed,
                     TEST_BLOCK_REWARD,
                     TEST_REDUCED_BLOCK_REWARD,
                     TEST_REWARD_DELAY,
-                    TEST_MIN_ROUND_EXP,
+                    min_round_exp,
+                    max_round_exp,
                     TEST_END_HEIGHT,
                     Timestamp::zero(), // Length depends only on block number.
-                );
+                )
+                .with_init_round_exp(init_round_exp);
                 let mut highway = Highway::new(instance_id, validators.clone(), params);
                 let effects = highway.activate_validator(vid, v_sec, start_time);
 
@@ -1012,8 +1035,8 @@ mod test_harness {
     use std::{collections::HashSet, fmt::Debug};
 
     use super::{
-        crank_until, ConsensusValue, HighwayTestHarness, HighwayTestHarnessBuilder,
-        InstantDeliveryNoDropping, TestRunError,
+        crank_until, ConsensusValue, HighwayMessage, HighwayTestHarness, HighwayTestHarnessBuilder,
+        InstantDeliveryNoDropping, TestRunError, Vertex, TEST_MIN_ROUND_EXP,
     };
     use crate::{
         components::consensus::tests::consensus_des_testing::{Fault, ValidatorId},
@@ -1207,4 +1230,96 @@ mod test_harness {
             "Nodes saw different set of equivocators.",
         );
     }
+
+    #[test]
+    fn liveness_test_heterogeneous_round_exps() {
+        let _ = logging::init_with_config(&LoggingConfig::new(LoggingFormat::Text, true));
+
+        let mut rng = TestRng::new();
+        let cv_count = 10;
+
+        let mut highway_test_harness = HighwayTestHarnessBuilder::new()
+            .max_faulty_validators(3)
+            .consensus_values_count(cv_count)
+            .weight_limits(100, 120)
+            .round_exp_limits(TEST_MIN_ROUND_EXP, TEST_MIN_ROUND_EXP + 2)
+            .build(&mut rng)
+            .ok()
+            .expect("Construction was successful");
+
+        crank_until(&mut highway_test_harness, &mut rng, |hth| {
+            hth.virtual_net
+                .validators()
+                .all(|v| v.finalized_count() == cv_count as usize)
+        })
+        .unwrap();
+
+        let handle = highway_test_harness.mutable_handle();
+        let validators = handle.validators();
+
+        let (finalized_values, first_round_exps): (Vec<Vec<ConsensusValue>>, Vec<Option<u8>>) =
+            validators
+                .map(|v| {
+                    (
+                        v.finalized_values().cloned().collect::<Vec<_>>(),
+                        v.messages_produced().find_map(|hwm| match hwm {
+                            HighwayMessage::NewVertex(Vertex::Vote(swvote)) => {
+                                Some(swvote.wire_vote.round_exp)
+                            }
+                            _ => None,
+                        }),
+                    )
+                })
+                .unzip();
+
+        // Validators started out with different round lengths.
+        assert!(
+            first_round_exps.iter().any(|re| *re != first_round_exps[0]),
+            "Expected validators to start with different round exponents: {:?}",
+            first_round_exps
+        );
+        assert_eq_vectors(
+            finalized_values,
+            "Nodes finalized different consensus values.",
+        );
+    }
+
+    #[test]
+    fn liveness_test_heterogeneous_round_exps_some_mute() {
+        let _ = logging::init_with_config(&LoggingConfig::new(LoggingFormat::Text, true));
+
+        let mut rng = TestRng::new();
+        let cv_count = 10;
+        let fault_perc = 20;
+
+        let mut highway_test_harness = HighwayTestHarnessBuilder::new()
+            .max_faulty_validators(3)
+            .faulty_weight_perc(fault_perc)
+            .fault_type(Fault::Mute)
+            .consensus_values_count(cv_count)
+            .weight_limits(100, 120)
+            .round_exp_limits(TEST_MIN_ROUND_EXP, TEST_MIN_ROUND_EXP + 3)
+            .build(&mut rng)
+            .ok()
+            .expect("Construction was successful");
+
+        crank_until(&mut highway_test_harness, &mut rng, |hth| {
+            hth.virtual_net
+                .validators()
+                .all(|v| v.finalized_count() == cv_count as usize)
+        })
+        .unwrap();
+
+        let handle = highway_test_harness.mutable_handle();
+        let validators = handle.validators();
+
+        let finalized_values: Vec<Vec<ConsensusValue>> = validators
+            .map(|v| v.finalized_values().cloned().collect::<Vec<_>>())
+            .collect();
+
+        assert_eq_vectors(
+            finalized_values,
+            "Nodes finalized different consensus values.",
+        );
+    }
 }
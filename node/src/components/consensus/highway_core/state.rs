@@ -352,7 +352,9 @@ impl<C: Context> State<C> {
             error!("Nonexistent validator should be rejected in Highway::pre_validate_vote.");
             return Err(VoteError::Creator); // Should be unreachable.
         }
-        if wvote.round_exp < self.params.min_round_exp() {
+        if wvote.round_exp < self.params.min_round_exp()
+            || wvote.round_exp > self.params.max_round_exp()
+        {
             return Err(VoteError::RoundLength);
         }
         if wvote.value.is_none() && !wvote.panorama.has_correct() {
@@ -510,6 +512,17 @@ impl<C: Context> State<C> {
         }
         equivocators
     }
+
+    /// Returns a vector of validator indexes that are faulty in the panorama of the block
+    /// identified by `fhash`, including the ones that equivocated before its parent.
+    pub(super) fn get_equivocators(&self, fhash: &C::Hash) -> Vec<ValidatorIndex> {
+        self.vote(fhash)
+            .panorama
+            .enumerate()
+            .filter(|(_, obs)| obs.is_faulty())
+            .map(|(vid, _)| vid)
+            .collect()
+    }
 }
 
 /// Returns the round length, given the round exponent.
@@ -8,6 +8,7 @@ pub(crate) struct Params {
     reduced_block_reward: u64,
     reward_delay: u64,
     min_round_exp: u8,
+    max_round_exp: u8,
     init_round_exp: u8,
     end_height: u64,
     end_timestamp: Timestamp,
@@ -30,15 +31,19 @@ impl Params {
     ///   `reward_delay * t` after B's timestamp, where `t` is the round length of B itself.
     /// * `min_round_exp`: The minimum round exponent. `1 << min_round_exp` milliseconds is the
     ///   minimum round length.
+    /// * `max_round_exp`: The maximum round exponent. `1 << max_round_exp` milliseconds is the
+    ///   maximum round length.
     /// * `end_height`, `end_timestamp`: The last block will be the first one that has at least the
     ///   specified height _and_ is no earlier than the specified timestamp. No children of this
     ///   block can be proposed.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         seed: u64,
         block_reward: u64,
         reduced_block_reward: u64,
         reward_delay: u64,
         min_round_exp: u8,
+        max_round_exp: u8,
         end_height: u64,
         end_timestamp: Timestamp,
     ) -> Params {
@@ -46,18 +51,34 @@ impl Params {
             reduced_block_reward <= block_reward,
             "reduced block reward must not be greater than the reward for a finalized block"
         );
+        assert!(
+            min_round_exp <= max_round_exp,
+            "minimum round exponent must not be greater than the maximum"
+        );
         Params {
             seed,
             block_reward,
             reduced_block_reward,
             reward_delay,
             min_round_exp,
+            max_round_exp,
             init_round_exp: min_round_exp, // TODO: The median seen by previous era's switch block?
             end_height,
             end_timestamp,
         }
     }
 
+    /// Returns the parameters with the initial round exponent replaced by `init_round_exp`.
+    #[cfg(test)]
+    pub(crate) fn with_init_round_exp(mut self, init_round_exp: u8) -> Params {
+        assert!(
+            self.min_round_exp <= init_round_exp && init_round_exp <= self.max_round_exp,
+            "initial round exponent must be between the minimum and the maximum"
+        );
+        self.init_round_exp = init_round_exp;
+        self
+    }
+
     /// Returns the random seed.
     pub(crate) fn seed(&self) -> u64 {
         self.seed
@@ -86,6 +107,12 @@ impl Params {
         self.min_round_exp
     }
 
+    /// Returns the maximum round exponent. `1 << self.max_round_exp()` milliseconds is the maximum
+    /// round length.
+    pub(crate) fn max_round_exp(&self) -> u8 {
+        self.max_round_exp
+    }
+
     /// Returns the initial round exponent.
     pub(crate) fn init_round_exp(&self) -> u8 {
         self.init_round_exp
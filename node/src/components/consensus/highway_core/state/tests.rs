@@ -112,6 +112,7 @@ impl State<TestContext> {
             TEST_BLOCK_REWARD / 5,
             TEST_REWARD_DELAY,
             4,
+            19,
             u64::MAX,
             Timestamp::from(u64::MAX),
         );
@@ -1,24 +1,28 @@
+mod write_ahead_log;
+
 use std::{
     collections::{BTreeMap, HashMap},
     fmt::Debug,
     iter,
+    path::Path,
     rc::Rc,
 };
 
-use anyhow::Error;
+use anyhow::{bail, Error};
 use itertools::Itertools;
 use rand::{CryptoRng, Rng};
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
     components::consensus::{
         consensus_protocol::{BlockContext, ConsensusProtocol, ConsensusProtocolResult},
         highway_core::{
             active_validator::Effect as AvEffect,
+            evidence::Evidence,
             finality_detector::FinalityDetector,
             highway::{Dependency, Highway, Params, PreValidatedVertex, ValidVertex, Vertex},
-            validators::Validators,
+            validators::{Validator, Validators},
             Weight,
         },
         traits::{Context, NodeIdT, ValidatorSecret},
@@ -29,6 +33,7 @@ use crate::{
     },
     types::{ProtoBlock, Timestamp},
 };
+use write_ahead_log::WriteAheadLog;
 
 #[derive(Debug)]
 pub(crate) struct HighwayProtocol<I, C: Context> {
@@ -38,21 +43,94 @@ pub(crate) struct HighwayProtocol<I, C: Context> {
     pending_values: HashMap<C::ConsensusValue, Vec<ValidVertex<C>>>,
     finality_detector: FinalityDetector<C>,
     highway: Highway<C>,
+    /// The log every vertex is written to before it is added to the state or gossiped, if any.
+    wal: Option<WriteAheadLog<Vertex<C>>>,
 }
 
 impl<I: NodeIdT, C: Context> HighwayProtocol<I, C> {
+    /// Creates a new protocol instance.
+    ///
+    /// If a write-ahead log path is given, the vertices in the log are restored to the state, and
+    /// every vertex added from now on is appended to it.
     pub(crate) fn new(
         instance_id: C::InstanceId,
         validators: Validators<C::ValidatorId>,
         params: Params,
         ftt: Weight,
-    ) -> Self {
-        HighwayProtocol {
+        wal_path: Option<&Path>,
+    ) -> Result<Self, Error> {
+        let mut highway = Highway::new(instance_id, validators, params);
+        let wal = match wal_path {
+            None => None,
+            Some(wal_path) => {
+                let (wal, vertices) = WriteAheadLog::open(wal_path)?;
+                let restored_count = vertices.len();
+                for vertex in vertices {
+                    Self::restore_vertex(&mut highway, vertex);
+                }
+                if restored_count > 0 {
+                    info!(%restored_count, path = %wal_path.display(), "restored Highway vertices");
+                }
+                Some(wal)
+            }
+        };
+        Ok(HighwayProtocol {
             vertex_deps: BTreeMap::new(),
             pending_values: HashMap::new(),
             finality_detector: FinalityDetector::new(ftt),
-            highway: Highway::new(instance_id, validators, params),
+            highway,
+            wal,
+        })
+    }
+
+    /// Adds a vertex read from the write-ahead log to the state. The log contains the vertices in
+    /// the order they were added, so all their dependencies are already in the state.
+    fn restore_vertex(highway: &mut Highway<C>, vertex: Vertex<C>) {
+        if highway.has_vertex(&vertex) {
+            return;
+        }
+        let pvv = match highway.pre_validate_vertex(vertex) {
+            Ok(pvv) => pvv,
+            Err((vertex, err)) => {
+                warn!(?vertex, ?err, "invalid vertex in write-ahead log");
+                return;
+            }
+        };
+        if let Some(dep) = highway.missing_dependency(&pvv) {
+            warn!(
+                ?pvv,
+                ?dep,
+                "vertex in write-ahead log is missing a dependency"
+            );
+            return;
+        }
+        match highway.validate_vertex(pvv) {
+            Ok(vv) => highway.restore_vertex(vv),
+            Err((pvv, err)) => warn!(?pvv, ?err, "invalid vertex in write-ahead log"),
+        }
+    }
+
+    /// Returns whether the state contains any vote by the given validator, e.g. restored from the
+    /// write-ahead log after a restart.
+    pub(crate) fn has_votes_by(&self, validator_id: &C::ValidatorId) -> bool {
+        self.highway.has_votes_by(validator_id)
+    }
+
+    /// Appends the vertex to the write-ahead log, if any.
+    ///
+    /// If that fails, the validator is deactivated and a fatal error is returned: gossiping or
+    /// citing a vertex that would be missing after a restart could make this validator equivocate.
+    fn write_ahead(&mut self, vertex: &Vertex<C>) -> Result<(), CpResult<I, C>> {
+        if let Some(wal) = self.wal.as_mut() {
+            if let Err(error) = wal.append(vertex) {
+                self.highway.deactivate_validator();
+                return Err(ConsensusProtocolResult::Fatal(format!(
+                    "failed to write to the Highway write-ahead log: {:?}",
+                    error
+                )));
+            }
         }
+        Ok(())
     }
 
     pub(crate) fn activate_validator(
@@ -91,6 +169,9 @@ impl<I: NodeIdT, C: Context> HighwayProtocol<I, C> {
     }
 
     fn process_new_vertex(&mut self, v: Vertex<C>) -> Vec<CpResult<I, C>> {
+        if let Err(fatal) = self.write_ahead(&v) {
+            return vec![fatal];
+        }
         let msg = HighwayMessage::NewVertex(v);
         let serialized_msg = rmp_serde::to_vec(&msg).expect("should serialize message");
         let result = ConsensusProtocolResult::CreatedGossipMessage(serialized_msg);
@@ -175,15 +256,40 @@ impl<I: NodeIdT, C: Context> HighwayProtocol<I, C> {
     where
         R: Rng + CryptoRng + ?Sized,
     {
+        // Write the vertex before adding it: the votes we create in response cite it, and must
+        // come after it in the log.
+        if let Err(fatal) = self.write_ahead(vv.inner()) {
+            return vec![fatal];
+        }
+        let opt_perpetrator = self.potential_perpetrator(vv.inner());
         let av_effects = self.highway.add_valid_vertex(vv.clone(), rng);
         let mut results = self.process_av_effects(av_effects);
         let msg = HighwayMessage::NewVertex(vv.into());
         results.push(ConsensusProtocolResult::CreatedGossipMessage(
             rmp_serde::to_vec(&msg).expect("should serialize message"),
         ));
+        if let Some(perpetrator) =
+            opt_perpetrator.filter(|vid| self.highway.opt_evidence(vid).is_some())
+        {
+            results.push(ConsensusProtocolResult::NewEvidence(perpetrator));
+        }
         results
     }
 
+    /// Returns the ID of the validator that adding the vertex could prove faulty, i.e. the creator
+    /// of a vote or the perpetrator of evidence, unless there is evidence against them already.
+    fn potential_perpetrator(&self, vertex: &Vertex<C>) -> Option<C::ValidatorId> {
+        let idx = match vertex {
+            Vertex::Vote(vote) => vote.wire_vote.creator,
+            Vertex::Evidence(evidence) => evidence.perpetrator(),
+        };
+        let vid = self.highway.validators().get_by_index(idx)?.id();
+        if self.highway.opt_evidence(vid).is_some() {
+            return None;
+        }
+        Some(vid.clone())
+    }
+
     fn remove_satisfied_deps(&mut self) -> impl Iterator<Item = (I, PreValidatedVertex<C>)> + '_ {
         let satisfied_deps = self
             .vertex_deps
@@ -307,7 +413,28 @@ where
 
     /// Turns this instance into a passive observer, that does not create any new vertices.
     fn deactivate_validator(&mut self) {
-        self.highway.deactivate_validator()
+        self.highway.deactivate_validator();
+        // We won't vote in this era again, so there is nothing left to protect from equivocation.
+        self.wal = None;
+    }
+
+    fn evidence(&self, vid: &C::ValidatorId) -> Option<Vec<u8>> {
+        let evidence = self.highway.opt_evidence(vid)?;
+        Some(rmp_serde::to_vec(evidence).expect("should serialize evidence"))
+    }
+
+    fn validate_evidence(&self, vid: &C::ValidatorId, evidence: &[u8]) -> Result<(), Error> {
+        let evidence: Evidence<C> = rmp_serde::from_read_ref(evidence)?;
+        self.highway.validate_evidence(&evidence)?;
+        let validators = self.highway.validators();
+        if validators
+            .get_by_index(evidence.perpetrator())
+            .map(Validator::id)
+            != Some(vid)
+        {
+            bail!("evidence against a different validator");
+        }
+        Ok(())
     }
 }
 
@@ -0,0 +1,177 @@
+//! A write-ahead log of the vertices added to a Highway protocol instance.
+//!
+//! Every vertex is appended to the log and flushed to disk before it is added to the protocol
+//! state or gossiped. A validator that restarts mid-era restores its protocol state from the log,
+//! so it never signs a vote conflicting with one it already sent before the restart.
+//!
+//! Each entry is serialized with MessagePack, prefixed by its length as a little-endian
+//! `u32`. An incomplete entry at the end of the log, e.g. due to a crash while writing, is dropped.
+//! A complete entry which can't be deserialized is an error, as the log is corrupt.
+
+use std::{
+    convert::TryFrom,
+    fmt::{self, Debug, Formatter},
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Error};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::warn;
+
+/// Size of the length prefix of each entry.
+const LENGTH_PREFIX_SIZE: usize = 4;
+
+pub(super) struct WriteAheadLog<T> {
+    /// The path of the log file.
+    path: PathBuf,
+    /// The log file, opened for appending.
+    file: File,
+    _entry: PhantomData<T>,
+}
+
+impl<T> Debug for WriteAheadLog<T> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("WriteAheadLog")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> WriteAheadLog<T> {
+    /// Opens the log at the given path, creating it if it doesn't exist, and returns it along with
+    /// the entries it contains, in the order they were appended.
+    pub(super) fn open(path: &Path) -> Result<(Self, Vec<T>), Error> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        let mut bytes = Vec::new();
+        let _ = file
+            .read_to_end(&mut bytes)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while let Some((entry, entry_size)) = Self::read_entry(&bytes[offset..])
+            .with_context(|| format!("corrupt entry at {} in {}", offset, path.display()))?
+        {
+            entries.push(entry);
+            offset += entry_size;
+        }
+        if offset < bytes.len() {
+            warn!(
+                path = %path.display(),
+                dropped_bytes = bytes.len() - offset,
+                "dropping incomplete entry at the end of the Highway write-ahead log"
+            );
+            file.set_len(offset as u64)
+                .with_context(|| format!("failed to truncate {}", path.display()))?;
+        }
+
+        let wal = WriteAheadLog {
+            path: path.to_path_buf(),
+            file,
+            _entry: PhantomData,
+        };
+        Ok((wal, entries))
+    }
+
+    /// Appends the entry to the log, and waits until it has been written to disk.
+    pub(super) fn append(&mut self, entry: &T) -> Result<(), Error> {
+        let serialized_entry = rmp_serde::to_vec(entry)?;
+        let length = match u32::try_from(serialized_entry.len()) {
+            Ok(length) => length,
+            Err(_) => bail!("entry too large: {} bytes", serialized_entry.len()),
+        };
+        let mut bytes = Vec::with_capacity(LENGTH_PREFIX_SIZE + serialized_entry.len());
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.extend_from_slice(&serialized_entry);
+        self.file
+            .write_all(&bytes)
+            .and_then(|()| self.file.sync_data())
+            .with_context(|| format!("failed to write to {}", self.path.display()))
+    }
+
+    /// Returns the entry at the beginning of `bytes` and its size, or `None` if there is no
+    /// complete entry.  Returns an error if there is a complete entry which can't be deserialized.
+    fn read_entry(bytes: &[u8]) -> Result<Option<(T, usize)>, Error> {
+        if bytes.len() < LENGTH_PREFIX_SIZE {
+            return Ok(None);
+        }
+        let mut length_bytes = [0; LENGTH_PREFIX_SIZE];
+        length_bytes.copy_from_slice(&bytes[..LENGTH_PREFIX_SIZE]);
+        let entry_size = LENGTH_PREFIX_SIZE + u32::from_le_bytes(length_bytes) as usize;
+        let serialized_entry = match bytes.get(LENGTH_PREFIX_SIZE..entry_size) {
+            Some(serialized_entry) => serialized_entry,
+            None => return Ok(None),
+        };
+        let entry = rmp_serde::from_read_ref(serialized_entry)?;
+        Ok(Some((entry, entry_size)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn should_restore_appended_entries_and_drop_incomplete_entry() {
+        let tempdir = tempfile::tempdir().expect("should get tempdir");
+        let path = tempdir.path().join("highway.wal");
+
+        let entries = vec!["first".to_string(), "second".to_string()];
+        {
+            let (mut wal, restored) = WriteAheadLog::<String>::open(&path).unwrap();
+            assert!(restored.is_empty());
+            for entry in &entries {
+                wal.append(entry).unwrap();
+            }
+        }
+
+        // Simulate a crash while writing the next entry.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[100, 0, 0, 0, 1, 2]).unwrap();
+        drop(file);
+
+        let (mut wal, restored) = WriteAheadLog::<String>::open(&path).unwrap();
+        assert_eq!(entries, restored);
+
+        // Entries appended after the restart follow the restored ones.
+        wal.append(&"third".to_string()).unwrap();
+        drop(wal);
+        let (_, restored) = WriteAheadLog::<String>::open(&path).unwrap();
+        assert_eq!(vec!["first", "second", "third"], restored);
+    }
+
+    #[test]
+    fn should_fail_to_open_log_with_corrupt_entry_without_truncating_it() {
+        let tempdir = tempfile::tempdir().expect("should get tempdir");
+        let path = tempdir.path().join("highway.wal");
+
+        {
+            let (mut wal, _) = WriteAheadLog::<String>::open(&path).unwrap();
+            wal.append(&"first".to_string()).unwrap();
+        }
+
+        // A complete entry which can't be deserialized, followed by a valid one.
+        let serialized_entry = rmp_serde::to_vec(&"second".to_string()).unwrap();
+        let mut bytes = vec![1, 0, 0, 0, 0xc1];
+        bytes.extend_from_slice(&(serialized_entry.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&serialized_entry);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&bytes).unwrap();
+        drop(file);
+        let length = fs::metadata(&path).unwrap().len();
+
+        assert!(WriteAheadLog::<String>::open(&path).is_err());
+        assert_eq!(length, fs::metadata(&path).unwrap().len());
+    }
+}
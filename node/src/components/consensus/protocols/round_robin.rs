@@ -0,0 +1,958 @@
+//! A simple leader-based consensus protocol, as an alternative to Highway.
+//!
+//! Time is divided into rounds of a fixed length, and the validators take turns, in the order of
+//! their IDs, being the leader of a round. At the beginning of its round the leader proposes a
+//! block. The validators prevote for it once they have validated it, and precommit once they have
+//! seen prevotes by a quorum. A block is finalized as soon as a quorum precommitted it in the same
+//! round. As in Tendermint, a validator that precommitted is locked on that block, and only
+//! prevotes for a different one if a quorum prevoted for it in a later round.
+//!
+//! A quorum is a set of validators with a total weight greater than `(total weight + ftt) / 2`,
+//! so that any two quorums overlap by more than `ftt`. The protocol is safe as long as the faulty
+//! validators' total weight is at most `ftt`.
+//!
+//! Finalized blocks don't contain any rewards, and don't list equivocators: Unlike in Highway,
+//! different nodes may know about different equivocations when a block is finalized. Evidence
+//! against equivocators is still collected and announced.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{self, Debug, Formatter},
+    iter,
+};
+
+use anyhow::{bail, Error};
+use rand::{CryptoRng, Rng};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    components::consensus::{
+        consensus_protocol::{
+            BlockContext, ConsensusProtocol, ConsensusProtocolResult, FinalizedBlock,
+        },
+        highway_core::{
+            validators::{ValidatorIndex, Validators},
+            Weight,
+        },
+        traits::{Context, NodeIdT, ValidatorSecret},
+    },
+    types::{TimeDiff, Timestamp},
+};
+
+/// The maximum number of heights ahead of our own for which messages are kept until we catch up.
+const MAX_FUTURE_HEIGHTS: u64 = 10;
+
+type CpResult<I, C> =
+    ConsensusProtocolResult<I, <C as Context>::ConsensusValue, <C as Context>::ValidatorId>;
+
+/// The kind of a signed message. A validator must not sign two different messages of the same
+/// kind in the same height and round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum MessageKind {
+    Proposal,
+    Prevote,
+    Precommit,
+}
+
+/// The content of a signed message.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "C::Hash: Serialize",
+    deserialize = "C::Hash: Deserialize<'de>",
+))]
+enum Content<C: Context> {
+    /// A block proposed by the round's leader. If the leader re-proposes a candidate a quorum has
+    /// prevoted for in an earlier round, `valid_round` is that round.
+    Proposal {
+        height: u64,
+        round: u64,
+        value: C::ConsensusValue,
+        timestamp: Timestamp,
+        valid_round: Option<u64>,
+    },
+    /// A vote for the proposal with the given candidate hash.
+    Prevote {
+        height: u64,
+        round: u64,
+        candidate: C::Hash,
+    },
+    /// A vote to finalize the proposal with the given candidate hash.
+    Precommit {
+        height: u64,
+        round: u64,
+        candidate: C::Hash,
+    },
+}
+
+impl<C: Context> Content<C> {
+    fn height(&self) -> u64 {
+        match self {
+            Content::Proposal { height, .. }
+            | Content::Prevote { height, .. }
+            | Content::Precommit { height, .. } => *height,
+        }
+    }
+
+    fn round(&self) -> u64 {
+        match self {
+            Content::Proposal { round, .. }
+            | Content::Prevote { round, .. }
+            | Content::Precommit { round, .. } => *round,
+        }
+    }
+
+    fn kind(&self) -> MessageKind {
+        match self {
+            Content::Proposal { .. } => MessageKind::Proposal,
+            Content::Prevote { .. } => MessageKind::Prevote,
+            Content::Precommit { .. } => MessageKind::Precommit,
+        }
+    }
+
+    /// Returns the hash identifying the proposed candidate, or the one voted for.
+    fn candidate(&self) -> C::Hash {
+        match self {
+            Content::Proposal {
+                value, timestamp, ..
+            } => candidate_hash::<C>(value, *timestamp),
+            Content::Prevote { candidate, .. } | Content::Precommit { candidate, .. } => {
+                candidate.clone()
+            }
+        }
+    }
+}
+
+/// Returns the hash identifying a proposed value with the given timestamp.
+fn candidate_hash<C: Context>(value: &C::ConsensusValue, timestamp: Timestamp) -> C::Hash {
+    let serialized = rmp_serde::to_vec(&(value, timestamp)).expect("should serialize candidate");
+    C::hash(&serialized)
+}
+
+/// A message signed by a validator.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "C::Hash: Serialize",
+    deserialize = "C::Hash: Deserialize<'de>",
+))]
+struct SignedMessage<C: Context> {
+    content: Content<C>,
+    creator: ValidatorIndex,
+    signature: C::Signature,
+}
+
+impl<C: Context> SignedMessage<C> {
+    fn new<R: Rng + CryptoRng + ?Sized>(
+        content: Content<C>,
+        creator: ValidatorIndex,
+        instance_id: &C::InstanceId,
+        secret: &C::ValidatorSecret,
+        rng: &mut R,
+    ) -> Self {
+        let hash = Self::hash_to_sign(instance_id, creator, &content);
+        let signature = secret.sign(&hash, rng);
+        SignedMessage {
+            content,
+            creator,
+            signature,
+        }
+    }
+
+    /// Returns the hash that is signed by the creator. It includes the protocol instance ID, so
+    /// that messages cannot be replayed in a different era.
+    fn hash_to_sign(
+        instance_id: &C::InstanceId,
+        creator: ValidatorIndex,
+        content: &Content<C>,
+    ) -> C::Hash {
+        let serialized =
+            rmp_serde::to_vec(&(instance_id, creator, content)).expect("should serialize message");
+        C::hash(&serialized)
+    }
+
+    /// Returns an error if the creator is not a validator or the signature is invalid.
+    fn verify(
+        &self,
+        instance_id: &C::InstanceId,
+        validators: &Validators<C::ValidatorId>,
+    ) -> Result<(), Error> {
+        let creator_id = match validators.get_by_index(self.creator) {
+            Some(validator) => validator.id(),
+            None => bail!("message by unknown validator {:?}", self.creator),
+        };
+        let hash = Self::hash_to_sign(instance_id, self.creator, &self.content);
+        if !C::verify_signature(&hash, creator_id, &self.signature) {
+            bail!("invalid signature by {:?}", creator_id);
+        }
+        Ok(())
+    }
+}
+
+/// A proof that a block was finalized: its proposal together with precommits by a quorum.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "C::Hash: Serialize",
+    deserialize = "C::Hash: Deserialize<'de>",
+))]
+struct Certificate<C: Context> {
+    proposal: SignedMessage<C>,
+    precommits: Vec<SignedMessage<C>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "C::Hash: Serialize",
+    deserialize = "C::Hash: Deserialize<'de>",
+))]
+enum RoundRobinMessage<C: Context> {
+    Signed(SignedMessage<C>),
+    /// A request for the certificate of the block at the given height, sent by a node that fell
+    /// behind.
+    RequestCertificate(u64),
+    Certificate(Certificate<C>),
+}
+
+impl<C: Context> RoundRobinMessage<C> {
+    fn serialize(&self) -> Vec<u8> {
+        rmp_serde::to_vec(self).expect("should serialize message")
+    }
+}
+
+/// Two conflicting messages signed by the same validator.
+type Equivocation<C> = (SignedMessage<C>, SignedMessage<C>);
+
+/// The messages and votes of the height that is currently being decided.
+#[derive(Debug)]
+struct HeightState<C: Context> {
+    /// All messages received in this height, by round, kind and creator.
+    messages: BTreeMap<(u64, MessageKind, ValidatorIndex), SignedMessage<C>>,
+    /// The first proposal received for each candidate.
+    proposals: HashMap<C::Hash, SignedMessage<C>>,
+    /// The values that were successfully validated.
+    valid_values: HashSet<C::ConsensusValue>,
+    /// The values whose validation was requested.
+    requested_values: HashSet<C::ConsensusValue>,
+    /// The round and candidate this validator precommitted most recently.
+    locked: Option<(u64, C::Hash)>,
+    /// The latest round in which a quorum prevoted for a valid proposal, and its candidate.
+    valid: Option<(u64, C::Hash)>,
+}
+
+impl<C: Context> Default for HeightState<C> {
+    fn default() -> Self {
+        HeightState {
+            messages: BTreeMap::new(),
+            proposals: HashMap::new(),
+            valid_values: HashSet::new(),
+            requested_values: HashSet::new(),
+            locked: None,
+            valid: None,
+        }
+    }
+}
+
+impl<C: Context> HeightState<C> {
+    /// Returns the messages of the given kind in the given round.
+    fn messages_in(
+        &self,
+        round: u64,
+        kind: MessageKind,
+    ) -> impl Iterator<Item = &SignedMessage<C>> {
+        self.messages
+            .range((round, kind, ValidatorIndex(0))..=(round, kind, ValidatorIndex(u32::MAX)))
+            .map(|(_, msg)| msg)
+    }
+
+    fn has_message(&self, round: u64, kind: MessageKind, creator: ValidatorIndex) -> bool {
+        self.messages.contains_key(&(round, kind, creator))
+    }
+
+    /// Returns the candidate's proposal, if we have it and its value is valid.
+    fn valid_proposal(&self, candidate: &C::Hash) -> Option<&SignedMessage<C>> {
+        let proposal = self.proposals.get(candidate)?;
+        match &proposal.content {
+            Content::Proposal { value, .. } if self.valid_values.contains(value) => Some(proposal),
+            _ => None,
+        }
+    }
+}
+
+/// A validator's own index and secret key.
+struct ActiveValidator<C: Context> {
+    idx: ValidatorIndex,
+    secret: C::ValidatorSecret,
+}
+
+impl<C: Context> Debug for ActiveValidator<C> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("ActiveValidator")
+            .field("idx", &self.idx)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RoundRobinProtocol<I, C: Context> {
+    instance_id: C::InstanceId,
+    validators: Validators<C::ValidatorId>,
+    /// The minimum total weight of a quorum.
+    quorum: u128,
+    round_len: TimeDiff,
+    start_timestamp: Timestamp,
+    /// The minimum number of blocks in this instance.
+    end_height: u64,
+    /// The minimum timestamp of the last block in this instance.
+    end_timestamp: Timestamp,
+    /// Our own index and secret key, if we are an active validator.
+    active_validator: Option<ActiveValidator<C>>,
+    /// The latest round whose timer has fired, if we are an active validator.
+    round: Option<u64>,
+    /// The height and round of the block we requested from the deploy buffer, if any.
+    pending_proposal: Option<(u64, u64)>,
+    /// The height of the next block to be finalized.
+    height: u64,
+    /// The timestamp of the latest finalized block.
+    last_timestamp: Option<Timestamp>,
+    /// Whether the terminal block has been finalized.
+    finished: bool,
+    state: HeightState<C>,
+    /// Messages for heights we haven't reached yet, with their senders.
+    future_messages: BTreeMap<u64, Vec<(I, SignedMessage<C>)>>,
+    /// The certificates of all finalized blocks, by height.
+    certificates: Vec<Certificate<C>>,
+    /// Evidence against equivocators, by validator index.
+    evidence: BTreeMap<ValidatorIndex, Equivocation<C>>,
+}
+
+impl<I: NodeIdT, C: Context> RoundRobinProtocol<I, C> {
+    /// Creates a new protocol instance.
+    ///
+    /// Round `r` begins at `start_timestamp + r * round_len`. The instance ends with the first
+    /// block that has at least height `end_height - 1` and timestamp `end_timestamp`.
+    pub(crate) fn new(
+        instance_id: C::InstanceId,
+        validators: Validators<C::ValidatorId>,
+        ftt: Weight,
+        round_len: TimeDiff,
+        start_timestamp: Timestamp,
+        end_height: u64,
+        end_timestamp: Timestamp,
+    ) -> Self {
+        assert!(round_len.millis() > 0, "round length must be positive");
+        let total_weight = u128::from(validators.total_weight());
+        let quorum = (total_weight + u128::from(ftt)) / 2 + 1;
+        RoundRobinProtocol {
+            instance_id,
+            validators,
+            quorum,
+            round_len,
+            start_timestamp,
+            end_height,
+            end_timestamp,
+            active_validator: None,
+            round: None,
+            pending_proposal: None,
+            height: 0,
+            last_timestamp: None,
+            finished: false,
+            state: HeightState::default(),
+            future_messages: BTreeMap::new(),
+            certificates: Vec::new(),
+            evidence: BTreeMap::new(),
+        }
+    }
+
+    /// Starts participating as the given validator. Returns the timer for the first round.
+    pub(crate) fn activate_validator(
+        &mut self,
+        our_id: C::ValidatorId,
+        secret: C::ValidatorSecret,
+        timestamp: Timestamp,
+    ) -> Vec<CpResult<I, C>> {
+        let idx = match self.validators.get_index(&our_id) {
+            Some(idx) => idx,
+            None => {
+                warn!(?our_id, "not activating: not a validator in this instance");
+                return vec![];
+            }
+        };
+        self.active_validator = Some(ActiveValidator { idx, secret });
+        vec![ConsensusProtocolResult::ScheduleTimer(timestamp)]
+    }
+
+    fn round_start(&self, round: u64) -> Timestamp {
+        self.start_timestamp + self.round_len * round
+    }
+
+    fn round_at(&self, timestamp: Timestamp) -> u64 {
+        timestamp.saturating_sub(self.start_timestamp).millis() / self.round_len.millis()
+    }
+
+    fn leader(&self, round: u64) -> ValidatorIndex {
+        let validator_count = self.validators.iter().count() as u64;
+        ValidatorIndex((round % validator_count) as u32)
+    }
+
+    fn weight(&self, idx: ValidatorIndex) -> u128 {
+        self.validators
+            .get_by_index(idx)
+            .map_or(0, |validator| u128::from(validator.weight()))
+    }
+
+    /// Returns the candidates that a quorum sent a message of the given kind for, by round.
+    fn quorum_candidates(&self, kind: MessageKind) -> Vec<(u64, C::Hash)> {
+        let mut weights: BTreeMap<(u64, C::Hash), u128> = BTreeMap::new();
+        for ((round, msg_kind, creator), msg) in &self.state.messages {
+            if *msg_kind == kind {
+                *weights
+                    .entry((*round, msg.content.candidate()))
+                    .or_default() += self.weight(*creator);
+            }
+        }
+        weights
+            .into_iter()
+            .filter(|(_, weight)| *weight >= self.quorum)
+            .map(|(round_and_candidate, _)| round_and_candidate)
+            .collect()
+    }
+
+    /// Returns whether a quorum prevoted for the candidate in the given round.
+    fn has_prevote_quorum(&self, round: u64, candidate: &C::Hash) -> bool {
+        let weight: u128 = self
+            .state
+            .messages_in(round, MessageKind::Prevote)
+            .filter(|msg| msg.content.candidate() == *candidate)
+            .map(|msg| self.weight(msg.creator))
+            .sum();
+        weight >= self.quorum
+    }
+
+    /// Signs a message, adds it to the state and gossips it.
+    fn create_message<R: Rng + CryptoRng + ?Sized>(
+        &mut self,
+        content: Content<C>,
+        rng: &mut R,
+    ) -> Vec<CpResult<I, C>> {
+        let msg = match self.active_validator.as_ref() {
+            None => return vec![],
+            Some(av) => SignedMessage::new(content, av.idx, &self.instance_id, &av.secret, rng),
+        };
+        let serialized_msg = RoundRobinMessage::Signed(msg.clone()).serialize();
+        self.store_message(msg);
+        let mut results = vec![ConsensusProtocolResult::CreatedGossipMessage(
+            serialized_msg,
+        )];
+        results.extend(self.update(rng));
+        results
+    }
+
+    /// Adds a message to the current height's state.
+    fn store_message(&mut self, msg: SignedMessage<C>) {
+        let key = (msg.content.round(), msg.content.kind(), msg.creator);
+        if let Content::Proposal { .. } = msg.content {
+            let _ = self
+                .state
+                .proposals
+                .entry(msg.content.candidate())
+                .or_insert_with(|| msg.clone());
+        }
+        let _ = self.state.messages.insert(key, msg);
+    }
+
+    /// Handles a message with a valid signature. Returns an error if its content is invalid.
+    fn add_signed(
+        &mut self,
+        sender: I,
+        msg: SignedMessage<C>,
+    ) -> Result<Vec<CpResult<I, C>>, Error> {
+        let height = msg.content.height();
+        if height < self.height {
+            // If the sender is still voting after the block was finalized, it must have missed
+            // the precommits.
+            let opt_cert = self.certificates.get(height as usize).filter(|cert| {
+                cert.precommits.first().map_or(false, |precommit| {
+                    precommit.content.round() < msg.content.round()
+                })
+            });
+            return Ok(opt_cert
+                .map(|cert| {
+                    let msg = RoundRobinMessage::Certificate(cert.clone());
+                    ConsensusProtocolResult::CreatedTargetedMessage(msg.serialize(), sender)
+                })
+                .into_iter()
+                .collect());
+        }
+        if height > self.height {
+            let mut results = Vec::new();
+            // A leader who proposes in a later height has finalized ours already.
+            if msg.content.kind() == MessageKind::Proposal {
+                let request = RoundRobinMessage::<C>::RequestCertificate(self.height);
+                results.push(ConsensusProtocolResult::CreatedTargetedMessage(
+                    request.serialize(),
+                    sender.clone(),
+                ));
+            }
+            if height <= self.height + MAX_FUTURE_HEIGHTS {
+                self.future_messages
+                    .entry(height)
+                    .or_default()
+                    .push((sender, msg));
+            }
+            return Ok(results);
+        }
+
+        let key = (msg.content.round(), msg.content.kind(), msg.creator);
+        if let Some(existing) = self.state.messages.get(&key) {
+            if existing.content == msg.content || self.evidence.contains_key(&msg.creator) {
+                return Ok(vec![]);
+            }
+            let perpetrator = match self.validators.get_by_index(msg.creator) {
+                Some(validator) => validator.id().clone(),
+                None => return Ok(vec![]),
+            };
+            info!(?perpetrator, "detected an equivocation");
+            let _ = self.evidence.insert(msg.creator, (existing.clone(), msg));
+            return Ok(vec![ConsensusProtocolResult::NewEvidence(perpetrator)]);
+        }
+
+        let mut results = Vec::new();
+        if let Content::Proposal {
+            round,
+            value,
+            timestamp,
+            valid_round,
+            ..
+        } = &msg.content
+        {
+            if msg.creator != self.leader(*round) {
+                bail!("proposal by {:?} who is not the leader", msg.creator);
+            }
+            match valid_round {
+                None => {
+                    if *timestamp != self.round_start(*round) {
+                        bail!("proposal timestamp doesn't match the round");
+                    }
+                    if self.last_timestamp.map_or(false, |last| *timestamp <= last) {
+                        bail!("proposal timestamp is not after the previous block");
+                    }
+                }
+                Some(valid_round) if valid_round >= round => {
+                    bail!("re-proposal from a later round");
+                }
+                Some(_) => (),
+            }
+            if !self.state.valid_values.contains(value)
+                && self.state.requested_values.insert(value.clone())
+            {
+                results.push(ConsensusProtocolResult::ValidateConsensusValue(
+                    sender,
+                    value.clone(),
+                ));
+            }
+        }
+        self.store_message(msg);
+        Ok(results)
+    }
+
+    /// Returns an error if the certificate doesn't prove that its proposal was finalized.
+    fn verify_certificate(&self, cert: &Certificate<C>) -> Result<(), Error> {
+        cert.proposal.verify(&self.instance_id, &self.validators)?;
+        let (height, candidate) = match &cert.proposal.content {
+            Content::Proposal { height, .. } => (*height, cert.proposal.content.candidate()),
+            _ => bail!("certificate doesn't contain a proposal"),
+        };
+        let round = match cert.precommits.first() {
+            Some(precommit) => precommit.content.round(),
+            None => bail!("certificate without precommits"),
+        };
+        let mut creators = HashSet::new();
+        for precommit in &cert.precommits {
+            precommit.verify(&self.instance_id, &self.validators)?;
+            match &precommit.content {
+                Content::Precommit {
+                    height: precommit_height,
+                    round: precommit_round,
+                    candidate: precommit_candidate,
+                } if *precommit_height == height
+                    && *precommit_round == round
+                    && *precommit_candidate == candidate => {}
+                _ => bail!("precommit doesn't match the certificate's proposal"),
+            }
+            if !creators.insert(precommit.creator) {
+                bail!("duplicate precommit by {:?}", precommit.creator);
+            }
+        }
+        let weight: u128 = creators.into_iter().map(|idx| self.weight(idx)).sum();
+        if weight < self.quorum {
+            bail!("certificate precommits don't have a quorum");
+        }
+        Ok(())
+    }
+
+    /// Sends our votes, finalizes blocks and proposes a valid candidate, as far as the current
+    /// state allows.
+    fn update<R: Rng + CryptoRng + ?Sized>(&mut self, rng: &mut R) -> Vec<CpResult<I, C>> {
+        if self.finished {
+            return vec![];
+        }
+        if let Some((round, candidate)) = self
+            .quorum_candidates(MessageKind::Precommit)
+            .into_iter()
+            .find(|(_, candidate)| self.state.valid_proposal(candidate).is_some())
+        {
+            return self.finalize(round, candidate, rng);
+        }
+        if let Some((round, candidate)) = self
+            .quorum_candidates(MessageKind::Prevote)
+            .into_iter()
+            .filter(|(_, candidate)| self.state.valid_proposal(candidate).is_some())
+            .last()
+        {
+            if self.state.valid.as_ref().map_or(true, |(r, _)| *r < round) {
+                self.state.valid = Some((round, candidate));
+            }
+        }
+        let (our_idx, round) = match (self.active_validator.as_ref(), self.round) {
+            (Some(av), Some(round)) => (av.idx, round),
+            _ => return vec![],
+        };
+        if !self.state.has_message(round, MessageKind::Prevote, our_idx) {
+            if let Some(candidate) = self.prevote_candidate(round) {
+                let content = Content::Prevote {
+                    height: self.height,
+                    round,
+                    candidate,
+                };
+                return self.create_message(content, rng);
+            }
+        }
+        if !self
+            .state
+            .has_message(round, MessageKind::Precommit, our_idx)
+        {
+            let opt_candidate = self
+                .quorum_candidates(MessageKind::Prevote)
+                .into_iter()
+                .find(|(r, candidate)| {
+                    *r == round && self.state.valid_proposal(candidate).is_some()
+                })
+                .map(|(_, candidate)| candidate);
+            if let Some(candidate) = opt_candidate {
+                self.state.locked = Some((round, candidate.clone()));
+                let content = Content::Precommit {
+                    height: self.height,
+                    round,
+                    candidate,
+                };
+                return self.create_message(content, rng);
+            }
+        }
+        vec![]
+    }
+
+    /// Returns the candidate of the current round's proposal if we should prevote for it.
+    fn prevote_candidate(&self, round: u64) -> Option<C::Hash> {
+        let proposal =
+            self.state
+                .messages
+                .get(&(round, MessageKind::Proposal, self.leader(round)))?;
+        let candidate = proposal.content.candidate();
+        self.state.valid_proposal(&candidate)?;
+        let unlocked = match (&proposal.content, &self.state.locked) {
+            (_, None) => true,
+            (_, Some((_, locked_candidate))) if *locked_candidate == candidate => true,
+            (
+                Content::Proposal {
+                    valid_round: Some(valid_round),
+                    ..
+                },
+                Some((locked_round, _)),
+            ) => {
+                // We may unlock if a quorum prevoted for the candidate after we locked.
+                *locked_round <= *valid_round
+            }
+            _ => false,
+        };
+        let has_pol = match &proposal.content {
+            Content::Proposal {
+                valid_round: Some(valid_round),
+                ..
+            } => self.has_prevote_quorum(*valid_round, &candidate),
+            _ => true,
+        };
+        if unlocked && has_pol {
+            Some(candidate)
+        } else {
+            None
+        }
+    }
+
+    /// Finalizes the candidate a quorum precommitted in the given round, and moves on to the next
+    /// height.
+    fn finalize<R: Rng + CryptoRng + ?Sized>(
+        &mut self,
+        round: u64,
+        candidate: C::Hash,
+        rng: &mut R,
+    ) -> Vec<CpResult<I, C>> {
+        let proposal = self.state.proposals[&candidate].clone();
+        let precommits = self
+            .state
+            .messages_in(round, MessageKind::Precommit)
+            .filter(|msg| msg.content.candidate() == candidate)
+            .cloned()
+            .collect();
+        let (value, timestamp) = match &proposal.content {
+            Content::Proposal {
+                value, timestamp, ..
+            } => (value.clone(), *timestamp),
+            _ => unreachable!("proposals map contains only proposals"),
+        };
+        let proposer = match self.validators.get_by_index(proposal.creator) {
+            Some(validator) => validator.id().clone(),
+            None => unreachable!("proposal by unknown validator was accepted"),
+        };
+        let height = self.height;
+        let terminal = height + 1 >= self.end_height && timestamp >= self.end_timestamp;
+        self.certificates.push(Certificate {
+            proposal,
+            precommits,
+        });
+        self.height += 1;
+        self.last_timestamp = Some(timestamp);
+        self.finished = terminal;
+        self.pending_proposal = None;
+        self.state = HeightState::default();
+
+        let mut results = vec![ConsensusProtocolResult::FinalizedBlock(FinalizedBlock {
+            value,
+            new_equivocators: vec![],
+            equivocators: vec![],
+            rewards: BTreeMap::new(),
+            timestamp,
+            height,
+            terminal,
+            proposer,
+        })];
+        if terminal {
+            return results;
+        }
+
+        // Replay the messages we received for the new height.
+        for (sender, msg) in self
+            .future_messages
+            .remove(&self.height)
+            .unwrap_or_default()
+        {
+            match self.add_signed(sender, msg) {
+                Ok(replay_results) => results.extend(replay_results),
+                Err(error) => info!(%error, "invalid message for the new height"),
+            }
+        }
+        // If we are still behind, request the next certificate.
+        if let Some((sender, _)) = self
+            .future_messages
+            .values()
+            .next()
+            .and_then(|msgs| msgs.first())
+        {
+            let request = RoundRobinMessage::<C>::RequestCertificate(self.height);
+            results.push(ConsensusProtocolResult::CreatedTargetedMessage(
+                request.serialize(),
+                sender.clone(),
+            ));
+        }
+        results.extend(self.update(rng));
+        results
+    }
+}
+
+impl<I, C, R> ConsensusProtocol<I, C::ConsensusValue, C::ValidatorId, R>
+    for RoundRobinProtocol<I, C>
+where
+    I: NodeIdT,
+    C: Context,
+    R: Rng + CryptoRng + ?Sized,
+{
+    fn handle_message(
+        &mut self,
+        sender: I,
+        msg: Vec<u8>,
+        rng: &mut R,
+    ) -> Result<Vec<CpResult<I, C>>, Error> {
+        let invalid = |msg: Vec<u8>, sender: I, error: Error| {
+            Ok(vec![ConsensusProtocolResult::InvalidIncomingMessage(
+                msg, sender, error,
+            )])
+        };
+        match rmp_serde::from_read_ref(msg.as_slice()) {
+            Err(err) => invalid(msg, sender, err.into()),
+            Ok(RoundRobinMessage::Signed(signed)) => {
+                if let Err(error) = signed.verify(&self.instance_id, &self.validators) {
+                    return invalid(msg, sender, error);
+                }
+                match self.add_signed(sender.clone(), signed) {
+                    Ok(mut results) => {
+                        results.extend(self.update(rng));
+                        Ok(results)
+                    }
+                    Err(error) => invalid(msg, sender, error),
+                }
+            }
+            Ok(RoundRobinMessage::RequestCertificate(height)) => {
+                match self.certificates.get(height as usize) {
+                    Some(cert) => {
+                        let msg = RoundRobinMessage::Certificate(cert.clone());
+                        Ok(vec![ConsensusProtocolResult::CreatedTargetedMessage(
+                            msg.serialize(),
+                            sender,
+                        )])
+                    }
+                    None => {
+                        info!(%height, ?sender, "requested certificate doesn't exist");
+                        Ok(vec![])
+                    }
+                }
+            }
+            Ok(RoundRobinMessage::Certificate(cert)) => {
+                if cert.proposal.content.height() != self.height {
+                    return Ok(vec![]);
+                }
+                if let Err(error) = self.verify_certificate(&cert) {
+                    return invalid(msg, sender, error);
+                }
+                let mut results = Vec::new();
+                for signed in iter_certificate(cert) {
+                    match self.add_signed(sender.clone(), signed) {
+                        Ok(add_results) => results.extend(add_results),
+                        Err(error) => return invalid(msg, sender, error),
+                    }
+                }
+                results.extend(self.update(rng));
+                Ok(results)
+            }
+        }
+    }
+
+    fn handle_timer(
+        &mut self,
+        timestamp: Timestamp,
+        rng: &mut R,
+    ) -> Result<Vec<CpResult<I, C>>, Error> {
+        let our_idx = match self.active_validator.as_ref() {
+            Some(av) if !self.finished => av.idx,
+            _ => return Ok(vec![]),
+        };
+        let round = self.round_at(timestamp);
+        if self.round.map_or(false, |r| r >= round) {
+            return Ok(vec![]); // We already started this round.
+        }
+        self.round = Some(round);
+        let mut results = vec![ConsensusProtocolResult::ScheduleTimer(
+            self.round_start(round + 1),
+        )];
+        if self.leader(round) == our_idx {
+            match self.state.valid.clone() {
+                Some((valid_round, candidate)) => {
+                    // Re-propose the candidate a quorum prevoted for.
+                    let content = match &self.state.proposals[&candidate].content {
+                        Content::Proposal {
+                            value, timestamp, ..
+                        } => Content::Proposal {
+                            height: self.height,
+                            round,
+                            value: value.clone(),
+                            timestamp: *timestamp,
+                            valid_round: Some(valid_round),
+                        },
+                        _ => unreachable!("proposals map contains only proposals"),
+                    };
+                    results.extend(self.create_message(content, rng));
+                }
+                None => {
+                    self.pending_proposal = Some((self.height, round));
+                    let block_context = BlockContext::new(self.round_start(round), self.height);
+                    results.push(ConsensusProtocolResult::CreateNewBlock { block_context });
+                }
+            }
+        }
+        results.extend(self.update(rng));
+        Ok(results)
+    }
+
+    fn propose(
+        &mut self,
+        value: C::ConsensusValue,
+        block_context: BlockContext,
+        rng: &mut R,
+    ) -> Result<Vec<CpResult<I, C>>, Error> {
+        let height = block_context.height();
+        let round = self.round_at(block_context.timestamp());
+        if self.pending_proposal != Some((height, round)) || self.round != Some(round) {
+            info!(?block_context, "dropping outdated proposal");
+            return Ok(vec![]);
+        }
+        self.pending_proposal = None;
+        let _ = self.state.valid_values.insert(value.clone());
+        let content = Content::Proposal {
+            height,
+            round,
+            value,
+            timestamp: block_context.timestamp(),
+            valid_round: None,
+        };
+        Ok(self.create_message(content, rng))
+    }
+
+    fn resolve_validity(
+        &mut self,
+        value: &C::ConsensusValue,
+        valid: bool,
+        rng: &mut R,
+    ) -> Result<Vec<CpResult<I, C>>, Error> {
+        if !valid || !self.state.requested_values.contains(value) {
+            // TODO: Disconnect from the proposer?
+            return Ok(vec![]);
+        }
+        let _ = self.state.valid_values.insert(value.clone());
+        Ok(self.update(rng))
+    }
+
+    fn deactivate_validator(&mut self) {
+        self.active_validator = None;
+    }
+
+    fn evidence(&self, vid: &C::ValidatorId) -> Option<Vec<u8>> {
+        let idx = self.validators.get_index(vid)?;
+        let equivocation = self.evidence.get(&idx)?;
+        Some(rmp_serde::to_vec(equivocation).expect("should serialize evidence"))
+    }
+
+    fn validate_evidence(&self, vid: &C::ValidatorId, evidence: &[u8]) -> Result<(), Error> {
+        let (msg0, msg1): Equivocation<C> = rmp_serde::from_read_ref(evidence)?;
+        if Some(msg0.creator) != self.validators.get_index(vid) || msg1.creator != msg0.creator {
+            bail!("evidence against a different validator");
+        }
+        if msg0.content.height() != msg1.content.height()
+            || msg0.content.round() != msg1.content.round()
+            || msg0.content.kind() != msg1.content.kind()
+            || msg0.content == msg1.content
+        {
+            bail!("messages don't conflict");
+        }
+        msg0.verify(&self.instance_id, &self.validators)?;
+        msg1.verify(&self.instance_id, &self.validators)
+    }
+}
+
+/// Returns the certificate's proposal followed by its precommits.
+fn iter_certificate<C: Context>(cert: Certificate<C>) -> impl Iterator<Item = SignedMessage<C>> {
+    let Certificate {
+        proposal,
+        precommits,
+    } = cert;
+    iter::once(proposal).chain(precommits)
+}
@@ -0,0 +1,362 @@
+use std::{collections::VecDeque, fmt::Debug, iter};
+
+use rand::Rng;
+use tracing::trace;
+
+use super::*;
+use crate::{
+    components::consensus::{
+        highway_core::highway_testing::{TestContext, TestSecret},
+        tests::{
+            consensus_des_testing::{
+                Fault, Message, Node, Target, TargetedMessage, ValidatorId, VirtualNet,
+            },
+            queue::QueueEntry,
+        },
+    },
+    logging::{self, LoggingConfig, LoggingFormat},
+    testing::TestRng,
+};
+
+type ConsensusValue = Vec<u32>;
+
+type TestProtocol = RoundRobinProtocol<ValidatorId, TestContext>;
+
+const INSTANCE_ID: u64 = 0;
+const ROUND_LEN: u64 = 1000;
+/// The maximum network delay. Three hops must fit into a round, so that every proposal by an
+/// honest leader is finalized in the same round.
+const MAX_DELAY: u64 = ROUND_LEN / 5;
+const END_HEIGHT: u64 = 100_000;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum TestMessage {
+    Timer(Timestamp),
+    RequestBlock(BlockContext),
+    Consensus(Vec<u8>),
+}
+
+struct TestValidator {
+    protocol: TestProtocol,
+    fault: Option<Fault>,
+}
+
+type TestNode = Node<ConsensusValue, TestMessage, TestValidator>;
+
+struct TestHarness {
+    virtual_net: VirtualNet<ConsensusValue, TestMessage, TestValidator>,
+    /// Consensus values to be proposed, in order.
+    consensus_values: VecDeque<ConsensusValue>,
+    /// A validator that doesn't receive any consensus messages until the given time.
+    partitioned: Option<(ValidatorId, Timestamp)>,
+}
+
+impl TestHarness {
+    /// Creates a network of validators with similar weights. The first `mute_count` of them
+    /// don't send any messages.
+    fn new(rng: &mut TestRng, validator_count: u64, mute_count: u64) -> Self {
+        let validators: Validators<ValidatorId> = (0..validator_count)
+            .map(|i| (ValidatorId(i), rng.gen_range(100, 110)))
+            .collect();
+        let ftt = Weight((validators.total_weight().0 - 1) / 3);
+
+        let mut nodes = Vec::new();
+        let mut init_messages = Vec::new();
+        for vid in (0..validator_count).map(ValidatorId) {
+            let mut protocol = TestProtocol::new(
+                INSTANCE_ID,
+                validators.clone(),
+                ftt,
+                TimeDiff::from(ROUND_LEN),
+                Timestamp::zero(),
+                END_HEIGHT,
+                Timestamp::zero(),
+            );
+            for result in protocol.activate_validator(vid, TestSecret(vid.0), Timestamp::zero()) {
+                match result {
+                    ConsensusProtocolResult::ScheduleTimer(timestamp) => {
+                        let message = Message::new(vid, TestMessage::Timer(timestamp));
+                        init_messages.push(QueueEntry::new(timestamp, vid, message));
+                    }
+                    result => panic!("unexpected activation result: {:?}", result),
+                }
+            }
+            let fault = if vid.0 < mute_count {
+                Some(Fault::Mute)
+            } else {
+                None
+            };
+            nodes.push(Node::new(vid, TestValidator { protocol, fault }));
+        }
+
+        TestHarness {
+            virtual_net: VirtualNet::new(nodes, init_messages),
+            consensus_values: (0..).map(|i| vec![i]).take(1000).collect(),
+            partitioned: None,
+        }
+    }
+
+    fn node_mut(&mut self, vid: &ValidatorId) -> &mut TestNode {
+        self.virtual_net
+            .node_mut(vid)
+            .expect("validator should exist")
+    }
+
+    /// Delivers the next message in the queue, and schedules the messages produced in response.
+    fn crank(&mut self, rng: &mut TestRng) {
+        let QueueEntry {
+            delivery_time,
+            recipient,
+            message,
+        } = self
+            .virtual_net
+            .pop_message()
+            .expect("message queue should not be empty");
+        trace!(
+            "tick {}: {} received {:?} from {}",
+            delivery_time,
+            recipient,
+            message.payload(),
+            message.sender
+        );
+
+        let sender = message.sender;
+        let results = match message.payload().clone() {
+            TestMessage::Timer(timestamp) => self
+                .node_mut(&recipient)
+                .validator_mut()
+                .protocol
+                .handle_timer(timestamp, rng),
+            TestMessage::RequestBlock(block_context) => {
+                let value = self.consensus_values.pop_front().unwrap_or_default();
+                self.node_mut(&recipient).validator_mut().protocol.propose(
+                    value,
+                    block_context,
+                    rng,
+                )
+            }
+            TestMessage::Consensus(_)
+                if self.partitioned.map_or(false, |(vid, until)| {
+                    vid == recipient && delivery_time < until
+                }) =>
+            {
+                trace!("{} is partitioned, dropping message", recipient);
+                Ok(vec![])
+            }
+            TestMessage::Consensus(bytes) => self
+                .node_mut(&recipient)
+                .validator_mut()
+                .protocol
+                .handle_message(sender, bytes, rng),
+        }
+        .expect("protocol should not fail");
+
+        let messages = self.process_results(rng, recipient, delivery_time, results);
+        self.virtual_net.dispatch_messages(messages);
+    }
+
+    /// Handles the protocol results, and returns the messages to be dispatched.
+    fn process_results(
+        &mut self,
+        rng: &mut TestRng,
+        vid: ValidatorId,
+        now: Timestamp,
+        results: Vec<CpResult<ValidatorId, TestContext>>,
+    ) -> Vec<(TargetedMessage<TestMessage>, Timestamp)> {
+        let mut results: VecDeque<_> = results.into();
+        let mut messages = Vec::new();
+        let is_mute = matches!(self.node_mut(&vid).validator().fault, Some(Fault::Mute));
+        while let Some(result) = results.pop_front() {
+            let delivery_time = now + TimeDiff::from(rng.gen_range(1, MAX_DELAY));
+            let node = self.node_mut(&vid);
+            match result {
+                ConsensusProtocolResult::CreatedGossipMessage(_)
+                | ConsensusProtocolResult::CreatedTargetedMessage(_, _)
+                    if is_mute => {}
+                ConsensusProtocolResult::CreatedGossipMessage(bytes) => {
+                    let message = Message::new(vid, TestMessage::Consensus(bytes));
+                    let targeted = TargetedMessage::new(message, Target::AllExcept(vid));
+                    messages.push((targeted, delivery_time));
+                }
+                ConsensusProtocolResult::CreatedTargetedMessage(bytes, target) => {
+                    let message = Message::new(vid, TestMessage::Consensus(bytes));
+                    let targeted = TargetedMessage::new(message, Target::SingleValidator(target));
+                    messages.push((targeted, delivery_time));
+                }
+                ConsensusProtocolResult::InvalidIncomingMessage(_, sender, error) => {
+                    panic!("{} sent an invalid message to {}: {:?}", sender, vid, error);
+                }
+                ConsensusProtocolResult::ScheduleTimer(timestamp) => {
+                    let message = Message::new(vid, TestMessage::Timer(timestamp));
+                    let targeted = TargetedMessage::new(message, Target::SingleValidator(vid));
+                    messages.push((targeted, timestamp));
+                }
+                ConsensusProtocolResult::CreateNewBlock { block_context } => {
+                    let message = Message::new(vid, TestMessage::RequestBlock(block_context));
+                    let targeted = TargetedMessage::new(message, Target::SingleValidator(vid));
+                    messages.push((targeted, now));
+                }
+                ConsensusProtocolResult::FinalizedBlock(finalized_block) => {
+                    trace!(
+                        "{} finalized {:?} at height {}",
+                        vid,
+                        finalized_block.value,
+                        finalized_block.height
+                    );
+                    node.push_finalized(finalized_block.value);
+                }
+                ConsensusProtocolResult::ValidateConsensusValue(_, value) => {
+                    let protocol = &mut node.validator_mut().protocol;
+                    let new_results = protocol
+                        .resolve_validity(&value, true, rng)
+                        .expect("validation should not fail");
+                    results.extend(new_results);
+                }
+                ConsensusProtocolResult::NewEvidence(equivocator) => {
+                    node.new_equivocators(iter::once(equivocator));
+                }
+                ConsensusProtocolResult::Fatal(msg) => panic!("{} failed: {}", vid, msg),
+            }
+        }
+        messages
+    }
+
+    /// Delivers messages until every node has finalized at least `count` values.
+    fn crank_until_finalized(&mut self, rng: &mut TestRng, count: usize) {
+        while !self
+            .virtual_net
+            .validators()
+            .all(|node| node.finalized_count() >= count)
+        {
+            self.crank(rng);
+        }
+    }
+
+    /// Returns the first `count` values finalized by each node.
+    fn finalized_values(&self, count: usize) -> Vec<Vec<ConsensusValue>> {
+        self.virtual_net
+            .validators()
+            .map(|node| node.finalized_values().take(count).cloned().collect())
+            .collect()
+    }
+}
+
+/// Returns the protocol as a trait object, to call the methods that don't take an RNG.
+fn as_consensus_protocol(
+    protocol: &TestProtocol,
+) -> &dyn ConsensusProtocol<ValidatorId, ConsensusValue, ValidatorId, TestRng> {
+    protocol
+}
+
+// Test that all elements of the vector all equal.
+fn assert_eq_vectors<I: Eq + Debug>(coll: Vec<I>, error_msg: &str) {
+    let mut iter = coll.into_iter();
+    let reference = iter.next().unwrap();
+
+    iter.for_each(|v| assert_eq!(v, reference, "{}", error_msg));
+}
+
+#[test]
+fn liveness_test_no_faults() {
+    let _ = logging::init_with_config(&LoggingConfig::new(LoggingFormat::Text, true));
+
+    let mut rng = TestRng::new();
+    let cv_count = 10;
+    let validator_count = rng.gen_range(2, 8);
+    let mut test_harness = TestHarness::new(&mut rng, validator_count, 0);
+
+    test_harness.crank_until_finalized(&mut rng, cv_count);
+
+    // Without faults, every leader's proposal is finalized in its own round.
+    let expected: Vec<ConsensusValue> = (0..cv_count as u32).map(|i| vec![i]).collect();
+    for finalized in test_harness.finalized_values(cv_count) {
+        assert_eq!(expected, finalized, "Nodes finalized unexpected values.");
+    }
+}
+
+#[test]
+fn liveness_test_some_mute() {
+    let _ = logging::init_with_config(&LoggingConfig::new(LoggingFormat::Text, true));
+
+    let mut rng = TestRng::new();
+    let cv_count = 10;
+    let mut test_harness = TestHarness::new(&mut rng, 5, 1);
+
+    test_harness.crank_until_finalized(&mut rng, cv_count);
+
+    assert_eq_vectors(
+        test_harness.finalized_values(cv_count),
+        "Nodes finalized different consensus values.",
+    );
+}
+
+#[test]
+fn should_catch_up_after_partition() {
+    let _ = logging::init_with_config(&LoggingConfig::new(LoggingFormat::Text, true));
+
+    let mut rng = TestRng::new();
+    let cv_count = 20;
+    let mut test_harness = TestHarness::new(&mut rng, 4, 0);
+    let partition_end = Timestamp::zero() + TimeDiff::from(ROUND_LEN) * 12;
+    test_harness.partitioned = Some((ValidatorId(3), partition_end));
+
+    test_harness.crank_until_finalized(&mut rng, cv_count);
+
+    assert_eq_vectors(
+        test_harness.finalized_values(cv_count),
+        "Nodes finalized different consensus values.",
+    );
+}
+
+#[test]
+fn should_detect_equivocation_and_validate_evidence() {
+    let mut rng = TestRng::new();
+    let validators: Validators<ValidatorId> = (0..3).map(|i| (ValidatorId(i), 100)).collect();
+    let mut protocol = TestProtocol::new(
+        INSTANCE_ID,
+        validators,
+        Weight(99),
+        TimeDiff::from(ROUND_LEN),
+        Timestamp::zero(),
+        END_HEIGHT,
+        Timestamp::zero(),
+    );
+
+    let prevote = |value: ConsensusValue, rng: &mut TestRng| {
+        let content = Content::<TestContext>::Prevote {
+            height: 0,
+            round: 0,
+            candidate: candidate_hash::<TestContext>(&value, Timestamp::zero()),
+        };
+        let msg = SignedMessage::new(
+            content,
+            ValidatorIndex(0),
+            &INSTANCE_ID,
+            &TestSecret(0),
+            rng,
+        );
+        RoundRobinMessage::Signed(msg).serialize()
+    };
+    let sender = ValidatorId(0);
+
+    let results = protocol
+        .handle_message(sender, prevote(vec![1], &mut rng), &mut rng)
+        .unwrap();
+    assert!(results.is_empty());
+    assert!(as_consensus_protocol(&protocol).evidence(&sender).is_none());
+
+    let results = protocol
+        .handle_message(sender, prevote(vec![2], &mut rng), &mut rng)
+        .unwrap();
+    match results.as_slice() {
+        [ConsensusProtocolResult::NewEvidence(vid)] => assert_eq!(sender, *vid),
+        _ => panic!("unexpected results: {:?}", results),
+    }
+
+    let protocol = as_consensus_protocol(&protocol);
+    let evidence = protocol.evidence(&sender).expect("should have evidence");
+    protocol.validate_evidence(&sender, &evidence).unwrap();
+    assert!(protocol
+        .validate_evidence(&ValidatorId(1), &evidence)
+        .is_err());
+}
@@ -10,28 +10,37 @@ use std::{
 };
 
 use derive_more::From;
+use futures::TryFutureExt;
 use lmdb::DatabaseFlags;
 use prometheus::{self, Histogram, HistogramOpts, Registry};
 use rand::{CryptoRng, Rng};
 use thiserror::Error;
 use tokio::task;
-use tracing::trace;
+use tracing::{debug, error, info, trace};
 
 use casper_execution_engine::{
-    core::engine_state::{genesis::GenesisResult, EngineConfig, EngineState, Error},
-    shared::newtypes::CorrelationId,
+    core::engine_state::{
+        deploy_item::DeployItem, execute_request::ExecuteRequest, genesis::GenesisResult,
+        EngineConfig, EngineState, Error,
+    },
+    shared::newtypes::{Blake2bHash, CorrelationId},
     storage::{
         error::lmdb::Error as StorageLmdbError, global_state::lmdb::LmdbGlobalState,
         protocol_data_store::lmdb::LmdbProtocolDataStore,
         transaction_source::lmdb::LmdbEnvironment, trie_store::lmdb::LmdbTrieStore,
     },
 };
-use casper_types::ProtocolVersion;
 
 use crate::{
-    components::Component,
+    components::{chainspec_loader::to_protocol_version, Component},
     crypto::hash,
-    effect::{requests::ContractRuntimeRequest, EffectBuilder, EffectExt, Effects},
+    effect::{
+        requests::{ContractRuntimeRequest, NetworkRequest},
+        EffectBuilder, EffectExt, Effects,
+    },
+    protocol::Message,
+    small_network::NodeId,
+    types::Trie,
     Chainspec, StorageConfig,
 };
 
@@ -39,6 +48,7 @@ use crate::{
 pub(crate) struct ContractRuntime {
     engine_state: Arc<EngineState<LmdbGlobalState>>,
     metrics: Arc<ContractRuntimeMetrics>,
+    prune_global_state: bool,
 }
 
 impl Debug for ContractRuntime {
@@ -53,12 +63,17 @@ pub enum Event {
     /// A request made of the contract runtime component.
     #[from]
     Request(ContractRuntimeRequest),
+    /// We received a `GetRequest` message for a global state trie from a peer.
+    GetTrieForPeer { trie_key: Blake2bHash, peer: NodeId },
 }
 
 impl Display for Event {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Event::Request(request) => write!(f, "{}", request),
+            Event::GetTrieForPeer { trie_key, peer } => {
+                write!(f, "get trie {} for {}", trie_key, peer)
+            }
         }
     }
 }
@@ -66,11 +81,14 @@ impl Display for Event {
 /// Metrics for the contract runtime component.
 #[derive(Debug)]
 pub struct ContractRuntimeMetrics {
-    run_execute: Histogram,
-    apply_effect: Histogram,
+    execute_block: Histogram,
     commit_upgrade: Histogram,
+    commit_step: Histogram,
     run_query: Histogram,
     get_balance: Histogram,
+    get_era_validators: Histogram,
+    speculative_execute: Histogram,
+    prune_global_state: Histogram,
 }
 
 /// Value of upper bound of histogram.
@@ -80,16 +98,23 @@ const EXPONENTIAL_BUCKET_FACTOR: f64 = 2.0;
 /// Bucket count, with last going to +Inf.
 const EXPONENTIAL_BUCKET_COUNT: usize = 6;
 
-const RUN_EXECUTE_NAME: &str = "contract_runtime_run_execute";
-const RUN_EXECUTE_HELP: &str = "tracking run of engine_state.run_execute.";
-const APPLY_EFFECT_NAME: &str = "contract_runtime_apply_commit";
-const APPLY_EFFECT_HELP: &str = "tracking run of engine_state.apply_effect.";
+const EXECUTE_BLOCK_NAME: &str = "contract_runtime_execute_block";
+const EXECUTE_BLOCK_HELP: &str = "tracking run of engine_state.execute_block.";
 const RUN_QUERY_NAME: &str = "contract_runtime_run_query";
 const RUN_QUERY_HELP: &str = "tracking run of engine_state.run_query.";
 const COMMIT_UPGRADE_NAME: &str = "contract_runtime_commit_upgrade";
 const COMMIT_UPGRADE_HELP: &str = "tracking run of engine_state.commit_upgrade";
+const COMMIT_STEP_NAME: &str = "contract_runtime_commit_step";
+const COMMIT_STEP_HELP: &str = "tracking run of engine_state.commit_step.";
 const GET_BALANCE_NAME: &str = "contract_runtime_get_balance";
 const GET_BALANCE_HELP: &str = "tracking run of engine_state.get_balance.";
+const GET_ERA_VALIDATORS_NAME: &str = "contract_runtime_get_era_validators";
+const GET_ERA_VALIDATORS_HELP: &str = "tracking run of engine_state.get_era_validators.";
+const SPECULATIVE_EXECUTE_NAME: &str = "contract_runtime_speculative_execute";
+const SPECULATIVE_EXECUTE_HELP: &str =
+    "tracking run of engine_state.run_execute for speculative execution.";
+const PRUNE_GLOBAL_STATE_NAME: &str = "contract_runtime_prune_global_state";
+const PRUNE_GLOBAL_STATE_HELP: &str = "tracking run of engine_state.prune_global_state.";
 
 /// Create prometheus Histogram and register.
 fn register_histogram_metric(
@@ -112,11 +137,10 @@ impl ContractRuntimeMetrics {
     /// Constructor of metrics which creates and registers metrics objects for use.
     fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
         Ok(ContractRuntimeMetrics {
-            run_execute: register_histogram_metric(registry, RUN_EXECUTE_NAME, RUN_EXECUTE_HELP)?,
-            apply_effect: register_histogram_metric(
+            execute_block: register_histogram_metric(
                 registry,
-                APPLY_EFFECT_NAME,
-                APPLY_EFFECT_HELP,
+                EXECUTE_BLOCK_NAME,
+                EXECUTE_BLOCK_HELP,
             )?,
             run_query: register_histogram_metric(registry, RUN_QUERY_NAME, RUN_QUERY_HELP)?,
             commit_upgrade: register_histogram_metric(
@@ -124,21 +148,37 @@ impl ContractRuntimeMetrics {
                 COMMIT_UPGRADE_NAME,
                 COMMIT_UPGRADE_HELP,
             )?,
+            commit_step: register_histogram_metric(registry, COMMIT_STEP_NAME, COMMIT_STEP_HELP)?,
             get_balance: register_histogram_metric(registry, GET_BALANCE_NAME, GET_BALANCE_HELP)?,
+            get_era_validators: register_histogram_metric(
+                registry,
+                GET_ERA_VALIDATORS_NAME,
+                GET_ERA_VALIDATORS_HELP,
+            )?,
+            speculative_execute: register_histogram_metric(
+                registry,
+                SPECULATIVE_EXECUTE_NAME,
+                SPECULATIVE_EXECUTE_HELP,
+            )?,
+            prune_global_state: register_histogram_metric(
+                registry,
+                PRUNE_GLOBAL_STATE_NAME,
+                PRUNE_GLOBAL_STATE_HELP,
+            )?,
         })
     }
 }
 
 impl<REv, R> Component<REv, R> for ContractRuntime
 where
-    REv: From<Event> + Send,
+    REv: From<Event> + From<NetworkRequest<NodeId, Message>> + Send,
     R: Rng + CryptoRng + ?Sized,
 {
     type Event = Event;
 
     fn handle_event(
         &mut self,
-        _effect_builder: EffectBuilder<REv>,
+        effect_builder: EffectBuilder<REv>,
         _rng: &mut R,
         event: Self::Event,
     ) -> Effects<Self::Event> {
@@ -150,76 +190,99 @@ where
                 let result = self.commit_genesis(chainspec);
                 responder.respond(result).ignore()
             }
-            Event::Request(ContractRuntimeRequest::Execute {
+            Event::Request(ContractRuntimeRequest::ExecuteBlock {
                 execute_request,
                 responder,
             }) => {
-                trace!(?execute_request, "execute");
+                trace!(?execute_request, "execute block");
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
                 async move {
                     let correlation_id = CorrelationId::new();
                     let result = task::spawn_blocking(move || {
                         let start = Instant::now();
-                        let execution_result =
-                            engine_state.run_execute(correlation_id, execute_request);
-                        metrics.run_execute.observe(start.elapsed().as_secs_f64());
-                        execution_result
+                        let result = engine_state.execute_block(correlation_id, execute_request);
+                        metrics.execute_block.observe(start.elapsed().as_secs_f64());
+                        result
                     })
                     .await
                     .expect("should run");
-                    trace!(?result, "execute result");
+                    trace!(?result, "execute block result");
                     responder.respond(result).await
                 }
                 .ignore()
             }
-            Event::Request(ContractRuntimeRequest::Commit {
-                pre_state_hash,
-                effects,
+            Event::Request(ContractRuntimeRequest::Upgrade {
+                upgrade_config,
                 responder,
             }) => {
-                trace!(?pre_state_hash, ?effects, "commit");
+                trace!(?upgrade_config, "upgrade");
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
                 async move {
                     let correlation_id = CorrelationId::new();
                     let result = task::spawn_blocking(move || {
                         let start = Instant::now();
-                        let apply_result = engine_state.apply_effect(
-                            correlation_id,
-                            pre_state_hash.into(),
-                            effects,
-                        );
-                        metrics.apply_effect.observe(start.elapsed().as_secs_f64());
-                        apply_result
+                        let result = engine_state.commit_upgrade(correlation_id, upgrade_config);
+                        metrics
+                            .commit_upgrade
+                            .observe(start.elapsed().as_secs_f64());
+                        result
                     })
                     .await
                     .expect("should run");
-                    trace!(?result, "commit result");
+                    trace!(?result, "upgrade result");
                     responder.respond(result).await
                 }
                 .ignore()
             }
-            Event::Request(ContractRuntimeRequest::Upgrade {
-                upgrade_config,
+            Event::Request(ContractRuntimeRequest::Step {
+                step_request,
                 responder,
             }) => {
-                trace!(?upgrade_config, "upgrade");
+                trace!(?step_request, "step");
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
                 async move {
                     let correlation_id = CorrelationId::new();
                     let result = task::spawn_blocking(move || {
                         let start = Instant::now();
-                        let result = engine_state.commit_upgrade(correlation_id, upgrade_config);
+                        let result = engine_state.commit_step(correlation_id, step_request);
+                        metrics.commit_step.observe(start.elapsed().as_secs_f64());
+                        result
+                    })
+                    .await
+                    .expect("should run");
+                    trace!(?result, "step result");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            Event::Request(ContractRuntimeRequest::GetEraValidators {
+                global_state_hash,
+                protocol_version,
+                responder,
+            }) => {
+                trace!(%global_state_hash, "get era validators");
+                let engine_state = Arc::clone(&self.engine_state);
+                let metrics = Arc::clone(&self.metrics);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = task::spawn_blocking(move || {
+                        let start = Instant::now();
+                        let result = engine_state.get_era_validators(
+                            correlation_id,
+                            global_state_hash.into(),
+                            protocol_version,
+                        );
                         metrics
-                            .commit_upgrade
+                            .get_era_validators
                             .observe(start.elapsed().as_secs_f64());
                         result
                     })
                     .await
                     .expect("should run");
-                    trace!(?result, "upgrade result");
+                    trace!(?result, "get era validators result");
                     responder.respond(result).await
                 }
                 .ignore()
@@ -272,6 +335,157 @@ where
                 }
                 .ignore()
             }
+            Event::Request(ContractRuntimeRequest::SpeculativeExecute {
+                global_state_hash,
+                block_time,
+                protocol_version,
+                deploy,
+                responder,
+            }) => {
+                trace!(deploy_hash = %deploy.id(), %global_state_hash, "speculative execute");
+                let engine_state = Arc::clone(&self.engine_state);
+                let metrics = Arc::clone(&self.metrics);
+                let execute_request = ExecuteRequest::new(
+                    global_state_hash.into(),
+                    block_time.millis(),
+                    vec![Ok(DeployItem::from(*deploy))],
+                    protocol_version,
+                );
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = task::spawn_blocking(move || {
+                        let start = Instant::now();
+                        // The effects are never committed, leaving the global state untouched.
+                        let result = engine_state
+                            .run_execute(correlation_id, execute_request)
+                            .map(|mut execution_results| {
+                                execution_results
+                                    .pop_front()
+                                    .expect("should have one result per deploy")
+                            });
+                        metrics
+                            .speculative_execute
+                            .observe(start.elapsed().as_secs_f64());
+                        result
+                    })
+                    .await
+                    .expect("should run");
+                    trace!(?result, "speculative execute result");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            Event::Request(ContractRuntimeRequest::PruneGlobalState {
+                retained_global_state_hashes,
+                responder,
+            }) => {
+                if !self.prune_global_state {
+                    return responder.respond(Ok(0)).ignore();
+                }
+                trace!(
+                    retained_count = retained_global_state_hashes.len(),
+                    "prune global state"
+                );
+                let engine_state = Arc::clone(&self.engine_state);
+                let metrics = Arc::clone(&self.metrics);
+                let retained_state_hashes: Vec<_> = retained_global_state_hashes
+                    .into_iter()
+                    .map(Into::into)
+                    .collect();
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = task::spawn_blocking(move || {
+                        let start = Instant::now();
+                        let result =
+                            engine_state.prune_global_state(correlation_id, &retained_state_hashes);
+                        metrics
+                            .prune_global_state
+                            .observe(start.elapsed().as_secs_f64());
+                        result
+                    })
+                    .await
+                    .expect("should run");
+                    match &result {
+                        Ok(removed_count) => info!(%removed_count, "pruned global state"),
+                        Err(error) => error!(%error, "failed to prune global state"),
+                    }
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            Event::Request(ContractRuntimeRequest::GetTrie {
+                trie_key,
+                responder,
+            }) => {
+                trace!(%trie_key, "get trie");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = task::spawn_blocking(move || {
+                        engine_state.get_trie(correlation_id, &trie_key)
+                    })
+                    .await
+                    .expect("should run")
+                    .map(|maybe_bytes| maybe_bytes.map(Trie::new));
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            Event::Request(ContractRuntimeRequest::PutTrie { trie, responder }) => {
+                trace!(%trie, "put trie");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = task::spawn_blocking(move || {
+                        engine_state.put_trie(correlation_id, trie.as_bytes())
+                    })
+                    .await
+                    .expect("should run");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            Event::Request(ContractRuntimeRequest::FindMissingTries {
+                global_state_hash,
+                responder,
+            }) => {
+                trace!(%global_state_hash, "find missing tries");
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    let result = task::spawn_blocking(move || {
+                        engine_state
+                            .missing_trie_descendants(correlation_id, &global_state_hash.into())
+                    })
+                    .await
+                    .expect("should run");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            Event::GetTrieForPeer { trie_key, peer } => {
+                let engine_state = Arc::clone(&self.engine_state);
+                async move {
+                    let correlation_id = CorrelationId::new();
+                    task::spawn_blocking(move || engine_state.get_trie(correlation_id, &trie_key))
+                        .await
+                        .expect("should run")
+                }
+                .map_err(move |error| {
+                    debug!("failed to get trie {} for {}: {}", trie_key, peer, error)
+                })
+                .and_then(move |maybe_bytes| async move {
+                    match maybe_bytes {
+                        Some(bytes) => match Message::new_get_response(&Trie::new(bytes)) {
+                            Ok(message) => effect_builder.send_message(peer, message).await,
+                            Err(error) => error!("failed to create get-response: {}", error),
+                        },
+                        None => debug!("failed to get trie {} for {}", trie_key, peer),
+                    }
+                    Ok(())
+                })
+                .ignore()
+            }
         }
     }
 }
@@ -311,9 +525,18 @@ impl ContractRuntime {
             DatabaseFlags::empty(),
         )?);
 
-        let global_state = LmdbGlobalState::empty(environment, trie_store, protocol_data_store)?;
+        let mut global_state =
+            LmdbGlobalState::empty(environment, trie_store, protocol_data_store)?;
+        if contract_runtime_config.prune_global_state() {
+            // Pruning runs concurrently with execution, so needs to know which roots are in use.
+            global_state = global_state.with_used_roots_tracking();
+        }
         let engine_config = EngineConfig::new()
-            .with_use_system_contracts(contract_runtime_config.use_system_contracts());
+            .with_use_system_contracts(contract_runtime_config.use_system_contracts())
+            .with_keep_intermediate_state_roots(
+                contract_runtime_config.keep_intermediate_state_roots(),
+            )
+            .with_parallel_execution(contract_runtime_config.parallel_execution());
 
         let engine_state = Arc::new(EngineState::new(global_state, engine_config));
 
@@ -321,19 +544,21 @@ impl ContractRuntime {
         Ok(ContractRuntime {
             engine_state,
             metrics,
+            prune_global_state: contract_runtime_config.prune_global_state(),
         })
     }
 
+    /// Returns the engine state, for use outside of a reactor.
+    pub(crate) fn engine_state(&self) -> &EngineState<LmdbGlobalState> {
+        &self.engine_state
+    }
+
     /// Commits a genesis using a chainspec
-    fn commit_genesis(&self, chainspec: Box<Chainspec>) -> Result<GenesisResult, Error> {
+    pub(crate) fn commit_genesis(&self, chainspec: Box<Chainspec>) -> Result<GenesisResult, Error> {
         let correlation_id = CorrelationId::new();
         let serialized_chainspec = rmp_serde::to_vec(&chainspec)?;
         let genesis_config_hash = hash::hash(&serialized_chainspec);
-        let protocol_version = ProtocolVersion::from_parts(
-            chainspec.genesis.protocol_version.major as u32,
-            chainspec.genesis.protocol_version.minor as u32,
-            chainspec.genesis.protocol_version.patch as u32,
-        );
+        let protocol_version = to_protocol_version(&chainspec.genesis.protocol_version);
         // Transforms a chainspec into a valid genesis config for execution engine.
         let ee_config = (*chainspec).into();
         self.engine_state.commit_genesis(
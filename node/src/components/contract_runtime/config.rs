@@ -4,6 +4,9 @@ use casper_execution_engine::shared::utils;
 
 const DEFAULT_MAX_GLOBAL_STATE_SIZE: usize = 805_306_368_000; // 750 GiB
 const DEFAULT_USE_SYSTEM_CONTRACTS: bool = false;
+const DEFAULT_PRUNE_GLOBAL_STATE: bool = false;
+const DEFAULT_KEEP_INTERMEDIATE_STATE_ROOTS: bool = false;
+const DEFAULT_PARALLEL_EXECUTION: bool = false;
 
 /// Contract runtime configuration.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
@@ -18,6 +21,19 @@ pub struct Config {
     ///
     /// The size should be a multiple of the OS page size.
     max_global_state_size: Option<usize>,
+    /// Whether to remove the parts of global state which are unreachable from the global state
+    /// hashes of the retained blocks whenever blocks are pruned.  Global states used since the
+    /// previous pruning started, e.g. by blocks still being executed, are retained too.  Only has
+    /// an effect if the storage component is configured to retain a limited number of eras.
+    /// Defaults to false.
+    prune_global_state: Option<bool>,
+    /// Whether to commit the effects of each deploy of a block separately, keeping the global
+    /// state hash after each deploy, rather than committing the effects of the whole block once.
+    /// Defaults to false.
+    keep_intermediate_state_roots: Option<bool>,
+    /// Whether to execute the deploys of a block in parallel, re-executing in block order the ones
+    /// conflicting with earlier deploys.  Defaults to false.
+    parallel_execution: Option<bool>,
 }
 
 impl Config {
@@ -33,6 +49,21 @@ impl Config {
         utils::check_multiple_of_page_size(value);
         value
     }
+
+    pub(crate) fn prune_global_state(&self) -> bool {
+        self.prune_global_state
+            .unwrap_or(DEFAULT_PRUNE_GLOBAL_STATE)
+    }
+
+    pub(crate) fn keep_intermediate_state_roots(&self) -> bool {
+        self.keep_intermediate_state_roots
+            .unwrap_or(DEFAULT_KEEP_INTERMEDIATE_STATE_ROOTS)
+    }
+
+    pub(crate) fn parallel_execution(&self) -> bool {
+        self.parallel_execution
+            .unwrap_or(DEFAULT_PARALLEL_EXECUTION)
+    }
 }
 
 impl Default for Config {
@@ -40,6 +71,9 @@ impl Default for Config {
         Config {
             use_system_contracts: Some(DEFAULT_USE_SYSTEM_CONTRACTS),
             max_global_state_size: Some(DEFAULT_MAX_GLOBAL_STATE_SIZE),
+            prune_global_state: Some(DEFAULT_PRUNE_GLOBAL_STATE),
+            keep_intermediate_state_roots: Some(DEFAULT_KEEP_INTERMEDIATE_STATE_ROOTS),
+            parallel_execution: Some(DEFAULT_PARALLEL_EXECUTION),
         }
     }
 }
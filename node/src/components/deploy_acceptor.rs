@@ -8,13 +8,18 @@ use semver::Version;
 use tracing::{debug, error, warn};
 
 use crate::{
-    components::{chainspec_loader::Chainspec, storage::Storage, Component},
+    components::{
+        chainspec_loader::{Chainspec, DeployConfig},
+        consensus::EraId,
+        storage::Storage,
+        Component,
+    },
     effect::{
         announcements::DeployAcceptorAnnouncement, requests::StorageRequest, EffectBuilder,
         EffectExt, Effects,
     },
     small_network::NodeId,
-    types::{Deploy, Timestamp},
+    types::{BlockHeader, Deploy, Timestamp},
     utils::Source,
 };
 
@@ -39,12 +44,23 @@ impl<REv> ReactorEventT for REv where
 ///
 /// It validates a new `Deploy` as far as possible, stores it if valid, then announces the newly-
 /// accepted `Deploy`.
-#[derive(Debug, Default)]
-pub(crate) struct DeployAcceptor {}
+#[derive(Debug)]
+pub(crate) struct DeployAcceptor {
+    /// The version under which the chainspec is stored.
+    chainspec_version: Version,
+    /// The era of the next block to be added to the linear chain, whose deploy config is used for
+    /// validation.
+    current_era: EraId,
+}
 
 impl DeployAcceptor {
-    pub(crate) fn new() -> Self {
-        Self::default()
+    /// Creates a new deploy acceptor validating against the deploy config of the given era, which
+    /// should be the era of the block following the highest one held in storage.
+    pub(crate) fn new(chainspec_version: Version, current_era: EraId) -> Self {
+        DeployAcceptor {
+            chainspec_version,
+            current_era,
+        }
     }
 
     /// Handles receiving a new `Deploy` from a peer or client.
@@ -54,8 +70,7 @@ impl DeployAcceptor {
         deploy: Box<Deploy>,
         source: Source<NodeId>,
     ) -> Effects<Event> {
-        // TODO - where to get version from?
-        let chainspec_version = Version::new(1, 0, 0);
+        let chainspec_version = self.chainspec_version.clone();
         effect_builder
             .get_chainspec(chainspec_version.clone())
             .event(move |maybe_chainspec| Event::GetChainspecResult {
@@ -73,7 +88,8 @@ impl DeployAcceptor {
         source: Source<NodeId>,
         chainspec: Chainspec,
     ) -> Effects<Event> {
-        if is_valid(&*deploy, chainspec) {
+        let deploy_config = chainspec.deploy_config(self.current_era);
+        if is_valid(&*deploy, &chainspec.genesis.name, deploy_config) {
             let cloned_deploy = deploy.clone();
             effect_builder
                 .put_deploy_to_storage(cloned_deploy)
@@ -99,6 +115,12 @@ impl DeployAcceptor {
         Effects::new()
     }
 
+    /// Updates the current era after a block has been added to the linear chain.
+    fn handle_block_added(&mut self, block_header: &BlockHeader) -> Effects<Event> {
+        self.current_era = self.current_era.max(block_header.next_block_era_id());
+        Effects::new()
+    }
+
     fn handle_put_to_storage<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -141,38 +163,37 @@ impl<REv: ReactorEventT, R: Rng + CryptoRng + ?Sized> Component<REv, R> for Depl
                 source,
                 is_new,
             } => self.handle_put_to_storage(effect_builder, deploy, source, is_new),
+            Event::BlockAdded(block_header) => self.handle_block_added(&block_header),
         }
     }
 }
 
-fn is_valid(deploy: &Deploy, chainspec: Chainspec) -> bool {
-    if deploy.header().chain_name() != chainspec.genesis.name {
+fn is_valid(deploy: &Deploy, chain_name: &str, deploy_config: DeployConfig) -> bool {
+    if deploy.header().chain_name() != chain_name {
         warn!(
             deploy_hash = %deploy.id(),
             deploy_header = %deploy.header(),
-            chain_name = %chainspec.genesis.name,
+            %chain_name,
             "invalid chain identifier"
         );
         return false;
     }
 
-    if deploy.header().dependencies().len()
-        > chainspec.genesis.deploy_config.max_dependencies as usize
-    {
+    if deploy.header().dependencies().len() > deploy_config.max_dependencies as usize {
         warn!(
             deploy_hash = %deploy.id(),
             deploy_header = %deploy.header(),
-            max_dependencies = %chainspec.genesis.deploy_config.max_dependencies,
+            max_dependencies = %deploy_config.max_dependencies,
             "deploy dependency ceiling exceeded"
         );
         return false;
     }
 
-    if deploy.header().ttl() > chainspec.genesis.deploy_config.max_ttl {
+    if deploy.header().ttl() > deploy_config.max_ttl {
         warn!(
             deploy_hash = %deploy.id(),
             deploy_header = %deploy.header(),
-            max_ttl = %chainspec.genesis.deploy_config.max_ttl,
+            max_ttl = %deploy_config.max_ttl,
             "deploy ttl excessive"
         );
         return false;
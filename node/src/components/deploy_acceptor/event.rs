@@ -3,7 +3,11 @@ use std::fmt::{self, Display, Formatter};
 use semver::Version;
 
 use super::Source;
-use crate::{components::chainspec_loader::Chainspec, small_network::NodeId, types::Deploy};
+use crate::{
+    components::chainspec_loader::Chainspec,
+    small_network::NodeId,
+    types::{BlockHeader, Deploy},
+};
 
 /// `DeployAcceptor` events.
 #[derive(Debug)]
@@ -26,6 +30,8 @@ pub enum Event {
         source: Source<NodeId>,
         is_new: bool,
     },
+    /// A block has been added to the linear chain.
+    BlockAdded(Box<BlockHeader>),
 }
 
 impl Display for Event {
@@ -56,6 +62,9 @@ impl Display for Event {
                     write!(formatter, "had already stored {}", deploy.id())
                 }
             }
+            Event::BlockAdded(block_header) => {
+                write!(formatter, "block added at height {}", block_header.height())
+            }
         }
     }
 }
@@ -2,27 +2,47 @@
 //!
 //! The deploy buffer stores deploy hashes in memory, tracking their suitability for inclusion into
 //! a new block. Upon request, it returns a list of candidates that can be included.
+//!
+//! The candidates are chosen so that a block stays within the chainspec's gas and size limits.
+//! Deploys offering a higher gas price are preferred, but the deploys of any one account are
+//! always proposed in the order of their timestamps.
+//!
+//! Every `PRUNE_INTERVAL` the buffer drops the deploys which have expired, and forgets finalized
+//! blocks once their finalization lies further back than the chainspec's maximum TTL, as none of
+//! their deploys could be proposed again by then.
 
 use std::{
-    collections::{HashMap, HashSet},
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
     fmt::{self, Display, Formatter},
+    time::Duration,
 };
 
 use derive_more::From;
+use prometheus::{IntGauge, Registry};
 use rand::{CryptoRng, Rng};
 use semver::Version;
-use tracing::{error, info};
+use tracing::{debug, error, info, warn};
+
+use casper_types::U512;
 
 use crate::{
-    components::{chainspec_loader::DeployConfig, storage::Storage, Component},
+    components::{chainspec_loader::DeployConfig, consensus::EraId, storage::Storage, Component},
+    crypto::asymmetric_key::PublicKey,
     effect::{
         requests::{DeployBufferRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects, Responder,
     },
-    types::{DeployHash, DeployHeader, ProtoBlock, ProtoBlockHash, Timestamp},
+    types::{
+        Deploy, DeployHash, DeployHeader, DeployStatus, ProtoBlock, ProtoBlockHash, TimeDiff,
+        Timestamp,
+    },
     Chainspec,
 };
 
+/// The interval between two housekeeping passes over the buffer.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+
 /// An event for when using the deploy buffer as a component.
 #[derive(Debug, From)]
 pub enum Event {
@@ -31,7 +51,7 @@ pub enum Event {
     /// A new deploy should be buffered.
     Buffer {
         hash: DeployHash,
-        header: Box<DeployHeader>,
+        info: Box<DeployInfo>,
     },
     /// A proto block has been proposed. We should not propose duplicates of its deploys.
     ProposedProtoBlock(ProtoBlock),
@@ -39,9 +59,12 @@ pub enum Event {
     FinalizedProtoBlock(ProtoBlock),
     /// A proto block has been orphaned. Its deploys should be re-proposed.
     OrphanedProtoBlock(ProtoBlock),
+    /// Expired deploys and old finalized blocks should be dropped.
+    Prune,
     /// The result of the `DeployBuffer` getting the chainspec from the storage component.
     GetChainspecResult {
         maybe_chainspec: Box<Option<Chainspec>>,
+        era_id: EraId,
         current_instant: Timestamp,
         past_blocks: HashSet<ProtoBlockHash>,
         responder: Responder<HashSet<DeployHash>>,
@@ -62,6 +85,7 @@ impl Display for Event {
             Event::OrphanedProtoBlock(block) => {
                 write!(f, "deploy-buffer orphaned proto block {}", block)
             }
+            Event::Prune => write!(f, "deploy-buffer prune"),
             Event::GetChainspecResult {
                 maybe_chainspec, ..
             } => {
@@ -75,47 +99,221 @@ impl Display for Event {
     }
 }
 
+/// The information about a deploy needed to decide whether it can be included in a block.
+#[derive(Debug, Clone)]
+pub struct DeployInfo {
+    header: DeployHeader,
+    /// The size of the encoded deploy in bytes.
+    size: u64,
+    /// The maximum amount of gas the deploy can consume, or `None` if it isn't known.
+    gas_limit: Option<u64>,
+}
+
+impl DeployInfo {
+    /// Creates the buffer's information about the given deploy.
+    ///
+    /// The gas limit is derived from the payment amount and the gas price.  It is unknown for
+    /// deploys whose payment code doesn't take an amount argument.
+    pub(crate) fn new(deploy: &Deploy) -> Self {
+        let size = match rmp_serde::to_vec(deploy) {
+            Ok(bytes) => bytes.len() as u64,
+            Err(error) => {
+                warn!(deploy_hash = %deploy.id(), %error, "failed to encode deploy");
+                u64::MAX
+            }
+        };
+        let gas_price = deploy.header().gas_price().max(1);
+        let gas_limit = deploy.payment_amount().map(|amount| {
+            let gas = amount / gas_price;
+            if gas > U512::from(u64::MAX) {
+                u64::MAX
+            } else {
+                gas.as_u64()
+            }
+        });
+        DeployInfo {
+            header: deploy.header().clone(),
+            size,
+            gas_limit,
+        }
+    }
+
+    /// Returns the gas this deploy counts against the block's limit.  Deploys with an unknown gas
+    /// limit are assumed to use the configured estimate.
+    fn gas_limit(&self, custom_payment_gas_estimate: u64) -> u64 {
+        self.gas_limit.unwrap_or(custom_payment_gas_estimate)
+    }
+}
+
+/// A deploy held by the buffer which is neither finalized nor expired.
+#[derive(Debug, Clone)]
+pub struct PendingDeploy {
+    /// The hash of the deploy.
+    pub(crate) hash: DeployHash,
+    /// The header of the deploy.
+    pub(crate) header: DeployHeader,
+    /// Either `DeployStatus::Pending` or `DeployStatus::Proposed`.
+    pub(crate) status: DeployStatus,
+}
+
+/// A candidate deploy for inclusion in a block, ordered by gas price and then by age.
+#[derive(Debug)]
+struct Candidate<'a> {
+    hash: &'a DeployHash,
+    info: &'a DeployInfo,
+}
+
+impl<'a> PartialEq for Candidate<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+    }
+}
+
+impl<'a> Eq for Candidate<'a> {}
+
+impl<'a> Ord for Candidate<'a> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |candidate: &Self| {
+            (
+                candidate.info.header.gas_price(),
+                Reverse(candidate.info.header.timestamp()),
+                Reverse(*candidate.hash),
+            )
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+impl<'a> PartialOrd for Candidate<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Metrics for the deploy buffer.
+#[derive(Debug, Clone)]
+struct DeployBufferMetrics {
+    /// Number of deploys not yet included in a proposed block.
+    collected_deploys: IntGauge,
+    /// Number of deploys in proposed blocks which are neither finalized nor orphaned yet.
+    processed_deploys: IntGauge,
+    /// Number of deploys in finalized blocks still remembered by the buffer.
+    finalized_deploys: IntGauge,
+}
+
+impl DeployBufferMetrics {
+    /// Creates and registers the deploy buffer metrics.
+    fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let collected_deploys = IntGauge::new(
+            "deploy_buffer_collected_deploys",
+            "number of deploys not yet included in a proposed block",
+        )?;
+        let processed_deploys = IntGauge::new(
+            "deploy_buffer_processed_deploys",
+            "number of deploys in proposed blocks which are not finalized yet",
+        )?;
+        let finalized_deploys = IntGauge::new(
+            "deploy_buffer_finalized_deploys",
+            "number of deploys in finalized blocks tracked by the deploy buffer",
+        )?;
+        registry.register(Box::new(collected_deploys.clone()))?;
+        registry.register(Box::new(processed_deploys.clone()))?;
+        registry.register(Box::new(finalized_deploys.clone()))?;
+
+        Ok(DeployBufferMetrics {
+            collected_deploys,
+            processed_deploys,
+            finalized_deploys,
+        })
+    }
+}
+
 /// Deploy buffer.
 #[derive(Debug, Clone)]
 pub(crate) struct DeployBuffer {
     block_max_deploy_count: usize,
-    collected_deploys: HashMap<DeployHash, DeployHeader>,
-    processed: HashMap<ProtoBlockHash, HashMap<DeployHash, DeployHeader>>,
-    finalized: HashMap<ProtoBlockHash, HashMap<DeployHash, DeployHeader>>,
+    /// The gas assumed to be used by deploys whose payment code doesn't take an amount argument.
+    custom_payment_gas_estimate: u64,
+    /// The version under which the chainspec is stored.
+    chainspec_version: Version,
+    /// The time after which a finalized block is forgotten.
+    max_ttl: TimeDiff,
+    collected_deploys: HashMap<DeployHash, DeployInfo>,
+    processed: HashMap<ProtoBlockHash, HashMap<DeployHash, DeployInfo>>,
+    finalized: HashMap<ProtoBlockHash, HashMap<DeployHash, DeployInfo>>,
+    /// The times at which the blocks in `finalized` were finalized.
+    finalization_times: HashMap<ProtoBlockHash, Timestamp>,
+    metrics: DeployBufferMetrics,
 }
 
 impl DeployBuffer {
-    /// Creates a new, empty deploy buffer instance.
-    pub(crate) fn new(block_max_deploy_count: usize) -> Self {
-        DeployBuffer {
+    /// Creates a new, empty deploy buffer instance, along with the effects scheduling its first
+    /// housekeeping pass.
+    pub(crate) fn new<REv>(
+        registry: &Registry,
+        effect_builder: EffectBuilder<REv>,
+        block_max_deploy_count: usize,
+        custom_payment_gas_estimate: u64,
+        chainspec_version: Version,
+        max_ttl: TimeDiff,
+    ) -> Result<(Self, Effects<Event>), prometheus::Error>
+    where
+        REv: Send,
+    {
+        let deploy_buffer = DeployBuffer::new_without_pruning(
+            registry,
+            block_max_deploy_count,
+            custom_payment_gas_estimate,
+            chainspec_version,
+            max_ttl,
+        )?;
+        Ok((deploy_buffer, schedule_prune(effect_builder)))
+    }
+
+    /// Creates a new, empty deploy buffer instance without scheduling any housekeeping.
+    fn new_without_pruning(
+        registry: &Registry,
+        block_max_deploy_count: usize,
+        custom_payment_gas_estimate: u64,
+        chainspec_version: Version,
+        max_ttl: TimeDiff,
+    ) -> Result<Self, prometheus::Error> {
+        Ok(DeployBuffer {
             block_max_deploy_count,
+            custom_payment_gas_estimate,
+            chainspec_version,
+            max_ttl,
             collected_deploys: HashMap::new(),
             processed: HashMap::new(),
             finalized: HashMap::new(),
-        }
+            finalization_times: HashMap::new(),
+            metrics: DeployBufferMetrics::new(registry)?,
+        })
     }
 
     /// Adds a deploy to the deploy buffer.
     ///
     /// Returns `false` if the deploy has been rejected.
-    fn add_deploy(&mut self, hash: DeployHash, header: DeployHeader) {
+    fn add_deploy(&mut self, hash: DeployHash, info: DeployInfo) {
         // only add the deploy if it isn't contained in a finalized block
         if !self
             .finalized
             .values()
             .any(|block| block.contains_key(&hash))
         {
-            self.collected_deploys.insert(hash, header);
+            self.collected_deploys.insert(hash, info);
             info!("added deploy {} to the buffer", hash);
         } else {
             info!("deploy {} rejected from the buffer", hash);
         }
     }
 
-    /// Gets the chainspec from storage in order to call `remaining_deploys()`.
+    /// Gets the chainspec from storage in order to call `remaining_deploys()` with the deploy
+    /// config in effect during the given era.
     fn get_chainspec_from_storage<REv>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
         current_instant: Timestamp,
         past_blocks: HashSet<ProtoBlockHash>,
         responder: Responder<HashSet<DeployHash>>,
@@ -123,12 +321,11 @@ impl DeployBuffer {
     where
         REv: From<StorageRequest<Storage>> + Send,
     {
-        // TODO - should the current protocol version be passed in here?
-        let version = Version::from((1, 0, 0));
         effect_builder
-            .get_chainspec(version)
+            .get_chainspec(self.chainspec_version.clone())
             .event(move |maybe_chainspec| Event::GetChainspecResult {
                 maybe_chainspec: Box::new(maybe_chainspec),
+                era_id,
                 current_instant,
                 past_blocks,
                 responder,
@@ -136,6 +333,10 @@ impl DeployBuffer {
     }
 
     /// Returns a list of candidates for inclusion into a block.
+    ///
+    /// The candidates' total gas and size are within the limits set in `deploy_config`, and there
+    /// are at most `block_max_deploy_count` of them.  Deploys with higher gas prices are chosen
+    /// first, but an account's deploys are only ever chosen in the order of their timestamps.
     fn remaining_deploys(
         &mut self,
         deploy_config: DeployConfig,
@@ -148,18 +349,58 @@ impl DeployBuffer {
             .chain(self.finalized.values())
             .flat_map(|deploys| deploys.keys())
             .collect::<HashSet<_>>();
-        // deploys_to_return = all deploys in collected_deploys that aren't in finalized blocks or
-        // processed blocks from the set `past_blocks`
-        self.collected_deploys
-            .iter()
-            .filter(|&(hash, deploy)| {
-                self.is_deploy_valid(deploy, current_instant, &deploy_config, &past_deploys)
-                    && !past_deploys.contains(hash)
+
+        // Group all deploys in collected_deploys that aren't in finalized blocks or processed
+        // blocks from the set `past_blocks` by account, each group ordered by timestamp.
+        let mut deploys_by_account: HashMap<&PublicKey, Vec<Candidate>> = HashMap::new();
+        for (hash, info) in &self.collected_deploys {
+            if self.is_deploy_valid(&info.header, current_instant, &deploy_config, &past_deploys)
+                && !past_deploys.contains(hash)
+            {
+                deploys_by_account
+                    .entry(info.header.account())
+                    .or_default()
+                    .push(Candidate { hash, info });
+            }
+        }
+        let mut queues: Vec<VecDeque<Candidate>> = deploys_by_account
+            .into_iter()
+            .map(|(_account, mut candidates)| {
+                candidates
+                    .sort_by_key(|candidate| (candidate.info.header.timestamp(), *candidate.hash));
+                VecDeque::from(candidates)
             })
-            .map(|(hash, _deploy)| *hash)
-            .take(self.block_max_deploy_count)
-            .collect::<HashSet<_>>()
-        // TODO: check gas and block size limits
+            .collect();
+
+        // Repeatedly take the best of the accounts' oldest remaining deploys.  If it doesn't fit,
+        // none of that account's later deploys are considered either.
+        let mut heads: BinaryHeap<(Candidate, usize)> = queues
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, queue)| queue.pop_front().map(|candidate| (candidate, index)))
+            .collect();
+        let mut block_gas = 0u64;
+        let mut block_size = 0u64;
+        let mut deploys = HashSet::new();
+        while let Some((candidate, index)) = heads.pop() {
+            if deploys.len() >= self.block_max_deploy_count {
+                break;
+            }
+            let gas = block_gas
+                .saturating_add(candidate.info.gas_limit(self.custom_payment_gas_estimate));
+            let size = block_size.saturating_add(candidate.info.size);
+            if gas > deploy_config.block_gas_limit || size > u64::from(deploy_config.max_block_size)
+            {
+                continue;
+            }
+            block_gas = gas;
+            block_size = size;
+            deploys.insert(*candidate.hash);
+            if let Some(next_candidate) = queues[index].pop_front() {
+                heads.push((next_candidate, index));
+            }
+        }
+        deploys
     }
 
     /// Checks if a deploy is valid (for inclusion into the next block).
@@ -204,12 +445,13 @@ impl DeployBuffer {
         self.processed.insert(block, deploy_map);
     }
 
-    /// Notifies the deploy buffer that a block has been finalized.
-    fn finalized_block(&mut self, block: ProtoBlockHash) {
+    /// Notifies the deploy buffer that a block has been finalized at `current_instant`.
+    fn finalized_block(&mut self, block: ProtoBlockHash, current_instant: Timestamp) {
         if let Some(deploys) = self.processed.remove(&block) {
             self.collected_deploys
                 .retain(|deploy_hash, _| !deploys.contains_key(deploy_hash));
             self.finalized.insert(block, deploys);
+            self.finalization_times.insert(block, current_instant);
         } else if !block.is_empty() {
             // TODO: Events are not guaranteed to be handled in order, so this could happen!
             error!("finalized block that hasn't been processed!");
@@ -225,6 +467,125 @@ impl DeployBuffer {
             error!("orphaned block that hasn't been processed!");
         }
     }
+
+    /// Returns the lifecycle status of the given deploy as of `current_instant`.
+    ///
+    /// Deploys which were never buffered, or have been pruned, are reported as unknown.
+    fn deploy_status(&self, hash: &DeployHash, current_instant: Timestamp) -> DeployStatus {
+        let contains = |blocks: &HashMap<ProtoBlockHash, HashMap<DeployHash, DeployInfo>>| {
+            blocks.values().any(|deploys| deploys.contains_key(hash))
+        };
+        if contains(&self.finalized) {
+            DeployStatus::Finalized
+        } else if contains(&self.processed) {
+            DeployStatus::Proposed
+        } else {
+            match self.collected_deploys.get(hash) {
+                Some(info) if info.header.expires() < current_instant => DeployStatus::Expired,
+                Some(_) => DeployStatus::Pending,
+                None => DeployStatus::Unknown,
+            }
+        }
+    }
+
+    /// Returns the deploys which are neither finalized nor expired as of `current_instant`,
+    /// ordered by their timestamps.
+    ///
+    /// If `account` is given, only deploys sent from that account are returned.  If
+    /// `expires_before` is given, only deploys expiring before then are returned.
+    fn pending_deploys(
+        &self,
+        account: Option<&PublicKey>,
+        expires_before: Option<Timestamp>,
+        current_instant: Timestamp,
+    ) -> Vec<PendingDeploy> {
+        let proposed = self
+            .processed
+            .values()
+            .flatten()
+            .map(|(hash, info)| (hash, info, DeployStatus::Proposed));
+        let pending = self
+            .collected_deploys
+            .iter()
+            .map(|(hash, info)| (hash, info, DeployStatus::Pending));
+        // A deploy can be in several proposed blocks, so deduplicate them via a map, letting the
+        // proposed status take precedence.
+        let mut pending_deploys: Vec<PendingDeploy> = pending
+            .chain(proposed)
+            .filter(|(_, info, _)| {
+                let expires = info.header.expires();
+                expires >= current_instant
+                    && account.map_or(true, |account| info.header.account() == account)
+                    && expires_before.map_or(true, |expires_before| expires < expires_before)
+            })
+            .map(|(hash, info, status)| {
+                (
+                    *hash,
+                    PendingDeploy {
+                        hash: *hash,
+                        header: info.header.clone(),
+                        status,
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>()
+            .into_iter()
+            .map(|(_hash, pending_deploy)| pending_deploy)
+            .collect();
+        pending_deploys
+            .sort_by_key(|pending_deploy| (pending_deploy.header.timestamp(), pending_deploy.hash));
+        pending_deploys
+    }
+
+    /// Drops the deploys which have expired by `current_instant`, and forgets the blocks which
+    /// were finalized longer than `max_ttl` ago.
+    ///
+    /// The blocks of proposed but not yet finalized deploys are kept, so that their finalization
+    /// or orphaning can still be handled.
+    fn prune(&mut self, current_instant: Timestamp) {
+        let is_live =
+            |_: &DeployHash, info: &mut DeployInfo| info.header.expires() >= current_instant;
+        let collected_count = self.collected_deploys.len();
+        self.collected_deploys.retain(is_live);
+        for deploys in self.processed.values_mut() {
+            deploys.retain(is_live);
+        }
+
+        let max_ttl = self.max_ttl;
+        let finalized = &mut self.finalized;
+        self.finalization_times
+            .retain(|block_hash, finalization_time| {
+                let keep = *finalization_time + max_ttl >= current_instant;
+                if !keep {
+                    finalized.remove(block_hash);
+                }
+                keep
+            });
+
+        debug!(
+            expired = collected_count - self.collected_deploys.len(),
+            "pruned deploy buffer"
+        );
+    }
+
+    /// Updates the metrics to the current sizes of the buffer.
+    fn update_metrics(&self) {
+        let count = |blocks: &HashMap<ProtoBlockHash, HashMap<DeployHash, DeployInfo>>| {
+            blocks.values().map(HashMap::len).sum::<usize>() as i64
+        };
+        self.metrics
+            .collected_deploys
+            .set(self.collected_deploys.len() as i64);
+        self.metrics.processed_deploys.set(count(&self.processed));
+        self.metrics.finalized_deploys.set(count(&self.finalized));
+    }
+}
+
+/// Returns the effects triggering the next housekeeping pass after `PRUNE_INTERVAL`.
+fn schedule_prune<REv: Send>(effect_builder: EffectBuilder<REv>) -> Effects<Event> {
+    effect_builder
+        .set_timeout(PRUNE_INTERVAL)
+        .event(|_| Event::Prune)
 }
 
 impl<REv, R> Component<REv, R> for DeployBuffer
@@ -240,42 +601,73 @@ where
         _rng: &mut R,
         event: Self::Event,
     ) -> Effects<Self::Event> {
-        match event {
+        let effects = match event {
             Event::Request(DeployBufferRequest::ListForInclusion {
+                era_id,
                 current_instant,
                 past_blocks,
                 responder,
             }) => {
                 return self.get_chainspec_from_storage(
                     effect_builder,
+                    era_id,
                     current_instant,
                     past_blocks,
                     responder,
                 );
             }
-            Event::Buffer { hash, header } => self.add_deploy(hash, *header),
+            Event::Request(DeployBufferRequest::GetDeployStatus { hash, responder }) => {
+                let status = self.deploy_status(&hash, Timestamp::now());
+                return responder.respond(status).ignore();
+            }
+            Event::Request(DeployBufferRequest::ListPending {
+                account,
+                expires_before,
+                responder,
+            }) => {
+                let pending_deploys =
+                    self.pending_deploys(account.as_ref(), expires_before, Timestamp::now());
+                return responder.respond(pending_deploys).ignore();
+            }
+            Event::Buffer { hash, info } => {
+                self.add_deploy(hash, *info);
+                Effects::new()
+            }
             Event::ProposedProtoBlock(block) => {
                 let (hash, deploys, _) = block.destructure();
-                self.added_block(hash, deploys)
+                self.added_block(hash, deploys);
+                Effects::new()
+            }
+            Event::FinalizedProtoBlock(block) => {
+                self.finalized_block(*block.hash(), Timestamp::now());
+                Effects::new()
+            }
+            Event::OrphanedProtoBlock(block) => {
+                self.orphaned_block(*block.hash());
+                Effects::new()
+            }
+            Event::Prune => {
+                self.prune(Timestamp::now());
+                schedule_prune(effect_builder)
             }
-            Event::FinalizedProtoBlock(block) => self.finalized_block(*block.hash()),
-            Event::OrphanedProtoBlock(block) => self.orphaned_block(*block.hash()),
             Event::GetChainspecResult {
                 maybe_chainspec,
+                era_id,
                 current_instant,
                 past_blocks,
                 responder,
             } => {
                 let chainspec = maybe_chainspec.expect("should return chainspec");
                 let deploys = self.remaining_deploys(
-                    chainspec.genesis.deploy_config,
+                    chainspec.deploy_config(era_id),
                     current_instant,
                     past_blocks,
                 );
                 return responder.respond(deploys).ignore();
             }
-        }
-        Effects::new()
+        };
+        self.update_metrics();
+        effects
     }
 }
 
@@ -290,17 +682,17 @@ mod tests {
     use crate::{
         crypto::{asymmetric_key::SecretKey, hash::hash},
         testing::TestRng,
-        types::{Deploy, DeployHash, DeployHeader, NodeConfig, ProtoBlockHash, TimeDiff},
+        types::{Deploy, DeployHash, NodeConfig, ProtoBlockHash, TimeDiff},
     };
 
-    fn generate_deploy(
+    fn create_deploy(
         rng: &mut TestRng,
+        secret_key: &SecretKey,
         timestamp: Timestamp,
         ttl: TimeDiff,
+        gas_price: u64,
         dependencies: Vec<DeployHash>,
-    ) -> (DeployHash, DeployHeader) {
-        let secret_key = SecretKey::random(rng);
-        let gas_price = 10;
+    ) -> (DeployHash, DeployInfo) {
         let chain_name = "chain".to_string();
         let payment = ExecutableDeployItem::ModuleBytes {
             module_bytes: vec![],
@@ -319,11 +711,40 @@ mod tests {
             chain_name,
             payment,
             session,
-            &secret_key,
+            secret_key,
             rng,
         );
 
-        (*deploy.id(), deploy.take_header())
+        (*deploy.id(), DeployInfo::new(&deploy))
+    }
+
+    fn generate_deploy(
+        rng: &mut TestRng,
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        dependencies: Vec<DeployHash>,
+    ) -> (DeployHash, DeployInfo) {
+        let secret_key = SecretKey::random(rng);
+        let gas_price = 10;
+        create_deploy(rng, &secret_key, timestamp, ttl, gas_price, dependencies)
+    }
+
+    fn new_buffer(block_max_deploy_count: usize) -> DeployBuffer {
+        DeployBuffer::new_without_pruning(
+            &Registry::new(),
+            block_max_deploy_count,
+            NodeConfig::default().custom_payment_gas_estimate,
+            Version::new(1, 0, 0),
+            DeployConfig::default().max_ttl,
+        )
+        .unwrap()
+    }
+
+    /// Returns the given deploy info, changed to have the given gas limit and size.
+    fn with_limits(mut info: DeployInfo, gas_limit: u64, size: u64) -> DeployInfo {
+        info.gas_limit = Some(gas_limit);
+        info.size = size;
+        info
     }
 
     #[test]
@@ -335,7 +756,7 @@ mod tests {
         let block_time3 = Timestamp::from(220);
 
         let no_blocks = HashSet::new();
-        let mut buffer = DeployBuffer::new(NodeConfig::default().block_max_deploy_count as usize);
+        let mut buffer = new_buffer(NodeConfig::default().block_max_deploy_count as usize);
         let mut rng = TestRng::new();
         let (hash1, deploy1) = generate_deploy(&mut rng, creation_time, ttl, vec![]);
         let (hash2, deploy2) = generate_deploy(&mut rng, creation_time, ttl, vec![]);
@@ -410,7 +831,7 @@ mod tests {
         buffer.add_deploy(hash2, deploy2);
 
         // finalize the block
-        buffer.finalized_block(block_hash1);
+        buffer.finalized_block(block_hash1, block_time2);
 
         // add more deploys
         buffer.add_deploy(hash3, deploy3);
@@ -436,7 +857,7 @@ mod tests {
         let (hash2, deploy2) = generate_deploy(&mut rng, creation_time, ttl, vec![hash1]);
 
         let mut blocks = HashSet::new();
-        let mut buffer = DeployBuffer::new(NodeConfig::default().block_max_deploy_count as usize);
+        let mut buffer = new_buffer(NodeConfig::default().block_max_deploy_count as usize);
 
         // add deploy2
         buffer.add_deploy(hash2, deploy2);
@@ -464,4 +885,254 @@ mod tests {
         assert_eq!(deploys2.len(), 1);
         assert!(deploys2.contains(&hash2));
     }
+
+    #[test]
+    fn should_respect_block_limits() {
+        let creation_time = Timestamp::from(100);
+        let ttl = TimeDiff::from(100);
+        let block_time = Timestamp::from(120);
+        let deploy_config = DeployConfig {
+            block_gas_limit: 1_000,
+            max_block_size: 2_000,
+            ..DeployConfig::default()
+        };
+
+        let mut rng = TestRng::new();
+        let mut buffer = new_buffer(NodeConfig::default().block_max_deploy_count as usize);
+        let mut infos = HashMap::new();
+        for _ in 0..50 {
+            let (hash, info) = generate_deploy(&mut rng, creation_time, ttl, vec![]);
+            let gas_limit = rng.gen_range(0, 500);
+            let size = rng.gen_range(0, 500);
+            let info = with_limits(info, gas_limit, size);
+            infos.insert(hash, info.clone());
+            buffer.add_deploy(hash, info);
+        }
+
+        let no_blocks = HashSet::new();
+        let deploys = buffer.remaining_deploys(deploy_config, block_time, no_blocks);
+        assert!(!deploys.is_empty());
+        let total_gas: u64 = deploys
+            .iter()
+            .map(|hash| infos[hash].gas_limit(buffer.custom_payment_gas_estimate))
+            .sum();
+        let total_size: u64 = deploys.iter().map(|hash| infos[hash].size).sum();
+        assert!(total_gas <= deploy_config.block_gas_limit);
+        assert!(total_size <= u64::from(deploy_config.max_block_size));
+    }
+
+    #[test]
+    fn should_respect_block_max_deploy_count() {
+        let creation_time = Timestamp::from(100);
+        let ttl = TimeDiff::from(100);
+        let block_time = Timestamp::from(120);
+
+        let mut rng = TestRng::new();
+        let mut buffer = new_buffer(3);
+        for _ in 0..10 {
+            let (hash, info) = generate_deploy(&mut rng, creation_time, ttl, vec![]);
+            buffer.add_deploy(hash, info);
+        }
+
+        let deploys = buffer.remaining_deploys(DeployConfig::default(), block_time, HashSet::new());
+        assert_eq!(deploys.len(), 3);
+    }
+
+    #[test]
+    fn should_count_estimated_gas_for_deploys_with_unknown_gas_limit() {
+        let creation_time = Timestamp::from(100);
+        let ttl = TimeDiff::from(100);
+        let block_time = Timestamp::from(120);
+        let deploy_config = DeployConfig {
+            block_gas_limit: 1_000,
+            ..DeployConfig::default()
+        };
+
+        let mut rng = TestRng::new();
+        let mut buffer = new_buffer(3);
+        buffer.custom_payment_gas_estimate = 400;
+        for _ in 0..3 {
+            let (hash, info) = generate_deploy(&mut rng, creation_time, ttl, vec![]);
+            assert!(info.gas_limit.is_none());
+            buffer.add_deploy(hash, info);
+        }
+
+        // Only two of the deploys fit within the block's gas limit.
+        let deploys = buffer.remaining_deploys(deploy_config, block_time, HashSet::new());
+        assert_eq!(deploys.len(), 2);
+    }
+
+    #[test]
+    fn should_prefer_higher_gas_price() {
+        let creation_time = Timestamp::from(100);
+        let ttl = TimeDiff::from(100);
+        let block_time = Timestamp::from(120);
+        let deploy_config = DeployConfig {
+            block_gas_limit: 1_000,
+            ..DeployConfig::default()
+        };
+
+        let mut rng = TestRng::new();
+        let mut buffer = new_buffer(NodeConfig::default().block_max_deploy_count as usize);
+        let secret_key1 = SecretKey::random(&mut rng);
+        let secret_key2 = SecretKey::random(&mut rng);
+        let (cheap_hash, cheap_info) =
+            create_deploy(&mut rng, &secret_key1, creation_time, ttl, 1, vec![]);
+        let (dear_hash, dear_info) =
+            create_deploy(&mut rng, &secret_key2, creation_time, ttl, 100, vec![]);
+        buffer.add_deploy(cheap_hash, with_limits(cheap_info, 600, 0));
+        buffer.add_deploy(dear_hash, with_limits(dear_info, 600, 0));
+
+        // Only one of the deploys fits in a block.
+        let deploys = buffer.remaining_deploys(deploy_config, block_time, HashSet::new());
+        assert_eq!(deploys.len(), 1);
+        assert!(deploys.contains(&dear_hash));
+    }
+
+    #[test]
+    fn should_keep_account_deploys_in_timestamp_order() {
+        let ttl = TimeDiff::from(100);
+        let block_time = Timestamp::from(120);
+        let deploy_config = DeployConfig {
+            block_gas_limit: 1_000,
+            ..DeployConfig::default()
+        };
+
+        let mut rng = TestRng::new();
+        let account1 = SecretKey::random(&mut rng);
+        let account2 = SecretKey::random(&mut rng);
+
+        // Account 1's older deploy offers a low gas price, its newer one a high gas price.
+        let (old_hash, old_info) =
+            create_deploy(&mut rng, &account1, Timestamp::from(100), ttl, 1, vec![]);
+        let (new_hash, new_info) =
+            create_deploy(&mut rng, &account1, Timestamp::from(101), ttl, 100, vec![]);
+        let (other_hash, other_info) =
+            create_deploy(&mut rng, &account2, Timestamp::from(100), ttl, 50, vec![]);
+
+        // Only two of the three deploys fit in a block: the newer deploy of account 1 must not be
+        // proposed before the older one.
+        let mut buffer = new_buffer(NodeConfig::default().block_max_deploy_count as usize);
+        buffer.add_deploy(old_hash, with_limits(old_info.clone(), 400, 0));
+        buffer.add_deploy(new_hash, with_limits(new_info.clone(), 400, 0));
+        buffer.add_deploy(other_hash, with_limits(other_info.clone(), 400, 0));
+        let deploys = buffer.remaining_deploys(deploy_config, block_time, HashSet::new());
+        let expected: HashSet<_> = vec![old_hash, other_hash].into_iter().collect();
+        assert_eq!(deploys, expected);
+
+        // If account 1's older deploy doesn't fit, its newer one mustn't be proposed either.
+        let mut buffer = new_buffer(NodeConfig::default().block_max_deploy_count as usize);
+        buffer.add_deploy(old_hash, with_limits(old_info, 900, 0));
+        buffer.add_deploy(new_hash, with_limits(new_info, 100, 0));
+        buffer.add_deploy(other_hash, with_limits(other_info, 200, 0));
+        let deploys = buffer.remaining_deploys(deploy_config, block_time, HashSet::new());
+        let expected: HashSet<_> = vec![other_hash].into_iter().collect();
+        assert_eq!(deploys, expected);
+    }
+
+    #[test]
+    fn should_prune_expired_deploys_and_old_finalized_blocks() {
+        let mut rng = TestRng::new();
+        let max_ttl = DeployConfig::default().max_ttl;
+        let mut buffer = new_buffer(NodeConfig::default().block_max_deploy_count as usize);
+
+        let creation_time = Timestamp::from(100);
+        let (short_hash, short_deploy) =
+            generate_deploy(&mut rng, creation_time, TimeDiff::from(100), vec![]);
+        let (long_hash, long_deploy) =
+            generate_deploy(&mut rng, creation_time, TimeDiff::from(1_000), vec![]);
+        let (finalized_hash, finalized_deploy) =
+            generate_deploy(&mut rng, creation_time, TimeDiff::from(100), vec![]);
+        buffer.add_deploy(short_hash, short_deploy);
+        buffer.add_deploy(long_hash, long_deploy);
+        buffer.add_deploy(finalized_hash, finalized_deploy.clone());
+
+        let block_hash = ProtoBlockHash::new(hash(random::<[u8; 16]>()));
+        buffer.added_block(block_hash, vec![finalized_hash]);
+        let finalization_time = Timestamp::from(150);
+        buffer.finalized_block(block_hash, finalization_time);
+
+        // Only the expired deploy should be dropped.
+        buffer.prune(Timestamp::from(500));
+        assert!(!buffer.collected_deploys.contains_key(&short_hash));
+        assert!(buffer.collected_deploys.contains_key(&long_hash));
+        assert!(buffer.finalized.contains_key(&block_hash));
+
+        // The finalized deploy still can't be re-added.
+        buffer.add_deploy(finalized_hash, finalized_deploy);
+        assert!(!buffer.collected_deploys.contains_key(&finalized_hash));
+
+        // Once the maximum TTL has passed since finalization, the block should be forgotten.
+        buffer.prune(finalization_time + max_ttl + TimeDiff::from(1));
+        assert!(buffer.collected_deploys.is_empty());
+        assert!(buffer.finalized.is_empty());
+        assert!(buffer.finalization_times.is_empty());
+
+        buffer.update_metrics();
+        assert_eq!(buffer.metrics.collected_deploys.get(), 0);
+        assert_eq!(buffer.metrics.finalized_deploys.get(), 0);
+    }
+
+    #[test]
+    fn should_report_deploy_status_and_pending_deploys() {
+        let mut rng = TestRng::new();
+        let mut buffer = new_buffer(NodeConfig::default().block_max_deploy_count as usize);
+        let secret_key = SecretKey::random(&mut rng);
+        let account = PublicKey::from(&secret_key);
+
+        let creation_time = Timestamp::from(100);
+        let current_instant = Timestamp::from(150);
+        let (pending_hash, pending_deploy) = create_deploy(
+            &mut rng,
+            &secret_key,
+            creation_time,
+            TimeDiff::from(200),
+            10,
+            vec![],
+        );
+        let (proposed_hash, proposed_deploy) =
+            generate_deploy(&mut rng, creation_time, TimeDiff::from(100), vec![]);
+        let (finalized_hash, finalized_deploy) =
+            generate_deploy(&mut rng, creation_time, TimeDiff::from(100), vec![]);
+        let (expired_hash, expired_deploy) =
+            generate_deploy(&mut rng, creation_time, TimeDiff::from(10), vec![]);
+        buffer.add_deploy(pending_hash, pending_deploy);
+        buffer.add_deploy(proposed_hash, proposed_deploy);
+        buffer.add_deploy(finalized_hash, finalized_deploy);
+        buffer.add_deploy(expired_hash, expired_deploy);
+
+        let proposed_block_hash = ProtoBlockHash::new(hash(random::<[u8; 16]>()));
+        buffer.added_block(proposed_block_hash, vec![proposed_hash]);
+        let finalized_block_hash = ProtoBlockHash::new(hash(random::<[u8; 16]>()));
+        buffer.added_block(finalized_block_hash, vec![finalized_hash]);
+        buffer.finalized_block(finalized_block_hash, current_instant);
+
+        let status = |hash| buffer.deploy_status(hash, current_instant);
+        assert_eq!(status(&pending_hash), DeployStatus::Pending);
+        assert_eq!(status(&proposed_hash), DeployStatus::Proposed);
+        assert_eq!(status(&finalized_hash), DeployStatus::Finalized);
+        assert_eq!(status(&expired_hash), DeployStatus::Expired);
+        let (unknown_hash, _) =
+            generate_deploy(&mut rng, creation_time, TimeDiff::from(100), vec![]);
+        assert_eq!(status(&unknown_hash), DeployStatus::Unknown);
+
+        // Only the pending and proposed deploys should be listed.
+        let pending_deploys = buffer.pending_deploys(None, None, current_instant);
+        assert_eq!(pending_deploys.len(), 2);
+        assert!(pending_deploys.iter().any(|pending_deploy| {
+            pending_deploy.hash == pending_hash && pending_deploy.status == DeployStatus::Pending
+        }));
+        assert!(pending_deploys.iter().any(|pending_deploy| {
+            pending_deploy.hash == proposed_hash && pending_deploy.status == DeployStatus::Proposed
+        }));
+
+        // Filtering by account or expiry should restrict the list.
+        let pending_deploys = buffer.pending_deploys(Some(&account), None, current_instant);
+        assert_eq!(pending_deploys.len(), 1);
+        assert_eq!(pending_deploys[0].hash, pending_hash);
+        let pending_deploys =
+            buffer.pending_deploys(None, Some(Timestamp::from(250)), current_instant);
+        assert_eq!(pending_deploys.len(), 1);
+        assert_eq!(pending_deploys[0].hash, proposed_hash);
+    }
 }
@@ -7,6 +7,8 @@ use rand::{CryptoRng, Rng};
 use smallvec::smallvec;
 use tracing::{debug, error};
 
+use casper_execution_engine::shared::newtypes::Blake2bHash;
+
 use crate::{
     components::{fetcher::event::FetchResponder, storage::Storage, Component},
     effect::{
@@ -15,7 +17,9 @@ use crate::{
     },
     protocol::Message,
     small_network::NodeId,
-    types::{Block, BlockHash, Deploy, DeployHash, Item},
+    types::{
+        Block, BlockHash, BlockHeadersBatch, BlockHeadersBatchId, Deploy, DeployHash, Item, Trie,
+    },
     utils::Source,
     GossipConfig,
 };
@@ -225,6 +229,65 @@ impl ItemFetcher<Block> for Fetcher<Block> {
     }
 }
 
+impl ItemFetcher<BlockHeadersBatch> for Fetcher<BlockHeadersBatch> {
+    fn responders(
+        &mut self,
+    ) -> &mut HashMap<BlockHeadersBatchId, HashMap<NodeId, Vec<FetchResponder<BlockHeadersBatch>>>>
+    {
+        &mut self.responders
+    }
+
+    fn peer_timeout(&self) -> Duration {
+        self.get_from_peer_timeout
+    }
+
+    /// Block header batches are only fetched by joining nodes, which don't have them in storage, so
+    /// they are always requested from the peer.
+    fn get_from_storage<REv: ReactorEventT<BlockHeadersBatch>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: BlockHeadersBatchId,
+        peer: NodeId,
+    ) -> Effects<Event<BlockHeadersBatch>> {
+        effect_builder
+            .immediately()
+            .event(move |_| Event::GetFromStorageResult {
+                id,
+                peer,
+                maybe_item: Box::new(None),
+            })
+    }
+}
+
+impl ItemFetcher<Trie> for Fetcher<Trie> {
+    fn responders(
+        &mut self,
+    ) -> &mut HashMap<Blake2bHash, HashMap<NodeId, Vec<FetchResponder<Trie>>>> {
+        &mut self.responders
+    }
+
+    fn peer_timeout(&self) -> Duration {
+        self.get_from_peer_timeout
+    }
+
+    /// Tries are only fetched while synchronizing global state, and only once they are known to be
+    /// missing from the trie store, so they are always requested from the peer.
+    fn get_from_storage<REv: ReactorEventT<Trie>>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        id: Blake2bHash,
+        peer: NodeId,
+    ) -> Effects<Event<Trie>> {
+        effect_builder
+            .immediately()
+            .event(move |_| Event::GetFromStorageResult {
+                id,
+                peer,
+                maybe_item: Box::new(None),
+            })
+    }
+}
+
 impl<T, REv, R> Component<REv, R> for Fetcher<T>
 where
     Fetcher<T>: ItemFetcher<T>,
@@ -24,7 +24,7 @@ use crate::{
         EffectBuilder, EffectExt, Effects,
     },
     protocol::Message as NodeMessage,
-    types::{Deploy, DeployHash, Item},
+    types::{Deploy, DeployHash, Evidence, EvidenceId, Item},
     utils::Source,
 };
 pub use config::Config;
@@ -89,6 +89,28 @@ pub(crate) fn get_deploy_from_storage<T: Item + 'static, REv: ReactorEventT<T>>(
         })
 }
 
+/// This function can be passed in to `Gossiper::new()` as the `get_from_holder` arg when
+/// constructing a `Gossiper<Evidence>`.
+pub(crate) fn get_evidence_from_storage<T: Item + 'static, REv: ReactorEventT<T>>(
+    effect_builder: EffectBuilder<REv>,
+    evidence_id: EvidenceId,
+    sender: NodeId,
+) -> Effects<Event<Evidence>> {
+    effect_builder
+        .get_era_evidence_from_storage(evidence_id.era_id)
+        .event(move |era_evidence| {
+            let result = era_evidence
+                .into_iter()
+                .find(|evidence| *evidence.perpetrator() == evidence_id.perpetrator)
+                .ok_or_else(|| String::from("failed to get evidence from storage"));
+            Event::GetFromHolderResult {
+                item_id: evidence_id,
+                requester: sender,
+                result: Box::new(result),
+            }
+        })
+}
+
 /// The component which gossips to peers and handles incoming gossip messages from peers.
 #[allow(clippy::type_complexity)]
 pub(crate) struct Gossiper<T: Item + 'static, REv: ReactorEventT<T>> {
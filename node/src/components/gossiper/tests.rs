@@ -7,6 +7,7 @@ use std::{
 
 use derive_more::From;
 use prometheus::Registry;
+use semver::Version;
 use smallvec::smallvec;
 use tempfile::TempDir;
 use thiserror::Error;
@@ -17,6 +18,7 @@ use super::*;
 use crate::{
     components::{
         chainspec_loader::Chainspec,
+        consensus::EraId,
         deploy_acceptor::{self, DeployAcceptor},
         in_memory_network::{InMemoryNetwork, NetworkController, NodeId},
         storage::{self, Storage, StorageType},
@@ -127,7 +129,7 @@ impl reactor::Reactor<TestRng> for Reactor {
         let (storage_config, _storage_tempdir) = storage::Config::default_for_tests();
         let storage = Storage::new(&storage_config).unwrap();
 
-        let deploy_acceptor = DeployAcceptor::new();
+        let deploy_acceptor = DeployAcceptor::new(Version::new(1, 0, 0), EraId(0));
         let deploy_gossiper = Gossiper::new_for_partial_items(config, get_deploy_from_storage);
 
         let reactor = Reactor {
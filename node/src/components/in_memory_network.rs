@@ -559,6 +559,10 @@ where
                     responder.respond(Default::default()).ignore()
                 }
             }
+            NetworkRequest::ReportMisbehavior { responder, .. } => {
+                // Peers are never penalized on the in-memory network.
+                responder.respond(()).ignore()
+            }
         }
     }
 }
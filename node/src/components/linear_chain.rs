@@ -1,24 +1,33 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::{self, Display, Formatter},
     marker::PhantomData,
 };
 
 use derive_more::From;
-use futures::FutureExt;
 use rand::{CryptoRng, Rng};
 use tracing::{debug, error, info, warn};
 
+use casper_execution_engine::shared::motes::Motes;
+use casper_types::U512;
+
 use super::{storage::Storage, Component};
 use crate::{
-    components::storage::Value,
-    crypto::asymmetric_key::Signature,
+    components::{consensus::EraId, storage::Value},
+    crypto::asymmetric_key::PublicKey,
     effect::{
-        requests::{ConsensusRequest, LinearChainRequest, NetworkRequest, StorageRequest},
-        EffectExt, Effects,
+        announcements::LinearChainAnnouncement,
+        requests::{
+            ConsensusRequest, ContractRuntimeRequest, LinearChainRequest, NetworkRequest,
+            StorageRequest,
+        },
+        EffectBuilder, EffectExt, Effects,
     },
     protocol::Message,
-    types::{json_compatibility::ExecutionResult, Block, BlockHash, DeployHash},
+    types::{
+        json_compatibility::ExecutionResult, Block, BlockExecutionSummary, BlockHash,
+        BlockHeadersBatch, BlockHeadersBatchId, DeployHash, FinalitySignature, SignedBlockHeader,
+    },
 };
 
 #[derive(Debug, From)]
@@ -35,8 +44,26 @@ pub enum Event<I> {
     },
     /// A continuation for `GetBlock` scenario.
     GetBlockResult(BlockHash, Option<Block>, I),
-    /// New finality signature.
-    NewFinalitySignature(BlockHash, Signature),
+    /// New finality signature created by this node.
+    NewFinalitySignature(Box<FinalitySignature>),
+    /// A finality signature received from a peer.
+    FinalitySignatureReceived(Box<FinalitySignature>),
+    /// The result of getting the validators of the era of a finality signature.
+    GetEraValidatorsResult {
+        /// The finality signature.
+        finality_signature: Box<FinalitySignature>,
+        /// The era's validators along with their stakes, or `None` if the era is not active.
+        maybe_validator_stakes: Option<BTreeMap<PublicKey, Motes>>,
+    },
+    /// The result of getting the block signed by a finality signature.
+    GetSignedBlockResult {
+        /// The finality signature.
+        finality_signature: Box<FinalitySignature>,
+        /// The era's validators along with their stakes.
+        validator_stakes: BTreeMap<PublicKey, Motes>,
+        /// The signed block, or `None` if it isn't stored yet.
+        maybe_block: Option<Box<Block>>,
+    },
     /// The result of putting a block to storage.
     PutBlockResult {
         /// The block.
@@ -60,10 +87,30 @@ impl<I: Display> Display for Event<I> {
                 peer,
                 maybe_block.is_some()
             ),
-            Event::NewFinalitySignature(block_hash, _) => write!(
+            Event::NewFinalitySignature(finality_signature) => {
+                write!(f, "linear-chain new {}", finality_signature)
+            }
+            Event::FinalitySignatureReceived(finality_signature) => {
+                write!(f, "linear-chain received {}", finality_signature)
+            }
+            Event::GetEraValidatorsResult {
+                finality_signature,
+                maybe_validator_stakes,
+            } => write!(
+                f,
+                "linear-chain get era validators for {} found: {}",
+                finality_signature,
+                maybe_validator_stakes.is_some()
+            ),
+            Event::GetSignedBlockResult {
+                finality_signature,
+                maybe_block,
+                ..
+            } => write!(
                 f,
-                "linear-chain new finality signature for block: {}",
-                block_hash
+                "linear-chain get signed block for {} found: {}",
+                finality_signature,
+                maybe_block.is_some()
             ),
             Event::PutBlockResult { .. } => write!(f, "linear-chain put-block result"),
         }
@@ -77,14 +124,21 @@ pub(crate) struct LinearChain<I> {
     /// The last block this component put to storage which is presumably the last block in the
     /// linear chain.
     last_block: Option<Block>,
+    /// Verified finality signatures of blocks which haven't been put to storage yet.
+    pending_finality_signatures: HashMap<BlockHash, Vec<FinalitySignature>>,
+    /// The weight of finality signatures to collect for each block, as a percentage of the total
+    /// weight of the block's era's validators.
+    finality_signature_threshold_percent: u8,
     _marker: PhantomData<I>,
 }
 
 impl<I> LinearChain<I> {
-    pub fn new() -> Self {
+    pub fn new(finality_signature_threshold_percent: u8) -> Self {
         LinearChain {
             linear_chain: Vec::new(),
             last_block: None,
+            pending_finality_signatures: HashMap::new(),
+            finality_signature_threshold_percent,
             _marker: PhantomData,
         }
     }
@@ -94,11 +148,108 @@ impl<I> LinearChain<I> {
     }
 }
 
+/// Prunes the storage as of the given era, then prunes the global state down to what is reachable
+/// from the retained blocks, up to and including the one at the given height.
+async fn prune<REv>(effect_builder: EffectBuilder<REv>, era_id: EraId, height: u64)
+where
+    REv: From<StorageRequest<Storage>> + From<ContractRuntimeRequest> + Send,
+{
+    let lowest_retained_height = match effect_builder.prune_storage::<Storage>(era_id).await {
+        Some(lowest_retained_height) => lowest_retained_height,
+        None => return,
+    };
+    let retained_global_state_hashes = effect_builder
+        .get_blocks_in_height_range_from_storage::<Storage>(lowest_retained_height, height)
+        .await
+        .iter()
+        .map(|block| *block.global_state_hash())
+        .collect();
+    // Any error is logged by the contract runtime, and pruning is retried along with the next era.
+    let _ = effect_builder
+        .prune_global_state(retained_global_state_hashes)
+        .await;
+}
+
+/// Sends the signed block headers of the given batch which are in storage to the requesting peer.
+async fn send_block_headers_batch<REv, I>(
+    effect_builder: EffectBuilder<REv>,
+    batch_id: BlockHeadersBatchId,
+    sender: I,
+) where
+    REv: From<StorageRequest<Storage>> + From<NetworkRequest<I, Message>> + Send,
+    I: Send + 'static,
+{
+    let blocks = effect_builder
+        .get_blocks_in_height_range_from_storage::<Storage>(
+            batch_id.from_height,
+            batch_id.to_height(),
+        )
+        .await;
+    let mut headers = Vec::with_capacity(blocks.len());
+    // Only send the consecutive headers from the start of the batch, as a gap can't be verified.
+    for (expected_height, block) in (batch_id.from_height..).zip(blocks) {
+        if block.height() != expected_height {
+            break;
+        }
+        let proofs = effect_builder
+            .get_finality_signatures_from_storage::<Storage>(*block.hash())
+            .await;
+        headers.push(SignedBlockHeader {
+            header: block.take_header(),
+            proofs,
+        });
+    }
+    let batch = BlockHeadersBatch::new(batch_id, headers);
+    match Message::new_get_response(&batch) {
+        Ok(message) => effect_builder.send_message(sender, message).await,
+        Err(error) => error!("failed to create get-response {}", error),
+    }
+}
+
+/// Puts the given verified finality signature to storage, unless the signatures already stored for
+/// the block reach the threshold weight.
+async fn put_finality_signature<REv>(
+    effect_builder: EffectBuilder<REv>,
+    finality_signature: FinalitySignature,
+    validator_stakes: BTreeMap<PublicKey, Motes>,
+    threshold_percent: u8,
+) where
+    REv: From<StorageRequest<Storage>> + Send,
+{
+    let FinalitySignature {
+        block_hash,
+        signature,
+        public_key,
+        ..
+    } = finality_signature;
+    let stored_signatures = effect_builder
+        .get_finality_signatures_from_storage::<Storage>(block_hash)
+        .await;
+    let total_weight: Motes = validator_stakes.values().copied().sum();
+    let signed_weight: Motes = stored_signatures
+        .keys()
+        .filter_map(|public_key| validator_stakes.get(public_key))
+        .copied()
+        .sum();
+    if signed_weight.value() * 100 >= total_weight.value() * U512::from(threshold_percent) {
+        debug!(%block_hash, %public_key, "finality signature threshold already met");
+        return;
+    }
+    if !effect_builder
+        .put_finality_signature_to_storage::<Storage>(block_hash, public_key, signature)
+        .await
+    {
+        debug!(%block_hash, %public_key, "finality signature already stored");
+    }
+}
+
 impl<I, REv, R> Component<REv, R> for LinearChain<I>
 where
     REv: From<StorageRequest<Storage>>
         + From<ConsensusRequest>
+        + From<ContractRuntimeRequest>
         + From<NetworkRequest<I, Message>>
+        + From<LinearChainAnnouncement>
         + Send,
     R: Rng + CryptoRng + ?Sized,
     I: Display + Send + 'static,
@@ -115,34 +266,46 @@ where
             Event::Request(LinearChainRequest::BlockRequest(block_hash, sender)) => effect_builder
                 .get_block_from_storage(block_hash)
                 .event(move |maybe_block| Event::GetBlockResult(block_hash, maybe_block, sender)),
+            Event::Request(LinearChainRequest::BlockHeadersBatchRequest(batch_id, sender)) => {
+                if batch_id.count > BlockHeadersBatchId::MAX_COUNT {
+                    debug!(%batch_id, %sender, "requested block headers batch is too large");
+                    return Effects::new();
+                }
+                send_block_headers_batch(effect_builder, batch_id, sender).ignore()
+            }
             Event::Request(LinearChainRequest::LastFinalizedBlock(responder)) => {
                 responder.respond(self.last_block.clone()).ignore()
             }
-            Event::GetBlockResult(block_hash, maybe_block, sender) => {
-                match maybe_block {
-                    None => {
-                        debug!("failed to get {} for {}", block_hash, sender);
+            Event::GetBlockResult(block_hash, maybe_block, sender) => match maybe_block {
+                None => {
+                    debug!("failed to get {} for {}", block_hash, sender);
+                    Effects::new()
+                }
+                Some(block) => match Message::new_get_response(&block) {
+                    Ok(message) => effect_builder.send_message(sender, message).ignore(),
+                    Err(error) => {
+                        error!("failed to create get-response {}", error);
                         Effects::new()
-                    },
-                    Some(block) => match Message::new_get_response(&block) {
-                        Ok(message) => effect_builder.send_message(sender, message).ignore(),
-                        Err(error) => {
-                            error!("failed to create get-response {}", error);
-                            Effects::new()
-                        }
                     }
-                }
-            }
-            Event::LinearChainBlock{ block, execution_results } => {
-                effect_builder
-                .put_block_to_storage(Box::new(block.clone()))
-                .event(move |_| Event::PutBlockResult{ block, execution_results })
+                },
             },
-            Event::PutBlockResult { block, execution_results } => {
+            Event::LinearChainBlock {
+                block,
+                execution_results,
+            } => effect_builder
+                .put_block_to_storage(Box::new(block.clone()))
+                .event(move |_| Event::PutBlockResult {
+                    block,
+                    execution_results,
+                }),
+            Event::PutBlockResult {
+                block,
+                execution_results,
+            } => {
                 self.linear_chain.push(block.clone());
                 self.last_block = Some(block.clone());
 
-                let block_header = block.take_header();
+                let block_header = block.header().clone();
                 let block_hash = block_header.hash();
                 let era_id = block_header.era_id();
                 let height = block_header.height();
@@ -150,28 +313,135 @@ where
                 // Using `Debug` impl for the `block_hash` to not truncate it.
                 info!(?block_hash, ?era_id, ?height, "Linear chain block stored.");
 
-                let mut effects = effect_builder.put_execution_results_to_storage(block_hash, execution_results).ignore();
+                // Take the pending signatures of this block, and drop those of blocks from earlier
+                // eras, which will not be stored anymore.
+                let pending_finality_signatures = self
+                    .pending_finality_signatures
+                    .remove(&block_hash)
+                    .unwrap_or_default();
+                self.pending_finality_signatures
+                    .retain(|_, finality_signatures| {
+                        finality_signatures
+                            .iter()
+                            .all(|finality_signature| finality_signature.era_id >= era_id)
+                    });
+
+                let execution_summary = BlockExecutionSummary::new(&block, &execution_results);
+                let mut effects = effect_builder
+                    .put_execution_results_to_storage(block_hash, execution_results)
+                    .ignore();
                 effects.extend(
-                    effect_builder.handle_linear_chain_block(block_header)
-                    .event(move |signature| Event::NewFinalitySignature(block_hash, signature)));
+                    effect_builder
+                        .put_execution_summary_to_storage(block_hash, execution_summary)
+                        .ignore(),
+                );
+                effects.extend(
+                    effect_builder
+                        .announce_block_added(Box::new(block))
+                        .ignore(),
+                );
+                effects.extend(prune(effect_builder, era_id, height).ignore());
+                effects.extend(
+                    effect_builder
+                        .handle_linear_chain_block(block_header)
+                        .event(move |finality_signature| {
+                            Event::NewFinalitySignature(Box::new(finality_signature))
+                        }),
+                );
+                for finality_signature in pending_finality_signatures {
+                    effects.extend(effect_builder.immediately().event(move |_| {
+                        Event::FinalitySignatureReceived(Box::new(finality_signature))
+                    }));
+                }
                 effects
-            },
-            Event::NewFinalitySignature(block_hash, signature) => {
+            }
+            Event::NewFinalitySignature(finality_signature) => {
+                // Gossip our own signature, then handle it as one received from a peer.
+                let mut effects = effect_builder
+                    .announce_finality_signature(finality_signature.clone())
+                    .ignore();
+                effects.extend(
+                    effect_builder
+                        .get_era_validators(finality_signature.era_id)
+                        .event(
+                            move |maybe_validator_stakes| Event::GetEraValidatorsResult {
+                                finality_signature,
+                                maybe_validator_stakes,
+                            },
+                        ),
+                );
+                effects
+            }
+            Event::FinalitySignatureReceived(finality_signature) => {
+                if let Err(error) = finality_signature.verify() {
+                    warn!(%finality_signature, %error, "received invalid finality signature");
+                    return Effects::new();
+                }
                 effect_builder
-                .clone()
-                    .get_block_from_storage(block_hash)
-                    .then(move |maybe_block| match maybe_block {
-                        Some(mut block) => {
-                            block.append_proof(signature);
-                            effect_builder.put_block_to_storage(Box::new(block))
-                        }
-                        None => {
-                            warn!("Received a signature for {} but block was not found in the Linear chain storage", block_hash);
-                            panic!("Unhandled")
-                        }
+                    .get_era_validators(finality_signature.era_id)
+                    .event(
+                        move |maybe_validator_stakes| Event::GetEraValidatorsResult {
+                            finality_signature,
+                            maybe_validator_stakes,
+                        },
+                    )
+            }
+            Event::GetEraValidatorsResult {
+                finality_signature,
+                maybe_validator_stakes,
+            } => {
+                let validator_stakes = match maybe_validator_stakes {
+                    Some(validator_stakes) => validator_stakes,
+                    None => {
+                        debug!(%finality_signature, "dropping finality signature of inactive era");
+                        return Effects::new();
+                    }
+                };
+                if !validator_stakes.contains_key(&finality_signature.public_key) {
+                    warn!(%finality_signature, "received finality signature from non-validator");
+                    return Effects::new();
+                }
+                effect_builder
+                    .get_block_from_storage::<Storage>(finality_signature.block_hash)
+                    .event(move |maybe_block| Event::GetSignedBlockResult {
+                        finality_signature,
+                        validator_stakes,
+                        maybe_block: maybe_block.map(Box::new),
                     })
-                    .ignore()
-            },
+            }
+            Event::GetSignedBlockResult {
+                finality_signature,
+                validator_stakes,
+                maybe_block,
+            } => {
+                match maybe_block {
+                    None => {
+                        // The block hasn't been stored yet; handle the signature once it is.
+                        let pending = self
+                            .pending_finality_signatures
+                            .entry(finality_signature.block_hash)
+                            .or_default();
+                        if !pending
+                            .iter()
+                            .any(|pending| pending.public_key == finality_signature.public_key)
+                        {
+                            pending.push(*finality_signature);
+                        }
+                        Effects::new()
+                    }
+                    Some(block) if block.era_id() != finality_signature.era_id => {
+                        warn!(%finality_signature, block_era_id = %block.era_id(), "received finality signature with wrong era");
+                        Effects::new()
+                    }
+                    Some(_) => put_finality_signature(
+                        effect_builder,
+                        *finality_signature,
+                        validator_stakes,
+                        self.finality_signature_threshold_percent,
+                    )
+                    .ignore(),
+                }
+            }
         }
     }
 }
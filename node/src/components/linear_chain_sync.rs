@@ -1,24 +1,53 @@
 mod event;
+mod light_sync;
+mod trie_sync;
 
-use super::{fetcher::FetchResult, storage::Storage, Component};
+use super::{
+    fetcher::FetchResult,
+    storage::{Storage, Value},
+    Component,
+};
 use crate::{
     components::consensus::EraId,
+    crypto::{asymmetric_key::PublicKey, hash::Digest},
     effect::{self, EffectBuilder, EffectExt, EffectOptionExt, Effects},
-    types::{Block, BlockHash, FinalizedBlock},
+    types::{
+        Block, BlockHash, BlockHeadersBatch, BlockHeadersBatchId, FinalizedBlock, SyncMode, Trie,
+    },
 };
-use effect::requests::{
-    BlockExecutorRequest, BlockValidationRequest, FetcherRequest, StorageRequest,
+use casper_execution_engine::shared::{motes::Motes, newtypes::Blake2bHash};
+use effect::{
+    announcements::BlockExecutorAnnouncement,
+    requests::{
+        BlockExecutorRequest, BlockValidationRequest, ConsensusRequest, ContractRuntimeRequest,
+        FetcherRequest, StorageRequest,
+    },
 };
 pub use event::Event;
+use light_sync::LightSync;
 use rand::{CryptoRng, Rng};
-use std::fmt::Display;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    time::Duration,
+};
 use tracing::{error, info, trace, warn};
+use trie_sync::TrieSync;
+
+/// The delay before requesting a trie again once it failed with each peer, or before looking for
+/// the missing tries again once that failed.
+const TRIE_RETRY_DELAY: Duration = Duration::from_secs(5);
 
 pub trait ReactorEventT<I>:
     From<StorageRequest<Storage>>
     + From<FetcherRequest<I, Block>>
+    + From<FetcherRequest<I, BlockHeadersBatch>>
+    + From<FetcherRequest<I, Trie>>
     + From<BlockValidationRequest<Block, I>>
     + From<BlockExecutorRequest>
+    + From<ContractRuntimeRequest>
+    + From<ConsensusRequest>
+    + From<BlockExecutorAnnouncement>
     + Send
 {
 }
@@ -26,8 +55,13 @@ pub trait ReactorEventT<I>:
 impl<I, REv> ReactorEventT<I> for REv where
     REv: From<StorageRequest<Storage>>
         + From<FetcherRequest<I, Block>>
+        + From<FetcherRequest<I, BlockHeadersBatch>>
+        + From<FetcherRequest<I, Trie>>
         + From<BlockValidationRequest<Block, I>>
         + From<BlockExecutorRequest>
+        + From<ContractRuntimeRequest>
+        + From<ConsensusRequest>
+        + From<BlockExecutorAnnouncement>
         + Send
 {
 }
@@ -53,6 +87,18 @@ pub(crate) struct LinearChainSync<I> {
     // During synchronization we might see new eras being created.
     // Track the highest height and wait until it's handled by consensus.
     highest_block_seen: u64,
+    // How the linear chain is synchronized.
+    sync_mode: SyncMode,
+    // The genesis validators along with their stakes, used to verify finality signatures.
+    validator_stakes: BTreeMap<PublicKey, Motes>,
+    // The fault tolerance threshold, as a percentage of the total weight of the validators.
+    finality_threshold_percent: u8,
+    // State of the light synchronization, once the trusted block has been downloaded.
+    light_sync: Option<LightSync>,
+    // State of the global state synchronization, once the block headers have been verified.
+    trie_sync: Option<TrieSync>,
+    // Index of the peer to request the next batch of block headers or trie from.
+    next_peer_index: usize,
 }
 
 impl<I: Clone + 'static> LinearChainSync<I> {
@@ -60,6 +106,9 @@ impl<I: Clone + 'static> LinearChainSync<I> {
     pub fn new<REv: ReactorEventT<I>>(
         effect_builder: EffectBuilder<REv>,
         init_hash: Option<BlockHash>,
+        sync_mode: SyncMode,
+        validator_stakes: BTreeMap<PublicKey, Motes>,
+        finality_threshold_percent: u8,
     ) -> Self {
         LinearChainSync {
             peers: Vec::new(),
@@ -70,6 +119,12 @@ impl<I: Clone + 'static> LinearChainSync<I> {
             init_hash,
             init_block_era: None,
             highest_block_seen: 0,
+            sync_mode,
+            validator_stakes,
+            finality_threshold_percent,
+            light_sync: None,
+            trie_sync: None,
+            next_peer_index: 0,
         }
     }
 
@@ -129,6 +184,214 @@ impl<I: Clone + 'static> LinearChainSync<I> {
     pub(crate) fn init_block_era(&self) -> Option<EraId> {
         self.init_block_era
     }
+
+    /// Starts downloading the block headers from the downloaded trusted block.
+    fn start_light_sync<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        trusted_block: Block,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + Copy + 'static,
+        REv: ReactorEventT<I>,
+    {
+        info!("Trusted block downloaded. Starting downloading block headers.");
+        self.light_sync = Some(LightSync::new(
+            trusted_block.take_header(),
+            self.validator_stakes.clone(),
+            self.finality_threshold_percent,
+        ));
+        self.fetch_headers_batches(effect_builder)
+    }
+
+    /// Requests batches of block headers from the peers in turn, as long as more are needed.
+    fn fetch_headers_batches<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + Copy + 'static,
+        REv: ReactorEventT<I>,
+    {
+        let mut effects = Effects::new();
+        let light_sync = match self.light_sync.as_mut() {
+            Some(light_sync) => light_sync,
+            None => return effects,
+        };
+        while let Some(batch_id) = light_sync.next_batch() {
+            let peer = self.peers[self.next_peer_index % self.peers.len()];
+            self.next_peer_index += 1;
+            effects.extend(fetch_headers_batch(effect_builder, peer, batch_id));
+        }
+        effects
+    }
+
+    /// Returns the global state hash of the highest verified block, to be synchronized by the fast
+    /// synchronization.
+    fn trie_sync_target(&self) -> Option<Digest> {
+        let light_sync = self.light_sync.as_ref()?;
+        light_sync
+            .verified_header(light_sync.highest_verified())
+            .map(|header| *header.global_state_hash())
+    }
+
+    /// Starts synchronizing the global state of the highest verified block, resuming from the
+    /// tries already stored.
+    fn start_trie_sync<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        height: u64,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + Copy + 'static,
+        REv: ReactorEventT<I>,
+    {
+        let global_state_hash = match self.trie_sync_target() {
+            Some(global_state_hash) => global_state_hash,
+            None => return Effects::new(),
+        };
+        info!(%height, %global_state_hash, "Looking for missing global state.");
+        effect_builder
+            .find_missing_tries(global_state_hash)
+            .event(Event::MissingTriesFound)
+    }
+
+    /// Requests the missing tries from the peers in turn, or looks for any still missing once they
+    /// have all been stored.
+    fn fetch_tries<REv>(&mut self, effect_builder: EffectBuilder<REv>) -> Effects<Event<I>>
+    where
+        I: Send + Copy + 'static,
+        REv: ReactorEventT<I>,
+    {
+        let mut effects = Effects::new();
+        let trie_sync = match self.trie_sync.as_mut() {
+            Some(trie_sync) => trie_sync,
+            None => return effects,
+        };
+        if trie_sync.is_complete() {
+            info!(
+                global_state_hash = %trie_sync.global_state_hash(),
+                stored_count = trie_sync.stored_count(),
+                "Global state downloaded. Checking it is complete."
+            );
+            let global_state_hash = trie_sync.global_state_hash();
+            self.trie_sync = None;
+            return effect_builder
+                .find_missing_tries(global_state_hash)
+                .event(Event::MissingTriesFound);
+        }
+        while let Some(trie_key) = trie_sync.next_trie() {
+            let peer = self.peers[self.next_peer_index % self.peers.len()];
+            self.next_peer_index += 1;
+            effects.extend(fetch_trie(effect_builder, peer, trie_key));
+        }
+        effects
+    }
+
+    /// Requests the trie which failed to be downloaded or stored again from the next peer, or after
+    /// a delay if it failed with each of them.
+    fn retry_trie<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        trie_key: Blake2bHash,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + Copy + 'static,
+        REv: ReactorEventT<I>,
+    {
+        let trie_sync = match self.trie_sync.as_mut() {
+            Some(trie_sync) => trie_sync,
+            None => return Effects::new(),
+        };
+        if trie_sync.trie_failed(trie_key, self.peers.len()) {
+            warn!(%trie_key, "Could not get trie from any of the peers. Retrying later.");
+            return effect_builder
+                .set_timeout(TRIE_RETRY_DELAY)
+                .event(move |_| Event::RetryTrie(trie_key));
+        }
+        self.fetch_tries(effect_builder)
+    }
+
+    /// Downloads the highest verified block once its global state is complete.
+    fn fetch_synced_block<R, REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut R,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + Copy + 'static,
+        R: Rng + CryptoRng + ?Sized,
+        REv: ReactorEventT<I>,
+    {
+        let block_hash = match self
+            .light_sync
+            .as_ref()
+            .and_then(|light_sync| light_sync.verified_header(light_sync.highest_verified()))
+        {
+            Some(header) => header.hash(),
+            None => return Effects::new(),
+        };
+        self.reset_peers();
+        let peer = self.random_peer_unsafe(rng);
+        fetch_block_body(effect_builder, peer, block_hash)
+    }
+
+    /// Adds the block whose global state has been synchronized to the linear chain, without
+    /// executing it.  Consensus is first brought to the block's era by handing it the headers of
+    /// the switch blocks below it.
+    fn add_synced_block<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        block: Block,
+    ) -> Effects<Event<I>>
+    where
+        REv: ReactorEventT<I>,
+    {
+        self.init_block_era = Some(block.era_id());
+        let switch_block_headers = match self.light_sync.as_ref() {
+            Some(light_sync) => light_sync.switch_block_headers(block.height()),
+            None => Vec::new(),
+        };
+        info!(block_hash = %block.hash(), "Adding synchronized block to the linear chain.");
+        async move {
+            for header in switch_block_headers {
+                let _ = effect_builder.handle_linear_chain_block(header).await;
+            }
+            effect_builder
+                .announce_linear_chain_block(block, HashMap::new())
+                .await
+        }
+        .ignore()
+    }
+
+    /// Downloads the next block to be executed during light synchronization.
+    fn fetch_next_block_body<R, REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut R,
+    ) -> Effects<Event<I>>
+    where
+        I: Send + Copy + 'static,
+        R: Rng + CryptoRng + ?Sized,
+        REv: ReactorEventT<I>,
+    {
+        match self
+            .light_sync
+            .as_ref()
+            .and_then(LightSync::next_block_hash)
+        {
+            None => {
+                // We're done syncing but we have to wait for consensus to handle all blocks.
+                info!("Finished executing verified linear chain blocks.");
+                Effects::new()
+            }
+            Some(block_hash) => {
+                self.reset_peers();
+                let peer = self.random_peer_unsafe(rng);
+                fetch_block_body(effect_builder, peer, block_hash)
+            }
+        }
+    }
 }
 
 impl<I, REv, R> Component<REv, R> for LinearChainSync<I>
@@ -208,8 +471,19 @@ where
                             Event::GetBlockResult(block_hash, None),
                         );
                     }
+                    if let Err(error) = block.verify() {
+                        warn!(%block_hash, %peer, %error, "Received invalid block.");
+                        return self.handle_event(
+                            effect_builder,
+                            rng,
+                            Event::GetBlockResult(block_hash, None),
+                        );
+                    }
                     trace!(%block_hash, "Downloaded linear chain block.");
                     self.reset_peers();
+                    if self.sync_mode != SyncMode::Full {
+                        return self.start_light_sync(effect_builder, *block);
+                    }
                     self.new_block(*block.clone());
                     let curr_height = block.height();
                     // We instantiate with `highest_block_seen=0`, start downloading with the
@@ -237,8 +511,13 @@ where
                 // Reset used peers so we can download next block with the full set.
                 self.reset_peers();
                 // Execute block
-                // Download next block deploys.
-                let mut effects = self.fetch_next_block_deploys(effect_builder, rng);
+                // Download next block deploys, unless light syncing, where the next block is only
+                // downloaded once this one has been executed and checked.
+                let mut effects = if self.light_sync.is_some() {
+                    Effects::new()
+                } else {
+                    self.fetch_next_block_deploys(effect_builder, rng)
+                };
                 let finalized_block: FinalizedBlock = (*block).into();
                 let execute_block_effect = effect_builder
                     .execute_block(finalized_block)
@@ -280,6 +559,173 @@ where
                 }
                 Effects::new()
             }
+            Event::GetHeadersBatchResult(batch_id, fetch_result) => {
+                let maybe_batch = fetch_result.map(|fetch_result| match fetch_result {
+                    FetchResult::FromStorage(batch) | FetchResult::FromPeer(batch, _) => *batch,
+                });
+                let light_sync = match self.light_sync.as_mut() {
+                    Some(light_sync) => light_sync,
+                    None => {
+                        warn!(%batch_id, "Unexpected block headers batch.");
+                        return Effects::new();
+                    }
+                };
+                if let Err(error) = light_sync.batch_fetched(batch_id, maybe_batch) {
+                    error!(%error, "Could not download block headers from any of the peers.");
+                    panic!("Failed to download linear chain headers.")
+                }
+                if light_sync.start_bodies() {
+                    let highest_verified = light_sync.highest_verified();
+                    info!(
+                        %highest_verified,
+                        "Block headers verified. Starting downloading blocks."
+                    );
+                    self.highest_block_seen = highest_verified;
+                    if self.sync_mode == SyncMode::Fast {
+                        return self.start_trie_sync(effect_builder, highest_verified);
+                    }
+                    return self.fetch_next_block_body(effect_builder, rng);
+                }
+                self.fetch_headers_batches(effect_builder)
+            }
+            Event::GetBlockBodyResult(block_hash, fetch_result) => match fetch_result {
+                None => match self.random_peer(rng) {
+                    None => {
+                        error!(%block_hash, "Could not download linear block from any of the peers.");
+                        panic!("Failed to download linear chain.")
+                    }
+                    Some(peer) => fetch_block_body(effect_builder, peer, block_hash),
+                },
+                Some(FetchResult::FromStorage(block)) | Some(FetchResult::FromPeer(block, _)) => {
+                    if block.header().hash() != block_hash {
+                        warn!(%block_hash, "Block header doesn't match verified block hash.");
+                        return self.handle_event(
+                            effect_builder,
+                            rng,
+                            Event::GetBlockBodyResult(block_hash, None),
+                        );
+                    }
+                    if let Err(error) = block.verify() {
+                        warn!(%block_hash, %error, "Received invalid block.");
+                        return self.handle_event(
+                            effect_builder,
+                            rng,
+                            Event::GetBlockBodyResult(block_hash, None),
+                        );
+                    }
+                    trace!(%block_hash, "Downloaded verified linear chain block.");
+                    self.reset_peers();
+                    if self.sync_mode == SyncMode::Fast {
+                        return self.add_synced_block(effect_builder, *block);
+                    }
+                    let peer = self.random_peer_unsafe(rng);
+                    fetch_block_deploys(effect_builder, peer, *block)
+                }
+            },
+            Event::BlockExecuted(block) => {
+                let light_sync = match self.light_sync.as_mut() {
+                    Some(light_sync) if self.sync_mode == SyncMode::Light => light_sync,
+                    // The block synchronized by the fast synchronization is not executed.
+                    _ => return Effects::new(),
+                };
+                let height = block.height();
+                let header = match light_sync.verified_header(height) {
+                    Some(header) => header,
+                    // Not a block being synchronized.
+                    None => return Effects::new(),
+                };
+                if header.global_state_hash() != block.global_state_hash() {
+                    error!(
+                        block_hash = %block.hash(),
+                        expected = %header.global_state_hash(),
+                        computed = %block.global_state_hash(),
+                        "Global state hash of executed block doesn't match its verified header."
+                    );
+                    panic!("Failed to execute linear chain.")
+                }
+                light_sync.block_executed(height);
+                self.fetch_next_block_body(effect_builder, rng)
+            }
+            Event::MissingTriesFound(result) => {
+                let global_state_hash = match self.trie_sync_target() {
+                    Some(global_state_hash) => global_state_hash,
+                    None => return Effects::new(),
+                };
+                match result {
+                    Ok(missing_tries) if missing_tries.is_empty() => {
+                        info!(
+                            %global_state_hash,
+                            "Global state synchronized. Downloading its block."
+                        );
+                        self.fetch_synced_block(effect_builder, rng)
+                    }
+                    Ok(missing_tries) => {
+                        info!(
+                            %global_state_hash,
+                            missing_count = missing_tries.len(),
+                            "Starting downloading global state."
+                        );
+                        self.trie_sync = Some(TrieSync::new(global_state_hash, missing_tries));
+                        self.fetch_tries(effect_builder)
+                    }
+                    Err(error) => {
+                        error!(
+                            %global_state_hash,
+                            %error,
+                            "Could not read global state. Retrying later."
+                        );
+                        effect_builder
+                            .set_timeout(TRIE_RETRY_DELAY)
+                            .event(|_| Event::RetryFindMissingTries)
+                    }
+                }
+            }
+            Event::RetryFindMissingTries => match self.trie_sync_target() {
+                Some(global_state_hash) => effect_builder
+                    .find_missing_tries(global_state_hash)
+                    .event(Event::MissingTriesFound),
+                None => Effects::new(),
+            },
+            Event::GetTrieResult(trie_key, fetch_result) => {
+                let maybe_trie = fetch_result.map(|fetch_result| match fetch_result {
+                    FetchResult::FromStorage(trie) | FetchResult::FromPeer(trie, _) => *trie,
+                });
+                let trie_sync = match self.trie_sync.as_ref() {
+                    Some(trie_sync) => trie_sync,
+                    None => {
+                        warn!(%trie_key, "Unexpected trie.");
+                        return Effects::new();
+                    }
+                };
+                match trie_sync.trie_fetched(trie_key, maybe_trie) {
+                    Some(trie) => effect_builder
+                        .put_trie(trie)
+                        .event(move |result| Event::TrieStored(trie_key, result)),
+                    None => self.retry_trie(effect_builder, trie_key),
+                }
+            }
+            Event::TrieStored(trie_key, result) => {
+                let trie_sync = match self.trie_sync.as_mut() {
+                    Some(trie_sync) => trie_sync,
+                    None => return Effects::new(),
+                };
+                match result {
+                    Ok(missing_children) => {
+                        trie_sync.trie_stored(trie_key, missing_children);
+                        self.fetch_tries(effect_builder)
+                    }
+                    Err(error) => {
+                        error!(%trie_key, %error, "Could not store trie.");
+                        self.retry_trie(effect_builder, trie_key)
+                    }
+                }
+            }
+            Event::RetryTrie(trie_key) => {
+                if let Some(trie_sync) = self.trie_sync.as_mut() {
+                    trie_sync.retry_trie(trie_key);
+                }
+                self.fetch_tries(effect_builder)
+            }
         }
     }
 }
@@ -303,6 +749,45 @@ where
         })
 }
 
+fn fetch_headers_batch<I: Send + Copy + 'static, REv>(
+    effect_builder: EffectBuilder<REv>,
+    peer: I,
+    batch_id: BlockHeadersBatchId,
+) -> Effects<Event<I>>
+where
+    REv: ReactorEventT<I>,
+{
+    effect_builder
+        .fetch_block_headers_batch(batch_id, peer)
+        .event(move |result| Event::GetHeadersBatchResult(batch_id, result))
+}
+
+fn fetch_trie<I: Send + Copy + 'static, REv>(
+    effect_builder: EffectBuilder<REv>,
+    peer: I,
+    trie_key: Blake2bHash,
+) -> Effects<Event<I>>
+where
+    REv: ReactorEventT<I>,
+{
+    effect_builder
+        .fetch_trie(trie_key, peer)
+        .event(move |result| Event::GetTrieResult(trie_key, result))
+}
+
+fn fetch_block_body<I: Send + Copy + 'static, REv>(
+    effect_builder: EffectBuilder<REv>,
+    peer: I,
+    block_hash: BlockHash,
+) -> Effects<Event<I>>
+where
+    REv: ReactorEventT<I>,
+{
+    effect_builder
+        .fetch_block(block_hash, peer)
+        .event(move |result| Event::GetBlockBodyResult(block_hash, result))
+}
+
 fn fetch_block<I: Send + Copy + 'static, REv>(
     effect_builder: EffectBuilder<REv>,
     peer: I,
@@ -1,7 +1,8 @@
 use crate::{
     components::fetcher::FetchResult,
-    types::{Block, BlockHash},
+    types::{Block, BlockHash, BlockHeadersBatch, BlockHeadersBatchId, Trie},
 };
+use casper_execution_engine::{core::engine_state, shared::newtypes::Blake2bHash};
 use std::fmt::Display;
 
 #[derive(Debug)]
@@ -16,6 +17,22 @@ pub enum Event<I> {
     NewPeerConnected(I),
     BlockExecutionDone(BlockHash, u64),
     BlockHandled(u64),
+    /// The result of fetching a batch of block headers during light synchronization.
+    GetHeadersBatchResult(BlockHeadersBatchId, Option<FetchResult<BlockHeadersBatch>>),
+    /// The result of fetching a block to be executed during light synchronization.
+    GetBlockBodyResult(BlockHash, Option<FetchResult<Block>>),
+    /// A block has been executed.
+    BlockExecuted(Box<Block>),
+    /// The result of looking for the tries missing from the global state being synchronized.
+    MissingTriesFound(Result<Vec<Blake2bHash>, engine_state::Error>),
+    /// Looking for the missing tries failed earlier and should be retried.
+    RetryFindMissingTries,
+    /// The result of fetching a trie during global state synchronization.
+    GetTrieResult(Blake2bHash, Option<FetchResult<Trie>>),
+    /// The result of storing a trie, i.e. the hashes of its children which are still missing.
+    TrieStored(Blake2bHash, Result<Vec<Blake2bHash>, engine_state::Error>),
+    /// A trie failed with each peer earlier and should be requested again.
+    RetryTrie(Blake2bHash),
 }
 
 impl<I> Display for Event<I>
@@ -40,6 +57,31 @@ where
             Event::BlockHandled(height) => {
                 write!(f, "Block has been handled by consensus {}", height)
             }
+            Event::GetHeadersBatchResult(batch_id, r) => {
+                write!(f, "Get headers batch result for {}: {:?}", batch_id, r)
+            }
+            Event::GetBlockBodyResult(block_hash, r) => {
+                write!(f, "Get block body result for {}: {:?}", block_hash, r)
+            }
+            Event::BlockExecuted(block) => write!(f, "Block executed: {}", block.hash()),
+            Event::MissingTriesFound(result) => match result {
+                Ok(missing_tries) => write!(f, "Missing tries found: {}", missing_tries.len()),
+                Err(error) => write!(f, "Failed to find missing tries: {}", error),
+            },
+            Event::RetryFindMissingTries => write!(f, "Retry finding missing tries"),
+            Event::GetTrieResult(trie_key, r) => {
+                write!(f, "Get trie result for {}: {:?}", trie_key, r)
+            }
+            Event::TrieStored(trie_key, result) => match result {
+                Ok(missing_children) => write!(
+                    f,
+                    "Trie stored: {}, missing children: {}",
+                    trie_key,
+                    missing_children.len()
+                ),
+                Err(error) => write!(f, "Failed to store trie {}: {}", trie_key, error),
+            },
+            Event::RetryTrie(trie_key) => write!(f, "Retry getting trie {}", trie_key),
         }
     }
 }
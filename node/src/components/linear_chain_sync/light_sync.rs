@@ -0,0 +1,349 @@
+//! State of the light synchronization of the linear chain.
+//!
+//! Starting from the trusted block, the signed headers of the whole linear chain are downloaded in
+//! batches from several peers in parallel.  The headers below the trusted block are verified by
+//! following the parent hashes down to the genesis child.  The headers above it are verified by
+//! checking the era transitions and the finality signatures of each era's validators, up to the
+//! last block with enough signatures.  Only then are the block bodies downloaded and executed in
+//! order, and the resulting blocks checked against the verified headers.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use casper_execution_engine::shared::motes::Motes;
+use casper_types::U512;
+
+use crate::{
+    components::consensus::EraId,
+    crypto::asymmetric_key::PublicKey,
+    types::{
+        BlockHash, BlockHeader, BlockHeadersBatch, BlockHeadersBatchId, FinalitySignature,
+        SignedBlockHeader,
+    },
+};
+
+/// Number of block headers requested in a single batch.
+const BATCH_SIZE: u64 = 128;
+/// Maximum number of batches being downloaded at the same time.
+const MAX_BATCHES_IN_FLIGHT: usize = 8;
+/// Maximum number of attempts to download and verify a single batch.
+const MAX_BATCH_ATTEMPTS: usize = 10;
+
+/// Error returned when the light synchronization cannot proceed.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// No peer provided a valid batch of block headers.
+    #[error("failed to download valid {0} from peers")]
+    BatchUnavailable(BlockHeadersBatchId),
+}
+
+/// Returns the height of the first block header of the batch containing the given height.
+fn batch_start(height: u64) -> u64 {
+    height - height % BATCH_SIZE
+}
+
+#[derive(Debug)]
+pub(super) struct LightSync {
+    /// Height of the trusted block.
+    trusted_height: u64,
+    /// The validators along with their stakes.  The validator set doesn't change between eras yet.
+    validator_stakes: BTreeMap<PublicKey, Motes>,
+    /// The fault tolerance threshold, as a percentage of the total weight of an era's validators.
+    finality_threshold_percent: u8,
+    /// Start heights of the batches to be requested, in addition to the ones past the trusted
+    /// block.
+    batches_to_fetch: BTreeSet<u64>,
+    /// Start height of the next batch past the trusted block to be requested.
+    next_forward_batch: u64,
+    /// Number of batches currently being downloaded.
+    batches_in_flight: usize,
+    /// Number of failed attempts to download each batch.
+    failed_attempts: HashMap<u64, usize>,
+    /// Downloaded headers which have not been verified yet.
+    unverified_headers: BTreeMap<u64, SignedBlockHeader>,
+    /// Verified headers, by height.
+    verified_headers: BTreeMap<u64, BlockHeader>,
+    /// Height of the lowest verified header.
+    lowest_verified: u64,
+    /// Height of the highest verified header.
+    highest_verified: u64,
+    /// Height of the last header a peer had, if any peer has run out of headers.
+    tip_height: Option<u64>,
+    /// Whether the highest verifiable header has been verified.
+    tip_reached: bool,
+    /// Whether downloading the blocks has been started.
+    bodies_started: bool,
+    /// Height of the next block to be downloaded and executed.
+    next_body_height: u64,
+}
+
+impl LightSync {
+    pub(super) fn new(
+        trusted_header: BlockHeader,
+        validator_stakes: BTreeMap<PublicKey, Motes>,
+        finality_threshold_percent: u8,
+    ) -> Self {
+        let trusted_height = trusted_header.height();
+        let trusted_batch_start = batch_start(trusted_height);
+        let batches_to_fetch = (0..=trusted_batch_start)
+            .step_by(BATCH_SIZE as usize)
+            .collect();
+        let mut verified_headers = BTreeMap::new();
+        let _ = verified_headers.insert(trusted_height, trusted_header);
+        LightSync {
+            trusted_height,
+            validator_stakes,
+            finality_threshold_percent,
+            batches_to_fetch,
+            next_forward_batch: trusted_batch_start + BATCH_SIZE,
+            batches_in_flight: 0,
+            failed_attempts: HashMap::new(),
+            unverified_headers: BTreeMap::new(),
+            verified_headers,
+            lowest_verified: trusted_height,
+            highest_verified: trusted_height,
+            tip_height: None,
+            tip_reached: false,
+            bodies_started: false,
+            next_body_height: 0,
+        }
+    }
+
+    /// Returns the next batch to be requested, if any more are needed and not too many are being
+    /// downloaded already.
+    pub(super) fn next_batch(&mut self) -> Option<BlockHeadersBatchId> {
+        if self.batches_in_flight >= MAX_BATCHES_IN_FLIGHT {
+            return None;
+        }
+        let from_height = match self.batches_to_fetch.iter().next().copied() {
+            Some(from_height) => {
+                let _ = self.batches_to_fetch.remove(&from_height);
+                from_height
+            }
+            None if self.tip_height.is_none() && !self.tip_reached => {
+                let from_height = self.next_forward_batch;
+                self.next_forward_batch += BATCH_SIZE;
+                from_height
+            }
+            None => return None,
+        };
+        self.batches_in_flight += 1;
+        Some(BlockHeadersBatchId::new(from_height, BATCH_SIZE))
+    }
+
+    /// Handles the result of downloading a batch, verifying as many headers as possible.
+    pub(super) fn batch_fetched(
+        &mut self,
+        batch_id: BlockHeadersBatchId,
+        maybe_batch: Option<BlockHeadersBatch>,
+    ) -> Result<(), Error> {
+        self.batches_in_flight -= 1;
+        let batch = match maybe_batch {
+            Some(batch) => batch,
+            None => return self.retry_batch(batch_id.from_height),
+        };
+        let is_consecutive = batch
+            .headers()
+            .iter()
+            .zip(batch_id.from_height..)
+            .all(|(signed_header, height)| signed_header.header.height() == height);
+        let returned_count = batch.headers().len() as u64;
+        if !is_consecutive || returned_count > batch_id.count {
+            warn!(%batch_id, "received malformed block headers batch");
+            return self.retry_batch(batch_id.from_height);
+        }
+        if returned_count < batch_id.count {
+            // Every peer should have the trusted block and all of its ancestors.
+            if batch_id.from_height + returned_count <= self.trusted_height {
+                return self.retry_batch(batch_id.from_height);
+            }
+            let tip_height = batch_id.from_height + returned_count - 1;
+            debug!(%tip_height, "reached the tip of a peer's linear chain");
+            self.tip_height = Some(
+                self.tip_height
+                    .map_or(tip_height, |tip| tip.min(tip_height)),
+            );
+        }
+        for signed_header in batch.into_headers() {
+            let height = signed_header.header.height();
+            if !self.verified_headers.contains_key(&height) {
+                let _ = self.unverified_headers.insert(height, signed_header);
+            }
+        }
+        self.verify_ancestors()?;
+        self.verify_descendants()
+    }
+
+    /// Returns `true` exactly once: as soon as all the headers have been downloaded and verified,
+    /// so that downloading the blocks can start.
+    pub(super) fn start_bodies(&mut self) -> bool {
+        if self.bodies_started || self.lowest_verified != 0 || !self.tip_reached {
+            return false;
+        }
+        self.bodies_started = true;
+        true
+    }
+
+    /// Height of the highest verified header.
+    pub(super) fn highest_verified(&self) -> u64 {
+        self.highest_verified
+    }
+
+    /// Returns the hash of the next block to be downloaded and executed, if any.
+    pub(super) fn next_block_hash(&self) -> Option<BlockHash> {
+        self.verified_headers
+            .get(&self.next_body_height)
+            .map(BlockHeader::hash)
+    }
+
+    /// Returns the verified header of the block at the given height, if any.
+    pub(super) fn verified_header(&self, height: u64) -> Option<&BlockHeader> {
+        self.verified_headers.get(&height)
+    }
+
+    /// Returns the verified headers of the switch blocks below the given height, in order.
+    pub(super) fn switch_block_headers(&self, height: u64) -> Vec<BlockHeader> {
+        self.verified_headers
+            .range(..height)
+            .map(|(_, header)| header)
+            .filter(|header| header.switch_block())
+            .cloned()
+            .collect()
+    }
+
+    /// Marks the block at the given height as executed.
+    pub(super) fn block_executed(&mut self, height: u64) {
+        self.next_body_height = height + 1;
+    }
+
+    /// Puts the batch back to be requested again, or returns an error if it failed too often.
+    fn retry_batch(&mut self, from_height: u64) -> Result<(), Error> {
+        let attempts = self.failed_attempts.entry(from_height).or_default();
+        *attempts += 1;
+        if *attempts >= MAX_BATCH_ATTEMPTS {
+            return Err(Error::BatchUnavailable(BlockHeadersBatchId::new(
+                from_height,
+                BATCH_SIZE,
+            )));
+        }
+        let _ = self.batches_to_fetch.insert(from_height);
+        Ok(())
+    }
+
+    /// Drops the unverified headers of the batch containing the given height and requests it
+    /// again.
+    fn reject_batch(&mut self, height: u64) -> Result<(), Error> {
+        let from_height = batch_start(height);
+        let to_height = from_height + BATCH_SIZE;
+        let rejected: Vec<u64> = self
+            .unverified_headers
+            .range(from_height..to_height)
+            .map(|(height, _)| *height)
+            .collect();
+        for height in rejected {
+            let _ = self.unverified_headers.remove(&height);
+        }
+        self.retry_batch(from_height)
+    }
+
+    /// Verifies the downloaded headers below the lowest verified one by following parent hashes.
+    fn verify_ancestors(&mut self) -> Result<(), Error> {
+        while self.lowest_verified > 0 {
+            let height = self.lowest_verified - 1;
+            let signed_header = match self.unverified_headers.remove(&height) {
+                Some(signed_header) => signed_header,
+                None => return Ok(()),
+            };
+            let child = &self.verified_headers[&self.lowest_verified];
+            if signed_header.header.hash() != *child.parent_hash() {
+                warn!(%height, "block header doesn't match its child's parent hash");
+                return self.reject_batch(height);
+            }
+            let _ = self.verified_headers.insert(height, signed_header.header);
+            self.lowest_verified = height;
+        }
+        Ok(())
+    }
+
+    /// Verifies the downloaded headers above the highest verified one by checking their parent
+    /// hashes, era transitions and finality signatures.
+    fn verify_descendants(&mut self) -> Result<(), Error> {
+        while !self.tip_reached {
+            if self
+                .tip_height
+                .map_or(false, |tip_height| self.highest_verified >= tip_height)
+            {
+                self.tip_reached = true;
+                break;
+            }
+            let height = self.highest_verified + 1;
+            let signed_header = match self.unverified_headers.remove(&height) {
+                Some(signed_header) => signed_header,
+                None => return Ok(()),
+            };
+            let parent = &self.verified_headers[&self.highest_verified];
+            if *signed_header.header.parent_hash() != parent.hash() {
+                warn!(%height, "block header doesn't match its parent's hash");
+                return self.reject_batch(height);
+            }
+            let expected_era_id = if parent.switch_block() {
+                parent.era_id().successor()
+            } else {
+                parent.era_id()
+            };
+            if signed_header.header.era_id() != expected_era_id {
+                warn!(%height, "block header has an invalid era transition");
+                return self.reject_batch(height);
+            }
+            match self.is_finalized(&signed_header) {
+                Ok(true) => {
+                    let _ = self.verified_headers.insert(height, signed_header.header);
+                    self.highest_verified = height;
+                }
+                Ok(false) => {
+                    debug!(%height, "block header is not signed by enough validators yet");
+                    self.tip_reached = true;
+                }
+                Err(()) => {
+                    warn!(%height, "block header has an invalid finality signature");
+                    return self.reject_batch(height);
+                }
+            }
+        }
+        // Only headers below the lowest verified one are still needed.
+        let lowest_verified = self.lowest_verified;
+        self.unverified_headers
+            .retain(|height, _| *height < lowest_verified);
+        self.batches_to_fetch
+            .retain(|from_height| *from_height < lowest_verified);
+        Ok(())
+    }
+
+    /// Returns the validators of the given era along with their stakes.
+    fn era_validators(&self, _era_id: EraId) -> &BTreeMap<PublicKey, Motes> {
+        &self.validator_stakes
+    }
+
+    /// Returns whether the block is signed by validators whose total weight exceeds the fault
+    /// tolerance threshold, or an error if any of the signatures are invalid.
+    fn is_finalized(&self, signed_header: &SignedBlockHeader) -> Result<bool, ()> {
+        let block_hash = signed_header.header.hash();
+        let era_id = signed_header.header.era_id();
+        let validator_stakes = self.era_validators(era_id);
+        let mut signed_weight = U512::zero();
+        for (public_key, signature) in &signed_header.proofs {
+            let stake = validator_stakes.get(public_key).ok_or(())?;
+            FinalitySignature::new(block_hash, era_id, *signature, *public_key)
+                .verify()
+                .map_err(|_| ())?;
+            signed_weight += stake.value();
+        }
+        let total_weight: Motes = validator_stakes.values().copied().sum();
+        Ok(
+            signed_weight * 100
+                > total_weight.value() * U512::from(self.finality_threshold_percent),
+        )
+    }
+}
@@ -0,0 +1,131 @@
+//! State of the synchronization of the global state of a single block.
+//!
+//! The tries reachable from the block's global state hash which are not in the local trie store
+//! yet are downloaded from several peers in parallel, in breadth-first order.  Each trie is checked
+//! against the hash it was requested by before it is stored, and the children it points to which
+//! are still missing are queued in turn.  A trie which can't be downloaded or stored is requested
+//! again from the next peer, after a delay once it failed with each of them.
+//!
+//! The contract runtime only makes the global state available once it is complete, so an
+//! interrupted synchronization is resumed by looking for the missing tries again, which only
+//! traverses the tries downloaded so far.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use tracing::warn;
+
+use casper_execution_engine::shared::newtypes::Blake2bHash;
+
+use crate::{crypto::hash::Digest, types::Trie};
+
+/// Maximum number of tries being downloaded or stored at the same time.
+const MAX_TRIES_IN_FLIGHT: usize = 64;
+
+#[derive(Debug)]
+pub(super) struct TrieSync {
+    /// The global state hash being synchronized.
+    global_state_hash: Digest,
+    /// Hashes of the tries to be requested, in breadth-first order.
+    tries_to_fetch: VecDeque<Blake2bHash>,
+    /// Hashes of the tries which are queued, being downloaded or being stored.
+    pending: HashSet<Blake2bHash>,
+    /// Number of tries currently being downloaded or stored.
+    tries_in_flight: usize,
+    /// Number of failed attempts to download or store each trie.
+    failed_attempts: HashMap<Blake2bHash, usize>,
+    /// Number of tries stored so far.
+    stored_count: usize,
+}
+
+impl TrieSync {
+    /// Creates the synchronization state, given the tries found to be missing from the trie store.
+    pub(super) fn new(global_state_hash: Digest, missing_tries: Vec<Blake2bHash>) -> Self {
+        let pending = missing_tries.iter().copied().collect();
+        TrieSync {
+            global_state_hash,
+            tries_to_fetch: missing_tries.into(),
+            pending,
+            tries_in_flight: 0,
+            failed_attempts: HashMap::new(),
+            stored_count: 0,
+        }
+    }
+
+    /// The global state hash being synchronized.
+    pub(super) fn global_state_hash(&self) -> Digest {
+        self.global_state_hash
+    }
+
+    /// Returns the next trie to be requested, if any more are needed and not too many are being
+    /// downloaded already.
+    pub(super) fn next_trie(&mut self) -> Option<Blake2bHash> {
+        if self.tries_in_flight >= MAX_TRIES_IN_FLIGHT {
+            return None;
+        }
+        let trie_key = self.tries_to_fetch.pop_front()?;
+        self.tries_in_flight += 1;
+        Some(trie_key)
+    }
+
+    /// Handles the result of downloading a trie.  Returns the trie if it is valid and should be
+    /// stored.
+    pub(super) fn trie_fetched(
+        &self,
+        trie_key: Blake2bHash,
+        maybe_trie: Option<Trie>,
+    ) -> Option<Trie> {
+        match maybe_trie {
+            Some(trie) if trie.hash() == trie_key => Some(trie),
+            Some(_) => {
+                warn!(%trie_key, "received trie doesn't match its hash");
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Marks the trie as stored and queues the children it points to which are still missing.
+    pub(super) fn trie_stored(
+        &mut self,
+        trie_key: Blake2bHash,
+        missing_children: Vec<Blake2bHash>,
+    ) {
+        self.tries_in_flight -= 1;
+        self.stored_count += 1;
+        let _ = self.pending.remove(&trie_key);
+        for child in missing_children {
+            if self.pending.insert(child) {
+                self.tries_to_fetch.push_back(child);
+            }
+        }
+    }
+
+    /// Returns `true` if all the tries have been downloaded and stored.
+    pub(super) fn is_complete(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Number of tries stored so far.
+    pub(super) fn stored_count(&self) -> usize {
+        self.stored_count
+    }
+
+    /// Handles a failure to download or store the trie, which is put back to be requested again
+    /// from the next peer.  Returns `true` each time it failed as often as there are peers, in
+    /// which case it is only put back by `retry_trie`, e.g. after a delay.
+    pub(super) fn trie_failed(&mut self, trie_key: Blake2bHash, peer_count: usize) -> bool {
+        let attempts = self.failed_attempts.entry(trie_key).or_default();
+        *attempts += 1;
+        if *attempts % peer_count.max(1) == 0 {
+            return true;
+        }
+        self.retry_trie(trie_key);
+        false
+    }
+
+    /// Puts the trie back to be requested again, ahead of the others.
+    pub(super) fn retry_trie(&mut self, trie_key: Blake2bHash) {
+        self.tries_in_flight -= 1;
+        self.tries_to_fetch.push_front(trie_key);
+    }
+}
@@ -1,9 +1,11 @@
-//! Fully connected overlay network
+//! Overlay network
 //!
-//! The *small network* is an overlay network where each node participating is connected to every
-//! other node on the network. The *small* portion of the name stems from the fact that this
-//! approach is not scalable, as it requires at least $O(n)$ network connections and broadcast will
-//! result in $O(n^2)$ messages.
+//! By default, the *small network* is an overlay network where each node participating is
+//! connected to every other node on the network. The *small* portion of the name stems from the
+//! fact that this approach is not scalable, as it requires at least $O(n)$ network connections and
+//! broadcast will result in $O(n^2)$ messages.
+//!
+//! For larger networks, a bounded-degree overlay can be configured instead, see below.
 //!
 //! # Node IDs
 //!
@@ -28,15 +30,44 @@
 //! Nodes gossip their public listening addresses periodically, and on learning of a new address,
 //! a node will try to establish an outgoing connection.
 //!
+//! # Bounded-degree overlay
+//!
+//! If `overlay_degree` is configured, each node only initiates connections until it has that many
+//! outgoing connections, to peers chosen at random from the addresses it learned through gossip.
+//! Peers connecting to us are still connected back to, so every link is used in both directions.
+//! Whenever a connection is lost, and periodically, the node tops up its outgoing connections from
+//! its address book, dropping addresses it fails to connect to.
+//!
+//! Broadcasts are flooded through the overlay: each broadcast carries a random ID, and every node
+//! forwards it to all its peers the first time it sees it. Note that the receiving components see
+//! the neighbor that forwarded a broadcast as its sender, not the node that originated it.
+//!
 //! On losing an incoming or outgoing connection for a given peer, the other connection is closed.
 //! No explicit reconnect is attempted. Instead, if the peer is still online, the normal gossiping
 //! process will cause both peers to connect again.
+//!
+//! # Handshake
+//!
+//! The first message sent on every outgoing connection is a handshake, containing the chain name,
+//! protocol version and genesis hash from our chainspec, and our public listening address. Peers
+//! whose handshake doesn't match our own chain are disconnected, and are never announced to the
+//! other components.
+//!
+//! # Reputation
+//!
+//! Peers that send undecodable messages, exceed the incoming message rate limit, or are reported by
+//! other components for sending invalid messages are penalized. Once a peer's penalties reach the
+//! configured threshold, it is disconnected and banned for a limited time. Bans are enforced by
+//! node ID and by IP address, both for incoming and outgoing connections.
 
+mod chain_info;
 mod config;
 mod error;
 mod event;
+mod flooding;
 mod gossiped_address;
 mod message;
+mod reputation;
 #[cfg(test)]
 mod tests;
 
@@ -45,8 +76,9 @@ use std::{
     fmt::{self, Debug, Display, Formatter},
     io,
     net::{SocketAddr, TcpListener},
+    result,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::Context;
@@ -57,6 +89,7 @@ use futures::{
 };
 use openssl::pkey;
 use pkey::{PKey, Private};
+use prometheus::{IntCounter, IntCounterVec, Opts, Registry};
 use rand::{seq::IteratorRandom, CryptoRng, Rng};
 use serde::{de::DeserializeOwned, Serialize};
 use tokio::{
@@ -66,14 +99,21 @@ use tokio::{
         oneshot,
     },
     task::JoinHandle,
+    time,
 };
 use tokio_openssl::SslStream;
 use tokio_serde::{formats::SymmetricalMessagePack, SymmetricallyFramed};
 use tokio_util::codec::{Framed, LengthDelimitedCodec};
 use tracing::{debug, error, info, trace, warn};
 
-use self::error::Result;
-pub(crate) use self::{event::Event, gossiped_address::GossipedAddress, message::Message};
+pub(crate) use self::{
+    chain_info::ChainInfo, event::Event, gossiped_address::GossipedAddress, message::Message,
+};
+use self::{
+    error::Result,
+    flooding::{BroadcastId, SeenBroadcasts},
+    reputation::{BannedAddresses, RateLimiter, Reputations},
+};
 use crate::{
     components::Component,
     effect::{
@@ -84,16 +124,64 @@ use crate::{
     fatal,
     reactor::{EventQueueHandle, Finalize, QueueKind},
     tls::{self, KeyFingerprint, TlsCert},
+    types::Timestamp,
     utils,
 };
+pub use chain_info::HandshakeRejection;
 pub use config::Config;
 pub use error::Error;
+pub use reputation::{Misbehavior, PeerReputation};
 
 /// A node ID.
 ///
 /// The key fingerprint found on TLS certificates.
 pub(crate) type NodeId = KeyFingerprint;
 
+/// Metrics for the small network component.
+#[derive(Debug)]
+struct NetworkMetrics {
+    /// Number of peers whose handshake was rejected, by cause.
+    handshake_rejections: IntCounterVec,
+    /// Number of peers banned for misbehavior.
+    peers_banned: IntCounter,
+    /// Handle to the metrics registry, to unregister the metrics on drop.
+    registry: Registry,
+}
+
+impl NetworkMetrics {
+    /// Creates and registers the small network metrics.
+    fn new(registry: &Registry) -> result::Result<Self, prometheus::Error> {
+        let handshake_rejections = IntCounterVec::new(
+            Opts::new(
+                "net_handshake_rejections",
+                "number of peers whose handshake was rejected, by cause",
+            ),
+            &["cause"],
+        )?;
+        let peers_banned =
+            IntCounter::new("net_peers_banned", "number of peers banned for misbehavior")?;
+        registry.register(Box::new(handshake_rejections.clone()))?;
+        registry.register(Box::new(peers_banned.clone()))?;
+
+        Ok(NetworkMetrics {
+            handshake_rejections,
+            peers_banned,
+            registry: registry.clone(),
+        })
+    }
+}
+
+impl Drop for NetworkMetrics {
+    fn drop(&mut self) {
+        self.registry
+            .unregister(Box::new(self.handshake_rejections.clone()))
+            .expect("did not expect deregistering metrics to fail");
+        self.registry
+            .unregister(Box::new(self.peers_banned.clone()))
+            .expect("did not expect deregistering metrics to fail");
+    }
+}
+
 #[derive(Debug)]
 struct OutgoingConnection<P> {
     sender: UnboundedSender<Message<P>>,
@@ -109,9 +197,11 @@ pub(crate) struct SmallNetwork<REv: 'static, P> {
     public_address: SocketAddr,
     /// Our node ID,
     our_id: NodeId,
+    /// Information about our chain, sent to peers in the handshake.
+    chain_info: Arc<ChainInfo>,
     /// Handle to event queue.
     event_queue: EventQueueHandle<REv>,
-    /// Incoming network connection addresses.
+    /// Incoming network connection addresses, of peers whose handshake we accepted.
     incoming: HashMap<NodeId, SocketAddr>,
     /// Outgoing network connections' messages.
     outgoing: HashMap<NodeId, OutgoingConnection<P>>,
@@ -130,6 +220,18 @@ pub(crate) struct SmallNetwork<REv: 'static, P> {
     /// Join handle for the server thread.
     #[allow(dead_code)]
     server_join_handle: Option<JoinHandle<()>>,
+    /// The maximum number of messages per second accepted from a single peer.
+    max_incoming_message_rate: u32,
+    /// Peers' scores and bans.
+    reputations: Reputations,
+    /// The number of outgoing connections to maintain, or `None` for a full mesh.
+    overlay_degree: Option<usize>,
+    /// Public addresses of peers learned through gossip, handshakes and our configuration.
+    address_book: HashSet<SocketAddr>,
+    /// IDs of recently sent or received broadcasts.
+    seen_broadcasts: SeenBroadcasts,
+    /// Metrics for the small network.
+    metrics: NetworkMetrics,
 }
 
 impl<REv, P> SmallNetwork<REv, P>
@@ -141,7 +243,11 @@ where
     pub(crate) fn new(
         event_queue: EventQueueHandle<REv>,
         cfg: Config,
+        registry: &Registry,
+        chain_info: ChainInfo,
     ) -> Result<(SmallNetwork<REv, P>, Effects<Event<P>>)> {
+        let metrics = NetworkMetrics::new(registry)?;
+
         // First, we generate the TLS keys.
         let (cert, secret_key) = tls::generate_node_cert().map_err(Error::CertificateGeneration)?;
         let certificate = Arc::new(tls::validate_cert(cert).map_err(Error::OwnCertificateInvalid)?);
@@ -166,10 +272,12 @@ where
         let our_id = certificate.public_key_fingerprint();
         info!(%local_address, %public_address, "{}: starting server background task", our_id);
         let (server_shutdown_sender, server_shutdown_receiver) = oneshot::channel();
+        let reputations = Reputations::new(cfg.ban_threshold, cfg.ban_duration);
         let server_join_handle = tokio::spawn(server_task(
             event_queue,
             tokio::net::TcpListener::from_std(listener).map_err(Error::ListenerConversion)?,
             server_shutdown_receiver,
+            reputations.banned_addresses(),
             our_id,
         ));
 
@@ -178,6 +286,7 @@ where
             secret_key: Arc::new(secret_key),
             public_address,
             our_id,
+            chain_info: Arc::new(chain_info),
             event_queue,
             incoming: HashMap::new(),
             outgoing: HashMap::new(),
@@ -186,6 +295,15 @@ where
             next_gossip_address_index: 0,
             shutdown: Some(server_shutdown_sender),
             server_join_handle: Some(server_join_handle),
+            max_incoming_message_rate: cfg.max_incoming_message_rate,
+            reputations,
+            overlay_degree: match cfg.overlay_degree {
+                0 => None,
+                degree => Some(usize::from(degree)),
+            },
+            address_book: HashSet::new(),
+            seen_broadcasts: SeenBroadcasts::default(),
+            metrics,
         };
 
         // Bootstrap process.
@@ -195,6 +313,7 @@ where
             match utils::resolve_address(address) {
                 Ok(known_address) => {
                     model.pending.insert(known_address);
+                    model.address_book.insert(known_address);
 
                     // We successfully resolved an address, add an effect to connect to it.
                     effects.extend(
@@ -238,8 +357,25 @@ where
     }
 
     /// Queues a message to be sent to all nodes.
-    fn broadcast_message(&self, msg: Message<P>) {
-        for peer_id in self.outgoing.keys() {
+    ///
+    /// In a bounded-degree overlay, the message is sent to our peers, which flood it further.
+    fn broadcast_message<R: Rng + ?Sized>(&mut self, rng: &mut R, payload: P) {
+        let msg = if self.overlay_degree.is_some() {
+            let id = rng.gen();
+            let _ = self.seen_broadcasts.insert(id);
+            Message::Broadcast { id, payload }
+        } else {
+            Message::Payload(payload)
+        };
+        for peer_id in self.accepted_peers() {
+            self.send_message(*peer_id, msg.clone());
+        }
+    }
+
+    /// Forwards a flooded broadcast to all our peers except the one we received it from.
+    fn forward_broadcast(&self, sender: NodeId, id: BroadcastId, payload: P) {
+        let msg = Message::Broadcast { id, payload };
+        for peer_id in self.accepted_peers().filter(|peer_id| **peer_id != sender) {
             self.send_message(*peer_id, msg.clone());
         }
     }
@@ -253,8 +389,7 @@ where
         exclude: HashSet<NodeId>,
     ) -> HashSet<NodeId> {
         let peer_ids = self
-            .outgoing
-            .keys()
+            .accepted_peers()
             .filter(|&peer_id| !exclude.contains(peer_id))
             .choose_multiple(rng, count);
 
@@ -277,6 +412,13 @@ where
         peer_ids.into_iter().copied().collect()
     }
 
+    /// Returns the peers we have an outgoing connection to, and whose handshake we accepted.
+    fn accepted_peers(&self) -> impl Iterator<Item = &NodeId> {
+        self.outgoing
+            .keys()
+            .filter(move |peer_id| self.incoming.contains_key(peer_id))
+    }
+
     /// Queues a message to be sent to a specific node.
     fn send_message(&self, dest: NodeId, msg: Message<P>) {
         // Try to send the message.
@@ -293,7 +435,6 @@ where
 
     fn handle_incoming_handshake_completed(
         &mut self,
-        effect_builder: EffectBuilder<REv>,
         result: Result<(NodeId, Transport)>,
         address: SocketAddr,
     ) -> Effects<Event<P>> {
@@ -304,26 +445,30 @@ where
                     return Effects::new();
                 }
 
+                if self.reputations.is_banned(&peer_id, Timestamp::now()) {
+                    debug!(%peer_id, %address, "{}: banned peer - closing connection", self.our_id);
+                    return Effects::new();
+                }
+
                 debug!(%peer_id, %address, "{}: established incoming connection", self.our_id);
                 // The sink is never used, as we only read data from incoming connections.
                 let (_sink, stream) = framed::<P>(transport).split();
 
-                let _ = self.incoming.insert(peer_id, address);
-
-                // If the connection is now complete, announce the new peer before starting reader.
-                let mut effects = self.check_connection_complete(effect_builder, peer_id);
-
-                effects.extend(
-                    message_reader(self.event_queue, stream, self.our_id, peer_id).event(
-                        move |result| Event::IncomingClosed {
-                            result,
-                            peer_id,
-                            address,
-                        },
-                    ),
-                );
-
-                effects
+                // The connection is only recorded once the peer's handshake has been accepted.
+                message_reader(
+                    self.event_queue,
+                    stream,
+                    Arc::clone(&self.chain_info),
+                    self.max_incoming_message_rate,
+                    self.our_id,
+                    peer_id,
+                    address,
+                )
+                .event(move |result| Event::IncomingClosed {
+                    result,
+                    peer_id,
+                    address,
+                })
             }
             Err(err) => {
                 warn!(%address, %err, "{}: TLS handshake failed", self.our_id);
@@ -332,6 +477,79 @@ where
         }
     }
 
+    /// Records the incoming connection of a peer whose handshake we accepted.
+    fn handle_incoming_handshake_accepted(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        peer_id: NodeId,
+        address: SocketAddr,
+        public_address: SocketAddr,
+    ) -> Effects<Event<P>> {
+        debug!(%peer_id, %address, "{}: accepted handshake", self.our_id);
+        let _ = self.incoming.insert(peer_id, address);
+        let _ = self.address_book.insert(public_address);
+
+        // If the connection is now complete, announce the new peer.
+        let mut effects = self.check_connection_complete(effect_builder, peer_id);
+
+        // The handshake tells us where to connect back to, if we haven't yet.
+        if !self.outgoing.contains_key(&peer_id) {
+            effects.extend(self.connect_to_peer_if_required(public_address));
+        }
+        effects
+    }
+
+    /// Handles a closed incoming connection.
+    fn handle_incoming_closed<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        result: Result<()>,
+        peer_id: NodeId,
+        address: SocketAddr,
+    ) -> Effects<Event<P>> {
+        match result {
+            Ok(()) => info!(%peer_id, %address, "{}: connection closed", self.our_id),
+            Err(Error::HandshakeRejected(rejection)) => {
+                warn!(%peer_id, %address, %rejection, "{}: rejected peer", self.our_id);
+                self.metrics
+                    .handshake_rejections
+                    .with_label_values(&[rejection.cause()])
+                    .inc();
+            }
+            Err(Error::MessageDecoding(err)) => {
+                warn!(%peer_id, %address, %err, "{}: undecodable message", self.our_id);
+                self.handle_misbehavior(peer_id, Misbehavior::UndecodableMessage);
+            }
+            Err(err) => warn!(%peer_id, %address, %err, "{}: connection dropped", self.our_id),
+        }
+        self.remove(&peer_id);
+        self.fill_outgoing(rng)
+    }
+
+    /// Penalizes a misbehaving peer, and bans and disconnects it if its score reached the
+    /// threshold.
+    fn handle_misbehavior(&mut self, peer_id: NodeId, misbehavior: Misbehavior) {
+        let now = Timestamp::now();
+        if self.reputations.is_banned(&peer_id, now) {
+            return;
+        }
+        info!(%peer_id, %misbehavior, "{}: penalizing peer", self.our_id);
+        if !self.reputations.penalize(peer_id, misbehavior) {
+            return;
+        }
+        let addresses: Vec<_> = self
+            .outgoing
+            .get(&peer_id)
+            .map(|connection| connection.peer_address.ip())
+            .into_iter()
+            .chain(self.incoming.get(&peer_id).map(SocketAddr::ip))
+            .collect();
+        let until = self.reputations.ban(peer_id, addresses, now);
+        warn!(%peer_id, %until, "{}: banning peer", self.our_id);
+        self.metrics.peers_banned.inc();
+        self.remove(&peer_id);
+    }
+
     /// Sets up an established outgoing connection.
     fn setup_outgoing(
         &mut self,
@@ -355,10 +573,23 @@ where
             "should always add outgoing connect attempts to pendings: {:?}",
             self
         );
+
+        if self.reputations.is_banned(&peer_id, Timestamp::now()) {
+            debug!(%peer_id, %peer_address, "{}: banned peer - closing connection", self.our_id);
+            return Effects::new();
+        }
+
         let (sink, _stream) = framed::<P>(transport).split();
         debug!(%peer_id, %peer_address, "{}: established outgoing connection", self.our_id);
 
         let (sender, receiver) = mpsc::unbounded_channel();
+        // The handshake must be the first message on the connection. Sending cannot fail here,
+        // since we are still holding the receiver.
+        let handshake = Message::Handshake {
+            chain_info: (*self.chain_info).clone(),
+            public_address: self.public_address,
+        };
+        let _ = sender.send(handshake);
         let connection = OutgoingConnection {
             peer_address,
             sender,
@@ -383,8 +614,9 @@ where
         effects
     }
 
-    fn handle_outgoing_lost(
+    fn handle_outgoing_lost<R: Rng + ?Sized>(
         &mut self,
+        rng: &mut R,
         peer_id: Option<NodeId>,
         peer_address: SocketAddr,
         error: Option<Error>,
@@ -406,9 +638,11 @@ where
             } else {
                 warn!(%peer_address, "{}: outgoing connection closed", self.our_id);
             }
+            // The address is likely stale; we will learn it again if the peer is still online.
+            let _ = self.address_book.remove(&peer_address);
         }
 
-        Effects::new()
+        self.fill_outgoing(rng)
     }
 
     fn remove(&mut self, peer_id: &NodeId) {
@@ -416,6 +650,47 @@ where
         let _ = self.outgoing.remove(&peer_id);
     }
 
+    /// Handles a peer's public address learned through gossip.
+    fn handle_peer_address(&mut self, peer_address: SocketAddr) -> Effects<Event<P>> {
+        if peer_address == self.public_address {
+            return Effects::new();
+        }
+        let _ = self.address_book.insert(peer_address);
+        match self.overlay_degree {
+            Some(degree) if self.outgoing.len() + self.pending.len() >= degree => Effects::new(),
+            _ => self.connect_to_peer_if_required(peer_address),
+        }
+    }
+
+    /// Connects to random addresses from the address book until the number of outgoing and pending
+    /// connections reaches the overlay degree.
+    ///
+    /// Does nothing in a full mesh, where we connect to every address we learn about.
+    fn fill_outgoing<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Effects<Event<P>> {
+        let degree = match self.overlay_degree {
+            Some(degree) => degree,
+            None => return Effects::new(),
+        };
+        let missing = degree.saturating_sub(self.outgoing.len() + self.pending.len());
+        if missing == 0 {
+            return Effects::new();
+        }
+        let now = Timestamp::now();
+        let addresses = self
+            .address_book
+            .iter()
+            .filter(|address| {
+                !self.is_connected_to(**address)
+                    && !self.reputations.is_address_banned(address.ip(), now)
+            })
+            .copied()
+            .choose_multiple(rng, missing);
+        addresses
+            .into_iter()
+            .flat_map(|address| self.connect_to_peer_if_required(address))
+            .collect()
+    }
+
     /// Gossips our public listening address, and schedules the next such gossip round.
     fn gossip_our_address(&mut self, effect_builder: EffectBuilder<REv>) -> Effects<Event<P>> {
         self.next_gossip_address_index = self.next_gossip_address_index.wrapping_add(1);
@@ -441,18 +716,50 @@ where
     where
         REv: From<NetworkAnnouncement<NodeId, P>>,
     {
-        effect_builder
-            .announce_message_received(peer_id, msg.0)
-            .ignore()
+        if self.reputations.is_banned(&peer_id, Timestamp::now()) {
+            debug!(%peer_id, "{}: ignoring message from banned peer", self.our_id);
+            return Effects::new();
+        }
+        match msg {
+            Message::Handshake { .. } => {
+                warn!(%peer_id, "{}: ignoring repeated handshake", self.our_id);
+                Effects::new()
+            }
+            Message::Payload(payload) => effect_builder
+                .announce_message_received(peer_id, payload)
+                .ignore(),
+            Message::Broadcast { id, payload } => {
+                if !self.seen_broadcasts.insert(id) {
+                    trace!(%peer_id, %id, "{}: ignoring duplicate broadcast", self.our_id);
+                    return Effects::new();
+                }
+                if self.overlay_degree.is_some() {
+                    self.forward_broadcast(peer_id, id, payload.clone());
+                }
+                effect_builder
+                    .announce_message_received(peer_id, payload)
+                    .ignore()
+            }
+        }
     }
 
-    fn connect_to_peer_if_required(&mut self, peer_address: SocketAddr) -> Effects<Event<P>> {
-        if self.pending.contains(&peer_address)
+    /// Returns whether we are connected or currently connecting to the given address.
+    fn is_connected_to(&self, peer_address: SocketAddr) -> bool {
+        self.pending.contains(&peer_address)
             || self
                 .outgoing
-                .iter()
-                .any(|(_peer_id, connection)| connection.peer_address == peer_address)
+                .values()
+                .any(|connection| connection.peer_address == peer_address)
+    }
+
+    fn connect_to_peer_if_required(&mut self, peer_address: SocketAddr) -> Effects<Event<P>> {
+        if self
+            .reputations
+            .is_address_banned(peer_address.ip(), Timestamp::now())
         {
+            debug!(%peer_address, "{}: not connecting to banned address", self.our_id);
+            Effects::new()
+        } else if self.is_connected_to(peer_address) {
             // We're already trying to connect or are connected - do nothing.
             Effects::new()
         } else {
@@ -491,10 +798,10 @@ where
         }
     }
 
-    /// Returns the set of connected nodes.
+    /// Returns the set of connected nodes which completed the handshake in both directions.
     #[cfg(test)]
     pub(crate) fn connected_nodes(&self) -> HashSet<NodeId> {
-        self.outgoing.keys().cloned().collect()
+        self.accepted_peers().cloned().collect()
     }
 
     /// Returns the set of connected nodes.
@@ -593,8 +900,18 @@ where
                     .event(move |result| Event::IncomingHandshakeCompleted { result, address })
             }
             Event::IncomingHandshakeCompleted { result, address } => {
-                self.handle_incoming_handshake_completed(effect_builder, result, address)
+                self.handle_incoming_handshake_completed(result, address)
             }
+            Event::IncomingHandshakeAccepted {
+                peer_id,
+                address,
+                public_address,
+            } => self.handle_incoming_handshake_accepted(
+                effect_builder,
+                peer_id,
+                address,
+                public_address,
+            ),
             Event::IncomingMessage { peer_id, msg } => {
                 self.handle_message(effect_builder, peer_id, msg)
             }
@@ -602,16 +919,7 @@ where
                 result,
                 peer_id,
                 address,
-            } => {
-                match result {
-                    Ok(()) => info!(%peer_id, %address, "{}: connection closed", self.our_id),
-                    Err(err) => {
-                        warn!(%peer_id, %address, %err, "{}: connection dropped", self.our_id)
-                    }
-                }
-                self.remove(&peer_id);
-                Effects::new()
-            }
+            } => self.handle_incoming_closed(rng, result, peer_id, address),
             Event::OutgoingEstablished { peer_id, transport } => {
                 self.setup_outgoing(effect_builder, peer_id, transport)
             }
@@ -619,7 +927,7 @@ where
                 peer_id,
                 peer_address,
                 error,
-            } => self.handle_outgoing_lost(peer_id, peer_address, error),
+            } => self.handle_outgoing_lost(rng, peer_id, peer_address, error),
             Event::NetworkRequest {
                 req:
                     NetworkRequest::SendMessage {
@@ -629,14 +937,14 @@ where
                     },
             } => {
                 // We're given a message to send out.
-                self.send_message(dest, Message(payload));
+                self.send_message(dest, Message::Payload(payload));
                 responder.respond(()).ignore()
             }
             Event::NetworkRequest {
                 req: NetworkRequest::Broadcast { payload, responder },
             } => {
                 // We're given a message to broadcast.
-                self.broadcast_message(Message(payload));
+                self.broadcast_message(rng, payload);
                 responder.respond(()).ignore()
             }
             Event::NetworkRequest {
@@ -649,15 +957,42 @@ where
                     },
             } => {
                 // We're given a message to gossip.
-                let sent_to = self.gossip_message(rng, Message(payload), count, exclude);
+                let sent_to = self.gossip_message(rng, Message::Payload(payload), count, exclude);
                 responder.respond(sent_to).ignore()
             }
+            Event::NetworkRequest {
+                req:
+                    NetworkRequest::ReportMisbehavior {
+                        peer,
+                        misbehavior,
+                        responder,
+                    },
+            } => {
+                self.handle_misbehavior(peer, misbehavior);
+                responder.respond(()).ignore()
+            }
             Event::NetworkInfoRequest {
                 req: NetworkInfoRequest::GetPeers { responder },
             } => responder.respond(self.peers()).ignore(),
-            Event::GossipOurAddress => self.gossip_our_address(effect_builder),
+            Event::NetworkInfoRequest {
+                req: NetworkInfoRequest::GetPeerReputations { responder },
+            } => responder
+                .respond(self.reputations.reputations(Timestamp::now()))
+                .ignore(),
+            Event::PeerMisbehaved {
+                peer_id,
+                misbehavior,
+            } => {
+                self.handle_misbehavior(peer_id, misbehavior);
+                Effects::new()
+            }
+            Event::GossipOurAddress => {
+                let mut effects = self.gossip_our_address(effect_builder);
+                effects.extend(self.fill_outgoing(rng));
+                effects
+            }
             Event::PeerAddressReceived(gossiped_address) => {
-                self.connect_to_peer_if_required(gossiped_address.into())
+                self.handle_peer_address(gossiped_address.into())
             }
         }
     }
@@ -670,6 +1005,7 @@ async fn server_task<P, REv>(
     event_queue: EventQueueHandle<REv>,
     mut listener: tokio::net::TcpListener,
     shutdown: oneshot::Receiver<()>,
+    banned_addresses: BannedAddresses,
     our_id: NodeId,
 ) where
     REv: From<Event<P>>,
@@ -686,6 +1022,11 @@ async fn server_task<P, REv>(
             // the queue.
             match listener.accept().await {
                 Ok((stream, address)) => {
+                    if banned_addresses.is_banned(address.ip(), Timestamp::now()) {
+                        // Dropping the stream closes the connection.
+                        debug!(%address, "{}: rejecting connection from banned address", our_id);
+                        continue;
+                    }
                     // Move the incoming connection to the event queue for handling.
                     let event = Event::IncomingNew { stream, address };
                     event_queue
@@ -744,21 +1085,62 @@ async fn setup_tls(
 
 /// Network message reader.
 ///
-/// Schedules all received messages until the stream is closed or an error occurs.
+/// Checks the peer's handshake, then schedules all received messages until the stream is closed or
+/// an error occurs. Messages exceeding the rate limit are delayed, and reported as misbehavior.
 async fn message_reader<REv, P>(
     event_queue: EventQueueHandle<REv>,
     mut stream: SplitStream<FramedTransport<P>>,
+    chain_info: Arc<ChainInfo>,
+    max_incoming_message_rate: u32,
     our_id: NodeId,
     peer_id: NodeId,
-) -> io::Result<()>
+    address: SocketAddr,
+) -> Result<()>
 where
     P: DeserializeOwned + Send + Display,
     REv: From<Event<P>>,
 {
+    let public_address = match stream.next().await {
+        Some(Ok(Message::Handshake {
+            chain_info: peer_chain_info,
+            public_address,
+        })) => {
+            chain_info.check(&peer_chain_info)?;
+            public_address
+        }
+        Some(Ok(_)) => return Err(HandshakeRejection::MissingHandshake.into()),
+        Some(Err(err)) => return Err(receive_error(err)),
+        None => return Ok(()),
+    };
+    event_queue
+        .schedule(
+            Event::IncomingHandshakeAccepted {
+                peer_id,
+                address,
+                public_address,
+            },
+            QueueKind::NetworkIncoming,
+        )
+        .await;
+
+    let mut rate_limiter = RateLimiter::new(max_incoming_message_rate, Instant::now());
     while let Some(msg_result) = stream.next().await {
         match msg_result {
             Ok(msg) => {
                 debug!(%msg, %peer_id, "{}: message received", our_id);
+                if let Some(delay) = rate_limiter.throttle(Instant::now()) {
+                    debug!(%peer_id, ?delay, "{}: rate limit exceeded", our_id);
+                    event_queue
+                        .schedule(
+                            Event::PeerMisbehaved {
+                                peer_id,
+                                misbehavior: Misbehavior::RateLimitExceeded,
+                            },
+                            QueueKind::NetworkIncoming,
+                        )
+                        .await;
+                    time::delay_for(delay).await;
+                }
                 // We've received a message, push it to the reactor.
                 event_queue
                     .schedule(
@@ -769,13 +1151,26 @@ where
             }
             Err(err) => {
                 warn!(%err, %peer_id, "{}: receiving message failed, closing connection", our_id);
-                return Err(err);
+                return Err(receive_error(err));
             }
         }
     }
     Ok(())
 }
 
+/// Distinguishes messages that could not be decoded from other errors on an incoming connection.
+fn receive_error(err: io::Error) -> Error {
+    let is_decoding_error = err.kind() == io::ErrorKind::InvalidData
+        || err
+            .get_ref()
+            .map_or(false, |inner| inner.is::<rmp_serde::decode::Error>());
+    if is_decoding_error {
+        Error::MessageDecoding(err)
+    } else {
+        Error::MessageNotReceived(err)
+    }
+}
+
 /// Network message sender.
 ///
 /// Reads from a channel and sends all messages, until the stream is closed or an error occurs.
@@ -852,9 +1247,13 @@ where
             .field("certificate", &"<SSL cert>")
             .field("secret_key", &"<hidden>")
             .field("public_address", &self.public_address)
+            .field("chain_info", &self.chain_info)
             .field("event_queue", &"<event_queue>")
             .field("incoming", &self.incoming)
             .field("outgoing", &self.outgoing)
+            .field("reputations", &self.reputations)
+            .field("overlay_degree", &self.overlay_degree)
+            .field("address_book", &self.address_book)
             .field("pending", &self.pending)
             .finish()
     }
@@ -0,0 +1,117 @@
+use std::result;
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{components::chainspec_loader::Chainspec, crypto::hash::Digest};
+
+/// Information identifying the chain a node participates in.
+///
+/// It is sent to every peer in the handshake, and peers on a different chain are rejected.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainInfo {
+    /// The name of the chain, as specified in the chainspec.
+    pub(crate) name: String,
+    /// The protocol version, as specified in the chainspec.
+    pub(crate) protocol_version: Version,
+    /// The hash of the global state after executing the genesis block.
+    pub(crate) genesis_hash: Digest,
+}
+
+impl ChainInfo {
+    /// Creates the chain information for the given chainspec and genesis post state hash.
+    pub(crate) fn new(chainspec: &Chainspec, genesis_hash: Digest) -> Self {
+        ChainInfo {
+            name: chainspec.genesis.name.clone(),
+            protocol_version: chainspec.genesis.protocol_version.clone(),
+            genesis_hash,
+        }
+    }
+
+    /// Returns an error if a peer with the given chain information must be rejected.
+    pub(super) fn check(&self, peer: &ChainInfo) -> result::Result<(), HandshakeRejection> {
+        if self.name != peer.name {
+            Err(HandshakeRejection::ChainName {
+                ours: self.name.clone(),
+                theirs: peer.name.clone(),
+            })
+        } else if self.protocol_version != peer.protocol_version {
+            Err(HandshakeRejection::ProtocolVersion {
+                ours: self.protocol_version.clone(),
+                theirs: peer.protocol_version.clone(),
+            })
+        } else if self.genesis_hash != peer.genesis_hash {
+            Err(HandshakeRejection::GenesisHash {
+                ours: self.genesis_hash,
+                theirs: peer.genesis_hash,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+impl ChainInfo {
+    /// Creates chain information suitable for tests.
+    pub(crate) fn create_for_testing() -> Self {
+        ChainInfo {
+            name: "casper-test".to_string(),
+            protocol_version: Version::new(1, 0, 0),
+            genesis_hash: Digest::default(),
+        }
+    }
+}
+
+/// The reason why a peer was rejected during the handshake.
+#[derive(Debug, Error)]
+pub enum HandshakeRejection {
+    /// The first message received was not a handshake.
+    #[error("first message was not a handshake")]
+    MissingHandshake,
+    /// The peer runs a chain with a different name.
+    #[error("chain name mismatch: ours is {ours}, theirs is {theirs}")]
+    ChainName { ours: String, theirs: String },
+    /// The peer runs a different protocol version.
+    #[error("protocol version mismatch: ours is {ours}, theirs is {theirs}")]
+    ProtocolVersion { ours: Version, theirs: Version },
+    /// The peer's chain has a different genesis.
+    #[error("genesis hash mismatch: ours is {ours}, theirs is {theirs}")]
+    GenesisHash { ours: Digest, theirs: Digest },
+}
+
+impl HandshakeRejection {
+    /// Returns the cause of the rejection, as used in the metrics.
+    pub(super) fn cause(&self) -> &'static str {
+        match self {
+            HandshakeRejection::MissingHandshake => "missing_handshake",
+            HandshakeRejection::ChainName { .. } => "chain_name",
+            HandshakeRejection::ProtocolVersion { .. } => "protocol_version",
+            HandshakeRejection::GenesisHash { .. } => "genesis_hash",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reject_mismatching_chain_info() {
+        let ours = ChainInfo::create_for_testing();
+        assert!(ours.check(&ours.clone()).is_ok());
+
+        let mut theirs = ours.clone();
+        theirs.name = "other-chain".to_string();
+        assert_eq!("chain_name", ours.check(&theirs).unwrap_err().cause());
+
+        let mut theirs = ours.clone();
+        theirs.protocol_version = Version::new(2, 0, 0);
+        assert_eq!("protocol_version", ours.check(&theirs).unwrap_err().cause());
+
+        let mut theirs = ours.clone();
+        theirs.genesis_hash = Digest::from([1; Digest::LENGTH]);
+        assert_eq!("genesis_hash", ours.check(&theirs).unwrap_err().cause());
+    }
+}
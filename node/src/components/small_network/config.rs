@@ -21,6 +21,15 @@ const DEFAULT_PUBLIC_ADDRESS: &str = "127.0.0.1:0";
 /// Default interval for gossiping network addresses.
 const DEFAULT_GOSSIP_INTERVAL: Duration = Duration::from_secs(30);
 
+/// Default maximum number of incoming messages per second from a single peer.
+const DEFAULT_MAX_INCOMING_MESSAGE_RATE: u32 = 1000;
+
+/// Default score at which a misbehaving peer gets banned.
+const DEFAULT_BAN_THRESHOLD: u32 = 100;
+
+/// Default duration of a ban.
+const DEFAULT_BAN_DURATION: Duration = Duration::from_secs(600);
+
 // Default values for networking configuration:
 impl Default for Config {
     fn default() -> Self {
@@ -29,6 +38,10 @@ impl Default for Config {
             public_address: DEFAULT_PUBLIC_ADDRESS.to_string(),
             known_addresses: Vec::new(),
             gossip_interval: DEFAULT_GOSSIP_INTERVAL,
+            max_incoming_message_rate: DEFAULT_MAX_INCOMING_MESSAGE_RATE,
+            ban_threshold: DEFAULT_BAN_THRESHOLD,
+            ban_duration: DEFAULT_BAN_DURATION,
+            overlay_degree: 0,
         }
     }
 }
@@ -49,6 +62,19 @@ pub struct Config {
     /// Interval in milliseconds used for gossiping.
     #[serde(with = "crate::utils::milliseconds")]
     pub gossip_interval: Duration,
+    /// Maximum number of messages per second accepted from a single peer, `0` for no limit.
+    ///
+    /// Further messages are delayed, and count against the peer's reputation.
+    pub max_incoming_message_rate: u32,
+    /// Sum of misbehavior penalties at which a peer gets banned.
+    pub ban_threshold: u32,
+    /// Duration in milliseconds for which a misbehaving peer is banned.
+    #[serde(with = "crate::utils::milliseconds")]
+    pub ban_duration: Duration,
+    /// Number of outgoing connections each node maintains, or `0` to connect to every peer.
+    ///
+    /// With a bounded degree, broadcasts are flooded through the overlay.
+    pub overlay_degree: u16,
 }
 
 #[cfg(test)]
@@ -69,6 +95,10 @@ impl Config {
             public_address: bind_address.to_string(),
             known_addresses: Vec::new(),
             gossip_interval: DEFAULT_TEST_GOSSIP_INTERVAL,
+            max_incoming_message_rate: DEFAULT_MAX_INCOMING_MESSAGE_RATE,
+            ban_threshold: DEFAULT_BAN_THRESHOLD,
+            ban_duration: DEFAULT_BAN_DURATION,
+            overlay_degree: 0,
         }
     }
 
@@ -84,6 +114,10 @@ impl Config {
             public_address: format_address(TEST_BIND_INTERFACE, 0),
             known_addresses: vec![format_address(TEST_BIND_INTERFACE, known_peer_port)],
             gossip_interval: DEFAULT_TEST_GOSSIP_INTERVAL,
+            max_incoming_message_rate: DEFAULT_MAX_INCOMING_MESSAGE_RATE,
+            ban_threshold: DEFAULT_BAN_THRESHOLD,
+            ban_duration: DEFAULT_BAN_DURATION,
+            overlay_degree: 0,
         }
     }
 }
@@ -5,6 +5,7 @@ use thiserror::Error;
 use tokio::net::TcpStream;
 use tokio_openssl::HandshakeError;
 
+use super::HandshakeRejection;
 use crate::tls::ValidationError;
 
 pub(super) type Result<T> = result::Result<T, Error>;
@@ -42,6 +43,15 @@ pub enum Error {
     /// Failed to send message.
     #[error("failed to send message")]
     MessageNotSent(#[source] io::Error),
+    /// Failed to receive message.
+    #[error("failed to receive message")]
+    MessageNotReceived(#[source] io::Error),
+    /// Failed to decode a message received from a peer.
+    #[error("failed to decode message")]
+    MessageDecoding(#[source] io::Error),
+    /// The peer's handshake was rejected.
+    #[error("handshake rejected: {0}")]
+    HandshakeRejected(#[from] HandshakeRejection),
     /// Failed to create TLS acceptor.
     #[error("failed to create acceptor")]
     AcceptorCreation(#[source] ErrorStack),
@@ -57,6 +67,9 @@ pub enum Error {
     /// TLS validation error.
     #[error("TLS validation error: {0}")]
     TlsValidation(#[from] ValidationError),
+    /// Metrics-related error.
+    #[error("prometheus (metrics) error: {0}")]
+    Metrics(#[from] prometheus::Error),
     /// System time error.
     #[error("system time error: {0}")]
     SystemTime(#[from] SystemTimeError),
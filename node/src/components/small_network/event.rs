@@ -1,13 +1,12 @@
 use std::{
     fmt::{self, Debug, Display, Formatter},
-    io,
     net::SocketAddr,
 };
 
 use derive_more::From;
 use tokio::net::TcpStream;
 
-use super::{Error, GossipedAddress, Message, NodeId, Transport};
+use super::{Error, GossipedAddress, Message, Misbehavior, NodeId, Transport};
 use crate::effect::requests::{NetworkInfoRequest, NetworkRequest};
 
 #[derive(Debug, From)]
@@ -24,11 +23,17 @@ pub enum Event<P> {
         result: Result<(NodeId, Transport), Error>,
         address: SocketAddr,
     },
+    /// The peer sent a valid handshake on the incoming connection.
+    IncomingHandshakeAccepted {
+        peer_id: NodeId,
+        address: SocketAddr,
+        public_address: SocketAddr,
+    },
     /// Received network message.
     IncomingMessage { peer_id: NodeId, msg: Message<P> },
     /// Incoming connection closed.
     IncomingClosed {
-        result: io::Result<()>,
+        result: Result<(), Error>,
         peer_id: NodeId,
         address: SocketAddr,
     },
@@ -57,6 +62,11 @@ pub enum Event<P> {
     GossipOurAddress,
     /// We received a peer's public listening address via gossip.
     PeerAddressReceived(GossipedAddress),
+    /// A peer misbehaved on its incoming connection.
+    PeerMisbehaved {
+        peer_id: NodeId,
+        misbehavior: Misbehavior,
+    },
 }
 
 impl<P: Display> Display for Event<P> {
@@ -69,6 +79,9 @@ impl<P: Display> Display for Event<P> {
             Event::IncomingHandshakeCompleted { result, address } => {
                 write!(f, "handshake from {}, is_err {}", address, result.is_err())
             }
+            Event::IncomingHandshakeAccepted {
+                peer_id, address, ..
+            } => write!(f, "accepted handshake from {} at {}", peer_id, address),
             Event::IncomingMessage {
                 peer_id: node_id,
                 msg,
@@ -106,6 +119,10 @@ impl<P: Display> Display for Event<P> {
             Event::PeerAddressReceived(gossiped_address) => {
                 write!(f, "received gossiped peer address {}", gossiped_address)
             }
+            Event::PeerMisbehaved {
+                peer_id,
+                misbehavior,
+            } => write!(f, "peer {} misbehaved: {}", peer_id, misbehavior),
         }
     }
 }
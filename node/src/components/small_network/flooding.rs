@@ -0,0 +1,79 @@
+//! Duplicate suppression for broadcasts flooded through the overlay.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::{self, Display, Formatter},
+};
+
+use hex_fmt::HexFmt;
+use rand::{
+    distributions::{Distribution, Standard},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
+
+/// The maximum number of broadcast IDs remembered. Once exceeded, the oldest ones are forgotten.
+const MAX_SEEN_BROADCASTS: usize = 100_000;
+
+/// A random identifier of a broadcast message, chosen by the node that originated it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BroadcastId([u8; 8]);
+
+impl Display for BroadcastId {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", HexFmt(&self.0))
+    }
+}
+
+impl Distribution<BroadcastId> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BroadcastId {
+        BroadcastId(rng.gen())
+    }
+}
+
+/// The IDs of the most recent broadcasts we sent or received.
+#[derive(Debug, Default)]
+pub(super) struct SeenBroadcasts {
+    /// The remembered IDs, oldest first.
+    order: VecDeque<BroadcastId>,
+    /// The remembered IDs, for fast lookup.
+    ids: HashSet<BroadcastId>,
+}
+
+impl SeenBroadcasts {
+    /// Remembers the broadcast ID. Returns `false` if it was already known.
+    pub(super) fn insert(&mut self, id: BroadcastId) -> bool {
+        if !self.ids.insert(id) {
+            return false;
+        }
+        self.order.push_back(id);
+        if self.order.len() > MAX_SEEN_BROADCASTS {
+            if let Some(oldest) = self.order.pop_front() {
+                let _ = self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn should_detect_duplicates_and_forget_oldest() {
+        let mut rng = TestRng::new();
+        let mut seen = SeenBroadcasts::default();
+        let first: BroadcastId = rng.gen();
+        assert!(seen.insert(first));
+        assert!(!seen.insert(first));
+
+        for _ in 0..MAX_SEEN_BROADCASTS {
+            assert!(seen.insert(rng.gen()));
+        }
+        assert_eq!(MAX_SEEN_BROADCASTS, seen.ids.len());
+        // The first ID has been forgotten, so it is accepted again.
+        assert!(seen.insert(first));
+    }
+}
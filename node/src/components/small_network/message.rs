@@ -1,12 +1,45 @@
-use std::fmt::{self, Debug, Display, Formatter};
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    net::SocketAddr,
+};
 
 use serde::{Deserialize, Serialize};
 
+use super::{BroadcastId, ChainInfo};
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct Message<P>(pub(super) P);
+pub enum Message<P> {
+    /// The first message sent on every connection, identifying the sender's chain.
+    Handshake {
+        /// Information about the chain the sender participates in.
+        chain_info: ChainInfo,
+        /// The sender's public listening address.
+        public_address: SocketAddr,
+    },
+    /// A message with a payload for the other components.
+    Payload(P),
+    /// A broadcast flooded through the overlay, which every node forwards once to its peers.
+    Broadcast {
+        /// The ID used to detect duplicates.
+        id: BroadcastId,
+        /// The payload for the other components.
+        payload: P,
+    },
+}
 
 impl<P: Display> Display for Message<P> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "payload: {}", self.0)
+        match self {
+            Message::Handshake {
+                chain_info,
+                public_address,
+            } => write!(
+                f,
+                "handshake: chain {} v{}, public address {}",
+                chain_info.name, chain_info.protocol_version, public_address
+            ),
+            Message::Payload(payload) => write!(f, "payload: {}", payload),
+            Message::Broadcast { id, payload } => write!(f, "broadcast {}: {}", id, payload),
+        }
     }
 }
@@ -0,0 +1,294 @@
+//! Peer reputation tracking, rate limiting and banning.
+//!
+//! Every reported misbehavior of a peer adds a penalty to its score. Once the score reaches the
+//! configured threshold, the peer is banned for a limited time: we disconnect from it, and neither
+//! accept nor initiate connections to it or its IP addresses until the ban expires.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    net::IpAddr,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::NodeId;
+use crate::types::{TimeDiff, Timestamp};
+
+/// The length of the window in which incoming messages are counted for rate limiting.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(1);
+
+/// A kind of misbehavior by a peer, penalized by adding to the peer's score.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Misbehavior {
+    /// The peer sent a message that could not be decoded.
+    UndecodableMessage,
+    /// The peer sent a message that a component found to be invalid.
+    InvalidMessage,
+    /// The peer sent messages faster than its rate limit allows.
+    RateLimitExceeded,
+}
+
+impl Misbehavior {
+    /// Returns the penalty added to the peer's score.
+    fn penalty(self) -> u32 {
+        match self {
+            Misbehavior::UndecodableMessage => 50,
+            Misbehavior::InvalidMessage => 20,
+            Misbehavior::RateLimitExceeded => 10,
+        }
+    }
+}
+
+impl Display for Misbehavior {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Misbehavior::UndecodableMessage => write!(formatter, "undecodable message"),
+            Misbehavior::InvalidMessage => write!(formatter, "invalid message"),
+            Misbehavior::RateLimitExceeded => write!(formatter, "rate limit exceeded"),
+        }
+    }
+}
+
+/// The reputation of a single peer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerReputation {
+    /// The sum of the peer's penalties since it was last banned.
+    pub score: u32,
+    /// The time at which the peer's ban expires, if it is currently banned.
+    pub banned_until: Option<Timestamp>,
+}
+
+/// The IP addresses of banned peers, with the time each ban expires.
+///
+/// This is shared with the server task, so incoming connections from banned addresses can be
+/// dropped before even starting the TLS handshake.
+#[derive(Clone, Debug, Default)]
+pub(super) struct BannedAddresses(Arc<RwLock<HashMap<IpAddr, Timestamp>>>);
+
+impl BannedAddresses {
+    /// Returns whether the given IP address is banned at time `now`.
+    pub(super) fn is_banned(&self, ip: IpAddr, now: Timestamp) -> bool {
+        match self.0.read() {
+            Ok(guard) => guard.get(&ip).map_or(false, |until| *until > now),
+            Err(_) => {
+                error!("banned addresses lock has been poisoned");
+                false
+            }
+        }
+    }
+
+    /// Bans the given addresses until `until`, and removes all bans that expired at time `now`.
+    fn ban<I>(&self, addresses: I, until: Timestamp, now: Timestamp)
+    where
+        I: IntoIterator<Item = IpAddr>,
+    {
+        match self.0.write() {
+            Ok(mut guard) => {
+                guard.retain(|_, expiry| *expiry > now);
+                guard.extend(addresses.into_iter().map(|ip| (ip, until)));
+            }
+            Err(_) => error!("banned addresses lock has been poisoned"),
+        }
+    }
+}
+
+/// Tracks the scores of all peers, and bans those reaching the threshold.
+#[derive(Debug)]
+pub(super) struct Reputations {
+    /// The score at which a peer gets banned.
+    ban_threshold: u32,
+    /// The duration of a ban.
+    ban_duration: TimeDiff,
+    /// The score of each peer that misbehaved since it was last banned.
+    scores: HashMap<NodeId, u32>,
+    /// The time at which each peer's ban expires.
+    bans: HashMap<NodeId, Timestamp>,
+    /// The banned peers' IP addresses.
+    banned_addresses: BannedAddresses,
+}
+
+impl Reputations {
+    /// Creates a new reputation tracker.
+    pub(super) fn new(ban_threshold: u32, ban_duration: Duration) -> Self {
+        Reputations {
+            ban_threshold,
+            ban_duration: TimeDiff::from(ban_duration.as_millis() as u64),
+            scores: HashMap::new(),
+            bans: HashMap::new(),
+            banned_addresses: BannedAddresses::default(),
+        }
+    }
+
+    /// Returns a handle to the banned IP addresses.
+    pub(super) fn banned_addresses(&self) -> BannedAddresses {
+        self.banned_addresses.clone()
+    }
+
+    /// Adds the penalty for the misbehavior to the peer's score.
+    ///
+    /// Returns `true` if the score reached the threshold, i.e. the peer should be banned.
+    pub(super) fn penalize(&mut self, peer_id: NodeId, misbehavior: Misbehavior) -> bool {
+        let score = self.scores.entry(peer_id).or_default();
+        *score = score.saturating_add(misbehavior.penalty());
+        *score >= self.ban_threshold
+    }
+
+    /// Bans the peer and its IP addresses, and resets its score. Returns the ban's expiry time.
+    pub(super) fn ban<I>(&mut self, peer_id: NodeId, addresses: I, now: Timestamp) -> Timestamp
+    where
+        I: IntoIterator<Item = IpAddr>,
+    {
+        let until = now + self.ban_duration;
+        self.bans.retain(|_, expiry| *expiry > now);
+        let _ = self.bans.insert(peer_id, until);
+        let _ = self.scores.remove(&peer_id);
+        self.banned_addresses.ban(addresses, until, now);
+        until
+    }
+
+    /// Returns whether the peer is banned at time `now`.
+    pub(super) fn is_banned(&self, peer_id: &NodeId, now: Timestamp) -> bool {
+        self.bans.get(peer_id).map_or(false, |until| *until > now)
+    }
+
+    /// Returns whether the IP address is banned at time `now`.
+    pub(super) fn is_address_banned(&self, ip: IpAddr, now: Timestamp) -> bool {
+        self.banned_addresses.is_banned(ip, now)
+    }
+
+    /// Returns the reputation of every peer that misbehaved or is currently banned.
+    pub(super) fn reputations(&self, now: Timestamp) -> HashMap<NodeId, PeerReputation> {
+        let mut reputations: HashMap<NodeId, PeerReputation> = self
+            .scores
+            .iter()
+            .map(|(peer_id, score)| {
+                let reputation = PeerReputation {
+                    score: *score,
+                    banned_until: None,
+                };
+                (*peer_id, reputation)
+            })
+            .collect();
+        for (peer_id, until) in self.bans.iter().filter(|(_, until)| **until > now) {
+            reputations
+                .entry(*peer_id)
+                .or_insert(PeerReputation {
+                    score: 0,
+                    banned_until: None,
+                })
+                .banned_until = Some(*until);
+        }
+        reputations
+    }
+}
+
+/// Limits the rate of incoming messages on a single connection.
+///
+/// Messages are counted in fixed windows. Once the limit for the current window is reached, further
+/// messages have to wait for the next one.
+#[derive(Debug)]
+pub(super) struct RateLimiter {
+    /// The maximum number of messages per window, or `0` for no limit.
+    max_messages: u32,
+    /// The start of the current window.
+    window_start: Instant,
+    /// The number of messages received in the current window.
+    count: u32,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter allowing `max_messages` per second. `0` means unlimited.
+    pub(super) fn new(max_messages: u32, now: Instant) -> Self {
+        RateLimiter {
+            max_messages,
+            window_start: now,
+            count: 0,
+        }
+    }
+
+    /// Records a message received at time `now`.
+    ///
+    /// Returns `None` if the message is within the limit, otherwise the duration for which it needs
+    /// to be delayed.
+    pub(super) fn throttle(&mut self, now: Instant) -> Option<Duration> {
+        if self.max_messages == 0 {
+            return None;
+        }
+        if now.saturating_duration_since(self.window_start) >= RATE_LIMIT_WINDOW {
+            self.window_start = now;
+            self.count = 0;
+        }
+        if self.count < self.max_messages {
+            self.count += 1;
+            return None;
+        }
+        // The message is processed at the start of the next window, and counts against it.
+        self.window_start += RATE_LIMIT_WINDOW;
+        self.count = 1;
+        Some(self.window_start.saturating_duration_since(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn should_ban_after_reaching_threshold() {
+        let mut rng = TestRng::new();
+        let peer_id: NodeId = rng.gen();
+        let ip: IpAddr = [10, 0, 0, 1].into();
+        let mut reputations = Reputations::new(50, Duration::from_secs(60));
+        let now = Timestamp::from(1_000_000);
+
+        assert!(!reputations.penalize(peer_id, Misbehavior::InvalidMessage));
+        assert!(!reputations.penalize(peer_id, Misbehavior::InvalidMessage));
+        assert_eq!(40, reputations.reputations(now)[&peer_id].score);
+        assert!(reputations.penalize(peer_id, Misbehavior::RateLimitExceeded));
+
+        let until = reputations.ban(peer_id, vec![ip], now);
+        assert_eq!(now + TimeDiff::from(60_000), until);
+        assert!(reputations.is_banned(&peer_id, now));
+        assert!(reputations.is_address_banned(ip, now));
+        assert!(reputations.banned_addresses().is_banned(ip, now));
+        let expected = PeerReputation {
+            score: 0,
+            banned_until: Some(until),
+        };
+        assert_eq!(expected, reputations.reputations(now)[&peer_id]);
+
+        // The ban expires after the configured duration.
+        assert!(!reputations.is_banned(&peer_id, until));
+        assert!(!reputations.is_address_banned(ip, until));
+        assert!(reputations.reputations(until).is_empty());
+    }
+
+    #[test]
+    fn should_delay_messages_exceeding_rate_limit() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(3, start);
+        for _ in 0..3 {
+            assert_eq!(None, limiter.throttle(start));
+        }
+        let now = start + Duration::from_millis(400);
+        assert_eq!(Some(Duration::from_millis(600)), limiter.throttle(now));
+
+        // The delayed message counts against the next window.
+        let next_window = start + RATE_LIMIT_WINDOW;
+        for _ in 0..2 {
+            assert_eq!(None, limiter.throttle(next_window));
+        }
+        assert_eq!(Some(RATE_LIMIT_WINDOW), limiter.throttle(next_window));
+
+        // A limit of zero disables rate limiting.
+        let mut unlimited = RateLimiter::new(0, start);
+        assert!((0..1000).all(|_| unlimited.throttle(start).is_none()));
+    }
+}
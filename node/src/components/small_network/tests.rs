@@ -24,16 +24,17 @@ use crate::{
     effect::{
         announcements::{GossiperAnnouncement, NetworkAnnouncement},
         requests::{NetworkRequest, StorageRequest},
-        EffectBuilder, Effects,
+        EffectBuilder, EffectExt, Effects,
     },
     protocol,
     reactor::{self, EventQueueHandle, Finalize, Reactor, Runner},
-    small_network::{self, Config, GossipedAddress, NodeId, SmallNetwork},
+    small_network::{self, ChainInfo, Config, GossipedAddress, Misbehavior, NodeId, SmallNetwork},
     testing::{
         self, init_logging,
-        network::{Network, NetworkedReactor},
+        network::{Network, NetworkedReactor, Nodes},
         ConditionCheckReactor, TestRng,
     },
+    types::Timestamp,
     utils::Source,
 };
 
@@ -80,6 +81,8 @@ impl Display for Event {
 enum Message {
     #[from]
     AddressGossiper(gossiper::Message<GossipedAddress>),
+    /// A test message, identified by a number.
+    Test(u64),
 }
 
 impl Display for Message {
@@ -88,6 +91,22 @@ impl Display for Message {
     }
 }
 
+/// Test reactor configuration.
+#[derive(Debug)]
+struct TestReactorConfig {
+    network: Config,
+    chain_info: ChainInfo,
+}
+
+impl From<Config> for TestReactorConfig {
+    fn from(network: Config) -> Self {
+        TestReactorConfig {
+            network,
+            chain_info: ChainInfo::create_for_testing(),
+        }
+    }
+}
+
 /// Test reactor.
 ///
 /// Runs a single small network.
@@ -95,20 +114,22 @@ impl Display for Message {
 struct TestReactor {
     net: SmallNetwork<Event, Message>,
     address_gossiper: Gossiper<GossipedAddress, Event>,
+    /// The test messages received so far.
+    received_test_messages: HashSet<u64>,
 }
 
 impl Reactor<TestRng> for TestReactor {
     type Event = Event;
-    type Config = Config;
+    type Config = TestReactorConfig;
     type Error = anyhow::Error;
 
     fn new(
         cfg: Self::Config,
-        _registry: &Registry,
+        registry: &Registry,
         event_queue: EventQueueHandle<Self::Event>,
         _rng: &mut TestRng,
     ) -> anyhow::Result<(Self, Effects<Self::Event>)> {
-        let (net, effects) = SmallNetwork::new(event_queue, cfg)?;
+        let (net, effects) = SmallNetwork::new(event_queue, cfg.network, registry, cfg.chain_info)?;
         let gossiper_config = gossiper::Config::default();
         let address_gossiper = Gossiper::new_for_complete_items(gossiper_config);
 
@@ -116,6 +137,7 @@ impl Reactor<TestRng> for TestReactor {
             TestReactor {
                 net,
                 address_gossiper,
+                received_test_messages: HashSet::new(),
             },
             reactor::wrap_effects(Event::SmallNet, effects),
         ))
@@ -150,6 +172,10 @@ impl Reactor<TestRng> for TestReactor {
                     Message::AddressGossiper(message) => {
                         Event::AddressGossiper(gossiper::Event::MessageReceived { sender, message })
                     }
+                    Message::Test(number) => {
+                        let _ = self.received_test_messages.insert(number);
+                        return Effects::new();
+                    }
                 };
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
@@ -245,12 +271,12 @@ async fn run_two_node_network_five_times() {
 
         let start = Instant::now();
         net.add_node_with_config(
-            Config::default_local_net_first_node(first_node_port),
+            Config::default_local_net_first_node(first_node_port).into(),
             &mut rng,
         )
         .await
         .unwrap();
-        net.add_node_with_config(Config::default_local_net(first_node_port), &mut rng)
+        net.add_node_with_config(Config::default_local_net(first_node_port).into(), &mut rng)
             .await
             .unwrap();
         let end = Instant::now();
@@ -306,7 +332,7 @@ async fn bind_to_real_network_interface() {
     let local_net_config = Config::new((local_addr, port).into());
 
     let mut net = Network::<TestReactor>::new();
-    net.add_node_with_config(local_net_config, &mut rng)
+    net.add_node_with_config(local_net_config.into(), &mut rng)
         .await
         .unwrap();
 
@@ -317,6 +343,100 @@ async fn bind_to_real_network_interface() {
     net.finalize().await;
 }
 
+/// Returns the nodes reachable from `start` via accepted connections.
+fn reachable_from(nodes: &Nodes<TestReactor>, start: NodeId) -> HashSet<NodeId> {
+    let mut reached = HashSet::new();
+    let mut to_visit = vec![start];
+    while let Some(node_id) = to_visit.pop() {
+        if !reached.insert(node_id) {
+            continue;
+        }
+        if let Some(runner) = nodes.get(&node_id) {
+            to_visit.extend(runner.reactor().inner().net.connected_nodes());
+        }
+    }
+    reached
+}
+
+/// Checks whether or not the accepted connections form a connected graph containing all nodes.
+fn overlay_is_connected(nodes: &Nodes<TestReactor>) -> bool {
+    match nodes.keys().next() {
+        Some(start) => {
+            let reached = reachable_from(nodes, *start);
+            nodes.keys().all(|node_id| reached.contains(node_id))
+        }
+        None => false,
+    }
+}
+
+/// Returns whether all given nodes except `origin` received the test message.
+fn received_by<'a, I>(nodes: &Nodes<TestReactor>, members: I, origin: NodeId, number: u64) -> bool
+where
+    I: IntoIterator<Item = &'a NodeId>,
+{
+    members
+        .into_iter()
+        .filter(|node_id| **node_id != origin)
+        .all(|node_id| {
+            nodes[node_id]
+                .reactor()
+                .inner()
+                .received_test_messages
+                .contains(&number)
+        })
+}
+
+/// Returns a condition that is satisfied once every node except `origin` received the given test
+/// message.
+fn all_received(origin: NodeId, number: u64) -> impl Fn(&Nodes<TestReactor>) -> bool {
+    move |nodes| received_by(nodes, nodes.keys(), origin, number)
+}
+
+/// The overlay degree used in the tests.
+const OVERLAY_DEGREE: u16 = 2;
+
+/// Builds the configuration of a node in a bounded-degree overlay.
+fn overlay_config(mut network: Config, overlay_degree: u16) -> TestReactorConfig {
+    network.overlay_degree = overlay_degree;
+    network.into()
+}
+
+/// Adds a bounded-degree overlay network with the given number of nodes, and returns the IDs of
+/// the added nodes, starting with the one all others bootstrap from, and the port it listens on.
+async fn add_overlay_network(
+    net: &mut Network<TestReactor>,
+    rng: &mut TestRng,
+    number_of_nodes: usize,
+    overlay_degree: u16,
+) -> (Vec<NodeId>, u16) {
+    let first_node_port = testing::unused_port_on_localhost();
+    let first_node_config = Config::default_local_net_first_node(first_node_port);
+    let (first_node, _) = net
+        .add_node_with_config(overlay_config(first_node_config, overlay_degree), rng)
+        .await
+        .unwrap();
+    let mut node_ids = vec![first_node];
+    for _ in 1..number_of_nodes {
+        let config = Config::default_local_net(first_node_port);
+        let (node_id, _) = net
+            .add_node_with_config(overlay_config(config, overlay_degree), rng)
+            .await
+            .unwrap();
+        node_ids.push(node_id);
+    }
+    (node_ids, first_node_port)
+}
+
+/// Broadcasts a test message from the given node.
+async fn broadcast_test_message(net: &mut Network<TestReactor>, origin: &NodeId, number: u64) {
+    net.process_injected_effect_on(origin, |effect_builder| {
+        effect_builder
+            .broadcast_message::<NodeId, _>(Message::Test(number))
+            .ignore()
+    })
+    .await;
+}
+
 /// Check that a network of varying sizes will connect all nodes properly.
 #[tokio::test]
 async fn check_varying_size_network_connects() {
@@ -334,14 +454,14 @@ async fn check_varying_size_network_connects() {
         let first_node_port = testing::unused_port_on_localhost();
 
         net.add_node_with_config(
-            Config::default_local_net_first_node(first_node_port),
+            Config::default_local_net_first_node(first_node_port).into(),
             &mut rng,
         )
         .await
         .unwrap();
 
         for _ in 1..number_of_nodes {
-            net.add_node_with_config(Config::default_local_net(first_node_port), &mut rng)
+            net.add_node_with_config(Config::default_local_net(first_node_port).into(), &mut rng)
                 .await
                 .unwrap();
         }
@@ -359,3 +479,238 @@ async fn check_varying_size_network_connects() {
         net.finalize().await;
     }
 }
+
+/// Check that nodes on different chains refuse to connect to each other.
+#[tokio::test]
+async fn should_reject_peer_on_other_chain() {
+    init_logging();
+
+    let mut rng = TestRng::new();
+    let first_node_port = testing::unused_port_on_localhost();
+
+    let mut net = Network::new();
+    net.add_node_with_config(
+        Config::default_local_net_first_node(first_node_port).into(),
+        &mut rng,
+    )
+    .await
+    .unwrap();
+
+    let mut chain_info = ChainInfo::create_for_testing();
+    chain_info.name = "other-chain".to_string();
+    let other_chain_config = TestReactorConfig {
+        network: Config::default_local_net(first_node_port),
+        chain_info,
+    };
+    net.add_node_with_config(other_chain_config, &mut rng)
+        .await
+        .unwrap();
+
+    // The first node rejects the other node's handshake.
+    let rejected_chain_name =
+        |nodes: &HashMap<NodeId, Runner<ConditionCheckReactor<TestReactor>, TestRng>>| {
+            nodes.values().any(|runner| {
+                let metrics = &runner.reactor().inner().net.metrics;
+                metrics
+                    .handshake_rejections
+                    .with_label_values(&["chain_name"])
+                    .get()
+                    > 0
+            })
+        };
+    let timeout = Duration::from_secs(2);
+    net.settle_on(&mut rng, rejected_chain_name, timeout).await;
+
+    let quiet_for = Duration::from_millis(25);
+    net.settle(&mut rng, quiet_for, timeout).await;
+
+    assert!(
+        net.nodes().values().all(|runner| runner
+            .reactor()
+            .inner()
+            .net
+            .connected_nodes()
+            .is_empty()),
+        "nodes on different chains should not be connected"
+    );
+
+    net.finalize().await;
+}
+
+/// Check that a peer reported for misbehaving repeatedly gets banned and disconnected.
+#[tokio::test]
+async fn should_ban_misbehaving_peer() {
+    init_logging();
+
+    let mut rng = TestRng::new();
+    let first_node_port = testing::unused_port_on_localhost();
+
+    let mut net = Network::new();
+    let (first_node, _) = net
+        .add_node_with_config(
+            Config::default_local_net_first_node(first_node_port).into(),
+            &mut rng,
+        )
+        .await
+        .unwrap();
+    let (second_node, _) = net
+        .add_node_with_config(Config::default_local_net(first_node_port).into(), &mut rng)
+        .await
+        .unwrap();
+
+    let timeout = Duration::from_secs(2);
+    net.settle_on(&mut rng, network_is_complete, timeout).await;
+
+    // Two reports of undecodable messages reach the default ban threshold.
+    for _ in 0..2 {
+        net.process_injected_effect_on(&first_node, |effect_builder| {
+            effect_builder
+                .report_misbehavior::<_, Message>(second_node, Misbehavior::UndecodableMessage)
+                .ignore()
+        })
+        .await;
+    }
+
+    let quiet_for = Duration::from_millis(25);
+    net.settle(&mut rng, quiet_for, timeout).await;
+
+    let reputations = net.nodes()[&first_node]
+        .reactor()
+        .inner()
+        .net
+        .reputations
+        .reputations(Timestamp::now());
+    assert!(reputations[&second_node].banned_until.is_some());
+    assert!(
+        net.nodes().values().all(|runner| runner
+            .reactor()
+            .inner()
+            .net
+            .connected_nodes()
+            .is_empty()),
+        "banned peer should be disconnected"
+    );
+
+    net.finalize().await;
+}
+
+/// Check that broadcasts are flooded to all nodes of a bounded-degree overlay.
+#[tokio::test]
+async fn should_flood_broadcasts_through_overlay() {
+    init_logging();
+
+    let mut rng = TestRng::new();
+    let number_of_nodes = 8;
+    let timeout = Duration::from_secs(20);
+
+    let mut net = Network::new();
+    let (node_ids, _) =
+        add_overlay_network(&mut net, &mut rng, number_of_nodes, OVERLAY_DEGREE).await;
+    net.settle_on(&mut rng, overlay_is_connected, timeout).await;
+
+    // The overlay is not a full mesh: nodes only connect to a few peers.
+    assert!(
+        net.nodes().values().any(
+            |runner| runner.reactor().inner().net.connected_nodes().len() < number_of_nodes - 1
+        ),
+        "overlay should not be fully connected"
+    );
+
+    // Broadcast from the last node, rather than the first one that everyone bootstrapped from.
+    let origin = node_ids[number_of_nodes - 1];
+    broadcast_test_message(&mut net, &origin, 1).await;
+    net.settle_on(&mut rng, all_received(origin, 1), timeout)
+        .await;
+
+    net.finalize().await;
+}
+
+/// Check that the overlay recovers from nodes leaving and joining.
+#[tokio::test]
+async fn should_flood_broadcasts_after_churn() {
+    init_logging();
+
+    let mut rng = TestRng::new();
+    let timeout = Duration::from_secs(20);
+
+    let mut net = Network::new();
+    let (node_ids, first_node_port) =
+        add_overlay_network(&mut net, &mut rng, 8, OVERLAY_DEGREE).await;
+    net.settle_on(&mut rng, overlay_is_connected, timeout).await;
+
+    // Three nodes leave the network, and three new ones join.
+    for node_id in &node_ids[1..4] {
+        let runner = net.remove_node(node_id).unwrap();
+        runner.into_inner().finalize().await;
+    }
+    for _ in 0..3 {
+        let config = Config::default_local_net(first_node_port);
+        net.add_node_with_config(overlay_config(config, OVERLAY_DEGREE), &mut rng)
+            .await
+            .unwrap();
+    }
+    net.settle_on(&mut rng, overlay_is_connected, timeout).await;
+
+    let origins: Vec<NodeId> = net.nodes().keys().copied().collect();
+    for (number, origin) in origins.iter().enumerate() {
+        broadcast_test_message(&mut net, origin, number as u64).await;
+        net.settle_on(&mut rng, all_received(*origin, number as u64), timeout)
+            .await;
+    }
+
+    net.finalize().await;
+}
+
+/// Check that broadcasts don't cross a partition, and do once the partition is healed.
+#[tokio::test]
+async fn should_flood_broadcasts_after_partition_heals() {
+    init_logging();
+
+    let mut rng = TestRng::new();
+    let timeout = Duration::from_secs(20);
+
+    // Two groups of nodes, bootstrapping from different nodes, don't know about each other. The
+    // degree leaves room for one more connection in each group.
+    let mut net = Network::new();
+    let (group_a, _) = add_overlay_network(&mut net, &mut rng, 3, 3).await;
+    let (group_b, _) = add_overlay_network(&mut net, &mut rng, 3, 3).await;
+    let group_a_set: HashSet<NodeId> = group_a.iter().copied().collect();
+    let group_b_set: HashSet<NodeId> = group_b.iter().copied().collect();
+    let partitioned = |nodes: &Nodes<TestReactor>| {
+        reachable_from(nodes, group_a[0]) == group_a_set
+            && reachable_from(nodes, group_b[0]) == group_b_set
+    };
+    net.settle_on(&mut rng, partitioned, timeout).await;
+
+    let origin = group_a[0];
+    broadcast_test_message(&mut net, &origin, 1).await;
+    let group_a_received = |nodes: &Nodes<TestReactor>| received_by(nodes, &group_a, origin, 1);
+    net.settle_on(&mut rng, group_a_received, timeout).await;
+    net.settle(&mut rng, Duration::from_millis(25), timeout)
+        .await;
+    assert!(
+        group_b.iter().all(|node_id| !net.nodes()[node_id]
+            .reactor()
+            .inner()
+            .received_test_messages
+            .contains(&1)),
+        "broadcast should not cross the partition"
+    );
+
+    // Heal the partition by telling a node of the second group about the first group's address.
+    let address = net.nodes()[&origin].reactor().inner().net.public_address;
+    net.process_injected_effect_on(&group_b[1], |effect_builder| {
+        let gossiped_address = GossipedAddress::new(address, 0);
+        effect_builder.immediately().event(move |_| {
+            Event::SmallNet(small_network::Event::PeerAddressReceived(gossiped_address))
+        })
+    })
+    .await;
+    net.settle_on(&mut rng, overlay_is_connected, timeout).await;
+
+    broadcast_test_message(&mut net, &origin, 2).await;
+    net.settle_on(&mut rng, all_received(origin, 2), timeout)
+        .await;
+
+    net.finalize().await;
+}
@@ -9,7 +9,7 @@ mod lmdb_store;
 mod store;
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt::{Debug, Display},
     fs,
     hash::Hash,
@@ -22,17 +22,20 @@ use semver::Version;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use smallvec::smallvec;
 use tokio::task;
-use tracing::{debug, error, warn};
+use tracing::{debug, error, info, warn};
 
 use crate::{
-    components::{chainspec_loader::Chainspec, small_network::NodeId, Component},
-    crypto::asymmetric_key::Signature,
+    components::{chainspec_loader::Chainspec, consensus::EraId, small_network::NodeId, Component},
+    crypto::asymmetric_key::{PublicKey, Signature},
     effect::{
         requests::{NetworkRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects, Responder,
     },
     protocol::Message,
-    types::{json_compatibility::ExecutionResult, Block, Deploy, Item},
+    types::{
+        json_compatibility::ExecutionResult, Block, BlockExecutionSummary, Deploy, Evidence,
+        EvidenceId, Item, Timestamp,
+    },
 };
 use chainspec_store::ChainspecStore;
 pub use config::Config;
@@ -43,7 +46,7 @@ use in_mem_chainspec_store::InMemChainspecStore;
 use in_mem_store::InMemStore;
 use lmdb_chainspec_store::LmdbChainspecStore;
 use lmdb_store::LmdbStore;
-use store::{DeployStore, Multiple, Store};
+use store::{BlockStore, DeployStore, Multiple, Store};
 
 pub(crate) type Storage = LmdbStorage<Block, Deploy>;
 
@@ -91,11 +94,34 @@ pub trait Value: Clone + Serialize + DeserializeOwned + Send + Sync + Debug + Di
     fn take_header(self) -> Self::Header;
 }
 
+/// Trait defining the API for a block able to be held within the storage component.
+pub trait BlockValue: Value {
+    /// The type of the IDs of the deploys included in the block.
+    type DeployHash;
+
+    /// Returns the height of the block, i.e. the number of its ancestors.
+    fn height(&self) -> u64;
+    /// Returns the ID of the era to which the block belongs.
+    fn era_id(&self) -> EraId;
+    /// Returns the IDs of the deploys included in the block.
+    fn deploy_hashes(&self) -> &[Self::DeployHash];
+}
+
+/// Trait defining the API for a deploy able to be held within the storage component.
+pub trait DeployValue: Value {
+    /// Returns the account from which the deploy was sent.
+    fn account(&self) -> &PublicKey;
+    /// Returns the time at which the deploy was created.
+    fn timestamp(&self) -> Timestamp;
+}
+
 /// Metadata associated with a block.
 #[derive(Default, Clone, Serialize, Deserialize, Debug)]
 pub struct BlockMetadata {
-    /// The finalization signatures of a block.
-    pub proofs: Vec<Signature>,
+    /// The finalization signatures of a block, keyed by the public key of the signing validator.
+    pub proofs: BTreeMap<PublicKey, Signature>,
+    /// A summary of the results of executing the deploys in a block.
+    pub execution_summary: Option<BlockExecutionSummary>,
 }
 
 /// Metadata associated with a deploy.
@@ -122,19 +148,30 @@ impl<B: Value> Default for DeployMetadata<B> {
     }
 }
 
+/// A page of the deploys sent from a single account, ordered from the most recent.
+#[derive(Clone, Debug)]
+pub struct AccountDeploys<D: Value, B: Value> {
+    /// The total number of deploys from the account held in storage.
+    pub total_count: usize,
+    /// The IDs of the requested deploys along with their associated metadata.
+    pub deploys: Vec<(D::Id, DeployMetadata<B>)>,
+}
+
 /// Trait which will handle management of the various storage sub-components.
 ///
 /// If this trait is ultimately only used for testing scenarios, we shouldn't need to expose it to
 /// the reactor - it can simply use a concrete type which implements this trait.
 pub trait StorageType {
-    type Block: Value;
-    type Deploy: Value + Item;
+    type Block: BlockValue<DeployHash = <Self::Deploy as Value>::Id>;
+    type Deploy: DeployValue + Item;
 
-    fn block_store(&self) -> Arc<dyn Store<Value = Self::Block>>;
+    fn block_store(&self) -> Arc<dyn BlockStore<Block = Self::Block, Value = Self::Block>>;
     fn deploy_store(
         &self,
     ) -> Arc<dyn DeployStore<Block = Self::Block, Deploy = Self::Deploy, Value = Self::Deploy>>;
     fn chainspec_store(&self) -> Arc<dyn ChainspecStore>;
+    /// Returns the number of most recent eras to retain, or `None` if the full history is kept.
+    fn retained_eras(&self) -> Option<u64>;
     fn new(config: &Config) -> Result<Self>
     where
         Self: Sized;
@@ -172,6 +209,39 @@ pub trait StorageType {
         .ignore()
     }
 
+    fn get_evidence_for_peer<REv>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        evidence_id: EvidenceId,
+        peer: NodeId,
+    ) -> Effects<Event<Self>>
+    where
+        REv: From<NetworkRequest<NodeId, Message>> + Send,
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        async move {
+            task::spawn_blocking(move || block_store.get_era_evidence(evidence_id.era_id))
+                .await
+                .expect("should run")
+        }
+        .map_err(move |error| debug!("failed to get {} for {}: {}", evidence_id, peer, error))
+        .and_then(move |era_evidence| async move {
+            let maybe_evidence = era_evidence
+                .into_iter()
+                .find(|evidence| *evidence.perpetrator() == evidence_id.perpetrator);
+            match maybe_evidence {
+                Some(evidence) => match Message::new_get_response(&evidence) {
+                    Ok(message) => effect_builder.send_message(peer, message).await,
+                    Err(error) => error!("failed to create get-response: {}", error),
+                },
+                None => debug!("failed to get {} for {}", evidence_id, peer),
+            }
+            Ok(())
+        })
+        .ignore()
+    }
+
     fn put_block(&self, block: Box<Self::Block>, responder: Responder<bool>) -> Effects<Event<Self>>
     where
         Self: Sized,
@@ -179,7 +249,7 @@ pub trait StorageType {
         let block_store = self.block_store();
         let block_hash = *block.id();
         async move {
-            let result = task::spawn_blocking(move || block_store.put(*block))
+            let result = task::spawn_blocking(move || block_store.put_block(*block))
                 .await
                 .expect("should run")
                 .unwrap_or_else(|error| panic!("failed to put {}: {}", block_hash, error));
@@ -210,6 +280,69 @@ pub trait StorageType {
         .ignore()
     }
 
+    fn get_block_at_height(
+        &self,
+        height: u64,
+        responder: Responder<Option<Self::Block>>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        async move {
+            let result = task::spawn_blocking(move || block_store.get_by_height(height))
+                .await
+                .expect("should run")
+                .unwrap_or_else(|error| {
+                    panic!("failed to get block at height {}: {}", height, error)
+                });
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
+    fn get_highest_block(&self, responder: Responder<Option<Self::Block>>) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        async move {
+            let result = task::spawn_blocking(move || block_store.highest_block())
+                .await
+                .expect("should run")
+                .unwrap_or_else(|error| panic!("failed to get highest block: {}", error));
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
+    fn get_blocks_in_height_range(
+        &self,
+        from_height: u64,
+        to_height: u64,
+        responder: Responder<Vec<Self::Block>>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        async move {
+            let result = task::spawn_blocking(move || {
+                block_store.get_by_height_range(from_height, to_height)
+            })
+            .await
+            .expect("should run")
+            .unwrap_or_else(|error| {
+                panic!(
+                    "failed to get blocks in height range {}..={}: {}",
+                    from_height, to_height, error
+                )
+            });
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
     fn get_block_header(
         &self,
         block_hash: <Self::Block as Value>::Id,
@@ -244,7 +377,7 @@ pub trait StorageType {
         let deploy_store = self.deploy_store();
         let deploy_hash = *Value::id(&*deploy);
         async move {
-            let result = task::spawn_blocking(move || deploy_store.put(*deploy))
+            let result = task::spawn_blocking(move || deploy_store.put_deploy(*deploy))
                 .await
                 .expect("should run")
                 .unwrap_or_else(|error| panic!("failed to put {}: {}", deploy_hash, error));
@@ -335,6 +468,146 @@ pub trait StorageType {
         .ignore()
     }
 
+    fn put_execution_summary(
+        &self,
+        block_hash: <Self::Block as Value>::Id,
+        execution_summary: Box<BlockExecutionSummary>,
+        responder: Responder<()>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        async move {
+            let result = task::spawn_blocking(move || {
+                block_store.put_execution_summary(block_hash, *execution_summary)
+            })
+            .await
+            .expect("should run")
+            .unwrap_or_else(|error| {
+                panic!("failed to put execution summary {}: {}", block_hash, error)
+            });
+            if !result {
+                warn!(%block_hash, "replaced stored execution summary");
+            }
+            responder.respond(()).await
+        }
+        .ignore()
+    }
+
+    fn get_execution_summary(
+        &self,
+        block_hash: <Self::Block as Value>::Id,
+        responder: Responder<Option<BlockExecutionSummary>>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        async move {
+            let result =
+                task::spawn_blocking(move || block_store.get_execution_summary(block_hash))
+                    .await
+                    .expect("should run")
+                    .unwrap_or_else(|error| {
+                        panic!("failed to get execution summary {}: {}", block_hash, error)
+                    });
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
+    fn put_finality_signature(
+        &self,
+        block_hash: <Self::Block as Value>::Id,
+        public_key: Box<PublicKey>,
+        signature: Box<Signature>,
+        responder: Responder<bool>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        async move {
+            let result = task::spawn_blocking(move || {
+                block_store.put_finality_signature(block_hash, *public_key, *signature)
+            })
+            .await
+            .expect("should run")
+            .unwrap_or_else(|error| {
+                panic!("failed to put finality signature {}: {}", block_hash, error)
+            });
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
+    fn get_finality_signatures(
+        &self,
+        block_hash: <Self::Block as Value>::Id,
+        responder: Responder<BTreeMap<PublicKey, Signature>>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        async move {
+            let result =
+                task::spawn_blocking(move || block_store.get_finality_signatures(block_hash))
+                    .await
+                    .expect("should run")
+                    .unwrap_or_else(|error| {
+                        panic!(
+                            "failed to get finality signatures {}: {}",
+                            block_hash, error
+                        )
+                    });
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
+    fn put_evidence(
+        &self,
+        evidence: Box<Evidence>,
+        responder: Responder<bool>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        let evidence_id = evidence.id();
+        async move {
+            let result = task::spawn_blocking(move || block_store.put_evidence(*evidence))
+                .await
+                .expect("should run")
+                .unwrap_or_else(|error| panic!("failed to put {}: {}", evidence_id, error));
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
+    fn get_era_evidence(
+        &self,
+        era_id: EraId,
+        responder: Responder<Vec<Evidence>>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        async move {
+            let result = task::spawn_blocking(move || block_store.get_era_evidence(era_id))
+                .await
+                .expect("should run")
+                .unwrap_or_else(|error| {
+                    panic!("failed to get evidence in era {}: {}", era_id, error)
+                });
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
     fn get_deploy_and_metadata(
         &self,
         deploy_hash: <Self::Deploy as Value>::Id,
@@ -355,6 +628,93 @@ pub trait StorageType {
         .ignore()
     }
 
+    fn get_deploys_for_account(
+        &self,
+        account: PublicKey,
+        offset: usize,
+        limit: usize,
+        responder: Responder<AccountDeploys<Self::Deploy, Self::Block>>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let deploy_store = self.deploy_store();
+        async move {
+            let result = task::spawn_blocking(move || {
+                deploy_store.get_deploys_for_account(&account, offset, limit)
+            })
+            .await
+            .expect("should run")
+            .unwrap_or_else(|error| panic!("failed to get deploys for {}: {}", account, error));
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
+    fn prune(&self, era_id: EraId, responder: Responder<Option<u64>>) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        // Keep the given era and the ones preceding it, up to the configured number of eras.
+        let first_retained_era_id = match self.retained_eras() {
+            Some(retained_eras) if retained_eras > 0 && era_id.0 >= retained_eras => {
+                EraId(era_id.0 + 1 - retained_eras)
+            }
+            _ => return responder.respond(None).ignore(),
+        };
+        let block_store = self.block_store();
+        let deploy_store = self.deploy_store();
+        async move {
+            let result = task::spawn_blocking(move || -> Result<Option<u64>> {
+                let pruned_blocks = block_store.prune_eras_before(first_retained_era_id)?;
+                if pruned_blocks.is_empty() {
+                    return Ok(None);
+                }
+                let pruned_block_hashes: Vec<_> =
+                    pruned_blocks.iter().map(|block| *block.id()).collect();
+                let deploy_hashes: Vec<_> = pruned_blocks
+                    .iter()
+                    .flat_map(|block| block.deploy_hashes().iter().copied())
+                    .collect();
+                let pruned_deploy_count =
+                    deploy_store.prune_execution_results(&pruned_block_hashes, &deploy_hashes)?;
+                let lowest_retained_height = block_store.lowest_retained_height()?;
+                info!(
+                    pruned_block_count = pruned_blocks.len(),
+                    %pruned_deploy_count,
+                    %lowest_retained_height,
+                    "pruned storage"
+                );
+                Ok(Some(lowest_retained_height))
+            })
+            .await
+            .expect("should run")
+            .unwrap_or_else(|error| {
+                panic!(
+                    "failed to prune eras before {}: {}",
+                    first_retained_era_id, error
+                )
+            });
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
+    fn get_lowest_retained_height(&self, responder: Responder<u64>) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        async move {
+            let result = task::spawn_blocking(move || block_store.lowest_retained_height())
+                .await
+                .expect("should run")
+                .unwrap_or_else(|error| panic!("failed to get lowest retained height: {}", error));
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
     fn put_chainspec(
         &self,
         chainspec: Box<Chainspec>,
@@ -413,6 +773,9 @@ where
             Event::GetDeployForPeer { deploy_hash, peer } => {
                 self.get_deploy_for_peer(effect_builder, deploy_hash, peer)
             }
+            Event::GetEvidenceForPeer { evidence_id, peer } => {
+                self.get_evidence_for_peer(effect_builder, evidence_id, peer)
+            }
             Event::Request(StorageRequest::PutBlock { block, responder }) => {
                 self.put_block(block, responder)
             }
@@ -420,6 +783,17 @@ where
                 block_hash,
                 responder,
             }) => self.get_block(block_hash, responder),
+            Event::Request(StorageRequest::GetBlockAtHeight { height, responder }) => {
+                self.get_block_at_height(height, responder)
+            }
+            Event::Request(StorageRequest::GetHighestBlock { responder }) => {
+                self.get_highest_block(responder)
+            }
+            Event::Request(StorageRequest::GetBlocksInHeightRange {
+                from_height,
+                to_height,
+                responder,
+            }) => self.get_blocks_in_height_range(from_height, to_height, responder),
             Event::Request(StorageRequest::GetBlockHeader {
                 block_hash,
                 responder,
@@ -440,10 +814,48 @@ where
                 execution_results,
                 responder,
             }) => self.put_execution_results(block_hash, execution_results, responder),
+            Event::Request(StorageRequest::PutExecutionSummary {
+                block_hash,
+                execution_summary,
+                responder,
+            }) => self.put_execution_summary(block_hash, execution_summary, responder),
+            Event::Request(StorageRequest::GetExecutionSummary {
+                block_hash,
+                responder,
+            }) => self.get_execution_summary(block_hash, responder),
+            Event::Request(StorageRequest::PutFinalitySignature {
+                block_hash,
+                public_key,
+                signature,
+                responder,
+            }) => self.put_finality_signature(block_hash, public_key, signature, responder),
+            Event::Request(StorageRequest::GetFinalitySignatures {
+                block_hash,
+                responder,
+            }) => self.get_finality_signatures(block_hash, responder),
+            Event::Request(StorageRequest::PutEvidence {
+                evidence,
+                responder,
+            }) => self.put_evidence(evidence, responder),
+            Event::Request(StorageRequest::GetEraEvidence { era_id, responder }) => {
+                self.get_era_evidence(era_id, responder)
+            }
             Event::Request(StorageRequest::GetDeployAndMetadata {
                 deploy_hash,
                 responder,
             }) => self.get_deploy_and_metadata(deploy_hash, responder),
+            Event::Request(StorageRequest::GetDeploysForAccount {
+                account,
+                offset,
+                limit,
+                responder,
+            }) => self.get_deploys_for_account(account, offset, limit, responder),
+            Event::Request(StorageRequest::Prune { era_id, responder }) => {
+                self.prune(era_id, responder)
+            }
+            Event::Request(StorageRequest::GetLowestRetainedHeight { responder }) => {
+                self.get_lowest_retained_height(responder)
+            }
             Event::Request(StorageRequest::PutChainspec {
                 chainspec,
                 responder,
@@ -457,19 +869,24 @@ where
 
 // Concrete type of `Storage` backed by in-memory stores.
 #[derive(Debug)]
-pub(crate) struct InMemStorage<B: Value, D: Value> {
+pub(crate) struct InMemStorage<B: BlockValue, D: Value> {
     block_store: Arc<InMemStore<B, BlockMetadata>>,
     deploy_store: Arc<InMemStore<D, DeployMetadata<B>>>,
     chainspec_store: Arc<InMemChainspecStore>,
+    retained_eras: Option<u64>,
 }
 
 #[allow(trivial_casts)]
-impl<B: Value + 'static, D: Value + Item + 'static> StorageType for InMemStorage<B, D> {
+impl<B, D> StorageType for InMemStorage<B, D>
+where
+    B: BlockValue<DeployHash = D::Id> + 'static,
+    D: DeployValue + Item + 'static,
+{
     type Block = B;
     type Deploy = D;
 
-    fn block_store(&self) -> Arc<dyn Store<Value = B>> {
-        Arc::clone(&self.block_store) as Arc<dyn Store<Value = B>>
+    fn block_store(&self) -> Arc<dyn BlockStore<Block = B, Value = B>> {
+        Arc::clone(&self.block_store) as Arc<dyn BlockStore<Block = B, Value = B>>
     }
 
     fn deploy_store(&self) -> Arc<dyn DeployStore<Block = B, Deploy = D, Value = D>> {
@@ -480,25 +897,35 @@ impl<B: Value + 'static, D: Value + Item + 'static> StorageType for InMemStorage
         Arc::clone(&self.chainspec_store) as Arc<dyn ChainspecStore>
     }
 
-    fn new(_config: &Config) -> Result<Self> {
+    fn retained_eras(&self) -> Option<u64> {
+        self.retained_eras
+    }
+
+    fn new(config: &Config) -> Result<Self> {
         Ok(InMemStorage {
             block_store: Arc::new(InMemStore::new()),
             deploy_store: Arc::new(InMemStore::new()),
             chainspec_store: Arc::new(InMemChainspecStore::new()),
+            retained_eras: config.retained_eras(),
         })
     }
 }
 
 // Concrete type of `Storage` backed by LMDB stores.
 #[derive(Debug)]
-pub struct LmdbStorage<B: Value, D: Value> {
+pub struct LmdbStorage<B: BlockValue, D: Value> {
     block_store: Arc<LmdbStore<B, BlockMetadata>>,
     deploy_store: Arc<LmdbStore<D, DeployMetadata<B>>>,
     chainspec_store: Arc<LmdbChainspecStore>,
+    retained_eras: Option<u64>,
 }
 
 #[allow(trivial_casts)]
-impl<B: Value + 'static, D: Value + Item + 'static> StorageType for LmdbStorage<B, D> {
+impl<B, D> StorageType for LmdbStorage<B, D>
+where
+    B: BlockValue<DeployHash = D::Id> + 'static,
+    D: DeployValue + Item + 'static,
+{
     type Block = B;
     type Deploy = D;
 
@@ -514,7 +941,9 @@ impl<B: Value + 'static, D: Value + Item + 'static> StorageType for LmdbStorage<
         let chainspec_store_path = path.join(CHAINSPEC_STORE_FILENAME);
 
         let block_store = LmdbStore::new(block_store_path, config.max_block_store_size())?;
+        block_store.backfill_height_index()?;
         let deploy_store = LmdbStore::new(deploy_store_path, config.max_deploy_store_size())?;
+        deploy_store.backfill_account_index()?;
         let chainspec_store =
             LmdbChainspecStore::new(chainspec_store_path, config.max_chainspec_store_size())?;
 
@@ -522,11 +951,12 @@ impl<B: Value + 'static, D: Value + Item + 'static> StorageType for LmdbStorage<
             block_store: Arc::new(block_store),
             deploy_store: Arc::new(deploy_store),
             chainspec_store: Arc::new(chainspec_store),
+            retained_eras: config.retained_eras(),
         })
     }
 
-    fn block_store(&self) -> Arc<dyn Store<Value = B>> {
-        Arc::clone(&self.block_store) as Arc<dyn Store<Value = B>>
+    fn block_store(&self) -> Arc<dyn BlockStore<Block = B, Value = B>> {
+        Arc::clone(&self.block_store) as Arc<dyn BlockStore<Block = B, Value = B>>
     }
 
     fn deploy_store(&self) -> Arc<dyn DeployStore<Block = B, Deploy = D, Value = D>> {
@@ -536,4 +966,8 @@ impl<B: Value + 'static, D: Value + Item + 'static> StorageType for LmdbStorage<
     fn chainspec_store(&self) -> Arc<dyn ChainspecStore> {
         Arc::clone(&self.chainspec_store) as Arc<dyn ChainspecStore>
     }
+
+    fn retained_eras(&self) -> Option<u64> {
+        self.retained_eras
+    }
 }
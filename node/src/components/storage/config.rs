@@ -54,6 +54,13 @@ pub struct Config {
     ///
     /// The size should be a multiple of the OS page size.
     max_chainspec_store_size: Option<usize>,
+    /// The number of most recent eras for which blocks, deploys and execution results are kept.
+    ///
+    /// Once a block of a new era is stored, blocks of older eras are removed, along with their
+    /// execution results and any deploys executed only in those blocks.
+    ///
+    /// Defaults to keeping the full history.
+    retained_eras: Option<u64>,
 }
 
 impl Config {
@@ -69,6 +76,7 @@ impl Config {
             max_block_store_size: Some(DEFAULT_TEST_MAX_DB_SIZE),
             max_deploy_store_size: Some(DEFAULT_TEST_MAX_DB_SIZE),
             max_chainspec_store_size: Some(DEFAULT_TEST_MAX_DB_SIZE),
+            retained_eras: None,
         };
         (config, tempdir)
     }
@@ -104,6 +112,10 @@ impl Config {
         value
     }
 
+    pub(crate) fn retained_eras(&self) -> Option<u64> {
+        self.retained_eras
+    }
+
     fn default_path() -> PathBuf {
         ProjectDirs::from(QUALIFIER, ORGANIZATION, APPLICATION)
             .map(|project_dirs| project_dirs.data_dir().to_path_buf())
@@ -122,6 +134,7 @@ impl Default for Config {
             max_block_store_size: Some(DEFAULT_MAX_BLOCK_STORE_SIZE),
             max_deploy_store_size: Some(DEFAULT_MAX_DEPLOY_STORE_SIZE),
             max_chainspec_store_size: Some(DEFAULT_MAX_CHAINSPEC_STORE_SIZE),
+            retained_eras: None,
         }
     }
 }
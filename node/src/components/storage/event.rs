@@ -3,7 +3,7 @@ use std::fmt::{self, Display, Formatter};
 use derive_more::From;
 
 use super::{StorageType, Value};
-use crate::{effect::requests::StorageRequest, small_network::NodeId};
+use crate::{effect::requests::StorageRequest, small_network::NodeId, types::EvidenceId};
 
 /// `Storage` events.
 #[derive(Debug, From)]
@@ -13,6 +13,11 @@ pub enum Event<S: StorageType + 'static> {
         deploy_hash: <S::Deploy as Value>::Id,
         peer: NodeId,
     },
+    /// We received a `GetRequest` message for an `Evidence` from a peer.
+    GetEvidenceForPeer {
+        evidence_id: EvidenceId,
+        peer: NodeId,
+    },
     #[from]
     Request(StorageRequest<S>),
 }
@@ -23,6 +28,9 @@ impl<S: StorageType + 'static> Display for Event<S> {
             Event::GetDeployForPeer { deploy_hash, peer } => {
                 write!(formatter, "get deploy {} for {}", deploy_hash, peer)
             }
+            Event::GetEvidenceForPeer { evidence_id, peer } => {
+                write!(formatter, "get {} for {}", evidence_id, peer)
+            }
             Event::Request(request) => write!(formatter, "{}", request),
         }
     }
@@ -1,11 +1,21 @@
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
     fmt::Debug,
-    sync::RwLock,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
 };
 
-use super::{DeployMetadata, DeployStore, Multiple, Result, Store, Value};
-use crate::types::json_compatibility::ExecutionResult;
+use super::{
+    AccountDeploys, BlockMetadata, BlockStore, BlockValue, DeployMetadata, DeployStore,
+    DeployValue, Multiple, Result, Store, Value,
+};
+use crate::{
+    components::consensus::EraId,
+    crypto::asymmetric_key::{PublicKey, Signature},
+    types::{json_compatibility::ExecutionResult, BlockExecutionSummary, Evidence},
+};
 
 #[derive(Debug)]
 struct ValueAndMetadata<V, M> {
@@ -26,12 +36,18 @@ impl<V, M: Default + Send + Sync> ValueAndMetadata<V, M> {
 #[derive(Debug)]
 pub(super) struct InMemStore<V: Value, M> {
     inner: RwLock<HashMap<V::Id, ValueAndMetadata<V, M>>>,
+    /// The lowest height from which blocks haven't been pruned.  Only used by block stores.
+    lowest_retained_height: AtomicU64,
+    /// The evidence of equivocations, by era.  Only used by block stores.
+    evidence: RwLock<HashMap<EraId, Vec<Evidence>>>,
 }
 
 impl<V: Value, M> InMemStore<V, M> {
     pub(crate) fn new() -> Self {
         InMemStore {
             inner: RwLock::new(HashMap::new()),
+            lowest_retained_height: AtomicU64::new(0),
+            evidence: RwLock::new(HashMap::new()),
         }
     }
 }
@@ -87,10 +103,164 @@ impl<V: Value, M: Default + Send + Sync> Store for InMemStore<V, M> {
     }
 }
 
-impl<D: Value, B: Value> DeployStore for InMemStore<D, DeployMetadata<B>> {
+impl<B: BlockValue> BlockStore for InMemStore<B, BlockMetadata> {
+    type Block = B;
+
+    fn put_block(&self, block: B) -> Result<bool> {
+        self.put(block)
+    }
+
+    fn get_by_height(&self, height: u64) -> Result<Option<B>> {
+        Ok(self
+            .inner
+            .read()
+            .expect("should lock")
+            .values()
+            .filter_map(|value_and_metadata| value_and_metadata.value.as_ref())
+            .find(|block| block.height() == height)
+            .cloned())
+    }
+
+    fn get_by_height_range(&self, from_height: u64, to_height: u64) -> Result<Vec<B>> {
+        let mut blocks: Vec<B> = self
+            .inner
+            .read()
+            .expect("should lock")
+            .values()
+            .filter_map(|value_and_metadata| value_and_metadata.value.as_ref())
+            .filter(|block| (from_height..=to_height).contains(&block.height()))
+            .cloned()
+            .collect();
+        blocks.sort_by_key(BlockValue::height);
+        Ok(blocks)
+    }
+
+    fn highest_block(&self) -> Result<Option<B>> {
+        Ok(self
+            .inner
+            .read()
+            .expect("should lock")
+            .values()
+            .filter_map(|value_and_metadata| value_and_metadata.value.as_ref())
+            .max_by_key(|block| block.height())
+            .cloned())
+    }
+
+    fn put_execution_summary(
+        &self,
+        block_hash: B::Id,
+        execution_summary: BlockExecutionSummary,
+    ) -> Result<bool> {
+        let mut inner = self.inner.write().expect("should lock");
+        let value_and_metadata = inner.entry(block_hash).or_insert(ValueAndMetadata {
+            value: None,
+            metadata: BlockMetadata::default(),
+        });
+        Ok(value_and_metadata
+            .metadata
+            .execution_summary
+            .replace(execution_summary)
+            .is_none())
+    }
+
+    fn get_execution_summary(&self, block_hash: B::Id) -> Result<Option<BlockExecutionSummary>> {
+        Ok(self
+            .inner
+            .read()
+            .expect("should lock")
+            .get(&block_hash)
+            .and_then(|value_and_metadata| value_and_metadata.metadata.execution_summary.clone()))
+    }
+
+    fn put_finality_signature(
+        &self,
+        block_hash: B::Id,
+        public_key: PublicKey,
+        signature: Signature,
+    ) -> Result<bool> {
+        let mut inner = self.inner.write().expect("should lock");
+        let value_and_metadata = inner.entry(block_hash).or_insert(ValueAndMetadata {
+            value: None,
+            metadata: BlockMetadata::default(),
+        });
+        let proofs = &mut value_and_metadata.metadata.proofs;
+        if proofs.contains_key(&public_key) {
+            return Ok(false);
+        }
+        let _ = proofs.insert(public_key, signature);
+        Ok(true)
+    }
+
+    fn get_finality_signatures(&self, block_hash: B::Id) -> Result<BTreeMap<PublicKey, Signature>> {
+        Ok(self
+            .inner
+            .read()
+            .expect("should lock")
+            .get(&block_hash)
+            .map(|value_and_metadata| value_and_metadata.metadata.proofs.clone())
+            .unwrap_or_default())
+    }
+
+    fn put_evidence(&self, evidence: Evidence) -> Result<bool> {
+        let mut all_evidence = self.evidence.write().expect("should lock");
+        let era_evidence = all_evidence.entry(evidence.era_id()).or_default();
+        if era_evidence
+            .iter()
+            .any(|existing| existing.perpetrator() == evidence.perpetrator())
+        {
+            return Ok(false);
+        }
+        era_evidence.push(evidence);
+        Ok(true)
+    }
+
+    fn get_era_evidence(&self, era_id: EraId) -> Result<Vec<Evidence>> {
+        Ok(self
+            .evidence
+            .read()
+            .expect("should lock")
+            .get(&era_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn prune_eras_before(&self, era_id: EraId) -> Result<Vec<B>> {
+        let mut inner = self.inner.write().expect("should lock");
+        let mut height = self.lowest_retained_height.load(Ordering::SeqCst);
+        let mut pruned_blocks = vec![];
+        loop {
+            let maybe_block = inner
+                .values()
+                .filter_map(|value_and_metadata| value_and_metadata.value.as_ref())
+                .find(|block| block.height() == height)
+                .filter(|block| block.era_id() < era_id)
+                .cloned();
+            match maybe_block {
+                Some(block) => {
+                    let _ = inner.remove(block.id());
+                    pruned_blocks.push(block);
+                    height += 1;
+                }
+                None => break,
+            }
+        }
+        self.lowest_retained_height.store(height, Ordering::SeqCst);
+        Ok(pruned_blocks)
+    }
+
+    fn lowest_retained_height(&self) -> Result<u64> {
+        Ok(self.lowest_retained_height.load(Ordering::SeqCst))
+    }
+}
+
+impl<D: DeployValue, B: Value> DeployStore for InMemStore<D, DeployMetadata<B>> {
     type Block = B;
     type Deploy = D;
 
+    fn put_deploy(&self, deploy: D) -> Result<bool> {
+        self.put(deploy)
+    }
+
     fn put_execution_result(
         &self,
         id: D::Id,
@@ -130,4 +300,77 @@ impl<D: Value, B: Value> DeployStore for InMemStore<D, DeployMetadata<B>> {
                     .map(|value| (value.clone(), value_and_metadata.metadata.clone()))
             }))
     }
+
+    fn get_deploys_for_account(
+        &self,
+        account: &PublicKey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<AccountDeploys<D, B>> {
+        let inner = self.inner.read().expect("should lock");
+        let mut entries: Vec<_> = inner
+            .values()
+            .filter_map(|value_and_metadata| {
+                value_and_metadata
+                    .value
+                    .as_ref()
+                    .filter(|deploy| deploy.account() == account)
+                    .map(|deploy| {
+                        (
+                            deploy.timestamp(),
+                            *deploy.id(),
+                            &value_and_metadata.metadata,
+                        )
+                    })
+            })
+            .collect();
+        entries.sort_by(|(timestamp1, id1, _), (timestamp2, id2, _)| {
+            (timestamp2, id2).cmp(&(timestamp1, id1))
+        });
+        let deploys = entries
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(_timestamp, id, metadata)| (*id, (*metadata).clone()))
+            .collect();
+        Ok(AccountDeploys {
+            total_count: entries.len(),
+            deploys,
+        })
+    }
+
+    fn prune_execution_results(
+        &self,
+        block_hashes: &[B::Id],
+        deploy_hashes: &[D::Id],
+    ) -> Result<usize> {
+        let block_hashes: HashSet<_> = block_hashes.iter().collect();
+        let mut inner = self.inner.write().expect("should lock");
+
+        // Remove the execution results, noting any deploys left without execution results.
+        let mut emptied_ids = vec![];
+        for id in deploy_hashes {
+            let execution_results = match inner.get_mut(id) {
+                Some(value_and_metadata) => &mut value_and_metadata.metadata.execution_results,
+                None => continue,
+            };
+            if execution_results
+                .keys()
+                .any(|block_hash| block_hashes.contains(block_hash))
+            {
+                execution_results.retain(|block_hash, _| !block_hashes.contains(block_hash));
+                if execution_results.is_empty() {
+                    emptied_ids.push(*id);
+                }
+            }
+        }
+
+        let mut pruned_count = 0;
+        for id in emptied_ids {
+            if let Some(ValueAndMetadata { value: Some(_), .. }) = inner.remove(&id) {
+                pruned_count += 1;
+            }
+        }
+        Ok(pruned_count)
+    }
 }
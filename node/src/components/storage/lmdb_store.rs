@@ -1,21 +1,59 @@
-use std::{fmt::Debug, marker::PhantomData, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt::Debug,
+    marker::PhantomData,
+    path::Path,
+};
 
 use lmdb::{
-    self, Cursor, Database, DatabaseFlags, Environment, EnvironmentFlags, Transaction, WriteFlags,
+    self, Cursor, Database, DatabaseFlags, Environment, EnvironmentFlags, RwTransaction,
+    Transaction, WriteFlags,
 };
+use lmdb_sys::{MDB_cursor_op, MDB_FIRST, MDB_LAST, MDB_NEXT, MDB_PREV, MDB_SET_RANGE};
 use smallvec::smallvec;
 use tracing::info;
 
-use super::{DeployMetadata, DeployStore, Error, Multiple, Result, Store, Value};
-use crate::types::json_compatibility::ExecutionResult;
+use super::{
+    AccountDeploys, BlockMetadata, BlockStore, BlockValue, DeployMetadata, DeployStore,
+    DeployValue, Error, Multiple, Result, Store, Value,
+};
+use crate::{
+    components::consensus::EraId,
+    crypto::asymmetric_key::{PublicKey, Signature},
+    types::{json_compatibility::ExecutionResult, BlockExecutionSummary, Evidence},
+};
 
 /// Used to namespace metadata associated with stored values.
 #[repr(u8)]
 enum Tag {
-    // TODO - remove once used.
-    #[allow(unused)]
     BlockMetadata,
     DeployMetadata,
+    BlockHeight,
+    AccountDeploys,
+    LowestRetainedHeight,
+    Evidence,
+    AccountDeployCount,
+}
+
+/// The maximum number of index entries written per transaction when backfilling an index.
+#[cfg(not(test))]
+const BACKFILL_BATCH_SIZE: usize = 1_000;
+/// Small enough for tests to exercise backfilling in several batches.
+#[cfg(test)]
+const BACKFILL_BATCH_SIZE: usize = 2;
+
+/// Positions the cursor using the given operation, returning the entry it then points at, or
+/// `None` if there is no such entry.
+fn cursor_get<'txn, C: Cursor<'txn>>(
+    cursor: &C,
+    maybe_key: Option<&[u8]>,
+    op: MDB_cursor_op,
+) -> Option<(&'txn [u8], &'txn [u8])> {
+    match cursor.get(maybe_key, None, op) {
+        Ok((maybe_found_key, value)) => Some((maybe_found_key.expect("should have key"), value)),
+        Err(lmdb::Error::NotFound) => None,
+        Err(error) => panic!("should get: {:?}", error),
+    }
 }
 
 /// LMDB version of a store.
@@ -82,6 +120,68 @@ impl<V: Value, M> LmdbStore<V, M> {
         }
         .map_err(Error::from)
     }
+
+    /// Adds any stored values missing from an index, e.g. values stored by a previous version
+    /// which didn't maintain the index.
+    ///
+    /// The stored values are streamed through a cursor, with `index_key` returning the key of the
+    /// index entry for each serialized ID and value.  Values without an entry are added using
+    /// `add_to_index` in batches of up to `BACKFILL_BATCH_SIZE`, so the values aren't all held in
+    /// memory at once.  Returns the number of values added.
+    fn backfill_index<F, G>(&self, index_key: F, add_to_index: G) -> Result<usize>
+    where
+        F: Fn(&[u8], &V) -> Result<Vec<u8>>,
+        G: Fn(&mut RwTransaction, &[u8], &V) -> Result<bool>,
+    {
+        let mut backfilled_count = 0;
+        // The key of the first entry not yet read, if the previous batch didn't reach the end.
+        let mut maybe_resume_key: Option<Vec<u8>> = None;
+        loop {
+            let mut missing = Vec::new();
+            let txn = self.env.begin_ro_txn().expect("should create ro txn");
+            {
+                let cursor = txn
+                    .open_ro_cursor(self.db)
+                    .expect("should create ro cursor");
+                let mut maybe_entry = match &maybe_resume_key {
+                    Some(resume_key) => cursor_get(&cursor, Some(resume_key), MDB_SET_RANGE),
+                    None => cursor_get(&cursor, None, MDB_FIRST),
+                };
+                maybe_resume_key = None;
+                while let Some((serialized_id, serialized_value)) = maybe_entry {
+                    if missing.len() == BACKFILL_BATCH_SIZE {
+                        maybe_resume_key = Some(serialized_id.to_vec());
+                        break;
+                    }
+                    // Skip entries which aren't values, e.g. metadata and index entries.
+                    if rmp_serde::from_read_ref::<_, V::Id>(serialized_id).is_ok() {
+                        let value: V = rmp_serde::from_read_ref(serialized_value)?;
+                        match txn.get(self.db, &index_key(serialized_id, &value)?) {
+                            Ok(_) => (),
+                            Err(lmdb::Error::NotFound) => {
+                                missing.push((serialized_id.to_vec(), value))
+                            }
+                            Err(error) => panic!("should get: {:?}", error),
+                        }
+                    }
+                    maybe_entry = cursor_get(&cursor, None, MDB_NEXT);
+                }
+            }
+            txn.commit().expect("should commit txn");
+
+            let mut txn = self.env.begin_rw_txn().expect("should create rw txn");
+            for (serialized_id, value) in missing {
+                if add_to_index(&mut txn, &serialized_id, &value)? {
+                    backfilled_count += 1;
+                }
+            }
+            txn.commit().expect("should commit txn");
+
+            if maybe_resume_key.is_none() {
+                return Ok(backfilled_count);
+            }
+        }
+    }
 }
 
 impl<V: Value, M: Send + Sync> Store for LmdbStore<V, M> {
@@ -134,10 +234,488 @@ impl<V: Value, M: Send + Sync> Store for LmdbStore<V, M> {
     }
 }
 
-impl<D: Value, B: Value> DeployStore for LmdbStore<D, DeployMetadata<B>> {
+impl<B: BlockValue> LmdbStore<B, BlockMetadata> {
+    /// Adds any blocks missing from the height index, e.g. blocks stored by a previous version
+    /// which didn't maintain the index.
+    pub(super) fn backfill_height_index(&self) -> Result<()> {
+        let backfilled_count = self.backfill_index(
+            |_serialized_id, block| Self::serialized_height(block.height()),
+            |txn, serialized_id, block| {
+                let serialized_height = Self::serialized_height(block.height())?;
+                match txn.put(
+                    self.db,
+                    &serialized_height,
+                    &serialized_id,
+                    WriteFlags::NO_OVERWRITE,
+                ) {
+                    Ok(()) => Ok(true),
+                    Err(lmdb::Error::KeyExist) => Ok(false),
+                    Err(error) => panic!("should put: {:?}", error),
+                }
+            },
+        )?;
+        if backfilled_count > 0 {
+            info!(%backfilled_count, "backfilled block height index");
+        }
+        Ok(())
+    }
+
+    fn serialized_height(height: u64) -> Result<Vec<u8>> {
+        rmp_serde::to_vec(&(Tag::BlockHeight as u8, height)).map_err(Error::from)
+    }
+
+    fn serialized_lowest_retained_height_key() -> Result<Vec<u8>> {
+        rmp_serde::to_vec(&(Tag::LowestRetainedHeight as u8,)).map_err(Error::from)
+    }
+
+    fn serialized_era_evidence_key(era_id: EraId) -> Result<Vec<u8>> {
+        rmp_serde::to_vec(&(Tag::Evidence as u8, era_id)).map_err(Error::from)
+    }
+
+    /// Returns the lowest height from which blocks haven't been pruned using an existing
+    /// transaction.
+    fn get_lowest_retained_height_in_txn<T: Transaction>(&self, txn: &T) -> Result<u64> {
+        let serialized_key = Self::serialized_lowest_retained_height_key()?;
+        match txn.get(self.db, &serialized_key) {
+            Ok(serialized_value) => rmp_serde::from_read_ref(serialized_value).map_err(Error::from),
+            Err(lmdb::Error::NotFound) => Ok(0),
+            Err(error) => panic!("should get: {:?}", error),
+        }
+    }
+
+    /// Returns the block at the given height using an existing transaction.
+    fn get_by_height_in_txn<T: Transaction>(&self, txn: &T, height: u64) -> Result<Option<B>> {
+        let serialized_height = Self::serialized_height(height)?;
+        let serialized_id = match txn.get(self.db, &serialized_height) {
+            Ok(serialized_id) => serialized_id,
+            Err(lmdb::Error::NotFound) => return Ok(None),
+            Err(error) => panic!("should get: {:?}", error),
+        };
+        match txn.get(self.db, &serialized_id) {
+            Ok(serialized_value) => Ok(Some(rmp_serde::from_read_ref(serialized_value)?)),
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(error) => panic!("should get: {:?}", error),
+        }
+    }
+
+    /// Returns the ID of the block with the greatest height using an existing transaction.
+    ///
+    /// MessagePack encodes an unsigned integer big-endian in the narrowest format which holds it,
+    /// and wider formats have greater markers, so the height index keys sort in height order.  The
+    /// highest one is the last key before those of the following tag.
+    fn get_highest_id_in_txn<T: Transaction>(&self, txn: &T) -> Result<Option<Vec<u8>>> {
+        let serialized_bound = rmp_serde::to_vec(&(Tag::BlockHeight as u8 + 1, 0u64))?;
+        let cursor = txn
+            .open_ro_cursor(self.db)
+            .expect("should create ro cursor");
+        let maybe_entry = match cursor_get(&cursor, Some(&serialized_bound), MDB_SET_RANGE) {
+            Some(_) => cursor_get(&cursor, None, MDB_PREV),
+            None => cursor_get(&cursor, None, MDB_LAST),
+        };
+        Ok(maybe_entry
+            .filter(|(serialized_key, _)| {
+                matches!(
+                    rmp_serde::from_read_ref::<_, (u8, u64)>(serialized_key),
+                    Ok((tag, _)) if tag == Tag::BlockHeight as u8
+                )
+            })
+            .map(|(_, serialized_id)| serialized_id.to_vec()))
+    }
+}
+
+impl<B: BlockValue> BlockStore for LmdbStore<B, BlockMetadata> {
+    type Block = B;
+
+    fn put_block(&self, block: B) -> Result<bool> {
+        let serialized_id = Self::serialized_id(block.id(), None)?;
+        let serialized_height = Self::serialized_height(block.height())?;
+        let serialized_value = rmp_serde::to_vec(&block)?;
+        let mut txn = self.env.begin_rw_txn().expect("should create rw txn");
+        let result = match txn.put(
+            self.db,
+            &serialized_id,
+            &serialized_value,
+            WriteFlags::NO_OVERWRITE,
+        ) {
+            Ok(()) => true,
+            Err(lmdb::Error::KeyExist) => false,
+            Err(error) => panic!("should put: {:?}", error),
+        };
+        if result {
+            txn.put(
+                self.db,
+                &serialized_height,
+                &serialized_id,
+                WriteFlags::default(),
+            )?;
+        }
+        txn.commit().expect("should commit txn");
+        Ok(result)
+    }
+
+    fn get_by_height(&self, height: u64) -> Result<Option<B>> {
+        let txn = self.env.begin_ro_txn().expect("should create ro txn");
+        let result = self.get_by_height_in_txn(&txn, height);
+        txn.commit().expect("should commit txn");
+        result
+    }
+
+    fn get_by_height_range(&self, from_height: u64, to_height: u64) -> Result<Vec<B>> {
+        let txn = self.env.begin_ro_txn().expect("should create ro txn");
+        let mut blocks = vec![];
+        for height in from_height..=to_height {
+            if let Some(block) = self.get_by_height_in_txn(&txn, height)? {
+                blocks.push(block);
+            }
+        }
+        txn.commit().expect("should commit txn");
+        Ok(blocks)
+    }
+
+    fn highest_block(&self) -> Result<Option<B>> {
+        let txn = self.env.begin_ro_txn().expect("should create ro txn");
+        let result = match self.get_highest_id_in_txn(&txn)? {
+            Some(serialized_id) => match txn.get(self.db, &serialized_id) {
+                Ok(serialized_value) => Ok(Some(rmp_serde::from_read_ref(serialized_value)?)),
+                Err(lmdb::Error::NotFound) => Ok(None),
+                Err(error) => panic!("should get: {:?}", error),
+            },
+            None => Ok(None),
+        };
+        txn.commit().expect("should commit txn");
+        result
+    }
+
+    fn put_execution_summary(
+        &self,
+        block_hash: B::Id,
+        execution_summary: BlockExecutionSummary,
+    ) -> Result<bool> {
+        // Get existing metadata associated with this block.
+        let serialized_id = Self::serialized_id(&block_hash, Some(Tag::BlockMetadata))?;
+        let mut txn = self.env.begin_rw_txn().expect("should create rw txn");
+
+        let mut metadata: BlockMetadata = match txn.get(self.db, &serialized_id) {
+            Ok(serialized_value) => {
+                rmp_serde::from_read_ref(serialized_value).map_err(Error::from)?
+            }
+            Err(lmdb::Error::NotFound) => BlockMetadata::default(),
+            Err(error) => panic!("should get: {:?}", error),
+        };
+        let result = metadata
+            .execution_summary
+            .replace(execution_summary)
+            .is_none();
+
+        // Store the updated metadata.
+        let serialized_value = rmp_serde::to_vec(&metadata)?;
+        txn.put(
+            self.db,
+            &serialized_id,
+            &serialized_value,
+            WriteFlags::default(),
+        )?;
+        txn.commit().expect("should commit txn");
+        Ok(result)
+    }
+
+    fn get_execution_summary(&self, block_hash: B::Id) -> Result<Option<BlockExecutionSummary>> {
+        let serialized_id = Self::serialized_id(&block_hash, Some(Tag::BlockMetadata))?;
+        let txn = self.env.begin_ro_txn().expect("should create ro txn");
+        let result = match txn.get(self.db, &serialized_id) {
+            Ok(serialized_value) => {
+                let metadata: BlockMetadata = rmp_serde::from_read_ref(serialized_value)?;
+                Ok(metadata.execution_summary)
+            }
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(error) => panic!("should get: {:?}", error),
+        };
+        txn.commit().expect("should commit txn");
+        result
+    }
+
+    fn put_finality_signature(
+        &self,
+        block_hash: B::Id,
+        public_key: PublicKey,
+        signature: Signature,
+    ) -> Result<bool> {
+        // Get existing metadata associated with this block.
+        let serialized_id = Self::serialized_id(&block_hash, Some(Tag::BlockMetadata))?;
+        let mut txn = self.env.begin_rw_txn().expect("should create rw txn");
+
+        let mut metadata: BlockMetadata = match txn.get(self.db, &serialized_id) {
+            Ok(serialized_value) => {
+                rmp_serde::from_read_ref(serialized_value).map_err(Error::from)?
+            }
+            Err(lmdb::Error::NotFound) => BlockMetadata::default(),
+            Err(error) => panic!("should get: {:?}", error),
+        };
+        if metadata.proofs.contains_key(&public_key) {
+            txn.commit().expect("should commit txn");
+            return Ok(false);
+        }
+        let _ = metadata.proofs.insert(public_key, signature);
+
+        // Store the updated metadata.
+        let serialized_value = rmp_serde::to_vec(&metadata)?;
+        txn.put(
+            self.db,
+            &serialized_id,
+            &serialized_value,
+            WriteFlags::default(),
+        )?;
+        txn.commit().expect("should commit txn");
+        Ok(true)
+    }
+
+    fn get_finality_signatures(&self, block_hash: B::Id) -> Result<BTreeMap<PublicKey, Signature>> {
+        let serialized_id = Self::serialized_id(&block_hash, Some(Tag::BlockMetadata))?;
+        let txn = self.env.begin_ro_txn().expect("should create ro txn");
+        let result = match txn.get(self.db, &serialized_id) {
+            Ok(serialized_value) => {
+                let metadata: BlockMetadata = rmp_serde::from_read_ref(serialized_value)?;
+                Ok(metadata.proofs)
+            }
+            Err(lmdb::Error::NotFound) => Ok(BTreeMap::new()),
+            Err(error) => panic!("should get: {:?}", error),
+        };
+        txn.commit().expect("should commit txn");
+        result
+    }
+
+    fn put_evidence(&self, evidence: Evidence) -> Result<bool> {
+        // Get the existing evidence of the same era.
+        let serialized_key = Self::serialized_era_evidence_key(evidence.era_id())?;
+        let mut txn = self.env.begin_rw_txn().expect("should create rw txn");
+
+        let mut era_evidence: Vec<Evidence> = match txn.get(self.db, &serialized_key) {
+            Ok(serialized_value) => {
+                rmp_serde::from_read_ref(serialized_value).map_err(Error::from)?
+            }
+            Err(lmdb::Error::NotFound) => vec![],
+            Err(error) => panic!("should get: {:?}", error),
+        };
+        if era_evidence
+            .iter()
+            .any(|existing| existing.perpetrator() == evidence.perpetrator())
+        {
+            txn.commit().expect("should commit txn");
+            return Ok(false);
+        }
+        era_evidence.push(evidence);
+
+        // Store the updated evidence.
+        let serialized_value = rmp_serde::to_vec(&era_evidence)?;
+        txn.put(
+            self.db,
+            &serialized_key,
+            &serialized_value,
+            WriteFlags::default(),
+        )?;
+        txn.commit().expect("should commit txn");
+        Ok(true)
+    }
+
+    fn get_era_evidence(&self, era_id: EraId) -> Result<Vec<Evidence>> {
+        let serialized_key = Self::serialized_era_evidence_key(era_id)?;
+        let txn = self.env.begin_ro_txn().expect("should create ro txn");
+        let result = match txn.get(self.db, &serialized_key) {
+            Ok(serialized_value) => rmp_serde::from_read_ref(serialized_value).map_err(Error::from),
+            Err(lmdb::Error::NotFound) => Ok(vec![]),
+            Err(error) => panic!("should get: {:?}", error),
+        };
+        txn.commit().expect("should commit txn");
+        result
+    }
+
+    fn prune_eras_before(&self, era_id: EraId) -> Result<Vec<B>> {
+        let mut txn = self.env.begin_rw_txn().expect("should create rw txn");
+        let lowest_retained_height = self.get_lowest_retained_height_in_txn(&txn)?;
+
+        // Remove blocks in height order until reaching one which should be retained.
+        let mut height = lowest_retained_height;
+        let mut pruned_blocks = vec![];
+        while let Some(block) = self.get_by_height_in_txn(&txn, height)? {
+            if block.era_id() >= era_id {
+                break;
+            }
+            let serialized_id = Self::serialized_id(block.id(), None)?;
+            let serialized_height = Self::serialized_height(height)?;
+            let serialized_metadata_id = Self::serialized_id(block.id(), Some(Tag::BlockMetadata))?;
+            txn.del(self.db, &serialized_id, None)?;
+            txn.del(self.db, &serialized_height, None)?;
+            match txn.del(self.db, &serialized_metadata_id, None) {
+                Ok(()) | Err(lmdb::Error::NotFound) => (),
+                Err(error) => panic!("should delete: {:?}", error),
+            }
+            pruned_blocks.push(block);
+            height += 1;
+        }
+
+        if height > lowest_retained_height {
+            let serialized_key = Self::serialized_lowest_retained_height_key()?;
+            let serialized_value = rmp_serde::to_vec(&height)?;
+            txn.put(
+                self.db,
+                &serialized_key,
+                &serialized_value,
+                WriteFlags::default(),
+            )?;
+        }
+        txn.commit().expect("should commit txn");
+        Ok(pruned_blocks)
+    }
+
+    fn lowest_retained_height(&self) -> Result<u64> {
+        let txn = self.env.begin_ro_txn().expect("should create ro txn");
+        let result = self.get_lowest_retained_height_in_txn(&txn);
+        txn.commit().expect("should commit txn");
+        result
+    }
+}
+
+impl<D: DeployValue, B: Value> LmdbStore<D, DeployMetadata<B>> {
+    /// Adds any deploys missing from the account index, e.g. deploys stored by a previous version
+    /// which didn't maintain the index.
+    pub(super) fn backfill_account_index(&self) -> Result<()> {
+        let backfilled_count = self.backfill_index(
+            |_serialized_id, deploy| Self::account_index_key(deploy),
+            |txn, _serialized_id, deploy| self.add_to_account_index(txn, deploy),
+        )?;
+        if backfilled_count > 0 {
+            info!(%backfilled_count, "backfilled account deploys index");
+        }
+        Ok(())
+    }
+
+    /// Returns the prefix shared by the keys of all entries of the given account in the index of
+    /// deploys by account.
+    fn account_index_prefix(account: &PublicKey) -> Result<Vec<u8>> {
+        rmp_serde::to_vec(&(Tag::AccountDeploys as u8, account)).map_err(Error::from)
+    }
+
+    /// Returns the key of the given deploy's entry in the index of deploys by account.
+    ///
+    /// The account's prefix is followed by the deploy's timestamp and serialized ID, both with
+    /// their bits inverted, so that an account's entries are ordered from the most recent deploy to
+    /// the oldest.  The value of each entry is the serialized ID.
+    fn account_index_key(deploy: &D) -> Result<Vec<u8>> {
+        let mut key = Self::account_index_prefix(deploy.account())?;
+        key.extend_from_slice(&(!deploy.timestamp().millis()).to_be_bytes());
+        key.extend(
+            Self::serialized_id(deploy.id(), None)?
+                .iter()
+                .map(|byte| !byte),
+        );
+        Ok(key)
+    }
+
+    fn serialized_account_deploy_count_key(account: &PublicKey) -> Result<Vec<u8>> {
+        rmp_serde::to_vec(&(Tag::AccountDeployCount as u8, account)).map_err(Error::from)
+    }
+
+    /// Returns the number of deploys in the account index for the given account using an existing
+    /// transaction.
+    fn get_account_deploy_count<T: Transaction>(
+        &self,
+        txn: &T,
+        account: &PublicKey,
+    ) -> Result<u64> {
+        let serialized_key = Self::serialized_account_deploy_count_key(account)?;
+        match txn.get(self.db, &serialized_key) {
+            Ok(serialized_value) => rmp_serde::from_read_ref(serialized_value).map_err(Error::from),
+            Err(lmdb::Error::NotFound) => Ok(0),
+            Err(error) => panic!("should get: {:?}", error),
+        }
+    }
+
+    /// Sets the number of deploys in the account index for the given account.
+    fn put_account_deploy_count(
+        &self,
+        txn: &mut RwTransaction,
+        account: &PublicKey,
+        count: u64,
+    ) -> Result<()> {
+        let serialized_key = Self::serialized_account_deploy_count_key(account)?;
+        if count == 0 {
+            txn.del(self.db, &serialized_key, None)?;
+        } else {
+            let serialized_value = rmp_serde::to_vec(&count)?;
+            txn.put(
+                self.db,
+                &serialized_key,
+                &serialized_value,
+                WriteFlags::default(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Removes the given deploy from the index of deploys by account.
+    fn remove_from_account_index(&self, txn: &mut RwTransaction, deploy: &D) -> Result<()> {
+        let key = Self::account_index_key(deploy)?;
+        match txn.del(self.db, &key, None) {
+            Ok(()) => (),
+            Err(lmdb::Error::NotFound) => return Ok(()),
+            Err(error) => panic!("should delete: {:?}", error),
+        }
+        let count = self.get_account_deploy_count(&*txn, deploy.account())?;
+        self.put_account_deploy_count(txn, deploy.account(), count.saturating_sub(1))
+    }
+
+    /// Adds the given deploy to the index of deploys by account.  Returns false if it was already
+    /// indexed.
+    fn add_to_account_index(&self, txn: &mut RwTransaction, deploy: &D) -> Result<bool> {
+        let key = Self::account_index_key(deploy)?;
+        let serialized_id = Self::serialized_id(deploy.id(), None)?;
+        match txn.put(self.db, &key, &serialized_id, WriteFlags::NO_OVERWRITE) {
+            Ok(()) => (),
+            Err(lmdb::Error::KeyExist) => return Ok(false),
+            Err(error) => panic!("should put: {:?}", error),
+        }
+        let count = self.get_account_deploy_count(&*txn, deploy.account())?;
+        self.put_account_deploy_count(txn, deploy.account(), count + 1)?;
+        Ok(true)
+    }
+
+    /// Returns the metadata associated with the given deploy using an existing transaction, or a
+    /// default one if there is none.
+    fn get_metadata<T: Transaction>(&self, txn: &T, id: &D::Id) -> Result<DeployMetadata<B>> {
+        let serialized_metadata_id = Self::serialized_id(id, Some(Tag::DeployMetadata))?;
+        match txn.get(self.db, &serialized_metadata_id) {
+            Ok(serialized_value) => rmp_serde::from_read_ref(serialized_value).map_err(Error::from),
+            Err(lmdb::Error::NotFound) => Ok(DeployMetadata::default()),
+            Err(error) => panic!("should get: {:?}", error),
+        }
+    }
+}
+
+impl<D: DeployValue, B: Value> DeployStore for LmdbStore<D, DeployMetadata<B>> {
     type Block = B;
     type Deploy = D;
 
+    fn put_deploy(&self, deploy: D) -> Result<bool> {
+        let serialized_id = Self::serialized_id(deploy.id(), None)?;
+        let serialized_value = rmp_serde::to_vec(&deploy)?;
+        let mut txn = self.env.begin_rw_txn().expect("should create rw txn");
+        let result = match txn.put(
+            self.db,
+            &serialized_id,
+            &serialized_value,
+            WriteFlags::NO_OVERWRITE,
+        ) {
+            Ok(()) => true,
+            Err(lmdb::Error::KeyExist) => false,
+            Err(error) => panic!("should put: {:?}", error),
+        };
+        if result {
+            let _ = self.add_to_account_index(&mut txn, &deploy)?;
+        }
+        txn.commit().expect("should commit txn");
+        Ok(result)
+    }
+
     fn put_execution_result(
         &self,
         id: D::Id,
@@ -208,4 +786,95 @@ impl<D: Value, B: Value> DeployStore for LmdbStore<D, DeployMetadata<B>> {
         txn.commit().expect("should commit txn");
         Ok(Some((deploy, metadata)))
     }
+
+    fn get_deploys_for_account(
+        &self,
+        account: &PublicKey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<AccountDeploys<D, B>> {
+        let prefix = Self::account_index_prefix(account)?;
+        let txn = self.env.begin_ro_txn().expect("should create ro txn");
+        let total_count = self.get_account_deploy_count(&txn, account)? as usize;
+
+        // Scan the account's entries, which are ordered most recent first.
+        let mut ids = vec![];
+        {
+            let cursor = txn
+                .open_ro_cursor(self.db)
+                .expect("should create ro cursor");
+            let mut maybe_entry = cursor_get(&cursor, Some(&prefix), MDB_SET_RANGE);
+            let mut skipped = 0;
+            while let Some((key, serialized_id)) = maybe_entry {
+                if !key.starts_with(&prefix) || ids.len() == limit {
+                    break;
+                }
+                if skipped < offset {
+                    skipped += 1;
+                } else {
+                    ids.push(rmp_serde::from_read_ref::<_, D::Id>(serialized_id)?);
+                }
+                maybe_entry = cursor_get(&cursor, None, MDB_NEXT);
+            }
+        }
+
+        let deploys = ids
+            .into_iter()
+            .map(|id| Ok((id, self.get_metadata(&txn, &id)?)))
+            .collect::<Result<_>>()?;
+        txn.commit().expect("should commit txn");
+        Ok(AccountDeploys {
+            total_count,
+            deploys,
+        })
+    }
+
+    fn prune_execution_results(
+        &self,
+        block_hashes: &[B::Id],
+        deploy_hashes: &[D::Id],
+    ) -> Result<usize> {
+        let block_hashes: HashSet<_> = block_hashes.iter().collect();
+        let deploy_hashes: BTreeSet<_> = deploy_hashes.iter().collect();
+        let mut txn = self.env.begin_rw_txn().expect("should create rw txn");
+
+        // Remove the execution results, and any deploys left without execution results.
+        let mut pruned_count = 0;
+        for id in deploy_hashes {
+            let mut metadata = self.get_metadata(&txn, id)?;
+            let result_count = metadata.execution_results.len();
+            metadata
+                .execution_results
+                .retain(|block_hash, _| !block_hashes.contains(block_hash));
+            if metadata.execution_results.len() == result_count {
+                continue;
+            }
+            let serialized_metadata_id = Self::serialized_id(id, Some(Tag::DeployMetadata))?;
+            if !metadata.execution_results.is_empty() {
+                let serialized_value = rmp_serde::to_vec(&metadata)?;
+                txn.put(
+                    self.db,
+                    &serialized_metadata_id,
+                    &serialized_value,
+                    WriteFlags::default(),
+                )?;
+                continue;
+            }
+
+            txn.del(self.db, &serialized_metadata_id, None)?;
+            let serialized_id = Self::serialized_id(id, None)?;
+            let maybe_deploy: Option<D> = match txn.get(self.db, &serialized_id) {
+                Ok(serialized_value) => Some(rmp_serde::from_read_ref(serialized_value)?),
+                Err(lmdb::Error::NotFound) => None,
+                Err(error) => panic!("should get: {:?}", error),
+            };
+            if let Some(deploy) = maybe_deploy {
+                txn.del(self.db, &serialized_id, None)?;
+                self.remove_from_account_index(&mut txn, &deploy)?;
+                pruned_count += 1;
+            }
+        }
+        txn.commit().expect("should commit txn");
+        Ok(pruned_count)
+    }
 }
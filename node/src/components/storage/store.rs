@@ -1,7 +1,13 @@
+use std::collections::BTreeMap;
+
 use smallvec::SmallVec;
 
-use super::{DeployAndMetadata, Result, Value};
-use crate::types::json_compatibility::ExecutionResult;
+use super::{AccountDeploys, BlockValue, DeployAndMetadata, DeployValue, Result, Value};
+use crate::{
+    components::consensus::EraId,
+    crypto::asymmetric_key::{PublicKey, Signature},
+    types::{json_compatibility::ExecutionResult, BlockExecutionSummary, Evidence},
+};
 
 pub(super) type Multiple<T> = SmallVec<[T; 3]>;
 
@@ -24,9 +30,88 @@ pub trait Store: Send + Sync {
     fn ids(&self) -> Result<Vec<<Self::Value as Value>::Id>>;
 }
 
+pub trait BlockStore: Store {
+    type Block: BlockValue;
+
+    /// Stores the block and indexes it by its height.
+    ///
+    /// If the store did not have this block present, true is returned.  If the store did have
+    /// this block present, false is returned.
+    fn put_block(&self, block: Self::Block) -> Result<bool>;
+
+    /// Returns the block at the given height if it exists.
+    fn get_by_height(&self, height: u64) -> Result<Option<Self::Block>>;
+
+    /// Returns the blocks with heights in the inclusive range `from_height..=to_height`, ordered by
+    /// height.  Heights for which no block is held are skipped.
+    fn get_by_height_range(&self, from_height: u64, to_height: u64) -> Result<Vec<Self::Block>>;
+
+    /// Returns the block with the greatest height if any block is held.
+    fn highest_block(&self) -> Result<Option<Self::Block>>;
+
+    /// Stores the summary of executing the deploys in the given block.
+    ///
+    /// If the store did not have a summary for this block, true is returned.  If the store did
+    /// have one, it is replaced and false is returned.
+    fn put_execution_summary(
+        &self,
+        block_hash: <Self::Block as Value>::Id,
+        execution_summary: BlockExecutionSummary,
+    ) -> Result<bool>;
+
+    /// Returns the summary of executing the deploys in the given block if it exists.
+    fn get_execution_summary(
+        &self,
+        block_hash: <Self::Block as Value>::Id,
+    ) -> Result<Option<BlockExecutionSummary>>;
+
+    /// Stores the given validator's signature of the given block.
+    ///
+    /// If the store did not have a signature from this validator, true is returned.  If the store
+    /// did have one, it is left unchanged and false is returned.
+    fn put_finality_signature(
+        &self,
+        block_hash: <Self::Block as Value>::Id,
+        public_key: PublicKey,
+        signature: Signature,
+    ) -> Result<bool>;
+
+    /// Returns the validators' signatures of the given block, keyed by their public keys.
+    fn get_finality_signatures(
+        &self,
+        block_hash: <Self::Block as Value>::Id,
+    ) -> Result<BTreeMap<PublicKey, Signature>>;
+
+    /// Stores the given evidence of an equivocation.
+    ///
+    /// If the store did not have evidence against this validator in this era, true is returned.
+    /// If the store did have some, it is left unchanged and false is returned.
+    fn put_evidence(&self, evidence: Evidence) -> Result<bool>;
+
+    /// Returns all the evidence of equivocations in the given era.
+    fn get_era_evidence(&self, era_id: EraId) -> Result<Vec<Evidence>>;
+
+    /// Removes the blocks of eras before the given one, along with their metadata and height index
+    /// entries.
+    ///
+    /// Blocks are removed in height order starting from the lowest retained height, stopping at the
+    /// first block of a later era or the first height for which no block is held.  Returns the
+    /// removed blocks.
+    fn prune_eras_before(&self, era_id: EraId) -> Result<Vec<Self::Block>>;
+
+    /// Returns the lowest height from which blocks haven't been pruned.
+    fn lowest_retained_height(&self) -> Result<u64>;
+}
+
 pub trait DeployStore: Store {
     type Block: Value;
-    type Deploy: Value;
+    type Deploy: DeployValue;
+
+    /// Stores the deploy and indexes it by the account from which it was sent.
+    ///
+    /// If the store did not have this deploy present, true is returned.  If the store did have
+    /// this deploy present, false is returned.
+    fn put_deploy(&self, deploy: Self::Deploy) -> Result<bool>;
 
     fn put_execution_result(
         &self,
@@ -40,19 +125,53 @@ pub trait DeployStore: Store {
         &self,
         id: <Self::Deploy as Value>::Id,
     ) -> Result<Option<DeployAndMetadata<Self::Deploy, Self::Block>>>;
+
+    /// Returns up to `limit` of the deploys sent from the given account along with their metadata,
+    /// ordered from the most recent and skipping the first `offset` of them.
+    fn get_deploys_for_account(
+        &self,
+        account: &PublicKey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<AccountDeploys<Self::Deploy, Self::Block>>;
+
+    /// Removes the execution results for the given blocks from the given deploys, which should be
+    /// the ones included in those blocks.  Deploys left without any execution results are removed
+    /// too, along with their metadata and account index entries.  Other deploys aren't touched.
+    ///
+    /// Returns the number of deploys removed.
+    fn prune_execution_results(
+        &self,
+        block_hashes: &[<Self::Block as Value>::Id],
+        deploy_hashes: &[<Self::Deploy as Value>::Id],
+    ) -> Result<usize>;
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use smallvec::smallvec;
 
     use super::{
-        super::{Config, DeployMetadata, InMemStore, LmdbStore},
+        super::{BlockMetadata, Config, DeployMetadata, InMemStore, LmdbStore},
         *,
     };
+    use casper_execution_engine::{
+        core::engine_state::{
+            executable_deploy_item::ExecutableDeployItem,
+            execution_result::ExecutionResult as EngineExecutionResult,
+        },
+        shared::gas::Gas,
+    };
+
     use crate::{
+        crypto::{
+            asymmetric_key::{self, SecretKey},
+            hash::Digest,
+        },
         testing::TestRng,
-        types::{Block, Deploy},
+        types::{Block, BlockHash, Deploy, FinalizedBlock, ProtoBlock, TimeDiff, Timestamp},
     };
 
     fn should_put_then_get<T: Store<Value = Deploy>>(store: &mut T) {
@@ -88,4 +207,669 @@ mod tests {
         let mut in_mem_deploy_store = InMemStore::<Deploy, DeployMetadata<Block>>::new();
         should_put_then_get(&mut in_mem_deploy_store);
     }
+
+    fn should_get_deploys_for_account<
+        T: DeployStore<Deploy = Deploy, Block = Block, Value = Deploy>,
+    >(
+        store: &mut T,
+    ) {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random(&mut rng);
+        let account = PublicKey::from(&secret_key);
+
+        // Store deploys from the account out of timestamp order, along with an unrelated one.
+        let mut deploys: Vec<Deploy> = [3, 1, 2]
+            .iter()
+            .map(|millis: &u64| {
+                let module_bytes = ExecutableDeployItem::ModuleBytes {
+                    module_bytes: vec![],
+                    args: vec![],
+                };
+                Deploy::new(
+                    Timestamp::from(*millis),
+                    TimeDiff::from(60_000),
+                    1,
+                    vec![],
+                    String::from("casper-example"),
+                    module_bytes.clone(),
+                    module_bytes,
+                    &secret_key,
+                    &mut rng,
+                )
+            })
+            .collect();
+        for deploy in &deploys {
+            assert!(store.put_deploy(deploy.clone()).unwrap());
+            assert!(!store.put_deploy(deploy.clone()).unwrap());
+        }
+        assert!(store.put_deploy(Deploy::random(&mut rng)).unwrap());
+
+        // Record an execution result for one of them.
+        let block_hash = *Block::random(&mut rng).id();
+        let execution_result = ExecutionResult::from(&EngineExecutionResult::Success {
+            effect: Default::default(),
+            cost: Gas::default(),
+        });
+        store
+            .put_execution_result(*deploys[0].id(), block_hash, execution_result.clone())
+            .unwrap();
+
+        // The deploys should be returned most recent first.
+        deploys.sort_by_key(|deploy| std::cmp::Reverse(deploy.header().timestamp()));
+        let account_deploys = store.get_deploys_for_account(&account, 0, 10).unwrap();
+        assert_eq!(account_deploys.total_count, 3);
+        let deploy_hashes: Vec<_> = account_deploys
+            .deploys
+            .iter()
+            .map(|(deploy_hash, _metadata)| *deploy_hash)
+            .collect();
+        let expected_deploy_hashes: Vec<_> = deploys.iter().map(|deploy| *deploy.id()).collect();
+        assert_eq!(deploy_hashes, expected_deploy_hashes);
+        assert_eq!(
+            account_deploys.deploys[0].1.execution_results[&block_hash],
+            execution_result
+        );
+        assert!(account_deploys.deploys[1].1.execution_results.is_empty());
+
+        // Pagination should skip and limit the deploys.
+        let account_deploys = store.get_deploys_for_account(&account, 1, 1).unwrap();
+        assert_eq!(account_deploys.total_count, 3);
+        assert_eq!(account_deploys.deploys.len(), 1);
+        assert_eq!(account_deploys.deploys[0].0, expected_deploy_hashes[1]);
+
+        let unknown_account = PublicKey::random(&mut rng);
+        let account_deploys = store
+            .get_deploys_for_account(&unknown_account, 0, 10)
+            .unwrap();
+        assert_eq!(account_deploys.total_count, 0);
+        assert!(account_deploys.deploys.is_empty());
+    }
+
+    #[test]
+    fn lmdb_deploy_store_should_get_deploys_for_account() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_deploy_store = LmdbStore::<Deploy, DeployMetadata<Block>>::new(
+            config.path(),
+            config.max_deploy_store_size(),
+        )
+        .unwrap();
+        should_get_deploys_for_account(&mut lmdb_deploy_store);
+    }
+
+    #[test]
+    fn in_mem_deploy_store_should_get_deploys_for_account() {
+        let mut in_mem_deploy_store = InMemStore::<Deploy, DeployMetadata<Block>>::new();
+        should_get_deploys_for_account(&mut in_mem_deploy_store);
+    }
+
+    fn should_get_blocks_by_height<T: BlockStore<Block = Block, Value = Block>>(store: &mut T) {
+        let mut rng = TestRng::new();
+
+        // Store blocks with distinct heights.
+        let mut blocks = BTreeMap::new();
+        while blocks.len() < 5 {
+            let block = Block::random(&mut rng);
+            let _ = blocks.entry(block.height()).or_insert(block);
+        }
+        for block in blocks.values() {
+            assert!(store.put_block(block.clone()).unwrap());
+        }
+
+        for (height, block) in &blocks {
+            let recovered_block = store.get_by_height(*height).unwrap().unwrap();
+            assert_eq!(recovered_block, *block);
+        }
+        let unused_height = (0..).find(|height| !blocks.contains_key(height)).unwrap();
+        assert!(store.get_by_height(unused_height).unwrap().is_none());
+
+        let min_height = *blocks.keys().next().unwrap();
+        let max_height = *blocks.keys().last().unwrap();
+        let recovered_blocks = store.get_by_height_range(min_height, max_height).unwrap();
+        let expected_blocks: Vec<_> = blocks.values().cloned().collect();
+        assert_eq!(recovered_blocks, expected_blocks);
+
+        let recovered_blocks = store.get_by_height_range(min_height, min_height).unwrap();
+        assert_eq!(recovered_blocks, vec![blocks[&min_height].clone()]);
+    }
+
+    #[test]
+    fn lmdb_block_store_should_get_blocks_by_height() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_block_store =
+            LmdbStore::<Block, BlockMetadata>::new(config.path(), config.max_block_store_size())
+                .unwrap();
+        should_get_blocks_by_height(&mut lmdb_block_store);
+    }
+
+    #[test]
+    fn in_mem_block_store_should_get_blocks_by_height() {
+        let mut in_mem_block_store = InMemStore::<Block, BlockMetadata>::new();
+        should_get_blocks_by_height(&mut in_mem_block_store);
+    }
+
+    fn should_get_highest_block<T: BlockStore<Block = Block, Value = Block>>(store: &mut T) {
+        let mut rng = TestRng::new();
+        assert!(store.highest_block().unwrap().is_none());
+
+        // Store blocks out of height order, with heights spanning several integer encodings.
+        let heights = [300, 0, 127, u64::from(u32::MAX) + 1, 128, 255, 65_536, 256];
+        let mut max_height = 0;
+        for height in heights.iter() {
+            let finalized_block = FinalizedBlock::new(
+                ProtoBlock::new(vec![], false),
+                Timestamp::now(),
+                vec![],
+                vec![],
+                false,
+                EraId(0),
+                *height,
+                PublicKey::random(&mut rng),
+            );
+            let block = Block::new(
+                BlockHash::new(Digest::random(&mut rng)),
+                Digest::random(&mut rng),
+                finalized_block,
+            );
+            assert!(store.put_block(block).unwrap());
+
+            max_height = max_height.max(*height);
+            let highest_block = store.highest_block().unwrap().unwrap();
+            assert_eq!(highest_block.height(), max_height);
+        }
+    }
+
+    #[test]
+    fn lmdb_block_store_should_get_highest_block() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_block_store =
+            LmdbStore::<Block, BlockMetadata>::new(config.path(), config.max_block_store_size())
+                .unwrap();
+        should_get_highest_block(&mut lmdb_block_store);
+    }
+
+    #[test]
+    fn in_mem_block_store_should_get_highest_block() {
+        let mut in_mem_block_store = InMemStore::<Block, BlockMetadata>::new();
+        should_get_highest_block(&mut in_mem_block_store);
+    }
+
+    fn should_put_then_get_execution_summary<T: BlockStore<Block = Block, Value = Block>>(
+        store: &mut T,
+    ) {
+        let mut rng = TestRng::new();
+        let block = Block::random(&mut rng);
+        let block_hash = *block.id();
+        assert!(store.get_execution_summary(block_hash).unwrap().is_none());
+
+        // The summary may be stored before or after the block itself.
+        let execution_summary = BlockExecutionSummary {
+            deploys: vec![],
+            total_cost: 1.into(),
+        };
+        assert!(store
+            .put_execution_summary(block_hash, execution_summary.clone())
+            .unwrap());
+        assert!(store.put_block(block.clone()).unwrap());
+        assert_eq!(
+            store.get_execution_summary(block_hash).unwrap(),
+            Some(execution_summary)
+        );
+
+        // Storing a new summary should replace the existing one.
+        let execution_summary = BlockExecutionSummary {
+            deploys: vec![],
+            total_cost: 2.into(),
+        };
+        assert!(!store
+            .put_execution_summary(block_hash, execution_summary.clone())
+            .unwrap());
+        assert_eq!(
+            store.get_execution_summary(block_hash).unwrap(),
+            Some(execution_summary)
+        );
+        assert_eq!(store.get_by_height(block.height()).unwrap(), Some(block));
+    }
+
+    #[test]
+    fn lmdb_block_store_should_put_then_get_execution_summary() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_block_store =
+            LmdbStore::<Block, BlockMetadata>::new(config.path(), config.max_block_store_size())
+                .unwrap();
+        should_put_then_get_execution_summary(&mut lmdb_block_store);
+    }
+
+    #[test]
+    fn in_mem_block_store_should_put_then_get_execution_summary() {
+        let mut in_mem_block_store = InMemStore::<Block, BlockMetadata>::new();
+        should_put_then_get_execution_summary(&mut in_mem_block_store);
+    }
+
+    fn should_put_then_get_finality_signatures<T: BlockStore<Block = Block, Value = Block>>(
+        store: &mut T,
+    ) {
+        let mut rng = TestRng::new();
+        let block = Block::random(&mut rng);
+        let block_hash = *block.id();
+        assert!(store
+            .get_finality_signatures(block_hash)
+            .unwrap()
+            .is_empty());
+        assert!(store.put_block(block).unwrap());
+
+        let mut expected_signatures = BTreeMap::new();
+        for _ in 0..3 {
+            let secret_key = SecretKey::random(&mut rng);
+            let public_key = PublicKey::from(&secret_key);
+            let signature =
+                asymmetric_key::sign(block_hash.inner(), &secret_key, &public_key, &mut rng);
+            assert!(store
+                .put_finality_signature(block_hash, public_key, signature)
+                .unwrap());
+            let _ = expected_signatures.insert(public_key, signature);
+        }
+        assert_eq!(
+            store.get_finality_signatures(block_hash).unwrap(),
+            expected_signatures
+        );
+
+        // A second signature from the same validator should not replace the first.
+        let (public_key, _) = expected_signatures.iter().next().unwrap();
+        let other_signature = *expected_signatures.values().last().unwrap();
+        assert!(!store
+            .put_finality_signature(block_hash, *public_key, other_signature)
+            .unwrap());
+        assert_eq!(
+            store.get_finality_signatures(block_hash).unwrap(),
+            expected_signatures
+        );
+    }
+
+    #[test]
+    fn lmdb_block_store_should_put_then_get_finality_signatures() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_block_store =
+            LmdbStore::<Block, BlockMetadata>::new(config.path(), config.max_block_store_size())
+                .unwrap();
+        should_put_then_get_finality_signatures(&mut lmdb_block_store);
+    }
+
+    #[test]
+    fn in_mem_block_store_should_put_then_get_finality_signatures() {
+        let mut in_mem_block_store = InMemStore::<Block, BlockMetadata>::new();
+        should_put_then_get_finality_signatures(&mut in_mem_block_store);
+    }
+
+    fn should_put_then_get_era_evidence<T: BlockStore<Block = Block, Value = Block>>(
+        store: &mut T,
+    ) {
+        let mut rng = TestRng::new();
+        let era_id = EraId(3);
+        assert!(store.get_era_evidence(era_id).unwrap().is_empty());
+
+        let mut expected_evidence = vec![];
+        for _ in 0..3 {
+            let public_key = PublicKey::random(&mut rng);
+            let proof = Evidence::random(&mut rng).proof().to_vec();
+            let evidence = Evidence::new(era_id, public_key, proof);
+            assert!(store.put_evidence(evidence.clone()).unwrap());
+            expected_evidence.push(evidence);
+        }
+        assert_eq!(store.get_era_evidence(era_id).unwrap(), expected_evidence);
+        assert!(store.get_era_evidence(EraId(4)).unwrap().is_empty());
+
+        // Other evidence against the same validator should not replace the first.
+        let public_key = *expected_evidence[0].perpetrator();
+        let other_evidence = Evidence::new(era_id, public_key, vec![1, 2, 3]);
+        assert!(!store.put_evidence(other_evidence).unwrap());
+        assert_eq!(store.get_era_evidence(era_id).unwrap(), expected_evidence);
+    }
+
+    #[test]
+    fn lmdb_block_store_should_put_then_get_era_evidence() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_block_store =
+            LmdbStore::<Block, BlockMetadata>::new(config.path(), config.max_block_store_size())
+                .unwrap();
+        should_put_then_get_era_evidence(&mut lmdb_block_store);
+    }
+
+    #[test]
+    fn in_mem_block_store_should_put_then_get_era_evidence() {
+        let mut in_mem_block_store = InMemStore::<Block, BlockMetadata>::new();
+        should_put_then_get_era_evidence(&mut in_mem_block_store);
+    }
+
+    /// Returns a block at the given height, in the era holding two blocks per era, which includes
+    /// the given deploys.
+    fn block_with_deploys(rng: &mut TestRng, height: u64, deploys: &[&Deploy]) -> Block {
+        let deploy_hashes = deploys.iter().map(|deploy| *deploy.id()).collect();
+        let finalized_block = FinalizedBlock::new(
+            ProtoBlock::new(deploy_hashes, false),
+            Timestamp::now(),
+            vec![],
+            vec![],
+            false,
+            EraId(height / 2),
+            height,
+            PublicKey::random(rng),
+        );
+        Block::new(
+            BlockHash::new(Digest::random(rng)),
+            Digest::random(rng),
+            finalized_block,
+        )
+    }
+
+    /// Stores the given blocks, along with an execution result for each deploy they include.
+    fn put_blocks_and_deploys<
+        T: BlockStore<Block = Block, Value = Block>,
+        U: DeployStore<Deploy = Deploy, Block = Block, Value = Deploy>,
+    >(
+        block_store: &mut T,
+        deploy_store: &mut U,
+        blocks: &[Block],
+        deploys: &[Deploy],
+    ) {
+        let execution_result = ExecutionResult::from(&EngineExecutionResult::Success {
+            effect: Default::default(),
+            cost: Gas::default(),
+        });
+        for deploy in deploys {
+            assert!(deploy_store.put_deploy(deploy.clone()).unwrap());
+        }
+        for block in blocks {
+            assert!(block_store.put_block(block.clone()).unwrap());
+            for deploy_hash in block.deploy_hashes() {
+                deploy_store
+                    .put_execution_result(*deploy_hash, *block.id(), execution_result.clone())
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Prunes the eras before the given one the way the storage component does.
+    fn prune_eras_before<
+        T: BlockStore<Block = Block, Value = Block>,
+        U: DeployStore<Deploy = Deploy, Block = Block, Value = Deploy>,
+    >(
+        block_store: &mut T,
+        deploy_store: &mut U,
+        era_id: EraId,
+    ) -> (Vec<Block>, usize) {
+        let pruned_blocks = block_store.prune_eras_before(era_id).unwrap();
+        let block_hashes: Vec<_> = pruned_blocks.iter().map(|block| *block.id()).collect();
+        let deploy_hashes: Vec<_> = pruned_blocks
+            .iter()
+            .flat_map(|block| block.deploy_hashes().iter().copied())
+            .collect();
+        let pruned_deploy_count = deploy_store
+            .prune_execution_results(&block_hashes, &deploy_hashes)
+            .unwrap();
+        (pruned_blocks, pruned_deploy_count)
+    }
+
+    fn should_prune_eras<
+        T: BlockStore<Block = Block, Value = Block>,
+        U: DeployStore<Deploy = Deploy, Block = Block, Value = Deploy>,
+    >(
+        block_store: &mut T,
+        deploy_store: &mut U,
+    ) {
+        let mut rng = TestRng::new();
+
+        // Store a chain of blocks with two blocks per era, including deploys executed in pruned
+        // blocks only, in both pruned and retained blocks, and in retained blocks only.
+        let deploys: Vec<Deploy> = (0..3).map(|_| Deploy::random(&mut rng)).collect();
+        let blocks: Vec<Block> = (0..6)
+            .map(|height| {
+                let block_deploys: Vec<&Deploy> = match height {
+                    0 => vec![&deploys[0]],
+                    1 | 4 => vec![&deploys[1]],
+                    5 => vec![&deploys[2]],
+                    _ => vec![],
+                };
+                block_with_deploys(&mut rng, height, &block_deploys)
+            })
+            .collect();
+        put_blocks_and_deploys(block_store, deploy_store, &blocks, &deploys);
+
+        // Pruning the first two eras should remove the first four blocks, and only the deploy
+        // executed in pruned blocks alone.
+        assert_eq!(block_store.lowest_retained_height().unwrap(), 0);
+        let (pruned_blocks, pruned_deploy_count) =
+            prune_eras_before(block_store, deploy_store, EraId(2));
+        assert_eq!(pruned_blocks, blocks[..4].to_vec());
+        assert_eq!(pruned_deploy_count, 1);
+        assert_eq!(block_store.lowest_retained_height().unwrap(), 4);
+        assert!(block_store.get_by_height(3).unwrap().is_none());
+        assert_eq!(block_store.get_by_height(4).unwrap().unwrap(), blocks[4]);
+        assert!(block_store.prune_eras_before(EraId(2)).unwrap().is_empty());
+
+        assert!(deploy_store
+            .get_deploy_and_metadata(*deploys[0].id())
+            .unwrap()
+            .is_none());
+        let account_deploys = deploy_store
+            .get_deploys_for_account(deploys[0].header().account(), 0, 10)
+            .unwrap();
+        assert_eq!(account_deploys.total_count, 0);
+
+        let (_deploy, metadata) = deploy_store
+            .get_deploy_and_metadata(*deploys[1].id())
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.execution_results.len(), 1);
+        assert!(metadata.execution_results.contains_key(blocks[4].id()));
+        assert!(deploy_store
+            .get_deploy_and_metadata(*deploys[2].id())
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn lmdb_stores_should_prune_eras() {
+        let (block_config, _block_tempdir) = Config::default_for_tests();
+        let mut lmdb_block_store = LmdbStore::<Block, BlockMetadata>::new(
+            block_config.path(),
+            block_config.max_block_store_size(),
+        )
+        .unwrap();
+        let (deploy_config, _deploy_tempdir) = Config::default_for_tests();
+        let mut lmdb_deploy_store = LmdbStore::<Deploy, DeployMetadata<Block>>::new(
+            deploy_config.path(),
+            deploy_config.max_deploy_store_size(),
+        )
+        .unwrap();
+        should_prune_eras(&mut lmdb_block_store, &mut lmdb_deploy_store);
+    }
+
+    #[test]
+    fn in_mem_stores_should_prune_eras() {
+        let mut in_mem_block_store = InMemStore::<Block, BlockMetadata>::new();
+        let mut in_mem_deploy_store = InMemStore::<Deploy, DeployMetadata<Block>>::new();
+        should_prune_eras(&mut in_mem_block_store, &mut in_mem_deploy_store);
+    }
+
+    fn should_leave_deploys_of_retained_blocks_untouched<
+        T: BlockStore<Block = Block, Value = Block>,
+        U: DeployStore<Deploy = Deploy, Block = Block, Value = Deploy>,
+    >(
+        block_store: &mut T,
+        deploy_store: &mut U,
+    ) {
+        let mut rng = TestRng::new();
+
+        // Only the first block is pruned.  The last deploy is executed in it and a retained block.
+        let deploys: Vec<Deploy> = (0..4).map(|_| Deploy::random(&mut rng)).collect();
+        let blocks = vec![
+            block_with_deploys(&mut rng, 0, &[&deploys[0], &deploys[3]]),
+            block_with_deploys(&mut rng, 2, &[&deploys[1]]),
+            block_with_deploys(&mut rng, 3, &[&deploys[2], &deploys[3]]),
+        ];
+        put_blocks_and_deploys(block_store, deploy_store, &blocks, &deploys);
+        let get_execution_results = |deploy_store: &U, deploy: &Deploy| {
+            deploy_store
+                .get_deploy_and_metadata(*deploy.id())
+                .unwrap()
+                .map(|(_deploy, metadata)| metadata.execution_results)
+        };
+        let retained_execution_results: Vec<_> = deploys[1..3]
+            .iter()
+            .map(|deploy| get_execution_results(deploy_store, deploy).unwrap())
+            .collect();
+
+        let (pruned_blocks, pruned_deploy_count) =
+            prune_eras_before(block_store, deploy_store, EraId(1));
+        assert_eq!(pruned_blocks, vec![blocks[0].clone()]);
+        assert_eq!(pruned_deploy_count, 1);
+
+        // The deploys of retained blocks keep their execution results and account index entries.
+        for (deploy, execution_results) in deploys[1..3].iter().zip(retained_execution_results) {
+            assert_eq!(
+                get_execution_results(deploy_store, deploy),
+                Some(execution_results)
+            );
+            let account_deploys = deploy_store
+                .get_deploys_for_account(deploy.header().account(), 0, 10)
+                .unwrap();
+            assert_eq!(account_deploys.total_count, 1);
+        }
+        let execution_results = get_execution_results(deploy_store, &deploys[3]).unwrap();
+        assert_eq!(execution_results.len(), 1);
+        assert!(execution_results.contains_key(blocks[2].id()));
+    }
+
+    #[test]
+    fn lmdb_stores_should_leave_deploys_of_retained_blocks_untouched() {
+        let (block_config, _block_tempdir) = Config::default_for_tests();
+        let mut lmdb_block_store = LmdbStore::<Block, BlockMetadata>::new(
+            block_config.path(),
+            block_config.max_block_store_size(),
+        )
+        .unwrap();
+        let (deploy_config, _deploy_tempdir) = Config::default_for_tests();
+        let mut lmdb_deploy_store = LmdbStore::<Deploy, DeployMetadata<Block>>::new(
+            deploy_config.path(),
+            deploy_config.max_deploy_store_size(),
+        )
+        .unwrap();
+        should_leave_deploys_of_retained_blocks_untouched(
+            &mut lmdb_block_store,
+            &mut lmdb_deploy_store,
+        );
+    }
+
+    #[test]
+    fn in_mem_stores_should_leave_deploys_of_retained_blocks_untouched() {
+        let mut in_mem_block_store = InMemStore::<Block, BlockMetadata>::new();
+        let mut in_mem_deploy_store = InMemStore::<Deploy, DeployMetadata<Block>>::new();
+        should_leave_deploys_of_retained_blocks_untouched(
+            &mut in_mem_block_store,
+            &mut in_mem_deploy_store,
+        );
+    }
+
+    #[test]
+    fn lmdb_block_store_should_backfill_height_index() {
+        let mut rng = TestRng::new();
+        let (config, _tempdir) = Config::default_for_tests();
+
+        // Store blocks with distinct heights without indexing them, as done by previous versions.
+        // There are more blocks than are indexed per batch.
+        let mut blocks = BTreeMap::new();
+        while blocks.len() < 5 {
+            let block = Block::random(&mut rng);
+            let _ = blocks.entry(block.height()).or_insert(block);
+        }
+        {
+            let lmdb_block_store = LmdbStore::<Block, BlockMetadata>::new(
+                config.path(),
+                config.max_block_store_size(),
+            )
+            .unwrap();
+            for block in blocks.values() {
+                assert!(lmdb_block_store.put(block.clone()).unwrap());
+                assert!(lmdb_block_store
+                    .get_by_height(block.height())
+                    .unwrap()
+                    .is_none());
+            }
+        }
+
+        let lmdb_block_store =
+            LmdbStore::<Block, BlockMetadata>::new(config.path(), config.max_block_store_size())
+                .unwrap();
+        lmdb_block_store.backfill_height_index().unwrap();
+        for (height, block) in &blocks {
+            let recovered_block = lmdb_block_store.get_by_height(*height).unwrap().unwrap();
+            assert_eq!(recovered_block, *block);
+        }
+    }
+
+    #[test]
+    fn lmdb_deploy_store_should_backfill_account_index() {
+        let mut rng = TestRng::new();
+        let (config, _tempdir) = Config::default_for_tests();
+        let secret_key = SecretKey::random(&mut rng);
+        let account = PublicKey::from(&secret_key);
+
+        // Store deploys without indexing them, as done by previous versions.  There are more
+        // deploys than are indexed per batch.
+        let deploys: Vec<Deploy> = (1..=5)
+            .map(|millis: u64| {
+                let module_bytes = ExecutableDeployItem::ModuleBytes {
+                    module_bytes: vec![],
+                    args: vec![],
+                };
+                Deploy::new(
+                    Timestamp::from(millis),
+                    TimeDiff::from(60_000),
+                    1,
+                    vec![],
+                    String::from("casper-example"),
+                    module_bytes.clone(),
+                    module_bytes,
+                    &secret_key,
+                    &mut rng,
+                )
+            })
+            .collect();
+        {
+            let lmdb_deploy_store = LmdbStore::<Deploy, DeployMetadata<Block>>::new(
+                config.path(),
+                config.max_deploy_store_size(),
+            )
+            .unwrap();
+            for deploy in &deploys {
+                assert!(lmdb_deploy_store.put(deploy.clone()).unwrap());
+            }
+            let account_deploys = lmdb_deploy_store
+                .get_deploys_for_account(&account, 0, 10)
+                .unwrap();
+            assert_eq!(account_deploys.total_count, 0);
+        }
+
+        let lmdb_deploy_store = LmdbStore::<Deploy, DeployMetadata<Block>>::new(
+            config.path(),
+            config.max_deploy_store_size(),
+        )
+        .unwrap();
+        // Backfilling again should leave the index unchanged.
+        for _ in 0..2 {
+            lmdb_deploy_store.backfill_account_index().unwrap();
+            let account_deploys = lmdb_deploy_store
+                .get_deploys_for_account(&account, 0, 10)
+                .unwrap();
+            assert_eq!(account_deploys.total_count, 5);
+            let deploy_hashes: Vec<_> = account_deploys
+                .deploys
+                .iter()
+                .map(|(deploy_hash, _metadata)| *deploy_hash)
+                .collect();
+            let expected_deploy_hashes: Vec<_> =
+                deploys.iter().rev().map(|deploy| *deploy.id()).collect();
+            assert_eq!(deploy_hashes, expected_deploy_hashes);
+        }
+    }
 }
@@ -63,7 +63,7 @@ pub mod requests;
 
 use std::{
     any::type_name,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     future::Future,
     net::SocketAddr,
@@ -76,25 +76,28 @@ use smallvec::{smallvec, SmallVec};
 use tracing::error;
 
 use casper_execution_engine::{
-    core::{
-        engine_state::{
-            self, execute_request::ExecuteRequest, execution_result::ExecutionResults,
-            genesis::GenesisResult, BalanceRequest, BalanceResult, QueryRequest, QueryResult,
-        },
-        execution,
+    core::engine_state::{
+        self,
+        execute_request::ExecuteRequest,
+        execution_result::ExecutionResult as EngineExecutionResult,
+        genesis::GenesisResult,
+        upgrade::{UpgradeConfig, UpgradeResult},
+        BalanceRequest, BalanceResult, BlockExecutionResult, QueryRequest, QueryResult,
+        StepRequest, StepResult,
     },
-    shared::{additive_map::AdditiveMap, transform::Transform},
-    storage::global_state::CommitResult,
+    shared::{motes::Motes, newtypes::Blake2bHash},
 };
-use casper_types::Key;
+use casper_types::{auction::ValidatorWeights, ProtocolVersion};
 
 use crate::{
     components::{
         consensus::{BlockContext, EraId},
+        deploy_buffer::PendingDeploy,
         fetcher::FetchResult,
-        small_network::GossipedAddress,
+        small_network::{GossipedAddress, Misbehavior, PeerReputation},
         storage::{
-            DeployHashes, DeployHeaderResults, DeployMetadata, DeployResults, StorageType, Value,
+            AccountDeploys, DeployHashes, DeployHeaderResults, DeployMetadata, DeployResults,
+            StorageType, Value,
         },
     },
     crypto::{
@@ -103,15 +106,16 @@ use crate::{
     },
     reactor::{EventQueueHandle, QueueKind},
     types::{
-        json_compatibility::ExecutionResult, Block, BlockHash, BlockHeader, BlockLike, Deploy,
-        DeployHash, FinalizedBlock, Item, ProtoBlock,
+        json_compatibility::ExecutionResult, Block, BlockExecutionSummary, BlockHash, BlockHeader,
+        BlockHeadersBatch, BlockHeadersBatchId, BlockLike, Deploy, DeployHash, DeployStatus,
+        Evidence, FinalitySignature, FinalizedBlock, Item, ProtoBlock, Timestamp, Trie,
     },
     utils::Source,
     Chainspec,
 };
 use announcements::{
     ApiServerAnnouncement, BlockExecutorAnnouncement, ConsensusAnnouncement,
-    DeployAcceptorAnnouncement, GossiperAnnouncement, NetworkAnnouncement,
+    DeployAcceptorAnnouncement, GossiperAnnouncement, LinearChainAnnouncement, NetworkAnnouncement,
 };
 use requests::{
     BlockExecutorRequest, BlockValidationRequest, ConsensusRequest, ContractRuntimeRequest,
@@ -469,6 +473,39 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the scores and bans of peers that misbehaved.
+    pub async fn network_peer_reputations<I>(self) -> HashMap<I, PeerReputation>
+    where
+        REv: From<NetworkInfoRequest<I>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| NetworkInfoRequest::GetPeerReputations { responder },
+            QueueKind::Api,
+        )
+        .await
+    }
+
+    /// Reports that a peer misbehaved, e.g. by sending an invalid message.
+    ///
+    /// The networking component penalizes the peer, and bans it if it misbehaves repeatedly.
+    pub async fn report_misbehavior<I, P>(self, peer: I, misbehavior: Misbehavior)
+    where
+        REv: From<NetworkRequest<I, P>>,
+        I: Send + 'static,
+        P: Send + 'static,
+    {
+        self.make_request(
+            |responder| NetworkRequest::ReportMisbehavior {
+                peer,
+                misbehavior,
+                responder,
+            },
+            QueueKind::Network,
+        )
+        .await
+    }
+
     /// Announces that a network message has been received.
     pub(crate) async fn announce_message_received<I, P>(self, sender: I, payload: P)
     where
@@ -588,6 +625,35 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces that a block has been added to the linear chain.
+    pub(crate) async fn announce_block_added(self, block: Box<Block>)
+    where
+        REv: From<LinearChainAnnouncement>,
+    {
+        self.0
+            .schedule(
+                LinearChainAnnouncement::BlockAdded(block),
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces that a finality signature of a block in the linear chain has been created or
+    /// received and verified.
+    pub(crate) async fn announce_finality_signature(
+        self,
+        finality_signature: Box<FinalitySignature>,
+    ) where
+        REv: From<LinearChainAnnouncement>,
+    {
+        self.0
+            .schedule(
+                LinearChainAnnouncement::NewFinalitySignature(finality_signature),
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Puts the given block into the linear block store.
     pub(crate) async fn put_block_to_storage<S>(self, block: Box<S::Block>) -> bool
     where
@@ -620,6 +686,53 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the block at the requested height from the linear block store.
+    pub(crate) async fn get_block_at_height_from_storage<S>(self, height: u64) -> Option<S::Block>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetBlockAtHeight { height, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the block with the greatest height from the linear block store.
+    pub(crate) async fn get_highest_block_from_storage<S>(self) -> Option<S::Block>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetHighestBlock { responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the blocks with heights in the given inclusive range from the linear block store.
+    pub(crate) async fn get_blocks_in_height_range_from_storage<S>(
+        self,
+        from_height: u64,
+        to_height: u64,
+    ) -> Vec<S::Block>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetBlocksInHeightRange {
+                from_height,
+                to_height,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested block header from the linear block store.
     #[allow(unused)]
     pub(crate) async fn get_block_header_from_storage<S>(
@@ -714,6 +827,149 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Prunes the blocks of eras older than the configured number of eras to retain, along with
+    /// their deploys and execution results.  Returns the lowest retained block height if any
+    /// blocks were pruned.
+    pub(crate) async fn prune_storage<S>(self, era_id: EraId) -> Option<u64>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::Prune { era_id, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the lowest height from which blocks haven't been pruned from the linear block store.
+    pub(crate) async fn get_lowest_retained_height_from_storage<S>(self) -> u64
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetLowestRetainedHeight { responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Puts the given summary of executing the deploys in the given block into the linear block
+    /// store.
+    pub(crate) async fn put_execution_summary_to_storage<S>(
+        self,
+        block_hash: <S::Block as Value>::Id,
+        execution_summary: BlockExecutionSummary,
+    ) where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutExecutionSummary {
+                block_hash,
+                execution_summary: Box::new(execution_summary),
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Puts the given validator's signature of the given block into the linear block store.
+    ///
+    /// Returns `false` if a signature from the same validator was already stored.
+    pub(crate) async fn put_finality_signature_to_storage<S>(
+        self,
+        block_hash: <S::Block as Value>::Id,
+        public_key: PublicKey,
+        signature: Signature,
+    ) -> bool
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutFinalitySignature {
+                block_hash,
+                public_key: Box::new(public_key),
+                signature: Box::new(signature),
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the validators' signatures of the given block from the linear block store.
+    pub(crate) async fn get_finality_signatures_from_storage<S>(
+        self,
+        block_hash: <S::Block as Value>::Id,
+    ) -> BTreeMap<PublicKey, Signature>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetFinalitySignatures {
+                block_hash,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Puts the given evidence of an equivocation into the linear block store.
+    ///
+    /// Returns `false` if evidence against the same validator in the same era was already stored.
+    pub(crate) async fn put_evidence_to_storage<S>(self, evidence: Evidence) -> bool
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutEvidence {
+                evidence: Box::new(evidence),
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets all the evidence of equivocations in the given era from the linear block store.
+    pub(crate) async fn get_era_evidence_from_storage<S>(self, era_id: EraId) -> Vec<Evidence>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetEraEvidence { era_id, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the summary of executing the deploys in the given block from the linear block store.
+    pub(crate) async fn get_execution_summary_from_storage<S>(
+        self,
+        block_hash: <S::Block as Value>::Id,
+    ) -> Option<BlockExecutionSummary>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetExecutionSummary {
+                block_hash,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested deploys from the deploy store.
     pub(crate) async fn get_deploy_and_metadata_from_storage<S>(
         self,
@@ -733,6 +989,30 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets a page of the deploys sent from the given account, along with their metadata, from
+    /// the deploy store.
+    pub(crate) async fn get_deploys_for_account_from_storage<S>(
+        self,
+        account: PublicKey,
+        offset: usize,
+        limit: usize,
+    ) -> AccountDeploys<S::Deploy, S::Block>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetDeploysForAccount {
+                account,
+                offset,
+                limit,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested deploy using the `DeployFetcher`.
     pub(crate) async fn fetch_deploy<I>(
         self,
@@ -775,11 +1055,54 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the requested batch of signed block headers using the `BlockHeadersBatchFetcher`.
+    pub(crate) async fn fetch_block_headers_batch<I>(
+        self,
+        batch_id: BlockHeadersBatchId,
+        peer: I,
+    ) -> Option<FetchResult<BlockHeadersBatch>>
+    where
+        REv: From<FetcherRequest<I, BlockHeadersBatch>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| FetcherRequest::Fetch {
+                id: batch_id,
+                peer,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the requested global state trie using the `TrieFetcher`.
+    pub(crate) async fn fetch_trie<I>(
+        self,
+        trie_key: Blake2bHash,
+        peer: I,
+    ) -> Option<FetchResult<Trie>>
+    where
+        REv: From<FetcherRequest<I, Trie>>,
+        I: Send + 'static,
+    {
+        self.make_request(
+            |responder| FetcherRequest::Fetch {
+                id: trie_key,
+                peer,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Passes the timestamp of a future block for which deploys are to be proposed.
     // TODO: The input `BlockContext` will probably be a different type than the context in the
     //       return value in the future.
     pub(crate) async fn request_proto_block(
         self,
+        era_id: EraId,
         block_context: BlockContext,
         random_bit: bool,
     ) -> (ProtoBlock, BlockContext)
@@ -789,6 +1112,7 @@ impl<REv> EffectBuilder<REv> {
         let deploys = self
             .make_request(
                 |responder| DeployBufferRequest::ListForInclusion {
+                    era_id,
                     current_instant: block_context.timestamp(),
                     past_blocks: Default::default(), // TODO
                     responder,
@@ -802,6 +1126,39 @@ impl<REv> EffectBuilder<REv> {
         (proto_block, block_context)
     }
 
+    /// Requests the lifecycle status of a deploy from the deploy buffer.
+    pub(crate) async fn get_deploy_status(self, hash: DeployHash) -> DeployStatus
+    where
+        REv: From<DeployBufferRequest>,
+    {
+        self.make_request(
+            |responder| DeployBufferRequest::GetDeployStatus { hash, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Requests the deploys which are neither finalized nor expired from the deploy buffer,
+    /// optionally restricted to those sent from `account` or expiring before `expires_before`.
+    pub(crate) async fn list_pending_deploys(
+        self,
+        account: Option<PublicKey>,
+        expires_before: Option<Timestamp>,
+    ) -> Vec<PendingDeploy>
+    where
+        REv: From<DeployBufferRequest>,
+    {
+        self.make_request(
+            |responder| DeployBufferRequest::ListPending {
+                account,
+                expires_before,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Passes a finalized proto-block to the block executor component to execute it.
     pub(crate) async fn execute_block(self, finalized_block: FinalizedBlock)
     where
@@ -896,6 +1253,40 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces that a validator has been found to have equivocated.
+    pub(crate) async fn announce_fault(
+        self,
+        era_id: EraId,
+        public_key: PublicKey,
+        timestamp: Timestamp,
+    ) where
+        REv: From<ConsensusAnnouncement>,
+    {
+        self.0
+            .schedule(
+                ConsensusAnnouncement::Fault {
+                    era_id,
+                    public_key: Box::new(public_key),
+                    timestamp,
+                },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces that new evidence of an equivocation has been verified and stored.
+    pub(crate) async fn announce_new_evidence(self, evidence: Evidence)
+    where
+        REv: From<ConsensusAnnouncement>,
+    {
+        self.0
+            .schedule(
+                ConsensusAnnouncement::NewEvidence(Box::new(evidence)),
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Runs the genesis process on the contract runtime.
     pub(crate) async fn commit_genesis(
         self,
@@ -943,16 +1334,17 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
-    /// Requests an execution of deploys using Contract Runtime.
-    pub(crate) async fn request_execute(
+    /// Requests the execution of the deploys of a block, and the commit of their effects, using
+    /// Contract Runtime.
+    pub(crate) async fn execute_block(
         self,
         execute_request: ExecuteRequest,
-    ) -> Result<ExecutionResults, engine_state::RootNotFound>
+    ) -> Result<BlockExecutionResult, engine_state::Error>
     where
         REv: From<ContractRuntimeRequest>,
     {
         self.make_request(
-            |responder| ContractRuntimeRequest::Execute {
+            |responder| ContractRuntimeRequest::ExecuteBlock {
                 execute_request,
                 responder,
             },
@@ -961,19 +1353,17 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
-    /// Requests a commit of effects on the Contract Runtime component.
-    pub(crate) async fn request_commit(
+    /// Requests a query be executed on the Contract Runtime component.
+    pub(crate) async fn query_global_state(
         self,
-        pre_state_hash: Digest,
-        effects: AdditiveMap<Key, Transform>,
-    ) -> Result<CommitResult, engine_state::Error>
+        query_request: QueryRequest,
+    ) -> Result<QueryResult, engine_state::Error>
     where
         REv: From<ContractRuntimeRequest>,
     {
         self.make_request(
-            |responder| ContractRuntimeRequest::Commit {
-                pre_state_hash,
-                effects,
+            |responder| ContractRuntimeRequest::Query {
+                query_request,
                 responder,
             },
             QueueKind::Regular,
@@ -982,16 +1372,16 @@ impl<REv> EffectBuilder<REv> {
     }
 
     /// Requests a query be executed on the Contract Runtime component.
-    pub(crate) async fn query_global_state(
+    pub(crate) async fn get_balance(
         self,
-        query_request: QueryRequest,
-    ) -> Result<QueryResult, engine_state::Error>
+        balance_request: BalanceRequest,
+    ) -> Result<BalanceResult, engine_state::Error>
     where
         REv: From<ContractRuntimeRequest>,
     {
         self.make_request(
-            |responder| ContractRuntimeRequest::Query {
-                query_request,
+            |responder| ContractRuntimeRequest::GetBalance {
+                balance_request,
                 responder,
             },
             QueueKind::Regular,
@@ -999,17 +1389,96 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
-    /// Requests a query be executed on the Contract Runtime component.
-    pub(crate) async fn get_balance(
+    /// Requests the execution of a single deploy against the given global state, without
+    /// committing its effects.
+    pub(crate) async fn speculatively_execute(
         self,
-        balance_request: BalanceRequest,
-    ) -> Result<BalanceResult, engine_state::Error>
+        global_state_hash: Digest,
+        block_time: Timestamp,
+        protocol_version: ProtocolVersion,
+        deploy: Box<Deploy>,
+    ) -> Result<EngineExecutionResult, engine_state::RootNotFound>
     where
         REv: From<ContractRuntimeRequest>,
     {
         self.make_request(
-            |responder| ContractRuntimeRequest::GetBalance {
-                balance_request,
+            |responder| ContractRuntimeRequest::SpeculativeExecute {
+                global_state_hash,
+                block_time,
+                protocol_version,
+                deploy,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Requests the removal of the parts of global state which are unreachable from the given
+    /// global state hashes.  Does nothing unless the contract runtime is configured to prune.
+    pub(crate) async fn prune_global_state(
+        self,
+        retained_global_state_hashes: Vec<Digest>,
+    ) -> Result<usize, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::PruneGlobalState {
+                retained_global_state_hashes,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Reads the global state trie stored under the given hash.
+    pub(crate) async fn get_trie(
+        self,
+        trie_key: Blake2bHash,
+    ) -> Result<Option<Trie>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetTrie {
+                trie_key,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Stores the given global state trie, returning the hashes of the tries it points to which
+    /// are not stored yet.
+    pub(crate) async fn put_trie(self, trie: Trie) -> Result<Vec<Blake2bHash>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::PutTrie {
+                trie: Box::new(trie),
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Finds the hashes of the tries which remain to be stored to complete the given global state.
+    /// Once none do, the global state becomes available.
+    pub(crate) async fn find_missing_tries(
+        self,
+        global_state_hash: Digest,
+    ) -> Result<Vec<Blake2bHash>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::FindMissingTries {
+                global_state_hash,
                 responder,
             },
             QueueKind::Regular,
@@ -1019,26 +1488,76 @@ impl<REv> EffectBuilder<REv> {
 
     /// Returns a map of validators for given `era` to their weights as known from `root_hash`.
     ///
-    /// This operation is read only.
-    #[allow(dead_code)]
+    /// Returns `None` if the global state doesn't exist, or the auction contract doesn't know the
+    /// era's validators yet. This operation is read only.
     pub(crate) async fn get_validators(
         self,
-        _root_hash: Digest,
-        _era: u64,
-    ) -> Result<Option<HashMap<PublicKey, u64>>, execution::Error> {
-        todo!("get_validators")
+        root_hash: Digest,
+        era_id: EraId,
+        protocol_version: ProtocolVersion,
+    ) -> Result<Option<ValidatorWeights>, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetEraValidators {
+                global_state_hash: root_hash,
+                protocol_version,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+        .map(|maybe_era_validators| {
+            maybe_era_validators.and_then(|mut era_validators| era_validators.remove(&era_id.0))
+        })
     }
 
-    /// Runs auction using the system smart contract.
+    /// Runs the system operations at the end of a block using the system smart contracts:
+    /// slashing, paying out rewards and, in switch blocks, the auction.
     ///
-    /// Contract is ran on a given pre state hash and returns fully committed post state hash.
-    #[allow(dead_code)]
-    pub(crate) async fn run_auction(self, _root_hash: Digest) -> Result<Digest, execution::Error> {
-        todo!("run_auction")
+    /// The operations are run on the request's pre state hash, and their effects are committed.
+    pub(crate) async fn commit_step(
+        self,
+        step_request: StepRequest,
+    ) -> Result<StepResult, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::Step {
+                step_request,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Runs the upgrade described by the given config using the system smart contracts, and commits
+    /// its effects on top of the config's pre state hash.
+    pub(crate) async fn commit_upgrade(
+        self,
+        upgrade_config: UpgradeConfig,
+    ) -> Result<UpgradeResult, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::Upgrade {
+                upgrade_config,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
     }
 
     /// Request consensus to sign a block from the linear chain and possibly start a new era.
-    pub(crate) async fn handle_linear_chain_block(self, block_header: BlockHeader) -> Signature
+    pub(crate) async fn handle_linear_chain_block(
+        self,
+        block_header: BlockHeader,
+    ) -> FinalitySignature
     where
         REv: From<ConsensusRequest>,
     {
@@ -1048,6 +1567,22 @@ impl<REv> EffectBuilder<REv> {
         )
         .await
     }
+
+    /// Requests the validators of the given era along with their stakes, or `None` if the era is
+    /// not active.
+    pub(crate) async fn get_era_validators(
+        self,
+        era_id: EraId,
+    ) -> Option<BTreeMap<PublicKey, Motes>>
+    where
+        REv: From<ConsensusRequest>,
+    {
+        self.make_request(
+            |responder| ConsensusRequest::EraValidators(era_id, responder),
+            QueueKind::Regular,
+        )
+        .await
+    }
 }
 
 /// Construct a fatal error effect.
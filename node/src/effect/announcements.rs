@@ -10,7 +10,11 @@ use std::{
 
 use crate::{
     components::{consensus::EraId, small_network::GossipedAddress},
-    types::{json_compatibility::ExecutionResult, Block, Deploy, DeployHash, Item, ProtoBlock},
+    crypto::asymmetric_key::PublicKey,
+    types::{
+        json_compatibility::ExecutionResult, Block, Deploy, DeployHash, Evidence,
+        FinalitySignature, Item, ProtoBlock, Timestamp,
+    },
     utils::Source,
 };
 
@@ -125,6 +129,17 @@ pub enum ConsensusAnnouncement {
     /// TODO: this is only for purposes of detecting incomplete linear chain synchronization,
     /// remove when proper syncing is implemented
     GotMessageInEra(EraId),
+    /// An equivocation has been detected.
+    Fault {
+        /// The era in which the equivocation was detected.
+        era_id: EraId,
+        /// The public key of the equivocator.
+        public_key: Box<PublicKey>,
+        /// The timestamp of the finalized block reporting the equivocation.
+        timestamp: Timestamp,
+    },
+    /// New evidence of an equivocation has been verified and stored.
+    NewEvidence(Box<Evidence>),
 }
 
 impl Display for ConsensusAnnouncement {
@@ -147,6 +162,10 @@ impl Display for ConsensusAnnouncement {
             ConsensusAnnouncement::GotMessageInEra(era_id) => {
                 write!(formatter, "message in era {:?} received", era_id)
             }
+            ConsensusAnnouncement::Fault {
+                era_id, public_key, ..
+            } => write!(formatter, "fault in era {} by {}", era_id, public_key),
+            ConsensusAnnouncement::NewEvidence(evidence) => write!(formatter, "new {}", evidence),
         }
     }
 }
@@ -173,6 +192,27 @@ impl Display for BlockExecutorAnnouncement {
     }
 }
 
+/// A linear chain announcement.
+#[derive(Debug)]
+pub enum LinearChainAnnouncement {
+    /// A new block has been added to the linear chain.
+    BlockAdded(Box<Block>),
+    /// A finality signature of a block in the linear chain has been created or received and
+    /// verified.
+    NewFinalitySignature(Box<FinalitySignature>),
+}
+
+impl Display for LinearChainAnnouncement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LinearChainAnnouncement::BlockAdded(block) => write!(f, "block added {}", block.hash()),
+            LinearChainAnnouncement::NewFinalitySignature(finality_signature) => {
+                write!(f, "new {}", finality_signature)
+            }
+        }
+    }
+}
+
 /// A Gossiper announcement.
 #[derive(Debug)]
 pub enum GossiperAnnouncement<T: Item> {
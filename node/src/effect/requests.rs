@@ -4,7 +4,7 @@
 //! top-level module documentation for details.
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     net::SocketAddr,
 };
@@ -15,29 +15,38 @@ use casper_execution_engine::{
     core::engine_state::{
         self,
         balance::{BalanceRequest, BalanceResult},
+        block_execution::BlockExecutionResult,
         execute_request::ExecuteRequest,
-        execution_result::ExecutionResults,
+        execution_result::ExecutionResult as EngineExecutionResult,
         genesis::GenesisResult,
         query::{QueryRequest, QueryResult},
+        step::{StepRequest, StepResult},
         upgrade::{UpgradeConfig, UpgradeResult},
     },
-    shared::{additive_map::AdditiveMap, transform::Transform},
-    storage::global_state::CommitResult,
+    shared::{motes::Motes, newtypes::Blake2bHash},
 };
-use casper_types::{Key, URef};
+use casper_types::{auction::EraValidators, Key, ProtocolVersion, URef};
 
 use super::Responder;
 use crate::{
     components::{
+        consensus::EraId,
+        deploy_buffer::PendingDeploy,
         fetcher::FetchResult,
+        small_network::{Misbehavior, PeerReputation},
         storage::{
-            DeployHashes, DeployHeaderResults, DeployMetadata, DeployResults, StorageType, Value,
+            AccountDeploys, DeployHashes, DeployHeaderResults, DeployMetadata, DeployResults,
+            StorageType, Value,
         },
     },
-    crypto::{asymmetric_key::Signature, hash::Digest},
+    crypto::{
+        asymmetric_key::{PublicKey, Signature},
+        hash::Digest,
+    },
     types::{
-        json_compatibility::ExecutionResult, Block as LinearBlock, BlockHash, BlockHeader, Deploy,
-        DeployHash, FinalizedBlock, Item, ProtoBlockHash, StatusFeed, Timestamp,
+        json_compatibility::ExecutionResult, Block as LinearBlock, BlockExecutionSummary,
+        BlockHash, BlockHeader, BlockHeadersBatchId, Deploy, DeployHash, DeployStatus, Evidence,
+        FinalitySignature, FinalizedBlock, Item, ProtoBlockHash, StatusFeed, Timestamp, Trie,
     },
     utils::DisplayIter,
     Chainspec,
@@ -99,6 +108,15 @@ pub enum NetworkRequest<I, P> {
         /// Responder to be called when all messages are queued.
         responder: Responder<HashSet<I>>,
     },
+    /// Report that a peer misbehaved, e.g. by sending an invalid message.
+    ReportMisbehavior {
+        /// The misbehaving peer.
+        peer: I,
+        /// The kind of misbehavior.
+        misbehavior: Misbehavior,
+        /// Responder to be called when the misbehavior has been recorded.
+        responder: Responder<()>,
+    },
 }
 
 impl<I, P> NetworkRequest<I, P> {
@@ -134,6 +152,15 @@ impl<I, P> NetworkRequest<I, P> {
                 exclude,
                 responder,
             },
+            NetworkRequest::ReportMisbehavior {
+                peer,
+                misbehavior,
+                responder,
+            } => NetworkRequest::ReportMisbehavior {
+                peer,
+                misbehavior,
+                responder,
+            },
         }
     }
 }
@@ -152,6 +179,9 @@ where
                 write!(formatter, "broadcast: {}", payload)
             }
             NetworkRequest::Gossip { payload, .. } => write!(formatter, "gossip: {}", payload),
+            NetworkRequest::ReportMisbehavior {
+                peer, misbehavior, ..
+            } => write!(formatter, "report {}: {}", peer, misbehavior),
         }
     }
 }
@@ -165,6 +195,11 @@ pub enum NetworkInfoRequest<I> {
         /// Responder to be called with all connected peers.
         responder: Responder<HashMap<I, SocketAddr>>,
     },
+    /// Get the scores and bans of peers that misbehaved.
+    GetPeerReputations {
+        /// Responder to be called with the reputation of each misbehaving peer.
+        responder: Responder<HashMap<I, PeerReputation>>,
+    },
 }
 
 impl<I> Display for NetworkInfoRequest<I>
@@ -174,6 +209,9 @@ where
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         match self {
             NetworkInfoRequest::GetPeers { responder: _ } => write!(formatter, "get peers"),
+            NetworkInfoRequest::GetPeerReputations { .. } => {
+                write!(formatter, "get peer reputations")
+            }
         }
     }
 }
@@ -198,6 +236,29 @@ pub enum StorageRequest<S: StorageType + 'static> {
         /// storage.
         responder: Responder<Option<S::Block>>,
     },
+    /// Retrieve block at given height.
+    GetBlockAtHeight {
+        /// Height of block to be retrieved.
+        height: u64,
+        /// Responder to call with the result.  Returns `None` is the block doesn't exist in local
+        /// storage.
+        responder: Responder<Option<S::Block>>,
+    },
+    /// Retrieve the block with the greatest height.
+    GetHighestBlock {
+        /// Responder to call with the result.  Returns `None` if no blocks exist in local storage.
+        responder: Responder<Option<S::Block>>,
+    },
+    /// Retrieve blocks with heights in the given inclusive range.
+    GetBlocksInHeightRange {
+        /// Height of the first block to be retrieved.
+        from_height: u64,
+        /// Height of the last block to be retrieved.
+        to_height: u64,
+        /// Responder to call with the result.  Blocks which don't exist in local storage are
+        /// omitted.
+        responder: Responder<Vec<S::Block>>,
+    },
     /// Retrieve block header with given hash.
     GetBlockHeader {
         /// Hash of block to get header of.
@@ -238,6 +299,56 @@ pub enum StorageRequest<S: StorageType + 'static> {
         /// on this attempt or false if they were previously stored.
         responder: Responder<()>,
     },
+    /// Store the given summary of executing the deploys in the given block.
+    PutExecutionSummary {
+        /// Hash of block.
+        block_hash: <S::Block as Value>::Id,
+        /// Execution summary.
+        execution_summary: Box<BlockExecutionSummary>,
+        /// Responder to call with the result.
+        responder: Responder<()>,
+    },
+    /// Retrieve the summary of executing the deploys in the given block.
+    GetExecutionSummary {
+        /// Hash of block.
+        block_hash: <S::Block as Value>::Id,
+        /// Responder to call with the result.  Returns `None` if no summary is stored.
+        responder: Responder<Option<BlockExecutionSummary>>,
+    },
+    /// Store the given validator's signature of the given block.
+    PutFinalitySignature {
+        /// Hash of block.
+        block_hash: <S::Block as Value>::Id,
+        /// Public key of the signing validator.
+        public_key: Box<PublicKey>,
+        /// Signature over the block hash.
+        signature: Box<Signature>,
+        /// Responder to call with the result.  Returns true if the signature was stored on this
+        /// attempt or false if one from the same validator was previously stored.
+        responder: Responder<bool>,
+    },
+    /// Retrieve the validators' signatures of the given block.
+    GetFinalitySignatures {
+        /// Hash of block.
+        block_hash: <S::Block as Value>::Id,
+        /// Responder to call with the result.
+        responder: Responder<BTreeMap<PublicKey, Signature>>,
+    },
+    /// Store the given evidence of an equivocation.
+    PutEvidence {
+        /// Evidence to be stored.
+        evidence: Box<Evidence>,
+        /// Responder to call with the result.  Returns true if there was no evidence against the
+        /// same validator in the same era stored already.
+        responder: Responder<bool>,
+    },
+    /// Retrieve all the evidence of equivocations in the given era.
+    GetEraEvidence {
+        /// The era.
+        era_id: EraId,
+        /// Responder to call with the result.
+        responder: Responder<Vec<Evidence>>,
+    },
     /// Retrieve deploy and its metadata.
     GetDeployAndMetadata {
         /// Hash of deploy to be retrieved.
@@ -245,6 +356,31 @@ pub enum StorageRequest<S: StorageType + 'static> {
         /// Responder to call with the results.
         responder: Responder<Option<DeployAndMetadata<S>>>,
     },
+    /// Retrieve a page of the deploys sent from the given account, along with their metadata.
+    GetDeploysForAccount {
+        /// The account which sent the deploys.
+        account: PublicKey,
+        /// The number of most recent deploys to skip.
+        offset: usize,
+        /// The maximum number of deploys to retrieve.
+        limit: usize,
+        /// Responder to call with the results.
+        responder: Responder<AccountDeploys<S::Deploy, S::Block>>,
+    },
+    /// Prune the blocks of eras which are older than the configured number of eras to retain,
+    /// along with their deploys and execution results.
+    Prune {
+        /// The era of the most recently stored block.
+        era_id: EraId,
+        /// Responder to call with the result.  Returns the lowest retained block height if any
+        /// blocks were pruned, or `None` otherwise.
+        responder: Responder<Option<u64>>,
+    },
+    /// Retrieve the lowest height from which blocks haven't been pruned.
+    GetLowestRetainedHeight {
+        /// Responder to call with the result.
+        responder: Responder<u64>,
+    },
     /// Store given chainspec.
     PutChainspec {
         /// Chainspec.
@@ -266,6 +402,19 @@ impl<S: StorageType> Display for StorageRequest<S> {
         match self {
             StorageRequest::PutBlock { block, .. } => write!(formatter, "put {}", block),
             StorageRequest::GetBlock { block_hash, .. } => write!(formatter, "get {}", block_hash),
+            StorageRequest::GetBlockAtHeight { height, .. } => {
+                write!(formatter, "get block at height {}", height)
+            }
+            StorageRequest::GetHighestBlock { .. } => write!(formatter, "get highest block"),
+            StorageRequest::GetBlocksInHeightRange {
+                from_height,
+                to_height,
+                ..
+            } => write!(
+                formatter,
+                "get blocks at heights {}..={}",
+                from_height, to_height
+            ),
             StorageRequest::GetBlockHeader { block_hash, .. } => {
                 write!(formatter, "get {}", block_hash)
             }
@@ -281,9 +430,47 @@ impl<S: StorageType> Display for StorageRequest<S> {
             StorageRequest::PutExecutionResults { block_hash, .. } => {
                 write!(formatter, "put execution results for {}", block_hash)
             }
+            StorageRequest::PutExecutionSummary { block_hash, .. } => {
+                write!(formatter, "put execution summary for {}", block_hash)
+            }
+            StorageRequest::GetExecutionSummary { block_hash, .. } => {
+                write!(formatter, "get execution summary for {}", block_hash)
+            }
+            StorageRequest::PutFinalitySignature {
+                block_hash,
+                public_key,
+                ..
+            } => write!(
+                formatter,
+                "put finality signature for {} by {}",
+                block_hash, public_key
+            ),
+            StorageRequest::GetFinalitySignatures { block_hash, .. } => {
+                write!(formatter, "get finality signatures for {}", block_hash)
+            }
+            StorageRequest::PutEvidence { evidence, .. } => write!(formatter, "put {}", evidence),
+            StorageRequest::GetEraEvidence { era_id, .. } => {
+                write!(formatter, "get evidence in era {}", era_id)
+            }
             StorageRequest::GetDeployAndMetadata { deploy_hash, .. } => {
                 write!(formatter, "get deploy and metadata for {}", deploy_hash)
             }
+            StorageRequest::GetDeploysForAccount {
+                account,
+                offset,
+                limit,
+                ..
+            } => write!(
+                formatter,
+                "get {} deploys for {} from offset {}",
+                limit, account, offset
+            ),
+            StorageRequest::Prune { era_id, .. } => {
+                write!(formatter, "prune storage as of era {}", era_id)
+            }
+            StorageRequest::GetLowestRetainedHeight { .. } => {
+                write!(formatter, "get lowest retained height")
+            }
             StorageRequest::PutChainspec { chainspec, .. } => write!(
                 formatter,
                 "put chainspec {}",
@@ -302,6 +489,8 @@ impl<S: StorageType> Display for StorageRequest<S> {
 pub enum DeployBufferRequest {
     /// Request a list of deploys to propose in a new block.
     ListForInclusion {
+        /// The era of the block for which the deploys are requested.
+        era_id: EraId,
         /// The instant for which the deploy is requested.
         current_instant: Timestamp,
         /// Set of block hashes pointing to blocks whose deploys should be excluded.
@@ -309,21 +498,43 @@ pub enum DeployBufferRequest {
         /// Responder to call with the result.
         responder: Responder<HashSet<DeployHash>>,
     },
+    /// Request the lifecycle status of a deploy.
+    GetDeployStatus {
+        /// The hash of the deploy.
+        hash: DeployHash,
+        /// Responder to call with the result.
+        responder: Responder<DeployStatus>,
+    },
+    /// Request the deploys which are neither finalized nor expired.
+    ListPending {
+        /// If given, only deploys sent from this account are listed.
+        account: Option<PublicKey>,
+        /// If given, only deploys expiring before this instant are listed.
+        expires_before: Option<Timestamp>,
+        /// Responder to call with the result.
+        responder: Responder<Vec<PendingDeploy>>,
+    },
 }
 
 impl Display for DeployBufferRequest {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         match self {
             DeployBufferRequest::ListForInclusion {
+                era_id,
                 current_instant,
                 past_blocks,
                 responder: _,
             } => write!(
                 formatter,
-                "list for inclusion: instant {} past {}",
+                "list for inclusion: era {} instant {} past {}",
+                era_id,
                 current_instant,
                 past_blocks.len()
             ),
+            DeployBufferRequest::GetDeployStatus { hash, .. } => {
+                write!(formatter, "get status of {}", hash)
+            }
+            DeployBufferRequest::ListPending { .. } => write!(formatter, "list pending deploys"),
         }
     }
 }
@@ -350,6 +561,41 @@ pub enum ApiRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Option<LinearBlock>>,
     },
+    /// Return the block at the given height, if it exists.
+    GetBlockAtHeight {
+        /// The height of the block to be retrieved.
+        height: u64,
+        /// Responder to call with the result.
+        responder: Responder<Option<LinearBlock>>,
+    },
+    /// Return the blocks with heights in the given inclusive range.
+    GetBlocksInHeightRange {
+        /// The height of the first block to be retrieved.
+        from_height: u64,
+        /// The height of the last block to be retrieved.
+        to_height: u64,
+        /// Responder to call with the result.
+        responder: Responder<Vec<LinearBlock>>,
+    },
+    /// Return the summary of executing the deploys in the given block, if it exists.
+    GetBlockExecutionSummary {
+        /// The hash of the block.
+        block_hash: BlockHash,
+        /// Responder to call with the result.
+        responder: Responder<Option<BlockExecutionSummary>>,
+    },
+    /// Return all the evidence of equivocations in the given era.
+    GetEraEvidence {
+        /// The era.
+        era_id: EraId,
+        /// Responder to call with the result.
+        responder: Responder<Vec<Evidence>>,
+    },
+    /// Return the lowest height from which blocks haven't been pruned.
+    GetLowestRetainedHeight {
+        /// Responder to call with the result.
+        responder: Responder<u64>,
+    },
     /// Query the global state at the given root hash.
     QueryGlobalState {
         /// The global state hash.
@@ -370,6 +616,15 @@ pub enum ApiRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Result<BalanceResult, engine_state::Error>>,
     },
+    /// Execute the given deploy against the given global state without committing its effects.
+    SpeculativeExecute {
+        /// The global state hash.
+        global_state_hash: Digest,
+        /// The deploy to execute.
+        deploy: Box<Deploy>,
+        /// Responder to call with the result.
+        responder: Responder<Result<EngineExecutionResult, engine_state::RootNotFound>>,
+    },
     /// Return the specified deploy and metadata if it exists, else `None`.
     GetDeploy {
         /// The hash of the deploy to be retrieved.
@@ -377,11 +632,43 @@ pub enum ApiRequest<I> {
         /// Responder to call with the result.
         responder: Responder<Option<(Deploy, DeployMetadata<LinearBlock>)>>,
     },
+    /// Return the lifecycle status of the specified deploy, as known to the deploy buffer.
+    GetDeployStatus {
+        /// The hash of the deploy.
+        hash: DeployHash,
+        /// Responder to call with the result.
+        responder: Responder<DeployStatus>,
+    },
+    /// Return the deploys which are neither finalized nor expired.
+    ListPendingDeploys {
+        /// If given, only deploys sent from this account are listed.
+        account: Option<PublicKey>,
+        /// If given, only deploys expiring before this instant are listed.
+        expires_before: Option<Timestamp>,
+        /// Responder to call with the result.
+        responder: Responder<Vec<PendingDeploy>>,
+    },
+    /// Return a page of the deploys sent from the given account, along with their metadata.
+    GetDeploysForAccount {
+        /// The account which sent the deploys.
+        account: PublicKey,
+        /// The number of most recent deploys to skip.
+        offset: usize,
+        /// The maximum number of deploys to retrieve.
+        limit: usize,
+        /// Responder to call with the result.
+        responder: Responder<AccountDeploys<Deploy, LinearBlock>>,
+    },
     /// Return the connected peers.
     GetPeers {
         /// Responder to call with the result.
         responder: Responder<HashMap<I, SocketAddr>>,
     },
+    /// Return the scores and bans of peers that misbehaved.
+    GetPeerReputations {
+        /// Responder to call with the result.
+        responder: Responder<HashMap<I, PeerReputation>>,
+    },
     /// Return string formatted status or `None` if an error occurred.
     GetStatus {
         /// Responder to call with the result.
@@ -405,6 +692,27 @@ impl<I> Display for ApiRequest<I> {
             ApiRequest::GetBlock {
                 maybe_hash: None, ..
             } => write!(formatter, "get latest block"),
+            ApiRequest::GetBlockAtHeight { height, .. } => {
+                write!(formatter, "get block at height {}", height)
+            }
+            ApiRequest::GetBlocksInHeightRange {
+                from_height,
+                to_height,
+                ..
+            } => write!(
+                formatter,
+                "get blocks at heights {}..={}",
+                from_height, to_height
+            ),
+            ApiRequest::GetBlockExecutionSummary { block_hash, .. } => {
+                write!(formatter, "get execution summary for {}", block_hash)
+            }
+            ApiRequest::GetEraEvidence { era_id, .. } => {
+                write!(formatter, "get evidence in era {}", era_id)
+            }
+            ApiRequest::GetLowestRetainedHeight { .. } => {
+                write!(formatter, "get lowest retained height")
+            }
             ApiRequest::QueryGlobalState {
                 global_state_hash,
                 base_key,
@@ -424,8 +732,31 @@ impl<I> Display for ApiRequest<I> {
                 "balance {}, purse_uref: {}",
                 global_state_hash, purse_uref
             ),
+            ApiRequest::SpeculativeExecute {
+                global_state_hash,
+                deploy,
+                ..
+            } => write!(
+                formatter,
+                "speculative execute {} against {}",
+                deploy.id(),
+                global_state_hash
+            ),
             ApiRequest::GetDeploy { hash, .. } => write!(formatter, "get {}", hash),
+            ApiRequest::GetDeployStatus { hash, .. } => write!(formatter, "get status of {}", hash),
+            ApiRequest::ListPendingDeploys { .. } => write!(formatter, "list pending deploys"),
+            ApiRequest::GetDeploysForAccount {
+                account,
+                offset,
+                limit,
+                ..
+            } => write!(
+                formatter,
+                "get {} deploys for {} from offset {}",
+                limit, account, offset
+            ),
             ApiRequest::GetPeers { .. } => write!(formatter, "get peers"),
+            ApiRequest::GetPeerReputations { .. } => write!(formatter, "get peer reputations"),
             ApiRequest::GetStatus { .. } => write!(formatter, "get status"),
             ApiRequest::GetMetrics { .. } => write!(formatter, "get metrics"),
         }
@@ -443,21 +774,12 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the result.
         responder: Responder<Result<GenesisResult, engine_state::Error>>,
     },
-    /// An `ExecuteRequest` that contains multiple deploys that will be executed.
-    Execute {
-        /// Execution request containing deploys.
+    /// A request to execute the deploys of a block in order and commit their effects.
+    ExecuteBlock {
+        /// Execution request containing the block's deploys.
         execute_request: ExecuteRequest,
-        /// Responder to call with the execution result.
-        responder: Responder<Result<ExecutionResults, engine_state::RootNotFound>>,
-    },
-    /// A request to commit existing execution transforms.
-    Commit {
-        /// A valid pre state hash.
-        pre_state_hash: Digest,
-        /// Effects obtained through `ExecutionResult`
-        effects: AdditiveMap<Key, Transform>,
-        /// Responder to call with the commit result.
-        responder: Responder<Result<CommitResult, engine_state::Error>>,
+        /// Responder to call with the block execution result.
+        responder: Responder<Result<BlockExecutionResult, engine_state::Error>>,
     },
     /// A request to run upgrade.
     Upgrade {
@@ -466,6 +788,23 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the upgrade result.
         responder: Responder<Result<UpgradeResult, engine_state::Error>>,
     },
+    /// A request to run the system operations at the end of a block and commit their effects.
+    Step {
+        /// The step request.
+        step_request: StepRequest,
+        /// Responder to call with the step result.
+        responder: Responder<Result<StepResult, engine_state::Error>>,
+    },
+    /// A request to read the validator weights of the current and upcoming eras from the auction
+    /// contract's state.
+    GetEraValidators {
+        /// The global state hash to read the validators from.
+        global_state_hash: Digest,
+        /// The protocol version in effect at the given global state.
+        protocol_version: ProtocolVersion,
+        /// Responder to call with the era validators, or `None` if the global state doesn't exist.
+        responder: Responder<Result<Option<EraValidators>, engine_state::Error>>,
+    },
     /// A query request.
     Query {
         /// Query request.
@@ -480,6 +819,48 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the balance result.
         responder: Responder<Result<BalanceResult, engine_state::Error>>,
     },
+    /// A request to execute a single deploy without committing its effects.
+    SpeculativeExecute {
+        /// The global state hash to execute the deploy against.
+        global_state_hash: Digest,
+        /// The block time to execute the deploy at.
+        block_time: Timestamp,
+        /// The protocol version to execute the deploy under.
+        protocol_version: ProtocolVersion,
+        /// The deploy to execute.
+        deploy: Box<Deploy>,
+        /// Responder to call with the execution result.
+        responder: Responder<Result<EngineExecutionResult, engine_state::RootNotFound>>,
+    },
+    /// Remove the parts of global state which are unreachable from the given global state hashes,
+    /// if configured to do so.
+    PruneGlobalState {
+        /// The global state hashes to retain.
+        retained_global_state_hashes: Vec<Digest>,
+        /// Responder to call with the number of tries removed.
+        responder: Responder<Result<usize, engine_state::Error>>,
+    },
+    /// Read a global state trie.
+    GetTrie {
+        /// The hash of the trie.
+        trie_key: Blake2bHash,
+        /// Responder to call with the trie, if found.
+        responder: Responder<Result<Option<Trie>, engine_state::Error>>,
+    },
+    /// Store a global state trie.
+    PutTrie {
+        /// The trie.
+        trie: Box<Trie>,
+        /// Responder to call with the hashes of the tries it points to which are not stored yet.
+        responder: Responder<Result<Vec<Blake2bHash>, engine_state::Error>>,
+    },
+    /// Find the global state tries which remain to be stored to complete the given global state.
+    FindMissingTries {
+        /// The global state hash.
+        global_state_hash: Digest,
+        /// Responder to call with the hashes of the missing tries.
+        responder: Responder<Result<Vec<Blake2bHash>, engine_state::Error>>,
+    },
 }
 
 impl Display for ContractRuntimeRequest {
@@ -490,28 +871,31 @@ impl Display for ContractRuntimeRequest {
                 "commit genesis {}",
                 chainspec.genesis.protocol_version
             ),
-            ContractRuntimeRequest::Execute {
+            ContractRuntimeRequest::ExecuteBlock {
                 execute_request, ..
             } => write!(
                 formatter,
-                "execute request: {}",
+                "execute block request: {} deploys against {}",
+                execute_request.deploys.len(),
                 execute_request.parent_state_hash
             ),
 
-            ContractRuntimeRequest::Commit {
-                pre_state_hash,
-                effects,
-                ..
-            } => write!(
-                formatter,
-                "commit request: {} {:?}",
-                pre_state_hash, effects
-            ),
-
             ContractRuntimeRequest::Upgrade { upgrade_config, .. } => {
                 write!(formatter, "upgrade request: {:?}", upgrade_config)
             }
 
+            ContractRuntimeRequest::Step { step_request, .. } => {
+                write!(formatter, "step request: {:?}", step_request)
+            }
+
+            ContractRuntimeRequest::GetEraValidators {
+                global_state_hash, ..
+            } => write!(
+                formatter,
+                "get era validators request: {}",
+                global_state_hash
+            ),
+
             ContractRuntimeRequest::Query { query_request, .. } => {
                 write!(formatter, "query request: {:?}", query_request)
             }
@@ -519,6 +903,42 @@ impl Display for ContractRuntimeRequest {
             ContractRuntimeRequest::GetBalance {
                 balance_request, ..
             } => write!(formatter, "balance request: {:?}", balance_request),
+
+            ContractRuntimeRequest::SpeculativeExecute {
+                global_state_hash,
+                deploy,
+                ..
+            } => write!(
+                formatter,
+                "speculative execute request: {} against {}",
+                deploy.id(),
+                global_state_hash
+            ),
+
+            ContractRuntimeRequest::PruneGlobalState {
+                retained_global_state_hashes,
+                ..
+            } => write!(
+                formatter,
+                "prune global state request: retaining {} global state hashes",
+                retained_global_state_hashes.len()
+            ),
+
+            ContractRuntimeRequest::GetTrie { trie_key, .. } => {
+                write!(formatter, "get trie request: {}", trie_key)
+            }
+
+            ContractRuntimeRequest::PutTrie { trie, .. } => {
+                write!(formatter, "put trie request: {}", trie)
+            }
+
+            ContractRuntimeRequest::FindMissingTries {
+                global_state_hash, ..
+            } => write!(
+                formatter,
+                "find missing tries request: {}",
+                global_state_hash
+            ),
         }
     }
 }
@@ -590,6 +1010,8 @@ impl<T: Display, I: Display> Display for BlockValidationRequest<T, I> {
 pub enum LinearChainRequest<I> {
     /// Request whole block from the linear chain, by hash.
     BlockRequest(BlockHash, I),
+    /// Request a batch of signed block headers from the linear chain, by height.
+    BlockHeadersBatchRequest(BlockHeadersBatchId, I),
     /// Get last finalized block.
     LastFinalizedBlock(Responder<Option<LinearBlock>>),
 }
@@ -600,6 +1022,9 @@ impl<I: Display> Display for LinearChainRequest<I> {
             LinearChainRequest::BlockRequest(bh, peer) => {
                 write!(f, "block request for hash {} from {}", bh, peer)
             }
+            LinearChainRequest::BlockHeadersBatchRequest(batch_id, peer) => {
+                write!(f, "request for {} from {}", batch_id, peer)
+            }
             LinearChainRequest::LastFinalizedBlock(_) => write!(f, "last finalized block request"),
         }
     }
@@ -610,5 +1035,8 @@ impl<I: Display> Display for LinearChainRequest<I> {
 /// Consensus component requests.
 pub enum ConsensusRequest {
     /// Request for consensus to sign a new linear chain block and possibly start a new era.
-    HandleLinearBlock(Box<BlockHeader>, Responder<Signature>),
+    HandleLinearBlock(Box<BlockHeader>, Responder<FinalitySignature>),
+    /// Request for the validators of the given era along with their stakes.  Responds with `None`
+    /// if the era is not active.
+    EraValidators(EraId, Responder<Option<BTreeMap<PublicKey, Motes>>>),
 }
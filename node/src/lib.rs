@@ -21,6 +21,7 @@
     unused_qualifications
 )]
 
+pub mod archive;
 pub mod components;
 pub mod crypto;
 pub mod effect;
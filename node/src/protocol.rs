@@ -9,7 +9,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{consensus, gossiper, small_network::GossipedAddress},
-    types::{Deploy, Item, Tag},
+    types::{Deploy, Evidence, FinalitySignature, Item, Tag},
 };
 
 /// Reactor message.
@@ -24,6 +24,12 @@ pub enum Message {
     /// Address gossiper component message.
     #[from]
     AddressGossiper(gossiper::Message<GossipedAddress>),
+    /// Finality signature gossiper component message.
+    #[from]
+    FinalitySignatureGossiper(gossiper::Message<FinalitySignature>),
+    /// Evidence gossiper component message.
+    #[from]
+    EvidenceGossiper(gossiper::Message<Evidence>),
     /// Request to get an item from a peer.
     GetRequest {
         /// The type tag of the requested item.
@@ -62,6 +68,11 @@ impl Debug for Message {
             Message::Consensus(c) => f.debug_tuple("Consensus").field(&c).finish(),
             Message::DeployGossiper(dg) => f.debug_tuple("DeployGossiper").field(&dg).finish(),
             Message::AddressGossiper(ga) => f.debug_tuple("AddressGossiper").field(&ga).finish(),
+            Message::FinalitySignatureGossiper(fsg) => f
+                .debug_tuple("FinalitySignatureGossiper")
+                .field(&fsg)
+                .finish(),
+            Message::EvidenceGossiper(eg) => f.debug_tuple("EvidenceGossiper").field(&eg).finish(),
             Message::GetRequest { tag, serialized_id } => f
                 .debug_struct("GetRequest")
                 .field("tag", tag)
@@ -87,6 +98,10 @@ impl Display for Message {
             Message::AddressGossiper(gossiped_address) => {
                 write!(f, "AddressGossiper::({})", gossiped_address)
             }
+            Message::FinalitySignatureGossiper(finality_signature) => {
+                write!(f, "FinalitySignatureGossiper::({})", finality_signature)
+            }
+            Message::EvidenceGossiper(evidence) => write!(f, "EvidenceGossiper::({})", evidence),
             Message::GetRequest { tag, serialized_id } => {
                 write!(f, "GetRequest({}-{:10})", tag, HexFmt(serialized_id))
             }
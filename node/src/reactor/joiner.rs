@@ -21,7 +21,7 @@ use crate::{
         gossiper::{self, Gossiper},
         linear_chain,
         linear_chain_sync::{self, LinearChainSync},
-        small_network::{self, NodeId, SmallNetwork},
+        small_network::{self, ChainInfo, NodeId, SmallNetwork},
         storage::{self, Storage},
         Component,
     },
@@ -29,7 +29,7 @@ use crate::{
     effect::{
         announcements::{
             BlockExecutorAnnouncement, ConsensusAnnouncement, GossiperAnnouncement,
-            NetworkAnnouncement,
+            LinearChainAnnouncement, NetworkAnnouncement,
         },
         requests::{
             BlockExecutorRequest, BlockValidationRequest, ConsensusRequest, ContractRuntimeRequest,
@@ -43,7 +43,7 @@ use crate::{
         validator::{self, Error, ValidatorInitConfig},
         EventQueueHandle, Finalize,
     },
-    types::{Block, BlockHash, Deploy, ProtoBlock, Tag, Timestamp},
+    types::{Block, BlockHash, BlockHeadersBatch, Deploy, ProtoBlock, Tag, Timestamp, Trie},
     utils::{Source, WithDir},
 };
 
@@ -67,6 +67,14 @@ pub enum Event {
     #[from]
     DeployFetcher(fetcher::Event<Deploy>),
 
+    /// Block headers batch fetcher event.
+    #[from]
+    BlockHeadersBatchFetcher(fetcher::Event<BlockHeadersBatch>),
+
+    /// Trie fetcher event.
+    #[from]
+    TrieFetcher(fetcher::Event<Trie>),
+
     /// Block validator event.
     #[from]
     BlockValidator(block_validator::Event<Block, NodeId>),
@@ -104,6 +112,14 @@ pub enum Event {
     #[from]
     DeployFetcherRequest(FetcherRequest<NodeId, Deploy>),
 
+    /// Block headers batch fetcher request.
+    #[from]
+    BlockHeadersBatchFetcherRequest(FetcherRequest<NodeId, BlockHeadersBatch>),
+
+    /// Trie fetcher request.
+    #[from]
+    TrieFetcherRequest(FetcherRequest<NodeId, Trie>),
+
     /// Block validation request.
     #[from]
     BlockValidatorRequest(BlockValidationRequest<Block, NodeId>),
@@ -136,6 +152,10 @@ pub enum Event {
     /// Address Gossiper announcement.
     #[from]
     AddressGossiperAnnouncement(GossiperAnnouncement<GossipedAddress>),
+
+    /// Linear chain announcement.
+    #[from]
+    LinearChainAnnouncement(LinearChainAnnouncement),
 }
 
 impl From<StorageRequest<Storage>> for Event {
@@ -183,10 +203,18 @@ impl Display for Event {
             Event::DeployFetcherRequest(request) => {
                 write!(f, "deploy fetcher request: {}", request)
             }
+            Event::BlockHeadersBatchFetcherRequest(request) => {
+                write!(f, "block headers batch fetcher request: {}", request)
+            }
+            Event::TrieFetcherRequest(request) => write!(f, "trie fetcher request: {}", request),
             Event::LinearChainSync(event) => write!(f, "linear chain: {}", event),
             Event::BlockFetcher(event) => write!(f, "block fetcher: {}", event),
             Event::BlockValidator(event) => write!(f, "block validator event: {}", event),
             Event::DeployFetcher(event) => write!(f, "deploy fetcher event: {}", event),
+            Event::BlockHeadersBatchFetcher(event) => {
+                write!(f, "block headers batch fetcher event: {}", event)
+            }
+            Event::TrieFetcher(event) => write!(f, "trie fetcher event: {}", event),
             Event::BlockExecutor(event) => write!(f, "block executor event: {}", event),
             Event::BlockExecutorRequest(request) => {
                 write!(f, "block executor request: {}", request)
@@ -204,6 +232,7 @@ impl Display for Event {
             Event::AddressGossiperAnnouncement(ann) => {
                 write!(f, "address gossiper announcement: {}", ann)
             }
+            Event::LinearChainAnnouncement(ann) => write!(f, "linear chain announcement: {}", ann),
         }
     }
 }
@@ -220,6 +249,8 @@ pub struct Reactor<R: Rng + CryptoRng + ?Sized> {
     pub(super) linear_chain_sync: LinearChainSync<NodeId>,
     pub(super) block_validator: BlockValidator<Block, NodeId>,
     pub(super) deploy_fetcher: Fetcher<Deploy>,
+    pub(super) block_headers_batch_fetcher: Fetcher<BlockHeadersBatch>,
+    pub(super) trie_fetcher: Fetcher<Trie>,
     pub(super) block_executor: BlockExecutor,
     pub(super) linear_chain: linear_chain::LinearChain<NodeId>,
     pub(super) consensus: EraSupervisor<NodeId, R>,
@@ -242,7 +273,7 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
 
     fn new(
         initializer: Self::Config,
-        _registry: &Registry,
+        registry: &Registry,
         event_queue: EventQueueHandle<Self::Event>,
         rng: &mut R,
     ) -> Result<(Self, Effects<Self::Event>), Self::Error> {
@@ -255,7 +286,13 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
             contract_runtime,
         } = initializer;
 
-        let (net, net_effects) = SmallNetwork::new(event_queue, config.network.clone())?;
+        let genesis_post_state_hash = chainspec_loader
+            .genesis_post_state_hash()
+            .expect("Should have Genesis post state hash");
+
+        let chain_info = ChainInfo::new(chainspec_loader.chainspec(), genesis_post_state_hash);
+        let (net, net_effects) =
+            SmallNetwork::new(event_queue, config.network.clone(), registry, chain_info)?;
 
         let linear_chain_fetcher = Fetcher::new(config.gossip);
         let effects = reactor::wrap_effects(Event::Network, net_effects);
@@ -275,19 +312,38 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
             Some(hash) => info!("Synchronizing linear chain from: {:?}", hash),
         }
 
-        let linear_chain_sync = LinearChainSync::new(effect_builder, init_hash);
+        let linear_chain_sync = LinearChainSync::new(
+            effect_builder,
+            init_hash,
+            config.node.sync_mode,
+            chainspec_loader
+                .chainspec()
+                .genesis
+                .genesis_validator_stakes()
+                .into_iter()
+                .collect(),
+            chainspec_loader
+                .chainspec()
+                .genesis
+                .highway_config
+                .finality_threshold_percent,
+        );
 
         let block_validator = BlockValidator::new();
 
         let deploy_fetcher = Fetcher::new(config.gossip);
 
-        let genesis_post_state_hash = chainspec_loader
-            .genesis_post_state_hash()
-            .expect("Should have Genesis post state hash");
+        let block_headers_batch_fetcher = Fetcher::new(config.gossip);
+
+        let trie_fetcher = Fetcher::new(config.gossip);
 
-        let block_executor = BlockExecutor::new(genesis_post_state_hash);
+        let block_executor = BlockExecutor::new(
+            genesis_post_state_hash,
+            chainspec_loader.chainspec().clone(),
+        );
 
-        let linear_chain = linear_chain::LinearChain::new();
+        let linear_chain =
+            linear_chain::LinearChain::new(config.node.finality_signature_threshold_percent);
 
         let validator_stakes = chainspec_loader
             .chainspec()
@@ -321,6 +377,8 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                 linear_chain_fetcher,
                 block_validator,
                 deploy_fetcher,
+                block_headers_batch_fetcher,
+                trie_fetcher,
                 block_executor,
                 linear_chain,
                 consensus,
@@ -378,6 +436,43 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                     };
                     self.dispatch_event(effect_builder, rng, Event::BlockFetcher(event))
                 }
+                Message::GetResponse {
+                    tag: Tag::BlockHeadersBatch,
+                    serialized_item,
+                } => {
+                    let batch = match rmp_serde::from_read_ref(&serialized_item) {
+                        Ok(batch) => Box::new(batch),
+                        Err(err) => {
+                            error!(
+                                "failed to decode block headers batch from {}: {}",
+                                sender, err
+                            );
+                            return Effects::new();
+                        }
+                    };
+                    let event = fetcher::Event::GotRemotely {
+                        item: batch,
+                        source: Source::Peer(sender),
+                    };
+                    self.dispatch_event(effect_builder, rng, Event::BlockHeadersBatchFetcher(event))
+                }
+                Message::GetResponse {
+                    tag: Tag::Trie,
+                    serialized_item,
+                } => {
+                    let trie = match rmp_serde::from_read_ref(&serialized_item) {
+                        Ok(trie) => Box::new(trie),
+                        Err(err) => {
+                            error!("failed to decode trie from {}: {}", sender, err);
+                            return Effects::new();
+                        }
+                    };
+                    let event = fetcher::Event::GotRemotely {
+                        item: trie,
+                        source: Source::Peer(sender),
+                    };
+                    self.dispatch_event(effect_builder, rng, Event::TrieFetcher(event))
+                }
                 // needed so that consensus can notify us of the eras it knows of
                 // TODO: remove when proper syncing is implemented
                 Message::Consensus(msg) => self.dispatch_event(
@@ -430,6 +525,23 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
             Event::DeployFetcherRequest(request) => {
                 self.dispatch_event(effect_builder, rng, Event::DeployFetcher(request.into()))
             }
+            Event::BlockHeadersBatchFetcher(event) => reactor::wrap_effects(
+                Event::BlockHeadersBatchFetcher,
+                self.block_headers_batch_fetcher
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::BlockHeadersBatchFetcherRequest(request) => self.dispatch_event(
+                effect_builder,
+                rng,
+                Event::BlockHeadersBatchFetcher(request.into()),
+            ),
+            Event::TrieFetcher(event) => reactor::wrap_effects(
+                Event::TrieFetcher,
+                self.trie_fetcher.handle_event(effect_builder, rng, event),
+            ),
+            Event::TrieFetcherRequest(request) => {
+                self.dispatch_event(effect_builder, rng, Event::TrieFetcher(request.into()))
+            }
             Event::BlockExecutor(event) => reactor::wrap_effects(
                 Event::BlockExecutor,
                 self.block_executor.handle_event(effect_builder, rng, event),
@@ -446,11 +558,17 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                 block,
                 execution_results,
             }) => {
+                // Let the linear chain synchronizer check the block before it is stored.
+                let reactor_event = Event::LinearChainSync(
+                    linear_chain_sync::Event::BlockExecuted(Box::new(block.clone())),
+                );
+                let mut effects = self.dispatch_event(effect_builder, rng, reactor_event);
                 let reactor_event = Event::LinearChain(linear_chain::Event::LinearChainBlock {
                     block,
                     execution_results,
                 });
-                self.dispatch_event(effect_builder, rng, reactor_event)
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                effects
             }
             Event::LinearChain(event) => reactor::wrap_effects(
                 Event::LinearChain,
@@ -508,6 +626,22 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                     Event::Network(small_network::Event::PeerAddressReceived(gossiped_address));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
+            Event::LinearChainAnnouncement(LinearChainAnnouncement::BlockAdded(block)) => {
+                trace!(
+                    "block added to linear chain during joining: {}",
+                    block.hash()
+                );
+                Effects::new()
+            }
+            Event::LinearChainAnnouncement(LinearChainAnnouncement::NewFinalitySignature(
+                finality_signature,
+            )) => {
+                trace!(
+                    "new finality signature during joining: {}",
+                    finality_signature
+                );
+                Effects::new()
+            }
         }
     }
 
@@ -23,22 +23,23 @@ use crate::{
         block_executor::{self, BlockExecutor},
         block_validator::{self, BlockValidator},
         chainspec_loader::ChainspecLoader,
-        consensus::{self, EraSupervisor},
+        consensus::{self, EraId, EraSupervisor},
         contract_runtime::{self, ContractRuntime},
         deploy_acceptor::{self, DeployAcceptor},
-        deploy_buffer::{self, DeployBuffer},
+        deploy_buffer::{self, DeployBuffer, DeployInfo},
         fetcher::{self, Fetcher},
         gossiper::{self, Gossiper},
         linear_chain,
         metrics::Metrics,
-        small_network::{self, GossipedAddress, NodeId, SmallNetwork},
-        storage::{self, Storage},
+        small_network::{self, ChainInfo, GossipedAddress, NodeId, SmallNetwork},
+        storage::{self, Storage, StorageType, Value},
         Component,
     },
     effect::{
         announcements::{
             ApiServerAnnouncement, BlockExecutorAnnouncement, ConsensusAnnouncement,
-            DeployAcceptorAnnouncement, GossiperAnnouncement, NetworkAnnouncement,
+            DeployAcceptorAnnouncement, GossiperAnnouncement, LinearChainAnnouncement,
+            NetworkAnnouncement,
         },
         requests::{
             ApiRequest, BlockExecutorRequest, BlockValidationRequest, ConsensusRequest,
@@ -49,7 +50,7 @@ use crate::{
     },
     protocol::Message,
     reactor::{self, EventQueueHandle},
-    types::{Block, Deploy, ProtoBlock, Tag},
+    types::{Block, Deploy, Evidence, FinalitySignature, Item, ProtoBlock, Tag},
     utils::Source,
 };
 pub use config::Config;
@@ -87,6 +88,12 @@ pub enum Event {
     /// Address gossiper event.
     #[from]
     AddressGossiper(gossiper::Event<GossipedAddress>),
+    /// Finality signature gossiper event.
+    #[from]
+    FinalitySignatureGossiper(gossiper::Event<FinalitySignature>),
+    /// Evidence gossiper event.
+    #[from]
+    EvidenceGossiper(gossiper::Event<Evidence>),
     /// Contract runtime event.
     #[from]
     ContractRuntime(contract_runtime::Event),
@@ -145,6 +152,15 @@ pub enum Event {
     /// Address Gossiper announcement.
     #[from]
     AddressGossiperAnnouncement(GossiperAnnouncement<GossipedAddress>),
+    /// Finality signature Gossiper announcement.
+    #[from]
+    FinalitySignatureGossiperAnnouncement(GossiperAnnouncement<FinalitySignature>),
+    /// Evidence Gossiper announcement.
+    #[from]
+    EvidenceGossiperAnnouncement(GossiperAnnouncement<Evidence>),
+    /// Linear chain announcement.
+    #[from]
+    LinearChainAnnouncement(LinearChainAnnouncement),
 }
 
 impl From<StorageRequest<Storage>> for Event {
@@ -177,6 +193,18 @@ impl From<NetworkRequest<NodeId, gossiper::Message<GossipedAddress>>> for Event
     }
 }
 
+impl From<NetworkRequest<NodeId, gossiper::Message<FinalitySignature>>> for Event {
+    fn from(request: NetworkRequest<NodeId, gossiper::Message<FinalitySignature>>) -> Self {
+        Event::NetworkRequest(request.map_payload(Message::from))
+    }
+}
+
+impl From<NetworkRequest<NodeId, gossiper::Message<Evidence>>> for Event {
+    fn from(request: NetworkRequest<NodeId, gossiper::Message<Evidence>>) -> Self {
+        Event::NetworkRequest(request.map_payload(Message::from))
+    }
+}
+
 impl From<ContractRuntimeRequest> for Event {
     fn from(request: ContractRuntimeRequest) -> Event {
         Event::ContractRuntime(contract_runtime::Event::Request(request))
@@ -207,6 +235,10 @@ impl Display for Event {
             Event::DeployFetcher(event) => write!(f, "deploy fetcher: {}", event),
             Event::DeployGossiper(event) => write!(f, "deploy gossiper: {}", event),
             Event::AddressGossiper(event) => write!(f, "address gossiper: {}", event),
+            Event::FinalitySignatureGossiper(event) => {
+                write!(f, "finality signature gossiper: {}", event)
+            }
+            Event::EvidenceGossiper(event) => write!(f, "evidence gossiper: {}", event),
             Event::ContractRuntime(event) => write!(f, "contract runtime: {}", event),
             Event::BlockExecutor(event) => write!(f, "block executor: {}", event),
             Event::LinearChain(event) => write!(f, "linear-chain event {}", event),
@@ -233,6 +265,13 @@ impl Display for Event {
             Event::AddressGossiperAnnouncement(ann) => {
                 write!(f, "address gossiper announcement: {}", ann)
             }
+            Event::FinalitySignatureGossiperAnnouncement(ann) => {
+                write!(f, "finality signature gossiper announcement: {}", ann)
+            }
+            Event::EvidenceGossiperAnnouncement(ann) => {
+                write!(f, "evidence gossiper announcement: {}", ann)
+            }
+            Event::LinearChainAnnouncement(ann) => write!(f, "linear chain announcement: {}", ann),
         }
     }
 }
@@ -254,6 +293,8 @@ pub struct Reactor<R: Rng + CryptoRng + ?Sized> {
     metrics: Metrics,
     net: SmallNetwork<Event, Message>,
     address_gossiper: Gossiper<GossipedAddress, Event>,
+    finality_signature_gossiper: Gossiper<FinalitySignature, Event>,
+    evidence_gossiper: Gossiper<Evidence, Event>,
     storage: Storage,
     contract_runtime: ContractRuntime,
     api_server: ApiServer,
@@ -273,6 +314,16 @@ impl<R: Rng + CryptoRng + ?Sized> Reactor<R> {
     pub(crate) fn consensus(&self) -> &EraSupervisor<NodeId, R> {
         &self.consensus
     }
+
+    /// Inspect the linear chain.
+    pub(crate) fn linear_chain(&self) -> &LinearChain<NodeId> {
+        &self.linear_chain
+    }
+
+    /// Inspect the contract runtime.
+    pub(crate) fn contract_runtime(&self) -> &ContractRuntime {
+        &self.contract_runtime
+    }
 }
 
 impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
@@ -304,38 +355,80 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
         let metrics = Metrics::new(registry.clone());
 
         let effect_builder = EffectBuilder::new(event_queue);
-        let (net, net_effects) = SmallNetwork::new(event_queue, config.network)?;
+        // Post state hash is expected to be present.
+        let genesis_post_state_hash = chainspec_loader
+            .genesis_post_state_hash()
+            .expect("should have post state hash");
+        let chain_info = ChainInfo::new(chainspec_loader.chainspec(), genesis_post_state_hash);
+        let (net, net_effects) =
+            SmallNetwork::new(event_queue, config.network, registry, chain_info)?;
 
         let address_gossiper = Gossiper::new_for_complete_items(config.gossip);
+        let finality_signature_gossiper = Gossiper::new_for_complete_items(config.gossip);
+        let evidence_gossiper = Gossiper::new_for_partial_items(
+            config.gossip,
+            gossiper::get_evidence_from_storage::<Evidence, Event>,
+        );
 
-        let api_server = ApiServer::new(config.http_server, effect_builder);
-        let deploy_acceptor = DeployAcceptor::new();
+        let api_server = ApiServer::new(
+            config.http_server,
+            chainspec_loader.chainspec().clone(),
+            effect_builder,
+        );
+        let current_era = storage
+            .block_store()
+            .highest_block()?
+            .map_or(EraId(0), |block| block.header().next_block_era_id());
+        let deploy_acceptor = DeployAcceptor::new(
+            chainspec_loader
+                .chainspec()
+                .genesis
+                .protocol_version
+                .clone(),
+            current_era,
+        );
         let deploy_fetcher = Fetcher::new(config.gossip);
         let deploy_gossiper = Gossiper::new_for_partial_items(
             config.gossip,
             gossiper::get_deploy_from_storage::<Deploy, Event>,
         );
-        let deploy_buffer = DeployBuffer::new(config.node.block_max_deploy_count as usize);
-        // Post state hash is expected to be present.
-        let genesis_post_state_hash = chainspec_loader
-            .genesis_post_state_hash()
-            .expect("should have post state hash");
-        let block_executor =
-            BlockExecutor::new(genesis_post_state_hash).with_parent_map(linear_chain);
+        let (deploy_buffer, deploy_buffer_effects) = DeployBuffer::new(
+            registry,
+            effect_builder,
+            config.node.block_max_deploy_count as usize,
+            config.node.custom_payment_gas_estimate,
+            chainspec_loader
+                .chainspec()
+                .genesis
+                .protocol_version
+                .clone(),
+            chainspec_loader.chainspec().genesis.deploy_config.max_ttl,
+        )?;
+        let block_executor = BlockExecutor::new(
+            genesis_post_state_hash,
+            chainspec_loader.chainspec().clone(),
+        )
+        .with_parent_map(linear_chain);
         let proto_block_validator = BlockValidator::new();
-        let linear_chain = LinearChain::new();
+        let linear_chain = LinearChain::new(config.node.finality_signature_threshold_percent);
 
         let mut effects = reactor::wrap_effects(Event::Network, net_effects);
         effects.extend(reactor::wrap_effects(
             Event::Consensus,
             init_consensus_effects,
         ));
+        effects.extend(reactor::wrap_effects(
+            Event::DeployBuffer,
+            deploy_buffer_effects,
+        ));
 
         Ok((
             Reactor {
                 metrics,
                 net,
                 address_gossiper,
+                finality_signature_gossiper,
+                evidence_gossiper,
                 storage,
                 contract_runtime,
                 api_server,
@@ -398,6 +491,16 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                 self.address_gossiper
                     .handle_event(effect_builder, rng, event),
             ),
+            Event::FinalitySignatureGossiper(event) => reactor::wrap_effects(
+                Event::FinalitySignatureGossiper,
+                self.finality_signature_gossiper
+                    .handle_event(effect_builder, rng, event),
+            ),
+            Event::EvidenceGossiper(event) => reactor::wrap_effects(
+                Event::EvidenceGossiper,
+                self.evidence_gossiper
+                    .handle_event(effect_builder, rng, event),
+            ),
             Event::ContractRuntime(event) => reactor::wrap_effects(
                 Event::ContractRuntime,
                 self.contract_runtime
@@ -464,6 +567,18 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                     Message::AddressGossiper(message) => {
                         Event::AddressGossiper(gossiper::Event::MessageReceived { sender, message })
                     }
+                    Message::FinalitySignatureGossiper(message) => {
+                        Event::FinalitySignatureGossiper(gossiper::Event::MessageReceived {
+                            sender,
+                            message,
+                        })
+                    }
+                    Message::EvidenceGossiper(message) => {
+                        Event::EvidenceGossiper(gossiper::Event::MessageReceived {
+                            sender,
+                            message,
+                        })
+                    }
                     Message::GetRequest { tag, serialized_id } => match tag {
                         Tag::Deploy => {
                             let deploy_hash = match rmp_serde::from_read_ref(&serialized_id) {
@@ -500,6 +615,60 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                             warn!("received get request for gossiped-address from {}", sender);
                             return Effects::new();
                         }
+                        Tag::FinalitySignature => {
+                            warn!(
+                                "received get request for finality-signature from {}",
+                                sender
+                            );
+                            return Effects::new();
+                        }
+                        Tag::BlockHeadersBatch => {
+                            let batch_id = match rmp_serde::from_read_ref(&serialized_id) {
+                                Ok(batch_id) => batch_id,
+                                Err(error) => {
+                                    error!(
+                                        "failed to decode {:?} from {}: {}",
+                                        serialized_id, sender, error
+                                    );
+                                    return Effects::new();
+                                }
+                            };
+                            Event::LinearChain(linear_chain::Event::Request(
+                                LinearChainRequest::BlockHeadersBatchRequest(batch_id, sender),
+                            ))
+                        }
+                        Tag::Trie => {
+                            let trie_key = match rmp_serde::from_read_ref(&serialized_id) {
+                                Ok(trie_key) => trie_key,
+                                Err(error) => {
+                                    error!(
+                                        "failed to decode {:?} from {}: {}",
+                                        serialized_id, sender, error
+                                    );
+                                    return Effects::new();
+                                }
+                            };
+                            Event::ContractRuntime(contract_runtime::Event::GetTrieForPeer {
+                                trie_key,
+                                peer: sender,
+                            })
+                        }
+                        Tag::Evidence => {
+                            let evidence_id = match rmp_serde::from_read_ref(&serialized_id) {
+                                Ok(evidence_id) => evidence_id,
+                                Err(error) => {
+                                    error!(
+                                        "failed to decode {:?} from {}: {}",
+                                        serialized_id, sender, error
+                                    );
+                                    return Effects::new();
+                                }
+                            };
+                            Event::Storage(storage::Event::GetEvidenceForPeer {
+                                evidence_id,
+                                peer: sender,
+                            })
+                        }
                     },
                     Message::GetResponse {
                         tag,
@@ -523,6 +692,37 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                             warn!("received get request for gossiped-address from {}", sender);
                             return Effects::new();
                         }
+                        Tag::FinalitySignature => {
+                            warn!(
+                                "received get response for finality-signature from {}",
+                                sender
+                            );
+                            return Effects::new();
+                        }
+                        Tag::BlockHeadersBatch => {
+                            warn!(
+                                "received get response for block-headers-batch from {}",
+                                sender
+                            );
+                            return Effects::new();
+                        }
+                        Tag::Trie => {
+                            warn!("received get response for trie from {}", sender);
+                            return Effects::new();
+                        }
+                        Tag::Evidence => {
+                            let evidence = match rmp_serde::from_read_ref(&serialized_item) {
+                                Ok(evidence) => Box::new(evidence),
+                                Err(error) => {
+                                    error!("failed to decode evidence from {}: {}", sender, error);
+                                    return Effects::new();
+                                }
+                            };
+                            Event::Consensus(consensus::Event::EvidenceReceived {
+                                sender,
+                                evidence,
+                            })
+                        }
                     },
                 };
                 self.dispatch_event(effect_builder, rng, reactor_event)
@@ -551,7 +751,7 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
             }) => {
                 let event = deploy_buffer::Event::Buffer {
                     hash: *deploy.id(),
-                    header: Box::new(deploy.header().clone()),
+                    info: Box::new(DeployInfo::new(&deploy)),
                 };
                 let mut effects =
                     self.dispatch_event(effect_builder, rng, Event::DeployBuffer(event));
@@ -599,31 +799,90 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                     }
                     // Only interesting for the joiner
                     ConsensusAnnouncement::GotMessageInEra(_era_id) => Effects::new(),
+                    ConsensusAnnouncement::Fault {
+                        era_id,
+                        public_key,
+                        timestamp,
+                    } => {
+                        let reactor_event = Event::ApiServer(api_server::Event::Fault {
+                            era_id,
+                            public_key,
+                            timestamp,
+                        });
+                        self.dispatch_event(effect_builder, rng, reactor_event)
+                    }
                     ConsensusAnnouncement::Handled(_) => {
                         debug!("Ignoring `Handled` announcement in `validator` reactor.");
                         Effects::new()
                     }
+                    ConsensusAnnouncement::NewEvidence(evidence) => {
+                        let event = gossiper::Event::ItemReceived {
+                            item_id: evidence.id(),
+                            source: Source::<NodeId>::Client,
+                        };
+                        self.dispatch_event(effect_builder, rng, Event::EvidenceGossiper(event))
+                    }
                 }
             }
             Event::BlockExecutorAnnouncement(BlockExecutorAnnouncement::LinearChainBlock {
                 block,
                 execution_results,
             }) => {
+                let mut effects = Effects::new();
+                let block_hash = *block.hash();
+                for (deploy_hash, execution_result) in execution_results.iter() {
+                    let reactor_event = Event::ApiServer(api_server::Event::DeployProcessed {
+                        deploy_hash: *deploy_hash,
+                        block_hash,
+                        execution_result: Box::new(execution_result.clone()),
+                    });
+                    effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                }
+
                 let reactor_event = Event::LinearChain(linear_chain::Event::LinearChainBlock {
                     block,
                     execution_results,
                 });
-                self.dispatch_event(effect_builder, rng, reactor_event)
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                effects
             }
             Event::DeployGossiperAnnouncement(_ann) => {
                 unreachable!("the deploy gossiper should never make an announcement")
             }
+            Event::EvidenceGossiperAnnouncement(_ann) => {
+                unreachable!("the evidence gossiper should never make an announcement")
+            }
             Event::AddressGossiperAnnouncement(ann) => {
                 let GossiperAnnouncement::NewCompleteItem(gossiped_address) = ann;
                 let reactor_event =
                     Event::Network(small_network::Event::PeerAddressReceived(gossiped_address));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
+            Event::FinalitySignatureGossiperAnnouncement(ann) => {
+                let GossiperAnnouncement::NewCompleteItem(finality_signature) = ann;
+                let reactor_event = Event::LinearChain(
+                    linear_chain::Event::FinalitySignatureReceived(Box::new(finality_signature)),
+                );
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            Event::LinearChainAnnouncement(LinearChainAnnouncement::BlockAdded(block)) => {
+                let reactor_event = Event::DeployAcceptor(deploy_acceptor::Event::BlockAdded(
+                    Box::new(block.header().clone()),
+                ));
+                let mut effects = self.dispatch_event(effect_builder, rng, reactor_event);
+                let reactor_event = Event::ApiServer(api_server::Event::BlockAdded(block));
+                effects.extend(self.dispatch_event(effect_builder, rng, reactor_event));
+                effects
+            }
+            Event::LinearChainAnnouncement(LinearChainAnnouncement::NewFinalitySignature(
+                finality_signature,
+            )) => {
+                let event = gossiper::Event::ItemReceived {
+                    item_id: *finality_signature,
+                    source: Source::<NodeId>::Client,
+                };
+                self.dispatch_event(effect_builder, rng, Event::FinalitySignatureGossiper(event))
+            }
         }
     }
 }
@@ -1,25 +1,64 @@
-use std::{collections::HashSet, time::Duration};
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::Duration,
+};
 
 use anyhow::bail;
 use rand::Rng;
+use semver::Version;
 use tempfile::TempDir;
 
-use casper_execution_engine::{core::engine_state::genesis::GenesisAccount, shared::motes::Motes};
-use casper_types::U512;
+use casper_engine_test_support::internal::utils;
+use casper_execution_engine::{
+    core::engine_state::{
+        balance::BalanceResult,
+        executable_deploy_item::ExecutableDeployItem,
+        genesis::GenesisAccount,
+        query::{QueryRequest, QueryResult},
+    },
+    shared::{motes::Motes, newtypes::CorrelationId, stored_value::StoredValue},
+};
+use casper_types::{
+    auction::{
+        BidPurses, DelegationRate, ValidatorWeights, ARG_AMOUNT, ARG_DELEGATION_RATE,
+        ARG_PUBLIC_KEY, AUCTION_DELAY, BID_PURSES_KEY, METHOD_ADD_BID,
+    },
+    bytesrepr::ToBytes,
+    runtime_args, Key, ProtocolVersion, RuntimeArgs, U512,
+};
 
 use crate::{
-    components::{consensus::EraId, small_network, storage},
-    crypto::asymmetric_key::{PublicKey, SecretKey},
+    archive,
+    components::{
+        block_executor,
+        chainspec_loader::{ActivationPoint, UpgradePoint},
+        consensus::{EraId, BLOCK_REWARD},
+        small_network,
+        storage::{self, Storage, StorageType, Value},
+    },
+    crypto::{
+        asymmetric_key::{PublicKey, SecretKey},
+        hash::Digest,
+    },
+    effect::EffectExt,
     reactor::{initializer, joiner, validator, Runner},
     testing::{self, network::Network, ConditionCheckReactor, TestRng},
-    types::Timestamp,
+    types::{Block, Deploy, SystemTransaction, TimeDiff, Timestamp},
     utils::{External, Loadable, WithDir, RESOURCES_PATH},
     Chainspec,
 };
 
+/// The balance of a node's account that is not bonded at genesis.
+const UNBONDED_NODE_BALANCE: u64 = 1_000_000_000_000_000;
+/// The payment for a deploy placing a bid in the auction.
+const ADD_BID_PAYMENT: u64 = 10_000_000_000;
+/// The session contract placing a bid in the auction.
+const AUCTION_BIDS_CONTRACT: &str = "auction_bids.wasm";
+
 struct TestChain {
     keys: Vec<SecretKey>,
     storages: Vec<TempDir>,
+    storage_configs: Vec<storage::Config>,
     chainspec: Chainspec,
 }
 
@@ -58,9 +97,24 @@ impl TestChain {
             keys,
             chainspec,
             storages: Vec::new(),
+            storage_configs: Vec::new(),
         }
     }
 
+    /// Adds a node whose account is funded at genesis, but not bonded, and returns its secret key.
+    fn add_unbonded_node(&mut self, rng: &mut TestRng) -> SecretKey {
+        let secret_key = SecretKey::random(rng);
+        let public_key: PublicKey = (&secret_key).into();
+        self.chainspec.genesis.accounts.push(GenesisAccount::new(
+            public_key.into(),
+            public_key.to_account_hash(),
+            Motes::new(U512::from(UNBONDED_NODE_BALANCE)),
+            Motes::new(U512::zero()),
+        ));
+        self.keys.push(secret_key.duplicate());
+        secret_key
+    }
+
     /// Creates an initializer/validator configuration for the `idx`th validator.
     fn create_node_config(&mut self, idx: usize, first_node_port: u16) -> validator::Config {
         // Start with a default configuration.
@@ -82,6 +136,7 @@ impl TestChain {
         // Additionally set up storage in a temporary directory.
         let (storage_cfg, temp_dir) = storage::Config::default_for_tests();
         self.storages.push(temp_dir);
+        self.storage_configs.push(storage_cfg.clone());
         cfg.storage = storage_cfg;
 
         cfg
@@ -130,6 +185,13 @@ impl TestChain {
     }
 }
 
+/// Get the linear chain from a runner.
+fn linear_chain(
+    runner: &Runner<ConditionCheckReactor<validator::Reactor<TestRng>>, TestRng>,
+) -> &Vec<Block> {
+    runner.reactor().inner().linear_chain().linear_chain()
+}
+
 /// Get the set of era IDs from a runner.
 fn era_ids(
     runner: &Runner<ConditionCheckReactor<validator::Reactor<TestRng>>, TestRng>,
@@ -186,3 +248,465 @@ async fn run_validator_network() {
     net.settle_on(&mut rng, is_in_era(2), Duration::from_secs(60))
         .await;
 }
+
+/// Returns the balance of the account's main purse in the given global state.
+fn account_balance(
+    runner: &Runner<ConditionCheckReactor<validator::Reactor<TestRng>>, TestRng>,
+    state_hash: Digest,
+    public_key: &PublicKey,
+) -> U512 {
+    let engine_state = runner.reactor().inner().contract_runtime().engine_state();
+    let correlation_id = CorrelationId::new();
+    let account_key = Key::Account(public_key.to_account_hash());
+    let query_request = QueryRequest::new(state_hash.into(), account_key, vec![]);
+    let main_purse = match engine_state
+        .run_query(correlation_id, query_request)
+        .expect("should query account")
+    {
+        QueryResult::Success(StoredValue::Account(account)) => account.main_purse(),
+        result => panic!("unexpected account query result: {:?}", result),
+    };
+    match engine_state
+        .get_purse_balance(correlation_id, state_hash.into(), main_purse)
+        .expect("should get balance")
+    {
+        BalanceResult::Success(balance) => balance,
+        BalanceResult::RootNotFound => panic!("missing global state {}", state_hash),
+    }
+}
+
+/// Returns the bonding purses of the validators in the given global state.
+fn bid_purses(
+    runner: &Runner<ConditionCheckReactor<validator::Reactor<TestRng>>, TestRng>,
+    state_hash: Digest,
+    protocol_version: ProtocolVersion,
+) -> BidPurses {
+    let engine_state = runner.reactor().inner().contract_runtime().engine_state();
+    let auction = engine_state
+        .get_protocol_data(protocol_version)
+        .expect("should read protocol data")
+        .expect("should have protocol data")
+        .auction();
+    let path = vec![BID_PURSES_KEY.to_string()];
+    let query_request = QueryRequest::new(state_hash.into(), auction.into(), path);
+    match engine_state
+        .run_query(CorrelationId::new(), query_request)
+        .expect("should query bid purses")
+    {
+        QueryResult::Success(StoredValue::CLValue(cl_value)) => {
+            cl_value.into_t().expect("should be bid purses")
+        }
+        result => panic!("unexpected bid purses query result: {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn run_auction_at_switch_blocks() {
+    testing::init_logging();
+
+    let mut rng = TestRng::new();
+
+    const NETWORK_SIZE: usize = 3;
+    let mut chain = TestChain::new(&mut rng, NETWORK_SIZE);
+    // Use short eras, so that the test gets past a few switch blocks quickly.
+    chain.chainspec.genesis.highway_config.era_duration = TimeDiff::from(10_000);
+    chain.chainspec.genesis.highway_config.minimum_era_height = 5;
+
+    // The first validator runs two nodes, which equivocate. Its stake is small enough for the
+    // other validators to keep finalizing blocks.
+    let equivocator: PublicKey = (&chain.keys[0]).into();
+    let equivocator_balance = chain.chainspec.genesis.accounts[0].balance();
+    chain.chainspec.genesis.accounts[0] = GenesisAccount::new(
+        equivocator.into(),
+        equivocator.to_account_hash(),
+        equivocator_balance,
+        Motes::new(U512::from(10)),
+    );
+    let doppelganger_key = chain.keys[0].duplicate();
+    chain.keys.push(doppelganger_key);
+
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+
+    // Wait until the first node has added a switch block slashing the equivocator, and another
+    // one after it, to its linear chain.
+    let slash = SystemTransaction::Slash(equivocator);
+    let has_switch_blocks_after_slash = |nodes: &Nodes| {
+        let first_node = nodes.values().next().expect("need at least one node");
+        linear_chain(first_node)
+            .iter()
+            .filter(|block| block.header().switch_block())
+            .skip_while(|block| !block.header().system_transactions().contains(&slash))
+            .count()
+            >= 2
+    };
+    net.settle_on(
+        &mut rng,
+        has_switch_blocks_after_slash,
+        Duration::from_secs(180),
+    )
+    .await;
+
+    let first_node = net.nodes().values().next().expect("need at least one node");
+    let blocks = linear_chain(first_node);
+    let block_reward = chain.chainspec.genesis.highway_config.block_reward.value();
+    let first_state_hash = *blocks[0].header().global_state_hash();
+    let genesis_protocol_version = block_executor::protocol_version(&chain.chainspec, EraId(0));
+    let bonded = bid_purses(first_node, first_state_hash, genesis_protocol_version);
+    assert!(bonded.contains_key(&casper_types::PublicKey::from(equivocator)));
+    let mut expected_rewards: BTreeMap<PublicKey, U512> = BTreeMap::new();
+    for block in blocks.iter().skip(1) {
+        let header = block.header();
+        let state_hash = *header.global_state_hash();
+        let protocol_version = block_executor::protocol_version(&chain.chainspec, block.era_id());
+        // No deploys are sent, so only the system operations can change the global state.
+        assert!(header.deploy_hashes().is_empty());
+
+        for system_transaction in header.system_transactions() {
+            match system_transaction {
+                SystemTransaction::Slash(public_key) => {
+                    // Only the equivocator is slashed, and its bond is forfeited.
+                    assert_eq!(*public_key, equivocator);
+                    let bonded = bid_purses(first_node, state_hash, protocol_version);
+                    assert!(!bonded.contains_key(&casper_types::PublicKey::from(*public_key)));
+                }
+                SystemTransaction::Rewards(rewards) => {
+                    for (public_key, amount) in rewards {
+                        let motes = U512::from(*amount) * block_reward / BLOCK_REWARD;
+                        *expected_rewards.entry(*public_key).or_default() += motes;
+                    }
+                }
+            }
+        }
+
+        // The auction runs in every switch block, electing the bonded validators of the era after
+        // the auction delay, with their genesis stakes.
+        if header.switch_block() {
+            let engine_state = first_node
+                .reactor()
+                .inner()
+                .contract_runtime()
+                .engine_state();
+            let mut era_validators = engine_state
+                .get_era_validators(CorrelationId::new(), state_hash.into(), protocol_version)
+                .expect("should read era validators")
+                .expect("should have global state");
+            let elected_era = block.era_id().0 + 1 + AUCTION_DELAY;
+            let elected = era_validators
+                .remove(&elected_era)
+                .unwrap_or_else(|| panic!("auction didn't run in {}", block));
+            let bonded = bid_purses(first_node, state_hash, protocol_version);
+            let expected: ValidatorWeights = chain
+                .chainspec
+                .genesis
+                .accounts
+                .iter()
+                .filter_map(|account| {
+                    let public_key = account.public_key()?;
+                    let stake = account.bonded_amount().value();
+                    if stake.is_zero() || !bonded.contains_key(&public_key) {
+                        return None;
+                    }
+                    Some((public_key, stake))
+                })
+                .collect();
+            assert_eq!(elected, expected, "unexpected auction result in {}", block);
+        }
+    }
+    assert!(!expected_rewards.is_empty(), "no rewards were paid out");
+
+    // Each validator's main purse received the rewards in motes, scaled by the block reward.
+    let last_state_hash = *blocks[blocks.len() - 1].header().global_state_hash();
+    for (public_key, reward) in expected_rewards {
+        let balance_before = account_balance(first_node, first_state_hash, &public_key);
+        let balance_after = account_balance(first_node, last_state_hash, &public_key);
+        assert_eq!(
+            balance_after,
+            balance_before + reward,
+            "unexpected rewards for {}",
+            public_key
+        );
+    }
+}
+
+#[tokio::test]
+async fn upgrade_activates_at_era() {
+    testing::init_logging();
+
+    let mut rng = TestRng::new();
+
+    const NETWORK_SIZE: usize = 3;
+    let mut chain = TestChain::new(&mut rng, NETWORK_SIZE);
+    let mut new_costs = chain.chainspec.genesis.costs;
+    new_costs.regular += 1;
+    chain.chainspec.upgrades = vec![UpgradePoint {
+        activation_point: ActivationPoint { rank: 1 },
+        protocol_version: Version::new(1, 1, 0),
+        upgrade_installer_bytes: None,
+        upgrade_installer_args: None,
+        new_costs: Some(new_costs),
+        new_deploy_config: None,
+        new_consensus_protocol: None,
+    }];
+
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+
+    // Wait until all nodes have executed the switch block of the upgraded era: its system
+    // operations can only run under the new protocol version if the upgrade has been committed.
+    let has_upgraded_switch_block = |nodes: &Nodes| {
+        nodes.values().all(|runner| {
+            linear_chain(runner)
+                .iter()
+                .any(|block| block.era_id() == EraId(1) && block.header().switch_block())
+        })
+    };
+    net.settle_on(
+        &mut rng,
+        has_upgraded_switch_block,
+        Duration::from_secs(180),
+    )
+    .await;
+
+    let new_protocol_version = ProtocolVersion::from_parts(1, 1, 0);
+    for runner in net.nodes().values() {
+        let wasm_costs = runner
+            .reactor()
+            .inner()
+            .contract_runtime()
+            .engine_state()
+            .wasm_costs(new_protocol_version)
+            .expect("should read protocol data");
+        assert_eq!(wasm_costs, Some(new_costs));
+    }
+}
+
+/// Creates a deploy placing a bid in the auction, signed by the bidding validator.
+fn add_bid_deploy(
+    chainspec: &Chainspec,
+    secret_key: &SecretKey,
+    amount: U512,
+    rng: &mut TestRng,
+) -> Deploy {
+    let public_key: PublicKey = secret_key.into();
+    let payment_args = runtime_args! {
+        ARG_AMOUNT => U512::from(ADD_BID_PAYMENT),
+    };
+    let payment = ExecutableDeployItem::ModuleBytes {
+        module_bytes: vec![],
+        args: payment_args.to_bytes().expect("should serialize"),
+    };
+    let delegation_rate: DelegationRate = 0;
+    let session_args = runtime_args! {
+        "entry_point" => METHOD_ADD_BID,
+        ARG_PUBLIC_KEY => casper_types::PublicKey::from(public_key),
+        ARG_DELEGATION_RATE => delegation_rate,
+        ARG_AMOUNT => amount,
+    };
+    let session = ExecutableDeployItem::ModuleBytes {
+        module_bytes: utils::read_wasm_file_bytes(AUCTION_BIDS_CONTRACT),
+        args: session_args.to_bytes().expect("should serialize"),
+    };
+    Deploy::new(
+        Timestamp::now(),
+        TimeDiff::from(60_000),
+        1,
+        vec![],
+        chainspec.genesis.name.clone(),
+        payment,
+        session,
+        secret_key,
+        rng,
+    )
+}
+
+/// Returns the eras in which the given validator is bonded, as known to a runner.
+fn eras_validated_by(
+    runner: &Runner<ConditionCheckReactor<validator::Reactor<TestRng>>, TestRng>,
+    public_key: &PublicKey,
+) -> HashSet<EraId> {
+    runner
+        .reactor()
+        .inner()
+        .consensus()
+        .active_eras()
+        .iter()
+        .filter(|(_, era)| era.validator_stakes().contains_key(public_key))
+        .map(|(era_id, _)| *era_id)
+        .collect()
+}
+
+#[tokio::test]
+async fn bonded_validator_joins_after_auction_delay() {
+    testing::init_logging();
+
+    let mut rng = TestRng::new();
+
+    const NETWORK_SIZE: usize = 3;
+    let mut chain = TestChain::new(&mut rng, NETWORK_SIZE);
+    let bidder_secret_key = chain.add_unbonded_node(&mut rng);
+    let bidder: PublicKey = (&bidder_secret_key).into();
+    // Use short eras, so that the test gets past the auction delay quickly.
+    chain.chainspec.genesis.highway_config.era_duration = TimeDiff::from(10_000);
+    chain.chainspec.genesis.highway_config.minimum_era_height = 5;
+
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+
+    // Place the bid in the first era.
+    let deploy = add_bid_deploy(
+        &chain.chainspec,
+        &bidder_secret_key,
+        U512::from(500),
+        &mut rng,
+    );
+    let node_id = *net.nodes().keys().next().expect("need at least one node");
+    net.process_injected_effect_on(&node_id, |effect_builder| {
+        effect_builder
+            .announce_deploy_received(Box::new(deploy))
+            .ignore()
+    })
+    .await;
+
+    // The era following the first auction is still validated by the genesis validators only.
+    let has_era = |era_id| {
+        move |nodes: &Nodes| {
+            nodes
+                .values()
+                .all(|runner| era_ids(runner).contains(&EraId(era_id)))
+        }
+    };
+    net.settle_on(&mut rng, has_era(1), Duration::from_secs(90))
+        .await;
+    for runner in net.nodes().values() {
+        assert!(eras_validated_by(runner, &bidder).is_empty());
+    }
+
+    // Once the auction delay has passed, the bidder becomes a validator.
+    let bidder_is_validator = |nodes: &Nodes| {
+        nodes
+            .values()
+            .all(|runner| !eras_validated_by(runner, &bidder).is_empty())
+    };
+    net.settle_on(&mut rng, bidder_is_validator, Duration::from_secs(300))
+        .await;
+    let mut first_eras = HashSet::new();
+    for runner in net.nodes().values() {
+        let first_era = eras_validated_by(runner, &bidder)
+            .into_iter()
+            .min()
+            .expect("bidder should be a validator");
+        assert!(
+            first_era.0 > AUCTION_DELAY,
+            "joined too early in {:?}",
+            first_era
+        );
+        first_eras.insert(first_era);
+    }
+    assert_eq!(
+        first_eras.len(),
+        1,
+        "nodes disagree on the bidder's first era"
+    );
+
+    // The bidder takes part in the consensus protocol: all nodes finalize a block it proposed.
+    let has_block_by_bidder = |nodes: &Nodes| {
+        nodes.values().all(|runner| {
+            linear_chain(runner)
+                .iter()
+                .any(|block| *block.header().proposer() == bidder)
+        })
+    };
+    net.settle_on(&mut rng, has_block_by_bidder, Duration::from_secs(300))
+        .await;
+    let first_era = first_eras
+        .into_iter()
+        .next()
+        .expect("should have first era");
+    for runner in net.nodes().values() {
+        for block in linear_chain(runner) {
+            if *block.header().proposer() == bidder {
+                assert!(
+                    block.era_id() >= first_era,
+                    "bidder proposed {} before joining",
+                    block
+                );
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn export_and_import_blocks_across_switch_blocks() {
+    testing::init_logging();
+
+    let mut rng = TestRng::new();
+
+    const NETWORK_SIZE: usize = 3;
+    let mut chain = TestChain::new(&mut rng, NETWORK_SIZE);
+
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+
+    // Wait until all nodes have added two switch blocks, so that the exported blocks include the
+    // rewards, the auctions and the first block of a new era.
+    let have_switch_blocks = |nodes: &Nodes| {
+        nodes.values().all(|runner| {
+            linear_chain(runner)
+                .iter()
+                .filter(|block| block.header().switch_block())
+                .count()
+                >= 2
+        })
+    };
+    net.settle_on(&mut rng, have_switch_blocks, Duration::from_secs(120))
+        .await;
+    let to_height = net
+        .nodes()
+        .values()
+        .filter_map(|runner| linear_chain(runner).last().map(Block::height))
+        .min()
+        .expect("need at least one node");
+
+    // Stop the nodes, so that the first one's storage can be exported.
+    drop(net);
+    let exported_blocks = Storage::new(&chain.storage_configs[0])
+        .expect("should open storage")
+        .block_store()
+        .get_by_height_range(0, to_height)
+        .expect("should read blocks");
+    assert_eq!(exported_blocks.len() as u64, to_height + 1);
+    let archive_dir = tempfile::tempdir().expect("should create temp dir");
+    let archive_path = archive_dir.path().join("chain.archive");
+    let exported_count =
+        archive::export_blocks(&chain.storage_configs[0], 0, to_height, &archive_path)
+            .expect("should export blocks");
+    assert_eq!(exported_count, exported_blocks.len());
+
+    // Import the archive into a fresh node's storage, re-executing every block.
+    let mut config = validator::Config::default();
+    config.node.chainspec_config_path = External::value(chain.chainspec.clone());
+    let (storage_config, _storage_dir) = storage::Config::default_for_tests();
+    config.storage = storage_config.clone();
+    let imported_count = archive::import_blocks(
+        WithDir::new(RESOURCES_PATH.join("local"), config),
+        &archive_path,
+    )
+    .expect("should import blocks");
+    assert_eq!(imported_count, exported_count);
+
+    let imported_blocks = Storage::new(&storage_config)
+        .expect("should open storage")
+        .block_store()
+        .get_by_height_range(0, to_height)
+        .expect("should read blocks");
+    assert_eq!(imported_blocks, exported_blocks);
+}
@@ -2,16 +2,23 @@
 
 mod block;
 mod deploy;
+mod evidence;
 mod item;
 pub mod json_compatibility;
 mod node_config;
 mod status_feed;
 mod timestamp;
+mod trie;
 
-pub use block::{Block, BlockHash, BlockHeader};
+pub use block::{
+    Block, BlockExecutionSummary, BlockHash, BlockHeader, BlockHeadersBatch, BlockHeadersBatchId,
+    DeployExecutionSummary, Error as BlockError, FinalitySignature, SignedBlockHeader,
+};
 pub(crate) use block::{BlockLike, FinalizedBlock, ProtoBlock, ProtoBlockHash, SystemTransaction};
-pub use deploy::{Approval, Deploy, DeployHash, DeployHeader, Error as DeployError};
+pub use deploy::{Approval, Deploy, DeployHash, DeployHeader, DeployStatus, Error as DeployError};
+pub use evidence::{Evidence, EvidenceId};
 pub use item::{Item, Tag};
-pub use node_config::NodeConfig;
+pub use node_config::{NodeConfig, SyncMode};
 pub use status_feed::StatusFeed;
 pub use timestamp::{TimeDiff, Timestamp};
+pub use trie::Trie;
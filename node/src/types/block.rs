@@ -2,7 +2,7 @@
 use std::iter;
 use std::{
     array::TryFromSliceError,
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     convert::TryFrom,
     error::Error as StdError,
     fmt::{self, Debug, Display, Formatter},
@@ -17,21 +17,24 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value as JsonValue};
 use thiserror::Error;
 
-use super::{Item, Tag, Timestamp};
+use casper_types::U512;
+
+use super::{json_compatibility::ExecutionResult, Evidence, Item, Tag, Timestamp};
 use crate::{
-    components::{consensus::EraId, storage::Value},
+    components::{
+        consensus::EraId,
+        storage::{BlockValue, Value},
+    },
     crypto::{
-        asymmetric_key::{PublicKey, Signature},
+        self,
+        asymmetric_key::{self, PublicKey, Signature},
         hash::{self, Digest},
     },
     types::DeployHash,
     utils::DisplayIter,
 };
 #[cfg(test)]
-use crate::{
-    crypto::asymmetric_key::{self, SecretKey},
-    testing::TestRng,
-};
+use crate::{crypto::asymmetric_key::SecretKey, testing::TestRng};
 
 /// Error returned from constructing or validating a `Block`.
 #[derive(Debug, Error)]
@@ -43,6 +46,18 @@ pub enum Error {
     /// Error while decoding from JSON.
     #[error("decoding from JSON: {0}")]
     DecodeFromJson(Box<dyn StdError>),
+
+    /// The block's hash doesn't match its header.
+    #[error("block hash doesn't match the header's hash {0}")]
+    InvalidHash(BlockHash),
+
+    /// The header's body hash doesn't match the block's body.
+    #[error("body hash doesn't match the body's hash {0}")]
+    InvalidBodyHash(Digest),
+
+    /// The validators slashed in the block don't match those it holds evidence against in its era.
+    #[error("evidence doesn't match the slashed validators")]
+    InvalidEvidence,
 }
 
 impl From<FromHexError> for Error {
@@ -220,6 +235,7 @@ pub struct FinalizedBlock {
     proto_block: ProtoBlock,
     timestamp: Timestamp,
     system_transactions: Vec<SystemTransaction>,
+    evidence: Vec<Evidence>,
     switch_block: bool,
     era_id: EraId,
     height: u64,
@@ -227,10 +243,12 @@ pub struct FinalizedBlock {
 }
 
 impl FinalizedBlock {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         proto_block: ProtoBlock,
         timestamp: Timestamp,
         system_transactions: Vec<SystemTransaction>,
+        evidence: Vec<Evidence>,
         switch_block: bool,
         era_id: EraId,
         height: u64,
@@ -240,6 +258,7 @@ impl FinalizedBlock {
             proto_block,
             timestamp,
             system_transactions,
+            evidence,
             switch_block,
             era_id,
             height,
@@ -262,6 +281,23 @@ impl FinalizedBlock {
         &self.system_transactions
     }
 
+    /// The evidence against the validators slashed in this block.
+    pub(crate) fn evidence(&self) -> &Vec<Evidence> {
+        &self.evidence
+    }
+
+    /// Returns `true` if this block holds evidence against the given validator in its era.
+    pub(crate) fn has_evidence_against(&self, public_key: &PublicKey) -> bool {
+        self.evidence.iter().any(|evidence| {
+            evidence.era_id() == self.era_id && evidence.perpetrator() == public_key
+        })
+    }
+
+    /// Returns `true` if this is the last block of an era.
+    pub(crate) fn switch_block(&self) -> bool {
+        self.switch_block
+    }
+
     /// Returns the ID of the era this block belongs to.
     pub(crate) fn era_id(&self) -> EraId {
         self.era_id
@@ -288,6 +324,7 @@ impl From<Block> for FinalizedBlock {
         let switch_block = b.header().switch_block;
         let era_id = b.header().era_id;
         let height = b.header().height;
+        let evidence = b.evidence.clone();
         let header = b.take_header();
         let proposer = header.proposer;
         let system_transactions = header.system_transactions;
@@ -296,6 +333,7 @@ impl From<Block> for FinalizedBlock {
             proto_block,
             timestamp,
             system_transactions,
+            evidence,
             switch_block,
             era_id,
             height,
@@ -418,6 +456,16 @@ impl BlockHeader {
         self.era_id
     }
 
+    /// Era ID in which the child of this block is created, i.e. the next era if this is a switch
+    /// block.
+    pub fn next_block_era_id(&self) -> EraId {
+        if self.switch_block {
+            self.era_id.successor()
+        } else {
+            self.era_id
+        }
+    }
+
     /// Returns the height of this block, i.e. the number of ancestors.
     pub fn height(&self) -> u64 {
         self.height
@@ -466,7 +514,10 @@ pub struct Block {
     hash: BlockHash,
     header: BlockHeader,
     body: (), // TODO: implement body of block
-    proofs: Vec<Signature>,
+    proofs: BTreeMap<PublicKey, Signature>,
+    /// The evidence against the validators slashed in this block.  It is covered by the header's
+    /// body hash, so all nodes slash based on the same evidence.
+    evidence: Vec<Evidence>,
 }
 
 impl Block {
@@ -476,9 +527,8 @@ impl Block {
         finalized_block: FinalizedBlock,
     ) -> Self {
         let body = ();
-        let serialized_body = Self::serialize_body(&body)
-            .unwrap_or_else(|error| panic!("should serialize block body: {}", error));
-        let body_hash = hash::hash(&serialized_body);
+        let evidence = finalized_block.evidence;
+        let body_hash = Self::hash_body(&evidence);
 
         let era_id = finalized_block.era_id();
         let height = finalized_block.height();
@@ -503,7 +553,8 @@ impl Block {
             hash,
             header,
             body,
-            proofs: vec![],
+            proofs: BTreeMap::new(),
+            evidence,
         }
     }
 
@@ -531,14 +582,28 @@ impl Block {
         self.header.era_id()
     }
 
+    pub(crate) fn timestamp(&self) -> Timestamp {
+        self.header.timestamp()
+    }
+
     pub(crate) fn is_genesis_child(&self) -> bool {
         self.header.era_id == EraId(0) && self.header.height == 0
     }
 
-    /// Appends the given signature to this block's proofs.  It should have been validated prior to
-    /// this via `BlockHash::verify()`.
-    pub(crate) fn append_proof(&mut self, proof: Signature) {
-        self.proofs.push(proof)
+    /// Appends the given validator's signature to this block's proofs.  It should have been
+    /// validated prior to this via `FinalitySignature::verify()`.
+    pub(crate) fn append_proof(&mut self, public_key: PublicKey, signature: Signature) {
+        let _ = self.proofs.insert(public_key, signature);
+    }
+
+    /// The validators' signatures of this block, confirming it is finalized.
+    pub fn proofs(&self) -> &BTreeMap<PublicKey, Signature> {
+        &self.proofs
+    }
+
+    /// The evidence against the validators slashed in this block.
+    pub fn evidence(&self) -> &Vec<Evidence> {
+        &self.evidence
     }
 
     /// Convert the `Block` to a JSON value.
@@ -553,8 +618,43 @@ impl Block {
         Block::try_from(json)
     }
 
-    fn serialize_body(body: &()) -> Result<Vec<u8>, rmp_serde::encode::Error> {
-        rmp_serde::to_vec(body)
+    /// Checks that the block's hash matches its header, that the header's body hash matches the
+    /// evidence, and that the block holds evidence against exactly the validators it slashes.
+    pub(crate) fn verify(&self) -> Result<(), Error> {
+        let hash = self.header.hash();
+        if self.hash != hash {
+            return Err(Error::InvalidHash(hash));
+        }
+        let body_hash = Self::hash_body(&self.evidence);
+        if self.header.body_hash != body_hash {
+            return Err(Error::InvalidBodyHash(body_hash));
+        }
+        let slashed: BTreeSet<_> = self
+            .header
+            .system_transactions
+            .iter()
+            .filter_map(|system_transaction| match system_transaction {
+                SystemTransaction::Slash(public_key) => Some(public_key),
+                SystemTransaction::Rewards(_) => None,
+            })
+            .collect();
+        let perpetrators: BTreeSet<_> = self
+            .evidence
+            .iter()
+            .filter(|evidence| evidence.era_id() == self.header.era_id)
+            .map(Evidence::perpetrator)
+            .collect();
+        if slashed != perpetrators || perpetrators.len() != self.evidence.len() {
+            return Err(Error::InvalidEvidence);
+        }
+        Ok(())
+    }
+
+    /// Returns the hash of the block's body, which holds the evidence.
+    fn hash_body(evidence: &[Evidence]) -> Digest {
+        let serialized_body = rmp_serde::to_vec(evidence)
+            .unwrap_or_else(|error| panic!("should serialize block body: {}", error));
+        hash::hash(&serialized_body)
     }
 
     /// Generates a random instance using a `TestRng`.
@@ -574,6 +674,10 @@ impl Block {
             .take(system_transactions_count)
             .collect();
         let switch_block = rng.gen_bool(0.1);
+        let evidence_count = if switch_block { rng.gen_range(0, 3) } else { 0 };
+        let evidence = iter::repeat_with(|| Evidence::random(rng))
+            .take(evidence_count)
+            .collect();
         let era = rng.gen_range(0, 5);
         let secret_key: SecretKey = SecretKey::new_ed25519(rng.gen());
         let public_key = PublicKey::from(&secret_key);
@@ -582,6 +686,7 @@ impl Block {
             proto_block,
             timestamp,
             system_transactions,
+            evidence,
             switch_block,
             EraId(era),
             era * 10 + rng.gen_range(0, 10),
@@ -597,7 +702,7 @@ impl Block {
             let secret_key = SecretKey::random(rng);
             let public_key = PublicKey::from(&secret_key);
             let signature = asymmetric_key::sign(block.hash.inner(), &secret_key, &public_key, rng);
-            block.append_proof(signature);
+            block.append_proof(public_key, signature);
         }
 
         block
@@ -648,6 +753,22 @@ impl Value for Block {
     }
 }
 
+impl BlockValue for Block {
+    type DeployHash = DeployHash;
+
+    fn height(&self) -> u64 {
+        self.header.height()
+    }
+
+    fn era_id(&self) -> EraId {
+        self.header.era_id()
+    }
+
+    fn deploy_hashes(&self) -> &[DeployHash] {
+        self.header.deploy_hashes()
+    }
+}
+
 impl Item for Block {
     type Id = BlockHash;
 
@@ -659,6 +780,206 @@ impl Item for Block {
     }
 }
 
+/// A validator's signature of a block, confirming it is finalized.  Clients can verify a block's
+/// finality by checking that enough of its era's validators, by weight, have signed it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FinalitySignature {
+    /// Hash of the signed block.
+    pub block_hash: BlockHash,
+    /// Era in which the signed block was created.
+    pub era_id: EraId,
+    /// Signature over the block hash.
+    pub signature: Signature,
+    /// Public key of the signing validator.
+    pub public_key: PublicKey,
+}
+
+impl FinalitySignature {
+    /// Creates a new finality signature.
+    pub(crate) fn new(
+        block_hash: BlockHash,
+        era_id: EraId,
+        signature: Signature,
+        public_key: PublicKey,
+    ) -> Self {
+        FinalitySignature {
+            block_hash,
+            era_id,
+            signature,
+            public_key,
+        }
+    }
+
+    /// Verifies the signature over the block hash against the public key.
+    pub fn verify(&self) -> crypto::Result<()> {
+        asymmetric_key::verify(self.block_hash.inner(), &self.signature, &self.public_key)
+    }
+}
+
+impl Display for FinalitySignature {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "finality signature for {} in era {} by {}",
+            self.block_hash, self.era_id, self.public_key
+        )
+    }
+}
+
+impl Item for FinalitySignature {
+    type Id = FinalitySignature;
+
+    const TAG: Tag = Tag::FinalitySignature;
+    const ID_IS_COMPLETE_ITEM: bool = true;
+
+    fn id(&self) -> Self::Id {
+        *self
+    }
+}
+
+/// The identifier of a batch of consecutive block headers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct BlockHeadersBatchId {
+    /// Height of the first block header of the batch.
+    pub from_height: u64,
+    /// Maximum number of block headers in the batch.
+    pub count: u64,
+}
+
+impl BlockHeadersBatchId {
+    /// The maximum number of block headers a node will send in a single batch.
+    pub(crate) const MAX_COUNT: u64 = 256;
+
+    /// Creates a new batch identifier.
+    pub(crate) fn new(from_height: u64, count: u64) -> Self {
+        BlockHeadersBatchId { from_height, count }
+    }
+
+    /// Height of the last block header the batch can hold.
+    pub(crate) fn to_height(&self) -> u64 {
+        self.from_height + self.count.saturating_sub(1)
+    }
+}
+
+impl Display for BlockHeadersBatchId {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "block headers at heights {}..={}",
+            self.from_height,
+            self.to_height()
+        )
+    }
+}
+
+/// A block header along with the finality signatures collected for the block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedBlockHeader {
+    /// The block header.
+    pub header: BlockHeader,
+    /// The validators' signatures of the block.
+    pub proofs: BTreeMap<PublicKey, Signature>,
+}
+
+/// A batch of consecutive block headers.  It holds fewer headers than requested if the sender
+/// doesn't have them all, e.g. when the batch extends past the tip of its linear chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeadersBatch {
+    id: BlockHeadersBatchId,
+    headers: Vec<SignedBlockHeader>,
+}
+
+impl BlockHeadersBatch {
+    /// Creates a new batch.
+    pub(crate) fn new(id: BlockHeadersBatchId, headers: Vec<SignedBlockHeader>) -> Self {
+        BlockHeadersBatch { id, headers }
+    }
+
+    /// The signed block headers, in ascending order of height.
+    pub(crate) fn headers(&self) -> &[SignedBlockHeader] {
+        &self.headers
+    }
+
+    /// Consumes the batch, returning the signed block headers.
+    pub(crate) fn into_headers(self) -> Vec<SignedBlockHeader> {
+        self.headers
+    }
+}
+
+impl Display for BlockHeadersBatch {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{} ({} found)", self.id, self.headers.len())
+    }
+}
+
+impl Item for BlockHeadersBatch {
+    type Id = BlockHeadersBatchId;
+
+    const TAG: Tag = Tag::BlockHeadersBatch;
+    const ID_IS_COMPLETE_ITEM: bool = false;
+
+    fn id(&self) -> Self::Id {
+        self.id
+    }
+}
+
+/// The outcome of executing a single deploy of a block.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct DeployExecutionSummary {
+    /// The hash of the deploy.
+    pub deploy_hash: DeployHash,
+    /// The cost of executing the deploy.
+    pub cost: U512,
+    /// The error message if executing the deploy failed, or `None` if it succeeded.
+    pub error_message: Option<String>,
+}
+
+/// A summary of executing all the deploys of a block.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct BlockExecutionSummary {
+    /// The outcomes of executing the block's deploys, in the order in which they were executed.
+    pub deploys: Vec<DeployExecutionSummary>,
+    /// The total cost of executing the block's deploys.
+    pub total_cost: U512,
+}
+
+impl BlockExecutionSummary {
+    /// Creates the execution summary of the given block from the results of executing its deploys.
+    ///
+    /// Deploys without an execution result are skipped.
+    pub(crate) fn new(
+        block: &Block,
+        execution_results: &HashMap<DeployHash, ExecutionResult>,
+    ) -> Self {
+        let deploys: Vec<_> = block
+            .deploy_hashes()
+            .iter()
+            .filter_map(|deploy_hash| {
+                execution_results
+                    .get(deploy_hash)
+                    .map(|execution_result| DeployExecutionSummary {
+                        deploy_hash: *deploy_hash,
+                        cost: execution_result.cost(),
+                        error_message: execution_result.error_message().map(str::to_string),
+                    })
+            })
+            .collect();
+        let total_cost = deploys
+            .iter()
+            .fold(U512::zero(), |total_cost, deploy| total_cost + deploy.cost);
+        BlockExecutionSummary {
+            deploys,
+            total_cost,
+        }
+    }
+
+    /// Convert the `BlockExecutionSummary` to a JSON value.
+    pub fn to_json(&self) -> JsonValue {
+        let json_execution_summary = json::JsonBlockExecutionSummary::from(self);
+        json!(json_execution_summary)
+    }
+}
+
 /// This module provides structs which map to the main block types, but which are suitable for
 /// encoding to and decoding from JSON.  For all fields with binary data, this is converted to/from
 /// hex strings.
@@ -816,11 +1137,18 @@ mod json {
         }
     }
 
+    #[derive(Serialize, Deserialize)]
+    struct JsonProof {
+        public_key: String,
+        signature: String,
+    }
+
     #[derive(Serialize, Deserialize)]
     pub(super) struct JsonBlock {
         hash: JsonBlockHash,
         header: JsonBlockHeader,
-        proofs: Vec<String>,
+        proofs: Vec<JsonProof>,
+        evidence: Vec<JsonValue>,
     }
 
     impl From<&Block> for JsonBlock {
@@ -828,7 +1156,15 @@ mod json {
             JsonBlock {
                 hash: (&block.hash).into(),
                 header: (&block.header).into(),
-                proofs: block.proofs.iter().map(Signature::to_hex).collect(),
+                proofs: block
+                    .proofs
+                    .iter()
+                    .map(|(public_key, signature)| JsonProof {
+                        public_key: public_key.to_hex(),
+                        signature: signature.to_hex(),
+                    })
+                    .collect(),
+                evidence: block.evidence.iter().map(Evidence::to_json).collect(),
             }
         }
     }
@@ -837,20 +1173,61 @@ mod json {
         type Error = Error;
 
         fn try_from(block: JsonBlock) -> Result<Self, Self::Error> {
-            let mut proofs = vec![];
+            let mut proofs = BTreeMap::new();
             for json_proof in block.proofs.iter() {
-                let proof = Signature::from_hex(json_proof)
+                let public_key = PublicKey::from_hex(&json_proof.public_key)
+                    .map_err(|error| Error::DecodeFromJson(Box::new(error)))?;
+                let signature = Signature::from_hex(&json_proof.signature)
                     .map_err(|error| Error::DecodeFromJson(Box::new(error)))?;
-                proofs.push(proof);
+                let _ = proofs.insert(public_key, signature);
+            }
+            let mut evidence = vec![];
+            for json_evidence in block.evidence {
+                evidence.push(Evidence::from_json(json_evidence)?);
             }
             Ok(Block {
                 hash: block.hash.try_into()?,
                 header: block.header.try_into()?,
                 body: (),
                 proofs,
+                evidence,
             })
         }
     }
+
+    #[derive(Serialize, Deserialize)]
+    struct JsonDeployExecutionSummary {
+        deploy_hash: String,
+        cost: U512,
+        success: bool,
+        error_message: Option<String>,
+    }
+
+    impl From<&DeployExecutionSummary> for JsonDeployExecutionSummary {
+        fn from(summary: &DeployExecutionSummary) -> Self {
+            JsonDeployExecutionSummary {
+                deploy_hash: hex::encode(summary.deploy_hash.as_ref()),
+                cost: summary.cost,
+                success: summary.error_message.is_none(),
+                error_message: summary.error_message.clone(),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct JsonBlockExecutionSummary {
+        deploys: Vec<JsonDeployExecutionSummary>,
+        total_cost: U512,
+    }
+
+    impl From<&BlockExecutionSummary> for JsonBlockExecutionSummary {
+        fn from(summary: &BlockExecutionSummary) -> Self {
+            JsonBlockExecutionSummary {
+                deploys: summary.deploys.iter().map(Into::into).collect(),
+                total_cost: summary.total_cost,
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -869,4 +1246,47 @@ mod tests {
         let decoded = Block::from_json(json).unwrap();
         assert_eq!(block, decoded);
     }
+
+    fn new_switch_block(
+        rng: &mut TestRng,
+        system_transactions: Vec<SystemTransaction>,
+        evidence: Vec<Evidence>,
+    ) -> Block {
+        let finalized_block = FinalizedBlock::new(
+            ProtoBlock::new(vec![], rng.gen()),
+            Timestamp::now(),
+            system_transactions,
+            evidence,
+            true,
+            EraId(1),
+            10,
+            PublicKey::random(rng),
+        );
+        let parent_hash = BlockHash::new(Digest::random(rng));
+        Block::new(parent_hash, Digest::random(rng), finalized_block)
+    }
+
+    #[test]
+    fn verify_should_check_evidence_against_slashed_validators() {
+        let mut rng = TestRng::new();
+        let public_key = PublicKey::random(&mut rng);
+        let slash = vec![SystemTransaction::Slash(public_key)];
+        let evidence = Evidence::new(EraId(1), public_key, vec![1, 2, 3]);
+
+        let mut block = new_switch_block(&mut rng, slash.clone(), vec![evidence.clone()]);
+        assert!(block.verify().is_ok());
+
+        // The evidence is covered by the block hash.
+        block.evidence = vec![Evidence::new(EraId(1), public_key, vec![4, 5, 6])];
+        assert!(matches!(block.verify(), Err(Error::InvalidBodyHash(_))));
+
+        // No validator is slashed without evidence against it in the block's era.
+        let block = new_switch_block(&mut rng, slash.clone(), vec![]);
+        assert!(matches!(block.verify(), Err(Error::InvalidEvidence)));
+        let evidence_in_other_era = Evidence::new(EraId(0), public_key, vec![1, 2, 3]);
+        let block = new_switch_block(&mut rng, slash, vec![evidence_in_other_era]);
+        assert!(matches!(block.verify(), Err(Error::InvalidEvidence)));
+        let block = new_switch_block(&mut rng, vec![], vec![evidence]);
+        assert!(matches!(block.verify(), Err(Error::InvalidEvidence)));
+    }
 }
@@ -20,12 +20,13 @@ use tracing::warn;
 use casper_execution_engine::core::engine_state::{
     executable_deploy_item::ExecutableDeployItem, DeployItem,
 };
+use casper_types::U512;
 
 use super::{Item, Tag, TimeDiff, Timestamp};
 #[cfg(test)]
 use crate::testing::TestRng;
 use crate::{
-    components::storage::Value,
+    components::storage::{DeployValue, Value},
     crypto::{
         asymmetric_key::{self, PublicKey, SecretKey, Signature},
         hash::{self, Digest},
@@ -35,6 +36,8 @@ use crate::{
 };
 
 const DESER_ERROR_MSG_GENERAL: &str = "failed to deserialize deploy";
+/// The name of the runtime arg of the standard payment contract holding the payment amount.
+const STANDARD_PAYMENT_AMOUNT_ARG_NAME: &str = "amount";
 const DEPLOY_HASH_MISMATCH_MSG: &str = "deploy hash mismatch";
 const DEPLOY_BODY_HASH_MISMATCH_MSG: &str = "deploy body hash mismatch";
 
@@ -111,6 +114,34 @@ impl AsRef<[u8]> for DeployHash {
     }
 }
 
+/// The stage of a deploy's lifecycle, as known to this node.
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployStatus {
+    /// The deploy is waiting to be included in a proposed block.
+    Pending,
+    /// The deploy is included in a proposed block which has not been finalized yet.
+    Proposed,
+    /// The deploy is included in a finalized block.
+    Finalized,
+    /// The deploy expired before being included in a finalized block.
+    Expired,
+    /// The deploy is not being tracked by this node.
+    Unknown,
+}
+
+impl Display for DeployStatus {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            DeployStatus::Pending => write!(formatter, "pending"),
+            DeployStatus::Proposed => write!(formatter, "proposed"),
+            DeployStatus::Finalized => write!(formatter, "finalized"),
+            DeployStatus::Expired => write!(formatter, "expired"),
+            DeployStatus::Unknown => write!(formatter, "unknown"),
+        }
+    }
+}
+
 /// The header portion of a [`Deploy`](struct.Deploy.html).
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
 pub struct DeployHeader {
@@ -317,6 +348,19 @@ impl Deploy {
         &self.session
     }
 
+    /// Returns the amount of motes offered as payment, if the payment code's runtime args contain
+    /// the standard payment "amount" arg.
+    pub fn payment_amount(&self) -> Option<U512> {
+        self.payment
+            .clone()
+            .into_runtime_args()
+            .ok()?
+            .get(STANDARD_PAYMENT_AMOUNT_ARG_NAME)?
+            .clone()
+            .into_t()
+            .ok()
+    }
+
     /// Generates a random instance using a `TestRng`.
     #[cfg(test)]
     pub fn random(rng: &mut TestRng) -> Self {
@@ -473,6 +517,16 @@ impl Value for Deploy {
     }
 }
 
+impl DeployValue for Deploy {
+    fn account(&self) -> &PublicKey {
+        self.header().account()
+    }
+
+    fn timestamp(&self) -> Timestamp {
+        self.header().timestamp()
+    }
+}
+
 impl Item for Deploy {
     type Id = DeployHash;
 
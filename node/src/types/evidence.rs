@@ -0,0 +1,149 @@
+use std::fmt::{self, Display, Formatter};
+
+use hex_fmt::HexFmt;
+#[cfg(test)]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+
+use super::{block::Error, Item, Tag};
+#[cfg(test)]
+use crate::testing::TestRng;
+use crate::{components::consensus::EraId, crypto::asymmetric_key::PublicKey};
+
+/// The identifier of [`Evidence`](struct.Evidence.html): at most one piece of evidence against
+/// each validator is kept per era.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct EvidenceId {
+    /// The era in which the validator was faulty.
+    pub era_id: EraId,
+    /// The public key of the faulty validator.
+    pub perpetrator: PublicKey,
+}
+
+impl Display for EvidenceId {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "evidence against {} in era {}",
+            self.perpetrator, self.era_id
+        )
+    }
+}
+
+/// Proof that a validator was faulty in an era, e.g. because it signed two conflicting votes.
+///
+/// The proof itself is specific to the era's consensus protocol, and can be verified by any node
+/// which knows the era's validators.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Evidence {
+    era_id: EraId,
+    perpetrator: PublicKey,
+    #[serde(with = "serde_bytes")]
+    proof: Vec<u8>,
+}
+
+impl Evidence {
+    /// Creates new evidence against the given validator.
+    pub(crate) fn new(era_id: EraId, perpetrator: PublicKey, proof: Vec<u8>) -> Self {
+        Evidence {
+            era_id,
+            perpetrator,
+            proof,
+        }
+    }
+
+    /// The era in which the validator was faulty.
+    pub fn era_id(&self) -> EraId {
+        self.era_id
+    }
+
+    /// The public key of the faulty validator.
+    pub fn perpetrator(&self) -> &PublicKey {
+        &self.perpetrator
+    }
+
+    /// The serialized proof, as created by the era's consensus protocol.
+    pub(crate) fn proof(&self) -> &[u8] {
+        &self.proof
+    }
+
+    /// Convert the `Evidence` to a JSON value.
+    pub fn to_json(&self) -> JsonValue {
+        let json_evidence = JsonEvidence {
+            era_id: self.era_id,
+            perpetrator: self.perpetrator.to_hex(),
+            proof: hex::encode(&self.proof),
+        };
+        json!(json_evidence)
+    }
+
+    /// Try to convert the JSON value to `Evidence`.
+    pub fn from_json(input: JsonValue) -> Result<Self, Error> {
+        let json_evidence: JsonEvidence = serde_json::from_value(input)?;
+        Ok(Evidence {
+            era_id: json_evidence.era_id,
+            perpetrator: PublicKey::from_hex(&json_evidence.perpetrator)
+                .map_err(|error| Error::DecodeFromJson(Box::new(error)))?,
+            proof: hex::decode(&json_evidence.proof)?,
+        })
+    }
+
+    /// Generates a random instance using a `TestRng`.
+    #[cfg(test)]
+    pub fn random(rng: &mut TestRng) -> Self {
+        let proof_size = rng.gen_range(1, 200);
+        let proof = (0..proof_size).map(|_| rng.gen()).collect();
+        Evidence {
+            era_id: EraId(rng.gen_range(0, 5)),
+            perpetrator: PublicKey::random(rng),
+            proof,
+        }
+    }
+}
+
+impl Display for Evidence {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "evidence against {} in era {}, proof {:10}",
+            self.perpetrator,
+            self.era_id,
+            HexFmt(&self.proof)
+        )
+    }
+}
+
+impl Item for Evidence {
+    type Id = EvidenceId;
+
+    const TAG: Tag = Tag::Evidence;
+    const ID_IS_COMPLETE_ITEM: bool = false;
+
+    fn id(&self) -> Self::Id {
+        EvidenceId {
+            era_id: self.era_id,
+            perpetrator: self.perpetrator,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonEvidence {
+    era_id: EraId,
+    perpetrator: String,
+    proof: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_roundtrip() {
+        let mut rng = TestRng::new();
+        let evidence = Evidence::random(&mut rng);
+        let decoded = Evidence::from_json(evidence.to_json()).unwrap();
+        assert_eq!(evidence, decoded);
+    }
+}
@@ -30,6 +30,14 @@ pub enum Tag {
     Block,
     /// A gossiped public listening address.
     GossipedAddress,
+    /// A validator's signature of a block.
+    FinalitySignature,
+    /// A batch of block headers along with their finality signatures.
+    BlockHeadersBatch,
+    /// A node of the global state trie.
+    Trie,
+    /// Evidence that a validator was faulty.
+    Evidence,
 }
 
 /// A trait which allows an implementing type to be used by the gossiper and fetcher components, and
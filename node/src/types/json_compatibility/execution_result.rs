@@ -25,6 +25,18 @@ pub struct ExecutionResult {
     error_message: Option<String>,
 }
 
+impl ExecutionResult {
+    /// Returns the cost of executing the deploy.
+    pub fn cost(&self) -> U512 {
+        self.cost
+    }
+
+    /// Returns the error message if executing the deploy failed.
+    pub fn error_message(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+}
+
 impl From<&EngineExecutionResult> for ExecutionResult {
     fn from(ee_execution_result: &EngineExecutionResult) -> Self {
         match ee_execution_result {
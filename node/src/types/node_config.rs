@@ -4,6 +4,29 @@ use crate::{utils::External, Chainspec};
 
 const DEFAULT_CHAINSPEC_CONFIG_PATH: &str = "chainspec.toml";
 const DEFAULT_BLOCK_MAX_DEPLOY_COUNT: u32 = 3;
+const DEFAULT_CUSTOM_PAYMENT_GAS_ESTIMATE: u64 = 100_000_000_000;
+const DEFAULT_FINALITY_SIGNATURE_THRESHOLD_PERCENT: u8 = 67;
+
+/// How a joining node synchronizes the linear chain from the trusted hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    /// Download the blocks one at a time from the trusted block back to genesis, then execute
+    /// them all.
+    Full,
+    /// Download and verify the block headers in batches from several peers, then download and
+    /// execute the blocks, checking each against its verified header.
+    Light,
+    /// Download and verify the block headers like `Light`, then download the global state of the
+    /// highest verified block instead of executing the blocks.
+    Fast,
+}
+
+impl Default for SyncMode {
+    fn default() -> Self {
+        SyncMode::Full
+    }
+}
 
 /// Node configuration.
 #[derive(Debug, Deserialize, Serialize)]
@@ -14,8 +37,16 @@ pub struct NodeConfig {
     pub chainspec_config_path: External<Chainspec>,
     /// The maximum number of deploys permitted in a single block.
     pub block_max_deploy_count: u32,
+    /// The gas assumed to be used by a deploy when deciding whether it fits in a proposed block,
+    /// if its payment code doesn't take an amount argument from which to derive its gas limit.
+    pub custom_payment_gas_estimate: u64,
     /// Hash used as a trust anchor when joining, if any.
     pub trusted_hash: Option<String>,
+    /// How to synchronize the linear chain from the trusted hash.
+    pub sync_mode: SyncMode,
+    /// The weight of finality signatures to collect for each block, as a percentage of the total
+    /// weight of the block's era's validators.
+    pub finality_signature_threshold_percent: u8,
 }
 
 impl Default for NodeConfig {
@@ -23,7 +54,10 @@ impl Default for NodeConfig {
         NodeConfig {
             chainspec_config_path: External::path(DEFAULT_CHAINSPEC_CONFIG_PATH),
             block_max_deploy_count: DEFAULT_BLOCK_MAX_DEPLOY_COUNT,
+            custom_payment_gas_estimate: DEFAULT_CUSTOM_PAYMENT_GAS_ESTIMATE,
             trusted_hash: None,
+            sync_mode: SyncMode::default(),
+            finality_signature_threshold_percent: DEFAULT_FINALITY_SIGNATURE_THRESHOLD_PERCENT,
         }
     }
 }
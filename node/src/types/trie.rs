@@ -0,0 +1,54 @@
+use std::fmt::{self, Display, Formatter};
+
+use hex_fmt::HexFmt;
+use serde::{Deserialize, Serialize};
+
+use casper_execution_engine::shared::newtypes::Blake2bHash;
+
+use crate::types::{Item, Tag};
+
+/// A node of the global state trie in its serialized form, as exchanged with peers when
+/// synchronizing global state.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Trie {
+    #[serde(with = "serde_bytes")]
+    bytes: Vec<u8>,
+}
+
+impl Trie {
+    /// Constructs a new `Trie` from its serialized form.
+    pub(crate) fn new(bytes: Vec<u8>) -> Self {
+        Trie { bytes }
+    }
+
+    /// Returns the serialized trie.
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the hash of the trie, under which it is stored in the trie store.
+    pub fn hash(&self) -> Blake2bHash {
+        Blake2bHash::new(&self.bytes)
+    }
+}
+
+impl Display for Trie {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "trie {:10}, {} bytes",
+            HexFmt(self.hash().value()),
+            self.bytes.len()
+        )
+    }
+}
+
+impl Item for Trie {
+    type Id = Blake2bHash;
+    const TAG: Tag = Tag::Trie;
+    const ID_IS_COMPLETE_ITEM: bool = false;
+
+    fn id(&self) -> Self::Id {
+        self.hash()
+    }
+}
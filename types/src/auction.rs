@@ -353,13 +353,21 @@ pub trait Auction: StorageProvider + SystemProvider + RuntimeProvider {
         let mut unbonding_purses: UnbondingPurses =
             self.read(unbonding_purses_uref)?.ok_or(Error::Storage)?;
 
+        let mut bids = internal::get_bids(self)?;
+
         let mut bid_purses_modified = false;
         let mut unbonding_purses_modified = false;
+        let mut bids_modified = false;
         for validator_account_hash in validator_public_keys {
             if let Some(_bid_purse) = bid_purses.remove(&validator_account_hash) {
                 bid_purses_modified = true;
             }
 
+            // A slashed validator doesn't take part in future auctions with its forfeited bid.
+            if bids.remove(&validator_account_hash).is_some() {
+                bids_modified = true;
+            }
+
             if let Some(unbonding_list) = unbonding_purses.get_mut(&validator_account_hash) {
                 let size_before = unbonding_list.len();
 
@@ -377,6 +385,10 @@ pub trait Auction: StorageProvider + SystemProvider + RuntimeProvider {
             self.write(unbonding_purses_uref, unbonding_purses)?;
         }
 
+        if bids_modified {
+            internal::set_bids(self, bids)?;
+        }
+
         Ok(())
     }
 